@@ -446,6 +446,34 @@ impl CryptoBackend for OpenSslBackend {
 #[cfg(test)]
 mod tests {
     use super::*;
+
+    /// Network key used by the frame tests, key sequence 0
+    const NETWORK_KEY: [u8; 16] = [
+        0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f,
+        0x10,
+    ];
+
+    /// Key store with the test network key, and the default link key shared
+    /// with 0x1122334455667788
+    struct Keys;
+
+    impl psila_data::network::KeyStore for Keys {
+        fn network_key(&self, sequence: u8) -> Option<psila_data::Key> {
+            if sequence == 0 {
+                Some(NETWORK_KEY.into())
+            } else {
+                None
+            }
+        }
+        fn link_key(&self, address: psila_data::ExtendedAddress) -> Option<psila_data::Key> {
+            if address == 0x1122_3344_5566_7788 {
+                Some(psila_data::security::DEFAULT_LINK_KEY.into())
+            } else {
+                None
+            }
+        }
+    }
+
     #[test]
     fn test_aes_128_ecb_encrypt() {
         // Test vectors taken from NIST Special Publication 800-38A
@@ -816,4 +844,257 @@ mod tests {
 
         assert_eq!(hashed_key, correct_key);
     }
+
+    #[test]
+    fn test_green_power_decrypt() {
+        use psila_data::security::FrameCounter;
+        use psila_data::{green_power, Key};
+
+        let mut crypt = OpenSslBackend::default();
+
+        let key = Key::from([
+            0xC0, 0xC1, 0xC2, 0xC3, 0xC4, 0xC5, 0xC6, 0xC7, 0xC8, 0xC9, 0xCA, 0xCB, 0xCC, 0xCD,
+            0xCE, 0xCF,
+        ]);
+        // GPD frame from source identifier 0x87654321, security level 0b11,
+        // frame counter 2, encrypted Toggle command
+        let frame = [
+            0x8c, 0x38, 0x21, 0x43, 0x65, 0x87, 0x02, 0x00, 0x00, 0x00, 0x83, 0x5f, 0x1a, 0x30,
+            0x34,
+        ];
+        let mut output = [0u8; 8];
+        let used = green_power::decrypt(
+            &mut crypt,
+            &key,
+            0x8765_4321,
+            FrameCounter::new(2),
+            &frame,
+            10,
+            &mut output,
+        )
+        .unwrap();
+        assert_eq!(used, 1);
+        assert_eq!(output[0], 0x20);
+
+        // Wrong frame counter fails the MIC check
+        let used = green_power::decrypt(
+            &mut crypt,
+            &key,
+            0x8765_4321,
+            FrameCounter::new(3),
+            &frame,
+            10,
+            &mut output,
+        )
+        .unwrap();
+        assert_eq!(used, 0);
+    }
+
+    #[test]
+    fn test_network_decrypt_frame() {
+        use psila_data::network::decrypt_frame;
+
+        let mut crypt = OpenSslBackend::default();
+        // Synthetic NWK data frame from 0x1234 to 0x0000, encrypted for this
+        // test with the key above, key sequence 0 and frame counter 1
+        let frame = [
+            0x08, 0x02, 0x00, 0x00, 0x34, 0x12, 0x1e, 0x42, 0x28, 0x01, 0x00, 0x00, 0x00, 0x77,
+            0x66, 0x55, 0x44, 0x33, 0x22, 0x11, 0x00, 0x00, 0x42, 0x3c, 0x40, 0x5e, 0xd9, 0xd7,
+            0x44, 0xee, 0x29, 0x50, 0xb9, 0xc5,
+        ];
+        let mut data = frame;
+        let (header, payload) = decrypt_frame(&mut crypt, &Keys, &mut data).unwrap();
+        assert_eq!(header.source_address, 0x1234);
+        assert_eq!(header.sequence_number, 0x42);
+        assert_eq!(payload, [0x40, 0x01, 0x06, 0x00, 0x04, 0x01, 0x01, 0x01]);
+
+        // Tampered frame fails the MIC check
+        let mut data = frame;
+        data[7] = 0x43;
+        assert_eq!(
+            decrypt_frame(&mut crypt, &Keys, &mut data).unwrap_err(),
+            psila_data::Error::CryptoError(Error::IntegrityCheckFailed)
+        );
+
+        // Unknown key sequence
+        let mut data = frame;
+        data[21] = 0x01;
+        assert!(decrypt_frame(&mut crypt, &Keys, &mut data).is_err());
+    }
+
+    #[test]
+    fn test_network_decrypt_frame_invalid_mic() {
+        use psila_data::network::{decrypt_frame, decrypt_frame_with_options};
+
+        let mut crypt = OpenSslBackend::default();
+        let frame = [
+            0x08, 0x02, 0x00, 0x00, 0x34, 0x12, 0x1e, 0x42, 0x28, 0x01, 0x00, 0x00, 0x00, 0x77,
+            0x66, 0x55, 0x44, 0x33, 0x22, 0x11, 0x00, 0x00, 0x42, 0x3c, 0x40, 0x5e, 0xd9, 0xd7,
+            0x44, 0xee, 0x29, 0x50, 0xb9, 0xc5,
+        ];
+        let plain_text = [0x40, 0x01, 0x06, 0x00, 0x04, 0x01, 0x01, 0x01];
+
+        let mut data = frame;
+        let decrypted = decrypt_frame_with_options(&mut crypt, &Keys, &mut data, true).unwrap();
+        assert!(decrypted.mic_valid);
+        assert_eq!(decrypted.payload, plain_text);
+
+        // Corrupt the MIC
+        let mut corrupted = frame;
+        corrupted[33] ^= 0x01;
+
+        let mut data = corrupted;
+        assert_eq!(
+            decrypt_frame(&mut crypt, &Keys, &mut data).unwrap_err(),
+            psila_data::Error::CryptoError(Error::IntegrityCheckFailed)
+        );
+        let mut data = corrupted;
+        assert_eq!(
+            decrypt_frame_with_options(&mut crypt, &Keys, &mut data, false).unwrap_err(),
+            psila_data::Error::CryptoError(Error::IntegrityCheckFailed)
+        );
+        let mut data = corrupted;
+        let decrypted = decrypt_frame_with_options(&mut crypt, &Keys, &mut data, true).unwrap();
+        assert!(!decrypted.mic_valid);
+        assert_eq!(decrypted.header.source_address, 0x1234);
+        assert_eq!(decrypted.payload, plain_text);
+    }
+
+    #[test]
+    fn test_application_service_decrypt_frame() {
+        use psila_data::application_service::decrypt_frame;
+        use psila_data::ExtendedAddress;
+
+        let mut crypt = OpenSslBackend::default();
+        let source = ExtendedAddress::new(0x1122_3344_5566_7788);
+        let plain_text = [0x01, 0x17, 0x01];
+
+        // APS data frame secured with the network key sequence 0, the source
+        // address is in the auxiliary header
+        let mut data = [
+            0x20, 0x01, 0x06, 0x00, 0x04, 0x01, 0x01, 0x42, 0x28, 0x10, 0x00, 0x00, 0x00, 0x88,
+            0x77, 0x66, 0x55, 0x44, 0x33, 0x22, 0x11, 0x00, 0x99, 0xf2, 0x96, 0xac, 0xe1, 0x9f,
+            0x09,
+        ];
+        let (header, payload) =
+            decrypt_frame(&mut crypt, &Keys, ExtendedAddress::new(0), &mut data).unwrap();
+        assert!(header.control.security);
+        assert_eq!(header.counter, 0x42);
+        assert_eq!(payload, plain_text);
+
+        // APS data frame secured with the link key, the source address is
+        // taken from the network layer
+        let frame = [
+            0x20, 0x01, 0x06, 0x00, 0x04, 0x01, 0x01, 0x42, 0x00, 0x11, 0x00, 0x00, 0x00, 0x9e,
+            0xb8, 0xb7, 0x2d, 0xb0, 0x8f, 0x68,
+        ];
+        let mut data = frame;
+        let (header, payload) = decrypt_frame(&mut crypt, &Keys, source, &mut data).unwrap();
+        assert_eq!(header.counter, 0x42);
+        assert_eq!(payload, plain_text);
+
+        // No link key for the source
+        let mut data = frame;
+        assert_eq!(
+            decrypt_frame(&mut crypt, &Keys, ExtendedAddress::new(1), &mut data).unwrap_err(),
+            psila_data::Error::MissingKey
+        );
+
+        // Tampered header fails the MIC check
+        let mut data = frame;
+        data[7] = 0x43;
+        assert_eq!(
+            decrypt_frame(&mut crypt, &Keys, source, &mut data).unwrap_err(),
+            psila_data::Error::CryptoError(Error::IntegrityCheckFailed)
+        );
+    }
+
+    #[test]
+    fn test_secure_sender_counters() {
+        use psila_data::network::{decrypt_frame, header::DiscoverRoute, NetworkHeader};
+        use psila_data::security::{CryptoProvider, FrameCounter, SecureSender, SecurityLevel};
+        use psila_data::{ExtendedAddress, NetworkAddress};
+
+        let mut provider = CryptoProvider::new(OpenSslBackend::default());
+        let mut sender = SecureSender::new(
+            ExtendedAddress::new(0x0011_2233_4455_6677),
+            SecurityLevel::EncryptedIntegrity32,
+        );
+        let header = NetworkHeader::new_data_header(
+            2,
+            DiscoverRoute::SurpressDiscovery,
+            true,
+            NetworkAddress::new(0x0000),
+            NetworkAddress::new(0x1234),
+            30,
+            0x42,
+            None,
+        );
+        let payload = [0x40, 0x01, 0x06, 0x00, 0x04, 0x01, 0x01, 0x01];
+
+        let mut first = [0u8; 64];
+        let first_size = sender
+            .encrypt_network_frame(
+                &mut provider,
+                header.clone(),
+                &NETWORK_KEY,
+                0,
+                &payload,
+                &mut first,
+            )
+            .unwrap();
+        let mut second = [0u8; 64];
+        let second_size = sender
+            .encrypt_network_frame(
+                &mut provider,
+                header,
+                &NETWORK_KEY,
+                0,
+                &payload,
+                &mut second,
+            )
+            .unwrap();
+        assert_eq!(first_size, second_size);
+        assert_eq!(sender.counter(), Some(FrameCounter::new(2)));
+
+        // Frame counter, little endian, following the security control
+        assert_eq!(first[9..13], [0x00, 0x00, 0x00, 0x00]);
+        assert_eq!(second[9..13], [0x01, 0x00, 0x00, 0x00]);
+        // Same plain text, different nonce
+        assert_ne!(first[22..first_size], second[22..second_size]);
+
+        let mut crypt = OpenSslBackend::default();
+        let (_, decrypted) = decrypt_frame(&mut crypt, &Keys, &mut first[..first_size]).unwrap();
+        assert_eq!(decrypted, payload);
+        let (_, decrypted) = decrypt_frame(&mut crypt, &Keys, &mut second[..second_size]).unwrap();
+        assert_eq!(decrypted, payload);
+    }
+
+    #[test]
+    fn test_decrypt_payload_short() {
+        use psila_data::security::{CryptoProvider, SecurityLevel};
+
+        let key = [0u8; 16];
+        let level = SecurityLevel::EncryptedIntegrity32;
+        let mut provider = CryptoProvider::new(OpenSslBackend::default());
+        let mut output = [0u8; 64];
+
+        assert_eq!(
+            provider.decrypt_payload(&key, level, &[], 0, &mut output),
+            Err(psila_data::Error::WrongNumberOfBytes)
+        );
+        // Network header and auxiliary header, without the MIC
+        let frame = [
+            0x08, 0x02, 0x00, 0x00, 0x34, 0x12, 0x1e, 0x42, 0x28, 0x01, 0x00, 0x00, 0x00, 0x77,
+            0x66, 0x55, 0x44, 0x33, 0x22, 0x11, 0x00, 0x00,
+        ];
+        assert_eq!(
+            provider.decrypt_payload(&key, level, &frame, 8, &mut output),
+            Err(psila_data::Error::WrongNumberOfBytes)
+        );
+        assert_eq!(
+            provider.decrypt_payload(&key, level, &frame, frame.len(), &mut output),
+            Err(psila_data::Error::WrongNumberOfBytes)
+        );
+    }
 }
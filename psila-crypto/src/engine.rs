@@ -0,0 +1,444 @@
+//! # AES engines
+//!
+//! CCM* and AES-128 ECB implemented on top of a engine encrypting single
+//! blocks, which can be a hardware AES peripheral or a software
+//! implementation.
+
+use crate::{CryptoBackend, Error, BLOCK_SIZE, KEY_SIZE, LENGTH_FIELD_LENGTH};
+
+/// Length of the CCM* nonce
+const NONCE_SIZE: usize = 15 - LENGTH_FIELD_LENGTH;
+/// Additional data from this length and up need a longer length encoding,
+/// which is not supported
+const MAX_ADDITIONAL_DATA_SIZE: usize = 0xff00;
+
+/// Trait for AES-128 engines encrypting single blocks
+///
+/// Implement this for a hardware AES peripheral to offload the block
+/// encryption used by `EngineBackend`.
+pub trait AesEngine {
+    /// Set the key used for following block encryptions
+    fn set_key(&mut self, key: &[u8; KEY_SIZE]) -> Result<(), Error>;
+    /// Encrypt `block` in place
+    fn encrypt_block(&self, block: &mut [u8; BLOCK_SIZE]);
+}
+
+#[inline]
+fn block_xor(block: &mut [u8; BLOCK_SIZE], other: &[u8]) {
+    for (b, o) in block.iter_mut().zip(other.iter()) {
+        *b ^= *o;
+    }
+}
+
+/// Crypto backend using a `AesEngine` for the block encryption
+pub struct EngineBackend<Engine> {
+    engine: Engine,
+}
+
+impl<Engine> EngineBackend<Engine>
+where
+    Engine: AesEngine,
+{
+    /// Create a backend using `engine`
+    pub fn new(engine: Engine) -> Self {
+        Self { engine }
+    }
+
+    /// The AES engine of the backend
+    pub fn engine(&self) -> &Engine {
+        &self.engine
+    }
+
+    fn set_key(&mut self, key: &[u8]) -> Result<(), Error> {
+        if key.len() != KEY_SIZE {
+            return Err(Error::InvalidKeySize);
+        }
+        let mut block = [0u8; KEY_SIZE];
+        block.copy_from_slice(key);
+        self.engine.set_key(&block)
+    }
+
+    fn check_arguments(
+        nonce: &[u8],
+        mic_length: usize,
+        additional_data: &[u8],
+    ) -> Result<(), Error> {
+        if nonce.len() != NONCE_SIZE {
+            return Err(Error::InvalidNonceSize);
+        }
+        if mic_length > BLOCK_SIZE || mic_length == 2 || mic_length % 2 != 0 {
+            return Err(Error::InvalidIntegrityCodeSize);
+        }
+        if additional_data.len() >= MAX_ADDITIONAL_DATA_SIZE {
+            return Err(Error::InvalidAdditionalDataSize);
+        }
+        Ok(())
+    }
+
+    /// Counter block A_i
+    fn counter_block(nonce: &[u8], counter: u16) -> [u8; BLOCK_SIZE] {
+        let mut block = [0u8; BLOCK_SIZE];
+        block[0] = (LENGTH_FIELD_LENGTH - 1) as u8;
+        block[1..=NONCE_SIZE].copy_from_slice(nonce);
+        block[BLOCK_SIZE - 2..].copy_from_slice(&counter.to_be_bytes());
+        block
+    }
+
+    /// Encrypt or decrypt `input` into `output` in counter mode, starting
+    /// with counter one
+    fn counter_mode(&self, nonce: &[u8], input: &[u8], output: &mut [u8]) {
+        for (n, (i, o)) in input
+            .chunks(BLOCK_SIZE)
+            .zip(output.chunks_mut(BLOCK_SIZE))
+            .enumerate()
+        {
+            let mut block = Self::counter_block(nonce, n as u16 + 1);
+            self.engine.encrypt_block(&mut block);
+            block_xor(&mut block, i);
+            o.copy_from_slice(&block[..i.len()]);
+        }
+    }
+
+    /// Feed `data` to the CBC-MAC, padded with zeros to whole blocks
+    ///
+    /// `offset` is the number of bytes already in `state`.
+    fn authenticate_data(&self, state: &mut [u8; BLOCK_SIZE], mut offset: usize, data: &[u8]) {
+        for byte in data {
+            state[offset] ^= *byte;
+            offset += 1;
+            if offset == BLOCK_SIZE {
+                self.engine.encrypt_block(state);
+                offset = 0;
+            }
+        }
+        if offset > 0 {
+            self.engine.encrypt_block(state);
+        }
+    }
+
+    /// Calculate the unencrypted authentication tag T
+    fn authentication_tag(
+        &self,
+        nonce: &[u8],
+        message: &[u8],
+        additional_data: &[u8],
+        mic_length: usize,
+    ) -> [u8; BLOCK_SIZE] {
+        let mut flags = (LENGTH_FIELD_LENGTH - 1) as u8;
+        if !additional_data.is_empty() {
+            flags |= 0x40;
+        }
+        if mic_length > 0 {
+            flags |= (((mic_length - 2) / 2) as u8) << 3;
+        }
+        let mut state = [0u8; BLOCK_SIZE];
+        state[0] = flags;
+        state[1..=NONCE_SIZE].copy_from_slice(nonce);
+        state[BLOCK_SIZE - 2..].copy_from_slice(&(message.len() as u16).to_be_bytes());
+        self.engine.encrypt_block(&mut state);
+
+        if !additional_data.is_empty() {
+            block_xor(&mut state, &(additional_data.len() as u16).to_be_bytes());
+            self.authenticate_data(&mut state, 2, additional_data);
+        }
+        self.authenticate_data(&mut state, 0, message);
+        state
+    }
+}
+
+impl<Engine> CryptoBackend for EngineBackend<Engine>
+where
+    Engine: AesEngine,
+{
+    fn ccmstar_encrypt(
+        &mut self,
+        key: &[u8],
+        nonce: &[u8],
+        message: &[u8],
+        mic: &mut [u8],
+        additional_data: &[u8],
+        message_output: &mut [u8],
+    ) -> Result<usize, Error> {
+        Self::check_arguments(nonce, mic.len(), additional_data)?;
+        if message_output.len() < message.len() || message.len() > usize::from(u16::max_value()) {
+            return Err(Error::InvalidDataSize);
+        }
+        self.set_key(key)?;
+        let mut tag = self.authentication_tag(nonce, message, additional_data, mic.len());
+        let mut block = Self::counter_block(nonce, 0);
+        self.engine.encrypt_block(&mut block);
+        block_xor(&mut tag, &block);
+        mic.copy_from_slice(&tag[..mic.len()]);
+        self.counter_mode(nonce, message, &mut message_output[..message.len()]);
+        Ok(message.len())
+    }
+
+    fn ccmstar_decrypt(
+        &mut self,
+        key: &[u8],
+        nonce: &[u8],
+        message: &[u8],
+        mic: &[u8],
+        additional_data: &[u8],
+        message_output: &mut [u8],
+    ) -> Result<usize, Error> {
+        Self::check_arguments(nonce, mic.len(), additional_data)?;
+        if message_output.len() < message.len() || message.len() > usize::from(u16::max_value()) {
+            return Err(Error::InvalidDataSize);
+        }
+        self.set_key(key)?;
+        let output = &mut message_output[..message.len()];
+        self.counter_mode(nonce, message, output);
+        let mut tag = self.authentication_tag(nonce, output, additional_data, mic.len());
+        let mut block = Self::counter_block(nonce, 0);
+        self.engine.encrypt_block(&mut block);
+        block_xor(&mut tag, &block);
+        if tag[..mic.len()] == *mic {
+            Ok(message.len())
+        } else {
+            for b in output.iter_mut() {
+                *b = 0;
+            }
+            Ok(0)
+        }
+    }
+
+    fn aes128_ecb_encrypt_set_key(&mut self, key: &[u8]) -> Result<(), Error> {
+        self.set_key(key)
+    }
+
+    fn aes128_ecb_encrypt_process_block(
+        &mut self,
+        input: &[u8],
+        output: &mut [u8],
+    ) -> Result<(), Error> {
+        if input.len() != BLOCK_SIZE || output.len() != BLOCK_SIZE {
+            return Err(Error::InvalidDataSize);
+        }
+        let mut block = [0u8; BLOCK_SIZE];
+        block.copy_from_slice(input);
+        self.engine.encrypt_block(&mut block);
+        output.copy_from_slice(&block);
+        Ok(())
+    }
+
+    fn aes128_ecb_encrypt_finish(&mut self, input: &[u8], output: &mut [u8]) -> Result<(), Error> {
+        self.aes128_ecb_encrypt_process_block(input, output)
+    }
+}
+
+/// Software AES-128 engine
+#[cfg(feature = "software-aes")]
+pub struct SoftwareAes {
+    cipher: aes::Aes128,
+}
+
+#[cfg(feature = "software-aes")]
+impl Default for SoftwareAes {
+    fn default() -> Self {
+        use aes::cipher::{generic_array::GenericArray, NewBlockCipher};
+        Self {
+            cipher: aes::Aes128::new(GenericArray::from_slice(&[0u8; KEY_SIZE])),
+        }
+    }
+}
+
+#[cfg(feature = "software-aes")]
+impl AesEngine for SoftwareAes {
+    fn set_key(&mut self, key: &[u8; KEY_SIZE]) -> Result<(), Error> {
+        use aes::cipher::{generic_array::GenericArray, NewBlockCipher};
+        self.cipher = aes::Aes128::new(GenericArray::from_slice(key));
+        Ok(())
+    }
+
+    fn encrypt_block(&self, block: &mut [u8; BLOCK_SIZE]) {
+        use aes::cipher::{generic_array::GenericArray, BlockEncrypt};
+        self.cipher
+            .encrypt_block(GenericArray::from_mut_slice(&mut block[..]));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use core::cell::Cell;
+
+    /// Not a cipher, mixes the block with the key and counts the calls
+    #[derive(Default)]
+    struct MockEngine {
+        key: [u8; KEY_SIZE],
+        blocks: Cell<usize>,
+    }
+
+    impl AesEngine for MockEngine {
+        fn set_key(&mut self, key: &[u8; KEY_SIZE]) -> Result<(), Error> {
+            self.key = *key;
+            Ok(())
+        }
+
+        fn encrypt_block(&self, block: &mut [u8; BLOCK_SIZE]) {
+            let mut previous = 0x5a;
+            for (b, k) in block.iter_mut().zip(self.key.iter()) {
+                *b = (*b ^ *k).rotate_left(3).wrapping_add(previous);
+                previous = *b;
+            }
+            self.blocks.set(self.blocks.get() + 1);
+        }
+    }
+
+    const KEY: [u8; KEY_SIZE] = [
+        0xc0, 0xc1, 0xc2, 0xc3, 0xc4, 0xc5, 0xc6, 0xc7, 0xc8, 0xc9, 0xca, 0xcb, 0xcc, 0xcd, 0xce,
+        0xcf,
+    ];
+    const NONCE: [u8; NONCE_SIZE] = [
+        0xa0, 0xa1, 0xa2, 0xa3, 0xa4, 0xa5, 0xa6, 0xa7, 0x03, 0x02, 0x01, 0x00, 0x06,
+    ];
+    const ADDITIONAL_DATA: [u8; 8] = [0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07];
+    const MESSAGE: [u8; 23] = [
+        0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f, 0x10, 0x11, 0x12, 0x13, 0x14, 0x15, 0x16,
+        0x17, 0x18, 0x19, 0x1a, 0x1b, 0x1c, 0x1d, 0x1e,
+    ];
+
+    #[test]
+    fn mock_engine_round_trip() {
+        let mut backend = EngineBackend::new(MockEngine::default());
+
+        let mut encrypted = [0u8; 23];
+        let mut mic = [0u8; 4];
+        let used = backend
+            .ccmstar_encrypt(
+                &KEY,
+                &NONCE,
+                &MESSAGE,
+                &mut mic,
+                &ADDITIONAL_DATA,
+                &mut encrypted,
+            )
+            .unwrap();
+        assert_eq!(used, 23);
+        assert_ne!(encrypted, MESSAGE);
+        assert_eq!(backend.engine().key, KEY);
+        // B0, additional data, two message blocks, A0 and two counter blocks
+        assert_eq!(backend.engine().blocks.get(), 7);
+
+        let mut decrypted = [0u8; 23];
+        let used = backend
+            .ccmstar_decrypt(
+                &KEY,
+                &NONCE,
+                &encrypted,
+                &mic,
+                &ADDITIONAL_DATA,
+                &mut decrypted,
+            )
+            .unwrap();
+        assert_eq!(used, 23);
+        assert_eq!(decrypted, MESSAGE);
+
+        // Tampered additional data fails the integrity check
+        let mut additional_data = ADDITIONAL_DATA;
+        additional_data[0] ^= 0x01;
+        let used = backend
+            .ccmstar_decrypt(
+                &KEY,
+                &NONCE,
+                &encrypted,
+                &mic,
+                &additional_data,
+                &mut decrypted,
+            )
+            .unwrap();
+        assert_eq!(used, 0);
+        assert_eq!(decrypted, [0u8; 23]);
+    }
+
+    #[test]
+    fn mock_engine_larger_output() {
+        let mut backend = EngineBackend::new(MockEngine::default());
+        let mut expected = [0u8; 23];
+        let mut expected_mic = [0u8; 4];
+        backend
+            .ccmstar_encrypt(
+                &KEY,
+                &NONCE,
+                &MESSAGE,
+                &mut expected_mic,
+                &ADDITIONAL_DATA,
+                &mut expected,
+            )
+            .unwrap();
+
+        // Room left for the MIC after the message
+        let mut encrypted = [0u8; 27];
+        let mut mic = [0u8; 4];
+        let used = backend
+            .ccmstar_encrypt(
+                &KEY,
+                &NONCE,
+                &MESSAGE,
+                &mut mic,
+                &ADDITIONAL_DATA,
+                &mut encrypted,
+            )
+            .unwrap();
+        assert_eq!(used, 23);
+        assert_eq!(encrypted[..23], expected);
+        assert_eq!(encrypted[23..], [0u8; 4]);
+        assert_eq!(mic, expected_mic);
+    }
+
+    #[test]
+    fn mock_engine_ecb_and_arguments() {
+        let mut backend = EngineBackend::new(MockEngine::default());
+        backend.aes128_ecb_encrypt_set_key(&KEY).unwrap();
+        let mut expected = [0u8; BLOCK_SIZE];
+        backend.engine().encrypt_block(&mut expected);
+        let mut output = [0u8; BLOCK_SIZE];
+        backend
+            .aes128_ecb_encrypt_process_block(&[0u8; BLOCK_SIZE], &mut output)
+            .unwrap();
+        assert_eq!(output, expected);
+
+        let mut mic = [0u8; 4];
+        let mut output = [0u8; 23];
+        assert_eq!(
+            backend.ccmstar_encrypt(&KEY[..8], &NONCE, &MESSAGE, &mut mic, &[], &mut output),
+            Err(Error::InvalidKeySize)
+        );
+        assert_eq!(
+            backend.ccmstar_encrypt(&KEY, &NONCE[..12], &MESSAGE, &mut mic, &[], &mut output),
+            Err(Error::InvalidNonceSize)
+        );
+        assert_eq!(
+            backend.ccmstar_encrypt(&KEY, &NONCE, &MESSAGE, &mut mic[..3], &[], &mut output),
+            Err(Error::InvalidIntegrityCodeSize)
+        );
+    }
+
+    #[cfg(feature = "software-aes")]
+    #[test]
+    fn software_engine() {
+        // Zigbee specification C.2.2.2, CCM* encryption and authentication
+        let mut backend = EngineBackend::new(SoftwareAes::default());
+        let mut encrypted = [0u8; 23];
+        let mut mic = [0u8; 8];
+        backend
+            .ccmstar_encrypt(
+                &KEY,
+                &NONCE,
+                &MESSAGE,
+                &mut mic,
+                &ADDITIONAL_DATA,
+                &mut encrypted,
+            )
+            .unwrap();
+        assert_eq!(
+            encrypted,
+            [
+                0x1a, 0x55, 0xa3, 0x6a, 0xbb, 0x6c, 0x61, 0x0d, 0x06, 0x6b, 0x33, 0x75, 0x64, 0x9c,
+                0xef, 0x10, 0xd4, 0x66, 0x4e, 0xca, 0xd8, 0x54, 0xa8
+            ]
+        );
+        assert_eq!(mic, [0x0a, 0x89, 0x5c, 0xc1, 0xd8, 0xff, 0x94, 0x69]);
+    }
+}
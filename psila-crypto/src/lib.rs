@@ -1,5 +1,11 @@
 #![no_std]
 
+mod engine;
+
+#[cfg(feature = "software-aes")]
+pub use engine::SoftwareAes;
+pub use engine::{AesEngine, EngineBackend};
+
 /// Length of the message length field (L) in octets. Always 2 for Z
 pub const LENGTH_FIELD_LENGTH: usize = 2;
 /// Cipher block length
@@ -0,0 +1,22 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+
+use psila_data::application_service::ApplicationServiceHeader;
+use psila_data::pack::Pack;
+
+fn unpack_header(c: &mut Criterion) {
+    let data = [
+        0x40, 0x01, 0x06, 0x00, 0x04, 0x01, 0x01, 0x2a, 0x18, 0x11, 0x0a, 0x00, 0x00, 0x10, 0x01,
+    ];
+    c.bench_function("unpack data header", |b| {
+        b.iter(|| ApplicationServiceHeader::unpack(black_box(&data[..])))
+    });
+    let data = [
+        0x21, 0xd3, 0x30, 0x06, 0x00, 0x00, 0x00, 0xb5, 0x41, 0x24, 0x74, 0x03, 0x00, 0xb5,
+    ];
+    c.bench_function("unpack command header", |b| {
+        b.iter(|| ApplicationServiceHeader::unpack(black_box(&data[..])))
+    });
+}
+
+criterion_group!(benches, unpack_header);
+criterion_main!(benches);
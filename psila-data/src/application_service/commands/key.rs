@@ -29,6 +29,24 @@ pub struct RequestKey {
     pub partner_address: Option<ExtendedAddress>,
 }
 
+impl RequestKey {
+    /// Request a application link key shared with the partner device
+    pub fn application_link_key(partner_address: ExtendedAddress) -> Self {
+        Self {
+            key_type: RequestKeyType::ApplicationLinkKey,
+            partner_address: Some(partner_address),
+        }
+    }
+
+    /// Request a new trust center link key
+    pub fn trust_center_link_key() -> Self {
+        Self {
+            key_type: RequestKeyType::TrustCenterLinkKey,
+            partner_address: None,
+        }
+    }
+}
+
 impl Pack<RequestKey, Error> for RequestKey {
     fn pack(&self, data: &mut [u8]) -> Result<usize, Error> {
         match self.key_type {
@@ -61,10 +79,10 @@ impl Pack<RequestKey, Error> for RequestKey {
                 if data.len() < 9 {
                     return Err(Error::WrongNumberOfBytes);
                 }
+                let address =
+                    ExtendedAddress::unpack(&data[offset..offset + EXTENDED_ADDRESS_SIZE])?;
                 offset += EXTENDED_ADDRESS_SIZE;
-                Some(ExtendedAddress::unpack(
-                    &data[offset..offset + EXTENDED_ADDRESS_SIZE],
-                )?)
+                Some(address)
             }
             RequestKeyType::TrustCenterLinkKey => None,
         };
@@ -80,13 +98,20 @@ impl Pack<RequestKey, Error> for RequestKey {
 
 /// Switch key command
 ///
-///
+/// Tells the device to switch to the network key with the sequence number
 #[derive(Clone, Debug, PartialEq)]
 pub struct SwitchKey {
     /// Sequence number
     pub sequence: u8,
 }
 
+impl SwitchKey {
+    /// Switch to the network key with the provided key sequence number
+    pub fn new(sequence: u8) -> Self {
+        Self { sequence }
+    }
+}
+
 impl Pack<SwitchKey, Error> for SwitchKey {
     fn pack(&self, data: &mut [u8]) -> Result<usize, Error> {
         if data.is_empty() {
@@ -200,3 +225,73 @@ impl Pack<ConfirmKey, Error> for ConfirmKey {
         ))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application_service::Command;
+
+    #[test]
+    fn pack_request_application_link_key() {
+        let partner = ExtendedAddress::new(0x0011_2233_4455_6677);
+        let command = Command::RequestKey(RequestKey::application_link_key(partner));
+        let mut data = [0u8; 16];
+        let used = command.pack(&mut data).unwrap();
+        assert_eq!(used, 10);
+        assert_eq!(
+            data[..used],
+            [0x08, 0x02, 0x77, 0x66, 0x55, 0x44, 0x33, 0x22, 0x11, 0x00]
+        );
+        let (unpacked, used) = Command::unpack(&data[..used]).unwrap();
+        assert_eq!(used, 10);
+        assert_eq!(unpacked, command);
+    }
+
+    #[test]
+    fn pack_request_trust_center_link_key() {
+        let command = Command::RequestKey(RequestKey::trust_center_link_key());
+        let mut data = [0u8; 16];
+        let used = command.pack(&mut data).unwrap();
+        assert_eq!(data[..used], [0x08, 0x04]);
+    }
+
+    #[test]
+    fn pack_switch_key() {
+        let command = Command::SwitchKey(SwitchKey::new(0x01));
+        let mut data = [0u8; 16];
+        let used = command.pack(&mut data).unwrap();
+        assert_eq!(data[..used], [0x09, 0x01]);
+    }
+
+    #[test]
+    fn unpack_verify_and_confirm_key() {
+        let data = [
+            0x0f, 0x04, 0x77, 0x66, 0x55, 0x44, 0x33, 0x22, 0x11, 0x00, 0x00, 0x01, 0x02, 0x03,
+            0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e, 0x0f,
+        ];
+        let (command, used) = Command::unpack(&data[..]).unwrap();
+        assert_eq!(used, 26);
+        match command {
+            Command::VerifyKey(vk) => {
+                assert_eq!(vk.key_type, KeyType::UniqueTrustCenterLinkKey);
+                assert_eq!(vk.source, ExtendedAddress::new(0x0011_2233_4455_6677));
+                assert_eq!(vk.value[..4], [0x00, 0x01, 0x02, 0x03]);
+            }
+            _ => unreachable!(),
+        }
+
+        let data = [
+            0x10, 0x00, 0x04, 0x77, 0x66, 0x55, 0x44, 0x33, 0x22, 0x11, 0x00,
+        ];
+        let (command, used) = Command::unpack(&data[..]).unwrap();
+        assert_eq!(used, 11);
+        match command {
+            Command::ConfirmKey(ck) => {
+                assert_eq!(ck.status, ApplicationServiceStatus::Success);
+                assert_eq!(ck.key_type, KeyType::UniqueTrustCenterLinkKey);
+                assert_eq!(ck.destination, ExtendedAddress::new(0x0011_2233_4455_6677));
+            }
+            _ => unreachable!(),
+        }
+    }
+}
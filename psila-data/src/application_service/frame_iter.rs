@@ -0,0 +1,85 @@
+//! Iteration over back-to-back APS frames
+//!
+//! APS frames carry no length of their own, so concatenated frames are
+//! expected to each be prefixed with a one byte length, the same framing
+//! used for the packet queues.
+//!
+//! ```notrust
+//! +--------+------------+---------+--------+------------+---------+-----
+//! | Length | APS header | Payload | Length | APS header | Payload | ...
+//! +--------+------------+---------+--------+------------+---------+-----
+//! ```
+
+use crate::application_service::ApplicationServiceHeader;
+use crate::pack::Pack;
+
+/// Iterator yielding `(header, payload)` for each length-prefixed APS frame
+/// in a buffer
+///
+/// The iteration stops when the buffer is exhausted or when the remaining
+/// tail cannot hold a complete frame.
+pub struct ApsFrameIter<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> ApsFrameIter<'a> {
+    /// Create a iterator over the provided buffer
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    /// The part of the buffer which has not been consumed, non-empty after
+    /// the iteration has ended if the tail was too short to be a frame
+    pub fn remaining(&self) -> &'a [u8] {
+        self.data
+    }
+}
+
+impl<'a> Iterator for ApsFrameIter<'a> {
+    type Item = (ApplicationServiceHeader, &'a [u8]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (&length, tail) = self.data.split_first()?;
+        let length = length as usize;
+        if tail.len() < length {
+            return None;
+        }
+        let frame = &tail[..length];
+        let (header, used) = ApplicationServiceHeader::unpack(frame).ok()?;
+        self.data = &tail[length..];
+        Some((header, &frame[used..]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::application_service::header::FrameType;
+
+    #[test]
+    fn iterate_concatenated_frames() {
+        // Data frame for the On/Off cluster, a command frame and a truncated
+        // tail
+        let data = [
+            0x0a, 0x40, 0x01, 0x06, 0x00, 0x04, 0x01, 0x01, 0x2a, 0x01, 0x02, 0x03, 0x01, 0x2b,
+            0x05, 0x08, 0x40, 0x01,
+        ];
+        let mut frames = ApsFrameIter::new(&data[..]);
+
+        let (header, payload) = frames.next().unwrap();
+        assert_eq!(header.control.frame_type, FrameType::Data);
+        assert_eq!(header.cluster, Some(0x0006));
+        assert_eq!(header.profile, Some(0x0104));
+        assert_eq!(header.counter, 0x2a);
+        assert_eq!(payload, [0x01, 0x02]);
+
+        let (header, payload) = frames.next().unwrap();
+        assert_eq!(header.control.frame_type, FrameType::Command);
+        assert_eq!(header.counter, 0x2b);
+        assert_eq!(payload, [0x05]);
+
+        assert!(frames.next().is_none());
+        assert_eq!(frames.remaining(), [0x08, 0x40, 0x01]);
+        assert!(frames.next().is_none());
+    }
+}
@@ -3,12 +3,16 @@
 use core::convert::TryFrom;
 
 pub mod commands;
+mod frame_iter;
 pub mod header;
+pub mod security;
 
 use crate::Error;
 
 pub use commands::Command;
-pub use header::ApplicationServiceHeader;
+pub use frame_iter::ApsFrameIter;
+pub use header::{ApplicationServiceHeader, ExtendedHeader, Fragmentation};
+pub use security::{decrypt_frame, encrypt_frame, split_secured};
 
 extended_enum!(
     /// Status codes used in the application service sub-system
@@ -66,3 +70,85 @@ extended_enum!(
     /// issued with an unknown attribute identifier.
     UnsupportedAttribute => 0xb0,
 );
+
+/// Endpoint of the device profile (ZDP)
+pub const ZDP_ENDPOINT: u8 = 0x00;
+/// Last endpoint available to applications, the first is 1
+pub const LAST_APPLICATION_ENDPOINT: u8 = 0xf0;
+/// Endpoint of the green power proxy and sink
+pub const GREEN_POWER_ENDPOINT: u8 = 0xf2;
+/// Broadcast endpoint, the frame is delivered to all active endpoints
+pub const BROADCAST_ENDPOINT: u8 = 0xff;
+
+/// Check if `endpoint` is the device profile (ZDP) endpoint
+pub fn is_zdp_endpoint(endpoint: u8) -> bool {
+    endpoint == ZDP_ENDPOINT
+}
+
+/// Check if `endpoint` is available to applications, 1 to 240
+pub fn is_application_endpoint(endpoint: u8) -> bool {
+    endpoint != ZDP_ENDPOINT && endpoint <= LAST_APPLICATION_ENDPOINT
+}
+
+/// Check if `endpoint` is the green power endpoint
+pub fn is_green_power_endpoint(endpoint: u8) -> bool {
+    endpoint == GREEN_POWER_ENDPOINT
+}
+
+/// Check if `endpoint` is the broadcast endpoint
+pub fn is_broadcast_endpoint(endpoint: u8) -> bool {
+    endpoint == BROADCAST_ENDPOINT
+}
+
+/// Check if the APS `counter` is newer than the `last` seen counter
+///
+/// The APS counter is a single octet that wraps, so serial number arithmetic
+/// (RFC 1982) is used. A counter is newer if it is ahead of `last` by less
+/// than half the counter space. Equal counters are not newer, neither is a
+/// counter exactly half the counter space away, as the order is undefined.
+pub fn is_newer_counter(last: u8, counter: u8) -> bool {
+    let distance = counter.wrapping_sub(last);
+    distance != 0 && distance < 0x80
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn well_known_endpoints() {
+        assert!(is_zdp_endpoint(0x00));
+        assert!(!is_zdp_endpoint(0x01));
+        assert!(is_green_power_endpoint(242));
+        assert!(!is_green_power_endpoint(0xf0));
+        assert!(is_broadcast_endpoint(0xff));
+        assert!(!is_broadcast_endpoint(0x00));
+
+        assert!(!is_application_endpoint(ZDP_ENDPOINT));
+        assert!(is_application_endpoint(0x01));
+        assert!(is_application_endpoint(240));
+        assert!(!is_application_endpoint(241));
+        assert!(!is_application_endpoint(GREEN_POWER_ENDPOINT));
+        assert!(!is_application_endpoint(BROADCAST_ENDPOINT));
+    }
+
+    #[test]
+    fn counter_serial_arithmetic() {
+        assert!(is_newer_counter(0x00, 0x01));
+        assert!(is_newer_counter(0x10, 0x7f));
+        assert!(!is_newer_counter(0x10, 0x10));
+        assert!(!is_newer_counter(0x10, 0x0f));
+        assert!(!is_newer_counter(0x01, 0x00));
+        // wrap around
+        assert!(is_newer_counter(0xff, 0x00));
+        assert!(is_newer_counter(0xfe, 0x01));
+        assert!(is_newer_counter(0xf0, 0x6f));
+        assert!(!is_newer_counter(0x00, 0xff));
+        assert!(!is_newer_counter(0x01, 0xfe));
+        // half the counter space is undefined
+        assert!(!is_newer_counter(0x00, 0x80));
+        assert!(!is_newer_counter(0x80, 0x00));
+        assert!(is_newer_counter(0x00, 0x7f));
+        assert!(!is_newer_counter(0x00, 0x81));
+    }
+}
@@ -0,0 +1,204 @@
+//! # Application service layer security
+//!
+//! Encryption and decryption of frames secured on the application service
+//! layer (APS).
+
+use psila_crypto::{self, CryptoBackend};
+
+use crate::application_service::ApplicationServiceHeader;
+use crate::common::address::ExtendedAddress;
+use crate::common::key::KEY_SIZE;
+use crate::error::Error;
+use crate::network::KeyStore;
+use crate::pack::{Pack, PackFixed};
+use crate::security::{
+    decrypt_secured, derive_key, KeyIdentifier, Nonce, SecurityHeader, SecurityLevel,
+};
+
+/// Largest frame which can be decrypted
+const MAX_FRAME_SIZE: usize = 128;
+
+/// Select the key used for securing a APS frame
+///
+/// The key identifier of the auxiliary header tells which key is used. Frames
+/// secured with the network key carry the key sequence number. The data,
+/// key-transport and key-load keys are the link key shared with `source`,
+/// the latter two are derived from it when decrypting.
+fn select_key<Keys>(
+    keys: &Keys,
+    header: &SecurityHeader,
+    source: ExtendedAddress,
+) -> Result<[u8; KEY_SIZE], Error>
+where
+    Keys: KeyStore,
+{
+    if header.control.identifier == KeyIdentifier::Network {
+        let sequence = header.sequence.ok_or(Error::UnknownKeyIdentifier)?;
+        let key = keys.network_key(sequence).ok_or(Error::MissingKey)?;
+        return Ok(key.into());
+    }
+    let key = keys.link_key(source).ok_or(Error::MissingKey)?;
+    Ok(key.into())
+}
+
+/// Split the secured part of a APS frame
+///
+/// `data` shall start with the auxiliary security header, directly after the
+/// APS header. The MIC size is given by `level`, the security level is not
+/// sent over the air so the network security level shall be used.
+///
+/// Returns the auxiliary header, the cipher text and the MIC.
+pub fn split_secured(data: &[u8], level: SecurityLevel) -> Result<(&[u8], &[u8], &[u8]), Error> {
+    let (_, security_length) = SecurityHeader::unpack(data)?;
+    let mic_bytes = level.mic_bytes();
+    if data.len() < security_length + mic_bytes {
+        return Err(Error::WrongNumberOfBytes);
+    }
+    let (header, rest) = data.split_at(security_length);
+    let (payload, mic) = rest.split_at(rest.len() - mic_bytes);
+    Ok((header, payload, mic))
+}
+
+/// Parse the APS header of `frame` and decrypt the payload in place
+///
+/// If the security bit of the APS header is set, the auxiliary security
+/// header is parsed and the key is selected from `keys` using the key
+/// identifier, see `KeyStore`. `source` shall be the extended address of the
+/// NWK source, used when the auxiliary header doesn't carry the source
+/// address. The frame is decrypted and the MIC verified using the network
+/// security level, encryption with a 32-bit MIC.
+///
+/// Returns the APS header and the plain text payload. Frames without security
+/// are returned as is. Frames failing the MIC check are rejected.
+pub fn decrypt_frame<'a, Backend, Keys>(
+    backend: &mut Backend,
+    keys: &Keys,
+    source: ExtendedAddress,
+    frame: &'a mut [u8],
+) -> Result<(ApplicationServiceHeader, &'a [u8]), Error>
+where
+    Backend: CryptoBackend,
+    Keys: KeyStore,
+{
+    let (header, header_length) = ApplicationServiceHeader::unpack(frame)?;
+    if !header.control.security {
+        let frame: &'a [u8] = frame;
+        return Ok((header, &frame[header_length..]));
+    }
+    if frame.len() > MAX_FRAME_SIZE {
+        return Err(Error::WrongNumberOfBytes);
+    }
+    let (security_header, security_length) = SecurityHeader::unpack(&frame[header_length..])?;
+    let source = security_header.source.unwrap_or(source);
+    let key = select_key(keys, &security_header, source)?;
+
+    let level = SecurityLevel::EncryptedIntegrity32;
+    let mut buffer = [0u8; MAX_FRAME_SIZE];
+    let buffer = &mut buffer[..frame.len()];
+    buffer.copy_from_slice(frame);
+    let (payload_start, used) = decrypt_secured(
+        backend,
+        &key,
+        level,
+        buffer,
+        header_length,
+        Some(source),
+        &mut frame[header_length + security_length..],
+    )?;
+    let mic_offset = frame.len() - level.mic_bytes();
+    if used != mic_offset - payload_start {
+        return Err(Error::CryptoError(
+            psila_crypto::Error::IntegrityCheckFailed,
+        ));
+    }
+    let frame: &'a [u8] = frame;
+    Ok((header, &frame[payload_start..mic_offset]))
+}
+
+/// Build a APS frame with `header` and `payload` secured with `key`
+///
+/// The security bit of the APS header is set and the auxiliary header
+/// `security_header` follows the APS header. `key` is the network key or the
+/// link key depending on the key identifier of the auxiliary header, the
+/// key-transport and key-load keys are derived from the link key. `source`
+/// shall be the extended address of the sender, used for the nonce when the
+/// auxiliary header doesn't carry the source address. The payload is
+/// encrypted and the MIC appended using the network security level,
+/// encryption with a 32-bit MIC.
+///
+/// Returns the size of the frame written to `output`.
+pub fn encrypt_frame<Backend>(
+    backend: &mut Backend,
+    key: &[u8; KEY_SIZE],
+    source: ExtendedAddress,
+    header: &ApplicationServiceHeader,
+    security_header: &SecurityHeader,
+    payload: &[u8],
+    output: &mut [u8],
+) -> Result<usize, Error>
+where
+    Backend: CryptoBackend,
+{
+    let mut header = *header;
+    header.control.security = true;
+    let header_length = header.pack(output)?;
+    let level = SecurityLevel::EncryptedIntegrity32;
+    let mut security_header = *security_header;
+    security_header.control.set_level(level);
+    let security_length = security_header.pack(&mut output[header_length..])?;
+
+    let payload_start = header_length + security_length;
+    let mic_bytes = level.mic_bytes();
+    if output.len() < payload_start + payload.len() + mic_bytes {
+        return Err(Error::NotEnoughSpace);
+    }
+    let nonce = Nonce::aps(&security_header, level, source)?;
+    let key = derive_key(backend, key, security_header.control.identifier)?;
+
+    let mut mic = [0u8; 16];
+    let (additional_data, secured) = output.split_at_mut(payload_start);
+    let used = backend.ccmstar_encrypt(
+        &key,
+        nonce.as_ref(),
+        payload,
+        &mut mic[..mic_bytes],
+        additional_data,
+        &mut secured[..payload.len()],
+    )?;
+    let mic_offset = payload_start + used;
+    output[mic_offset..mic_offset + mic_bytes].copy_from_slice(&mic[..mic_bytes]);
+    // The security level is not sent over the air
+    let mut control = security_header.control;
+    control.set_level(SecurityLevel::None);
+    control.pack(&mut output[header_length..=header_length])?;
+    Ok(mic_offset + mic_bytes)
+}
+
+#[cfg(all(test, not(feature = "core")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_secured_frame() {
+        // Transport key, key-transport key, with the source address
+        let data = [
+            0x30, 0x02, 0x00, 0x00, 0x00, 0x15, 0x5a, 0x6f, 0x00, 0x00, 0x8d, 0x15, 0x00, 0xaa,
+            0xbb, 0xcc, 0x11, 0x22, 0x33, 0x44,
+        ];
+        let (header, payload, mic) =
+            split_secured(&data, SecurityLevel::EncryptedIntegrity32).unwrap();
+        assert_eq!(header, &data[..13]);
+        assert_eq!(payload, [0xaa, 0xbb, 0xcc]);
+        assert_eq!(mic, [0x11, 0x22, 0x33, 0x44]);
+
+        let (_, payload, mic) = split_secured(&data[..17], SecurityLevel::Integrity32).unwrap();
+        assert!(payload.is_empty());
+        assert_eq!(mic, [0xaa, 0xbb, 0xcc, 0x11]);
+
+        assert_eq!(
+            split_secured(&data[..16], SecurityLevel::EncryptedIntegrity32),
+            Err(Error::WrongNumberOfBytes)
+        );
+        assert!(split_secured(&data[..4], SecurityLevel::EncryptedIntegrity32).is_err());
+    }
+}
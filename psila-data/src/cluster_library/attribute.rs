@@ -1,11 +1,10 @@
 use core::convert::TryFrom;
 
-use crate::pack::Pack;
 use crate::Error;
 
 use byteorder::{ByteOrder, LittleEndian};
 
-use crate::common::types::{CharacterString, OctetString};
+use crate::common::types::{character_string, octet_string, CharacterString, OctetString};
 
 extended_enum!(
     /// Attribute data type
@@ -261,6 +260,8 @@ pub enum AttributeValue {
     Enumeration8(u8),
     /// 16-bit enumeration
     Enumeration16(u16),
+    /// 16-bit (semi-precision) floating point, raw IEEE 754 binary16 value
+    FloatingPoint16(u16),
     /// 32-bit floating point
     FloatingPoint32(f32),
     /// 64-bit floating point
@@ -269,10 +270,14 @@ pub enum AttributeValue {
     OctetString(Option<OctetString>),
     /// Character string (byte array) with max length 256 characters
     CharacterString(Option<CharacterString>),
-    /*
-        LongOctetString(Option<Vec<u8>>),
-        LongCharacterString(Option<String>),
-    */
+    /// Long octet string (byte array) with a 16-bit length
+    ///
+    /// With the `core` feature the length is limited to 256 octets
+    LongOctetString(Option<OctetString>),
+    /// Long character string with a 16-bit length
+    ///
+    /// With the `core` feature the length is limited to 256 octets
+    LongCharacterString(Option<CharacterString>),
     /// Time of day, per octet; hours,minutes,seconds,hundreds-of-seconds
     TimeOfDay(u32),
     /// Date, per octet; year since 1900,month,day of month,day of week
@@ -283,8 +288,73 @@ pub enum AttributeValue {
     ClusterIdentifier(u16),
     /// 16-bit attribute identifier
     AttributeIdentifier(u16),
+    /// BACnet OID
+    BuildingAutomationControlNetworkObjectIdentifier(u32),
     /// 64-bit IEEE address
     IeeeAddress(u64),
+    /// 128-bit security key
+    Key128([u8; 16]),
+}
+
+/// Length prefix marking a invalid short string
+const SHORT_STRING_INVALID: u16 = 0xff;
+/// Length prefix marking a invalid long string
+const LONG_STRING_INVALID: u16 = 0xffff;
+
+/// Read a length prefixed string, one octet prefix for short strings and two
+/// octets for long strings
+///
+/// Returns `None` for the invalid string, where the prefix is all ones, and
+/// the number of octets used.
+fn unpack_string(data: &[u8], long: bool) -> Result<(Option<&[u8]>, usize), Error> {
+    let (length, invalid, prefix) = if long {
+        if data.len() < 2 {
+            return Err(Error::WrongNumberOfBytes);
+        }
+        (LittleEndian::read_u16(&data[0..2]), LONG_STRING_INVALID, 2)
+    } else {
+        if data.is_empty() {
+            return Err(Error::WrongNumberOfBytes);
+        }
+        (u16::from(data[0]), SHORT_STRING_INVALID, 1)
+    };
+    if length == invalid {
+        return Ok((None, prefix));
+    }
+    let end = prefix + length as usize;
+    if data.len() < end {
+        return Err(Error::WrongNumberOfBytes);
+    }
+    Ok((Some(&data[prefix..end]), end))
+}
+
+/// Write a length prefixed string, `None` is written as the invalid string
+fn pack_string(value: Option<&[u8]>, long: bool, data: &mut [u8]) -> Result<usize, Error> {
+    let (invalid, prefix) = if long {
+        (LONG_STRING_INVALID, 2)
+    } else {
+        (SHORT_STRING_INVALID, 1)
+    };
+    let (length, value) = match value {
+        Some(value) => {
+            if value.len() >= invalid as usize {
+                return Err(Error::InvalidValue);
+            }
+            (value.len() as u16, value)
+        }
+        None => (invalid, &[][..]),
+    };
+    let end = prefix + value.len();
+    if data.len() < end {
+        return Err(Error::WrongNumberOfBytes);
+    }
+    if long {
+        LittleEndian::write_u16(&mut data[0..2], length);
+    } else {
+        data[0] = length as u8;
+    }
+    data[prefix..end].copy_from_slice(value);
+    Ok(end)
 }
 
 impl AttributeValue {
@@ -309,6 +379,7 @@ impl AttributeValue {
             | AttributeValue::Bitmap16(value)
             | AttributeValue::Unsigned16(value)
             | AttributeValue::Enumeration16(value)
+            | AttributeValue::FloatingPoint16(value)
             | AttributeValue::ClusterIdentifier(value)
             | AttributeValue::AttributeIdentifier(value) => {
                 LittleEndian::write_u16(&mut data[0..2], *value);
@@ -325,7 +396,8 @@ impl AttributeValue {
             | AttributeValue::Unsigned32(value)
             | AttributeValue::TimeOfDay(value)
             | AttributeValue::Date(value)
-            | AttributeValue::UtcTime(value) => {
+            | AttributeValue::UtcTime(value)
+            | AttributeValue::BuildingAutomationControlNetworkObjectIdentifier(value) => {
                 LittleEndian::write_u32(&mut data[0..4], *value);
                 4
             }
@@ -381,41 +453,25 @@ impl AttributeValue {
                 LittleEndian::write_f32(&mut data[0..4], *value);
                 4
             }
+            AttributeValue::Key128(value) => {
+                data[0..16].copy_from_slice(value);
+                16
+            }
             AttributeValue::FloatingPoint64(value) => {
                 LittleEndian::write_f64(&mut data[0..8], *value);
                 8
             }
             AttributeValue::OctetString(value) => {
-                if data.is_empty() {
-                    return Err(Error::WrongNumberOfBytes);
-                }
-                if let Some(value) = value {
-                    if data.len() <= value.len() {
-                        return Err(Error::WrongNumberOfBytes);
-                    }
-                    data[0] = value.len() as u8;
-                    let used = value.pack(&mut data[1..])?;
-                    1 + used
-                } else {
-                    data[0] = 0xff;
-                    1
-                }
+                pack_string(value.as_ref().map(|v| &v[..]), false, data)?
             }
             AttributeValue::CharacterString(value) => {
-                if data.is_empty() {
-                    return Err(Error::WrongNumberOfBytes);
-                }
-                if let Some(value) = value {
-                    if data.len() <= value.len() {
-                        return Err(Error::WrongNumberOfBytes);
-                    }
-                    data[0] = value.len() as u8;
-                    let used = value.pack(&mut data[1..])?;
-                    1 + used
-                } else {
-                    data[0] = 0xff;
-                    1
-                }
+                pack_string(value.as_ref().map(|v| v.as_bytes()), false, data)?
+            }
+            AttributeValue::LongOctetString(value) => {
+                pack_string(value.as_ref().map(|v| &v[..]), true, data)?
+            }
+            AttributeValue::LongCharacterString(value) => {
+                pack_string(value.as_ref().map(|v| v.as_bytes()), true, data)?
             }
         };
         Ok((length, data_type))
@@ -569,6 +625,10 @@ impl AttributeValue {
                 let value = LittleEndian::read_u16(&data[0..2]);
                 Ok((AttributeValue::Enumeration16(value), 2))
             }
+            AttributeDataType::FloatingPoint16 => {
+                let value = LittleEndian::read_u16(&data[0..2]);
+                Ok((AttributeValue::FloatingPoint16(value), 2))
+            }
             AttributeDataType::FloatingPoint32 => Ok((
                 AttributeValue::FloatingPoint32(LittleEndian::read_f32(&data[0..4])),
                 4,
@@ -577,79 +637,32 @@ impl AttributeValue {
                 AttributeValue::FloatingPoint64(LittleEndian::read_f64(&data[0..8])),
                 8,
             )),
-            AttributeDataType::OctetString => {
-                if data.is_empty() {
-                    return Err(Error::WrongNumberOfBytes);
-                }
-                let length = match data[0] {
-                    0xff => None,
-                    v => Some(v as usize),
+            AttributeDataType::OctetString | AttributeDataType::LongOctetString => {
+                let long = data_type == AttributeDataType::LongOctetString;
+                let (value, used) = unpack_string(data, long)?;
+                let value = match value {
+                    Some(value) => Some(octet_string(value)?),
+                    None => None,
                 };
-                let (value, used) = if length.is_some() {
-                    let (value, used) = OctetString::unpack(&data)?;
-                    (Some(value), used)
+                if long {
+                    Ok((AttributeValue::LongOctetString(value), used))
                 } else {
-                    (None, 1)
-                };
-                Ok((AttributeValue::OctetString(value), used))
-            }
-            AttributeDataType::CharacterString => {
-                if data.is_empty() {
-                    return Err(Error::WrongNumberOfBytes);
+                    Ok((AttributeValue::OctetString(value), used))
                 }
-                let length = match data[0] {
-                    0xff => None,
-                    v => Some(v as usize),
+            }
+            AttributeDataType::CharacterString | AttributeDataType::LongCharacterString => {
+                let long = data_type == AttributeDataType::LongCharacterString;
+                let (value, used) = unpack_string(data, long)?;
+                let value = match value {
+                    Some(value) => Some(character_string(value)?),
+                    None => None,
                 };
-                let (value, used) = if length.is_some() {
-                    let (value, used) = CharacterString::unpack(&data)?;
-                    (Some(value), used)
+                if long {
+                    Ok((AttributeValue::LongCharacterString(value), used))
                 } else {
-                    (None, 1)
-                };
-                Ok((AttributeValue::CharacterString(value), used))
-            }
-            /*
-                        AttributeDataType::LongOctetString => {
-                            if data.len() < 2 {
-                                return Err(Error::WrongNumberOfBytes);
-                            }
-                            let length = match LittleEndian::read_u16(&data[0..2]) {
-                                0xffff => None,
-                                v => Some(v as usize),
-                            };
-                            let (value, used) = if let Some(length) = length {
-                                if data.len() < length + 2 {
-                                    return Err(Error::WrongNumberOfBytes);
-                                }
-                                (Some(data[2..length + 2].to_vec()), length + 2)
-                            } else {
-                                (None, 2)
-                            };
-                            Ok((AttributeValue::LongOctetString(value), used))
-                        }
-                        AttributeDataType::LongCharacterString => {
-                            if data.len() < 2 {
-                                return Err(Error::WrongNumberOfBytes);
-                            }
-                            let length = match LittleEndian::read_u16(&data[0..2]) {
-                                0xffff => None,
-                                v => Some(v as usize),
-                            };
-                            let (value, used) = if let Some(length) = length {
-                                if data.len() < length + 1 {
-                                    return Err(Error::WrongNumberOfBytes);
-                                }
-                                match String::from_utf8(data[1..=length].to_vec()) {
-                                    Ok(s) => (Some(s), length + 1),
-                                    Err(_) => (None, length + 1),
-                                }
-                            } else {
-                                (None, 1)
-                            };
-                            Ok((AttributeValue::CharacterString(value), used))
-                        }
-            */
+                    Ok((AttributeValue::CharacterString(value), used))
+                }
+            }
             AttributeDataType::TimeOfDay => {
                 let value = LittleEndian::read_u32(&data[0..4]);
                 Ok((AttributeValue::TimeOfDay(value), 4))
@@ -668,12 +681,24 @@ impl AttributeValue {
             }
             AttributeDataType::AttributeIdentifier => {
                 let value = LittleEndian::read_u16(&data[0..2]);
-                Ok((AttributeValue::ClusterIdentifier(value), 2))
+                Ok((AttributeValue::AttributeIdentifier(value), 2))
+            }
+            AttributeDataType::BuildingAutomationControlNetworkObjectIdentifier => {
+                let value = LittleEndian::read_u32(&data[0..4]);
+                Ok((
+                    AttributeValue::BuildingAutomationControlNetworkObjectIdentifier(value),
+                    4,
+                ))
             }
             AttributeDataType::IeeeAddress => {
                 let value = LittleEndian::read_u64(&data[0..8]);
                 Ok((AttributeValue::IeeeAddress(value), 8))
             }
+            AttributeDataType::Key128 => {
+                let mut value = [0; 16];
+                value.copy_from_slice(&data[0..16]);
+                Ok((AttributeValue::Key128(value), 16))
+            }
             _ => Err(Error::UnsupportedAttributeValue),
         }
     }
@@ -717,20 +742,67 @@ impl AttributeValue {
             AttributeValue::Signed64(_) => AttributeDataType::Signed64,
             AttributeValue::Enumeration8(_) => AttributeDataType::Enumeration8,
             AttributeValue::Enumeration16(_) => AttributeDataType::Enumeration16,
+            AttributeValue::FloatingPoint16(_) => AttributeDataType::FloatingPoint16,
             AttributeValue::FloatingPoint32(_) => AttributeDataType::FloatingPoint32,
             AttributeValue::FloatingPoint64(_) => AttributeDataType::FloatingPoint64,
             AttributeValue::OctetString(_) => AttributeDataType::OctetString,
             AttributeValue::CharacterString(_) => AttributeDataType::CharacterString,
-            /*
-                        AttributeValue::LongOctetString(_) => AttributeDataType::LongOctetString,
-                        AttributeValue::LongCharacterString(_) => AttributeDataType::LongCharacterString,
-            */
+            AttributeValue::LongOctetString(_) => AttributeDataType::LongOctetString,
+            AttributeValue::LongCharacterString(_) => AttributeDataType::LongCharacterString,
             AttributeValue::TimeOfDay(_) => AttributeDataType::TimeOfDay,
             AttributeValue::Date(_) => AttributeDataType::Date,
             AttributeValue::UtcTime(_) => AttributeDataType::UtcTime,
             AttributeValue::ClusterIdentifier(_) => AttributeDataType::ClusterIdentifier,
             AttributeValue::AttributeIdentifier(_) => AttributeDataType::AttributeIdentifier,
+            AttributeValue::BuildingAutomationControlNetworkObjectIdentifier(_) => {
+                AttributeDataType::BuildingAutomationControlNetworkObjectIdentifier
+            }
             AttributeValue::IeeeAddress(_) => AttributeDataType::IeeeAddress,
+            AttributeValue::Key128(_) => AttributeDataType::Key128,
+        }
+    }
+
+    /// Get the raw integer of a bitmap value, the value of the bitmap types
+    /// with odd widths are returned in the lower bits
+    pub fn as_bitmap(&self) -> Option<u64> {
+        fn from_octets(octets: &[u8]) -> u64 {
+            octets
+                .iter()
+                .rev()
+                .fold(0u64, |value, octet| value << 8 | u64::from(*octet))
+        }
+        match self {
+            AttributeValue::Bitmap8(v) => Some(u64::from(*v)),
+            AttributeValue::Bitmap16(v) => Some(u64::from(*v)),
+            AttributeValue::Bitmap24(v) | AttributeValue::Bitmap32(v) => Some(u64::from(*v)),
+            AttributeValue::Bitmap40(v) => Some(from_octets(v)),
+            AttributeValue::Bitmap48(v) => Some(from_octets(v)),
+            AttributeValue::Bitmap56(v) => Some(from_octets(v)),
+            AttributeValue::Bitmap64(v) => Some(*v),
+            _ => None,
+        }
+    }
+
+    /// Test a bit of a bitmap value
+    ///
+    /// Returns `None` if the value isn't a bitmap or if the bit is outside
+    /// the width of the bitmap
+    pub fn bit(&self, bit: u8) -> Option<bool> {
+        let value = self.as_bitmap()?;
+        let width = self.data_type().num_octets()? * 8;
+        if usize::from(bit) >= width {
+            return None;
+        }
+        Some(value & (1 << bit) != 0)
+    }
+
+    /// Get the value of a boolean, `None` if the value isn't a boolean or
+    /// if the boolean is invalid
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            AttributeValue::Boolean(0x00) => Some(false),
+            AttributeValue::Boolean(0x01) => Some(true),
+            _ => None,
         }
     }
 
@@ -753,7 +825,8 @@ impl AttributeValue {
             | AttributeValue::Bitmap40(_)
             | AttributeValue::Bitmap48(_)
             | AttributeValue::Bitmap56(_)
-            | AttributeValue::Bitmap64(_) => true,
+            | AttributeValue::Bitmap64(_)
+            | AttributeValue::Key128(_) => true,
             AttributeValue::Boolean(v) => *v == 0x00 || *v == 0x01,
             AttributeValue::Unsigned8(v) | AttributeValue::Enumeration8(v) => *v != u8::max_value(),
             AttributeValue::Unsigned16(v)
@@ -764,7 +837,10 @@ impl AttributeValue {
             AttributeValue::Unsigned32(v)
             | AttributeValue::TimeOfDay(v)
             | AttributeValue::Date(v)
-            | AttributeValue::UtcTime(v) => *v != u32::max_value(),
+            | AttributeValue::UtcTime(v)
+            | AttributeValue::BuildingAutomationControlNetworkObjectIdentifier(v) => {
+                *v != u32::max_value()
+            }
             AttributeValue::Unsigned40(v) => *v != [0xff; 5],
             AttributeValue::Unsigned48(v) => *v != [0xff; 6],
             AttributeValue::Unsigned56(v) => *v != [0xff; 7],
@@ -779,14 +855,14 @@ impl AttributeValue {
             AttributeValue::Signed48(v) => *v != [0x80, 0x00, 0x00, 0x00, 0x00, 0x00],
             AttributeValue::Signed56(v) => *v != [0x80, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00],
             AttributeValue::Signed64(v) => *v != i64::min_value(),
-            AttributeValue::FloatingPoint32(v) => !v.is_normal(),
-            AttributeValue::FloatingPoint64(v) => !v.is_normal(),
+            // Not a number, exponent all ones and a non-zero mantissa
+            AttributeValue::FloatingPoint16(v) => *v & 0x7c00 != 0x7c00 || *v & 0x03ff == 0,
+            AttributeValue::FloatingPoint32(v) => !v.is_nan(),
+            AttributeValue::FloatingPoint64(v) => !v.is_nan(),
             AttributeValue::OctetString(v) => v.is_some(),
             AttributeValue::CharacterString(v) => v.is_some(),
-            /*
-                        AttributeValue::LongOctetString(v) => v.is_some(),
-                        AttributeValue::LongCharacterString(v) => v.is_some(),
-            */
+            AttributeValue::LongOctetString(v) => v.is_some(),
+            AttributeValue::LongCharacterString(v) => v.is_some(),
         }
     }
 }
@@ -843,9 +919,10 @@ impl std::fmt::Display for AttributeValue {
                 AttributeValue::Signed16(v) => write!(f, "{}", v),
                 AttributeValue::Signed24(v) | AttributeValue::Signed32(v) => write!(f, "{}", v),
                 AttributeValue::Signed64(v) => write!(f, "{}", v),
+                AttributeValue::FloatingPoint16(v) => write!(f, "{:04x}", v),
                 AttributeValue::FloatingPoint32(v) => write!(f, "{}", v),
                 AttributeValue::FloatingPoint64(v) => write!(f, "{}", v),
-                AttributeValue::OctetString(v) => {
+                AttributeValue::OctetString(v) | AttributeValue::LongOctetString(v) => {
                     if let Some(v) = v {
                         let hex: String = v.iter().map(|i| format!("{:02x}", i)).collect();
                         write!(f, "{}", hex)
@@ -853,7 +930,7 @@ impl std::fmt::Display for AttributeValue {
                         write!(f, "{}", STRING_INVALID)
                     }
                 }
-                AttributeValue::CharacterString(v) => {
+                AttributeValue::CharacterString(v) | AttributeValue::LongCharacterString(v) => {
                     if let Some(v) = v {
                         write!(f, "{}", v)
                     } else {
@@ -878,7 +955,14 @@ impl std::fmt::Display for AttributeValue {
                 AttributeValue::ClusterIdentifier(v) | AttributeValue::AttributeIdentifier(v) => {
                     write!(f, "{:04x}", v)
                 }
+                AttributeValue::BuildingAutomationControlNetworkObjectIdentifier(v) => {
+                    write!(f, "{:08x}", v)
+                }
                 AttributeValue::IeeeAddress(v) => write!(f, "{:08x}", v),
+                AttributeValue::Key128(v) => {
+                    let hex: String = v.iter().map(|i| format!("{:02x}", i)).collect();
+                    write!(f, "{}", hex)
+                }
             }
         }
     }
@@ -1072,4 +1156,181 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn bitmap_value() {
+        let data = [0x05, 0x80];
+        let (value, used) = AttributeValue::unpack(&data[..], AttributeDataType::Bitmap16).unwrap();
+        assert_eq!(used, 2);
+        assert_eq!(value, AttributeValue::Bitmap16(0x8005));
+        assert_eq!(value.as_bitmap(), Some(0x8005));
+        assert_eq!(value.bit(0), Some(true));
+        assert_eq!(value.bit(1), Some(false));
+        assert_eq!(value.bit(2), Some(true));
+        assert_eq!(value.bit(15), Some(true));
+        assert_eq!(value.bit(16), None);
+
+        let data = [0x01, 0x02, 0x03, 0x04, 0x05];
+        let (value, _) = AttributeValue::unpack(&data[..], AttributeDataType::Bitmap40).unwrap();
+        assert_eq!(value.as_bitmap(), Some(0x05_0403_0201));
+        assert_eq!(value.bit(32), Some(true));
+        assert_eq!(value.bit(40), None);
+
+        assert_eq!(AttributeValue::Unsigned16(0x8005).as_bitmap(), None);
+        assert_eq!(AttributeValue::Unsigned16(0x8005).bit(0), None);
+    }
+
+    #[test]
+    fn boolean_value() {
+        assert_eq!(AttributeValue::Boolean(0x00).as_bool(), Some(false));
+        assert_eq!(AttributeValue::Boolean(0x01).as_bool(), Some(true));
+        assert_eq!(AttributeValue::Boolean(0xff).as_bool(), None);
+        assert_eq!(AttributeValue::Bitmap8(0x01).as_bool(), None);
+    }
+
+    #[test]
+    fn unsigned48_value() {
+        let data = [0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0xff];
+        let (value, used) =
+            AttributeValue::unpack(&data[..], AttributeDataType::Unsigned48).unwrap();
+        assert_eq!(used, 6);
+        assert_eq!(
+            value,
+            AttributeValue::Unsigned48([0x01, 0x02, 0x03, 0x04, 0x05, 0x06])
+        );
+        assert!(value.is_valid());
+        let mut buffer = [0u8; 8];
+        let (used, data_type) = value.pack(&mut buffer).unwrap();
+        assert_eq!(used, 6);
+        assert_eq!(data_type, AttributeDataType::Unsigned48);
+        assert_eq!(buffer[..used], data[..6]);
+
+        let data = [0xff; 6];
+        let (value, _) = AttributeValue::unpack(&data[..], AttributeDataType::Unsigned48).unwrap();
+        assert!(!value.is_valid());
+    }
+
+    #[test]
+    fn single_precision_value() {
+        // 1.5 as IEEE 754 binary32
+        let data = [0x00, 0x00, 0xc0, 0x3f];
+        let (value, used) =
+            AttributeValue::unpack(&data[..], AttributeDataType::FloatingPoint32).unwrap();
+        assert_eq!(used, 4);
+        assert_eq!(value, AttributeValue::FloatingPoint32(1.5));
+        assert!(value.is_valid());
+        let mut buffer = [0u8; 4];
+        let (used, _) = value.pack(&mut buffer).unwrap();
+        assert_eq!(buffer[..used], data);
+
+        assert!(!AttributeValue::FloatingPoint32(core::f32::NAN).is_valid());
+    }
+
+    #[test]
+    fn identifier_and_key_values() {
+        let data = [0x34, 0x12];
+        let (value, _) =
+            AttributeValue::unpack(&data[..], AttributeDataType::AttributeIdentifier).unwrap();
+        assert_eq!(value, AttributeValue::AttributeIdentifier(0x1234));
+
+        let data = [0x00, 0x3c];
+        let (value, _) =
+            AttributeValue::unpack(&data[..], AttributeDataType::FloatingPoint16).unwrap();
+        assert_eq!(value, AttributeValue::FloatingPoint16(0x3c00));
+
+        let data = [0xaa; 16];
+        let (value, used) = AttributeValue::unpack(&data[..], AttributeDataType::Key128).unwrap();
+        assert_eq!(used, 16);
+        assert_eq!(value, AttributeValue::Key128([0xaa; 16]));
+    }
+
+    #[test]
+    fn short_strings() {
+        let data = [0x05, b'P', b's', b'i', b'l', b'a', 0x00];
+        let (value, used) =
+            AttributeValue::unpack(&data[..], AttributeDataType::CharacterString).unwrap();
+        assert_eq!(used, 6);
+        assert_eq!(
+            value,
+            AttributeValue::CharacterString(Some("Psila".to_string()))
+        );
+        assert_eq!(format!("{}", value), "Psila");
+        let mut buffer = [0u8; 8];
+        let (used, data_type) = value.pack(&mut buffer).unwrap();
+        assert_eq!(data_type, AttributeDataType::CharacterString);
+        assert_eq!(buffer[..used], data[..6]);
+
+        // Empty string
+        let data = [0x00];
+        let (value, used) =
+            AttributeValue::unpack(&data[..], AttributeDataType::OctetString).unwrap();
+        assert_eq!(used, 1);
+        assert_eq!(value, AttributeValue::OctetString(Some(vec![])));
+        assert!(value.is_valid());
+        let (used, _) = value.pack(&mut buffer).unwrap();
+        assert_eq!(buffer[..used], data);
+
+        // Invalid string, no octets are read after the length
+        let data = [0xff, 0x01];
+        let (value, used) =
+            AttributeValue::unpack(&data[..], AttributeDataType::OctetString).unwrap();
+        assert_eq!(used, 1);
+        assert_eq!(value, AttributeValue::OctetString(None));
+        assert!(!value.is_valid());
+        assert_eq!(format!("{}", value), "Invalid");
+        let (used, _) = value.pack(&mut buffer).unwrap();
+        assert_eq!(buffer[..used], [0xff]);
+
+        // Truncated string
+        let data = [0x03, 0x01, 0x02];
+        assert_eq!(
+            AttributeValue::unpack(&data[..], AttributeDataType::OctetString),
+            Err(Error::WrongNumberOfBytes)
+        );
+    }
+
+    #[test]
+    fn long_strings() {
+        let data = [0x03, 0x00, 0x01, 0x02, 0x03];
+        let (value, used) =
+            AttributeValue::unpack(&data[..], AttributeDataType::LongOctetString).unwrap();
+        assert_eq!(used, 5);
+        assert_eq!(value, AttributeValue::LongOctetString(Some(vec![1, 2, 3])));
+        assert_eq!(format!("{}", value), "010203");
+        let mut buffer = [0u8; 8];
+        let (used, data_type) = value.pack(&mut buffer).unwrap();
+        assert_eq!(data_type, AttributeDataType::LongOctetString);
+        assert_eq!(buffer[..used], data);
+
+        // Empty string
+        let data = [0x00, 0x00];
+        let (value, used) =
+            AttributeValue::unpack(&data[..], AttributeDataType::LongCharacterString).unwrap();
+        assert_eq!(used, 2);
+        assert_eq!(
+            value,
+            AttributeValue::LongCharacterString(Some(String::new()))
+        );
+        assert!(value.is_valid());
+        let (used, _) = value.pack(&mut buffer).unwrap();
+        assert_eq!(buffer[..used], data);
+
+        // Invalid string, no octets are read after the length
+        let data = [0xff, 0xff, 0x41];
+        let (value, used) =
+            AttributeValue::unpack(&data[..], AttributeDataType::LongCharacterString).unwrap();
+        assert_eq!(used, 2);
+        assert_eq!(value, AttributeValue::LongCharacterString(None));
+        assert!(!value.is_valid());
+        assert_eq!(format!("{}", value), "Invalid");
+        let (used, _) = value.pack(&mut buffer).unwrap();
+        assert_eq!(buffer[..used], [0xff, 0xff]);
+
+        // Not UTF-8
+        let data = [0x01, 0x00, 0xc3];
+        assert_eq!(
+            AttributeValue::unpack(&data[..], AttributeDataType::LongCharacterString),
+            Err(Error::InvalidValue)
+        );
+    }
 }
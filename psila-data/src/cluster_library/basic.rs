@@ -0,0 +1,225 @@
+//! # Basic cluster
+//!
+//! Basic information about a device, such as the manufacturer and model, and
+//! the command for resetting the device to factory defaults.
+
+use core::convert::TryFrom;
+
+use crate::cluster_library::{
+    AttributeIdentifier, AttributeIdentifierVec, AttributeValue, ClusterCommand,
+    ClusterLibraryHeader, ClusterLibraryStatus, Direction, ReadAttributes, ReadAttributesResponse,
+};
+use crate::Error;
+
+/// Basic cluster identifier
+pub const CLUSTER_IDENTIFIER: u16 = 0x0000;
+
+/// Power source bit telling that there is a secondary battery backup
+const BATTERY_BACKUP: u8 = 0x80;
+
+extended_enum!(
+    /// Basic cluster attribute identifiers
+    BasicAttribute, u16,
+    /// Version of the cluster library, unsigned 8-bit integer
+    ZclVersion => 0x0000,
+    /// Version of the application, unsigned 8-bit integer
+    ApplicationVersion => 0x0001,
+    /// Version of the stack, unsigned 8-bit integer
+    StackVersion => 0x0002,
+    /// Version of the hardware, unsigned 8-bit integer
+    HardwareVersion => 0x0003,
+    /// Name of the manufacturer, character string
+    ManufacturerName => 0x0004,
+    /// Model identifier, character string
+    ModelIdentifier => 0x0005,
+    /// Date of manufacturing, character string
+    DateCode => 0x0006,
+    /// Power source, see `PowerSource`
+    PowerSource => 0x0007,
+    /// Location of the device, character string
+    LocationDescription => 0x0010,
+    /// Physical environment of the device
+    PhysicalEnvironment => 0x0011,
+    /// Device enabled
+    DeviceEnabled => 0x0012,
+    /// Alarm mask
+    AlarmMask => 0x0013,
+    /// Disable local configuration
+    DisableLocalConfig => 0x0014,
+    /// Software build identifier, character string
+    SoftwareBuildIdentifier => 0x4000,
+);
+
+impl From<BasicAttribute> for AttributeIdentifier {
+    fn from(value: BasicAttribute) -> Self {
+        AttributeIdentifier::new(u16::from(value))
+    }
+}
+
+extended_enum!(
+    /// Primary power source of a device
+    PowerSource, u8,
+    /// Unknown power source
+    Unknown => 0x00,
+    /// Mains, single phase
+    MainsSinglePhase => 0x01,
+    /// Mains, three phase
+    MainsThreePhase => 0x02,
+    /// Battery
+    Battery => 0x03,
+    /// DC source
+    DcSource => 0x04,
+    /// Emergency mains, constantly powered
+    EmergencyMainsConstant => 0x05,
+    /// Emergency mains and transfer switch
+    EmergencyMainsTransferSwitch => 0x06,
+);
+
+extended_enum!(
+    /// Basic cluster command identifiers, sent from client to server
+    BasicCommand, u8,
+    /// Reset all attributes of all clusters to the factory defaults
+    ResetToFactoryDefaults => 0x00,
+);
+
+impl ClusterCommand for BasicCommand {
+    fn from_identifier(direction: Direction, identifier: u8) -> Result<Self, Error> {
+        match direction {
+            Direction::ToServer => Self::try_from(identifier),
+            Direction::ToClient => Err(Error::InvalidValue),
+        }
+    }
+}
+
+/// Create the header of a reset to factory defaults command, the command
+/// has no payload
+pub fn reset_to_factory_defaults(transaction_sequence: u8) -> ClusterLibraryHeader {
+    ClusterLibraryHeader::new_cluster_command(
+        Direction::ToServer,
+        transaction_sequence,
+        u8::from(BasicCommand::ResetToFactoryDefaults),
+        false,
+    )
+}
+
+/// Create a read attributes command reading `attributes`
+pub fn read_attributes(attributes: &[BasicAttribute]) -> ReadAttributes {
+    let mut identifiers = AttributeIdentifierVec::new();
+    for attribute in attributes {
+        identifiers.push(AttributeIdentifier::from(*attribute));
+    }
+    ReadAttributes {
+        attributes: identifiers,
+    }
+}
+
+/// Get the value of `attribute` from a read attributes response
+fn attribute_value(
+    response: &ReadAttributesResponse,
+    attribute: BasicAttribute,
+) -> Option<&AttributeValue> {
+    response
+        .attributes
+        .iter()
+        .filter(|a| a.identifier == u16::from(attribute))
+        .filter(|a| a.status == ClusterLibraryStatus::Success)
+        .find_map(|a| a.value.as_ref())
+}
+
+fn unsigned8(response: &ReadAttributesResponse, attribute: BasicAttribute) -> Option<u8> {
+    match attribute_value(response, attribute) {
+        Some(AttributeValue::Unsigned8(value)) => Some(*value),
+        _ => None,
+    }
+}
+
+fn string(response: &ReadAttributesResponse, attribute: BasicAttribute) -> Option<&str> {
+    match attribute_value(response, attribute) {
+        Some(AttributeValue::CharacterString(Some(value))) => Some(value.as_str()),
+        _ => None,
+    }
+}
+
+/// Get the cluster library version from a read attributes response
+pub fn zcl_version(response: &ReadAttributesResponse) -> Option<u8> {
+    unsigned8(response, BasicAttribute::ZclVersion)
+}
+
+/// Get the application version from a read attributes response
+pub fn application_version(response: &ReadAttributesResponse) -> Option<u8> {
+    unsigned8(response, BasicAttribute::ApplicationVersion)
+}
+
+/// Get the manufacturer name from a read attributes response
+pub fn manufacturer_name(response: &ReadAttributesResponse) -> Option<&str> {
+    string(response, BasicAttribute::ManufacturerName)
+}
+
+/// Get the model identifier from a read attributes response
+pub fn model_identifier(response: &ReadAttributesResponse) -> Option<&str> {
+    string(response, BasicAttribute::ModelIdentifier)
+}
+
+/// Get the power source from a read attributes response, together with
+/// whether the device has a secondary battery backup
+pub fn power_source(response: &ReadAttributesResponse) -> Option<(PowerSource, bool)> {
+    match attribute_value(response, BasicAttribute::PowerSource) {
+        Some(AttributeValue::Enumeration8(value)) => {
+            let source = PowerSource::try_from(value & !BATTERY_BACKUP).ok()?;
+            Some((source, value & BATTERY_BACKUP == BATTERY_BACKUP))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(all(test, not(feature = "core")))]
+mod tests {
+    use super::*;
+    use crate::pack::Pack;
+
+    #[test]
+    fn unpack_model_identifier() {
+        // Model identifier, success, character string
+        let data = [
+            0x05, 0x00, 0x00, 0x42, 0x1e, 0x54, 0x52, 0x41, 0x44, 0x46, 0x52, 0x49, 0x20, 0x62,
+            0x75, 0x6c, 0x62, 0x20, 0x45, 0x32, 0x37, 0x20, 0x57, 0x20, 0x6f, 0x70, 0x61, 0x6c,
+            0x20, 0x31, 0x30, 0x30, 0x30, 0x6c, 0x6d, 0x07, 0x00, 0x00, 0x30, 0x81, 0x04, 0x00,
+            0x86,
+        ];
+        let (response, used) = ReadAttributesResponse::unpack(&data).unwrap();
+        assert_eq!(used, data.len());
+        assert_eq!(
+            model_identifier(&response),
+            Some("TRADFRI bulb E27 W opal 1000lm")
+        );
+        assert_eq!(
+            power_source(&response),
+            Some((PowerSource::MainsSinglePhase, true))
+        );
+        // Unsupported attribute
+        assert_eq!(manufacturer_name(&response), None);
+        assert_eq!(zcl_version(&response), None);
+    }
+
+    #[test]
+    fn pack_basic_commands() {
+        let cmd = read_attributes(&[
+            BasicAttribute::ManufacturerName,
+            BasicAttribute::ModelIdentifier,
+        ]);
+        let mut data = [0u8; 4];
+        let used = cmd.pack(&mut data[..]).unwrap();
+        assert_eq!(used, 4);
+        assert_eq!(data, [0x04, 0x00, 0x05, 0x00]);
+
+        let header = reset_to_factory_defaults(0x12);
+        let mut data = [0u8; 3];
+        let used = header.pack(&mut data[..]).unwrap();
+        assert_eq!(used, 3);
+        assert_eq!(data, [0x01, 0x12, 0x00]);
+        assert_eq!(
+            header.cluster_command::<BasicCommand>(),
+            Ok(BasicCommand::ResetToFactoryDefaults)
+        );
+    }
+}
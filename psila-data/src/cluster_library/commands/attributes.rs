@@ -162,7 +162,8 @@ impl Pack<WriteAttributeRecord, Error> for WriteAttributeRecord {
             return Err(Error::WrongNumberOfBytes);
         }
         let identifier = AttributeIdentifier::unpack(&data[0..2])?;
-        let data_type = AttributeDataType::try_from(data[2])?;
+        let data_type =
+            AttributeDataType::try_from(data[2]).map_err(|_| Error::UnknownDataType(data[2]))?;
         let (value, used) = AttributeValue::unpack(&data[3..], data_type)?;
         Ok((Self { identifier, value }, used + 3))
     }
@@ -269,6 +270,49 @@ pub struct ReportAttributes {
     pub attributes: WriteAttributeRecordVec,
 }
 
+/// A attribute report which failed to parse, see
+/// `ReportAttributes::unpack_partial`
+#[derive(Clone, Debug, PartialEq)]
+pub struct PartialReportAttributes {
+    /// The records parsed before the failing record
+    pub report: ReportAttributes,
+    /// Number of bytes used by the parsed records
+    pub used: usize,
+    /// The error of the failing record
+    pub error: Error,
+}
+
+impl ReportAttributes {
+    /// Parse the attribute records of a report, keeping the records parsed
+    /// before a failure
+    ///
+    /// The size of a attribute value is given by its data type. ZCL provides
+    /// no length for data types unknown to the decoder, so parsing can not
+    /// continue past a record with a unknown data type. Such a record fails
+    /// with `Error::UnknownDataType` and the preceding records are returned
+    /// with the error.
+    pub fn unpack_partial(data: &[u8]) -> Result<(Self, usize), PartialReportAttributes> {
+        let mut offset = 0;
+        let mut attributes = WriteAttributeRecordVec::new();
+        while offset < data.len() {
+            match WriteAttributeRecord::unpack(&data[offset..]) {
+                Ok((record, used)) => {
+                    attributes.push(record);
+                    offset += used;
+                }
+                Err(error) => {
+                    return Err(PartialReportAttributes {
+                        report: Self { attributes },
+                        used: offset,
+                        error,
+                    });
+                }
+            }
+        }
+        Ok((Self { attributes }, offset))
+    }
+}
+
 impl Pack<ReportAttributes, Error> for ReportAttributes {
     fn pack(&self, data: &mut [u8]) -> Result<usize, Error> {
         let mut offset = 0;
@@ -278,18 +322,10 @@ impl Pack<ReportAttributes, Error> for ReportAttributes {
         Ok(offset)
     }
 
+    /// Fails on the first record which can't be parsed, see
+    /// `ReportAttributes::unpack_partial` to keep the preceding records
     fn unpack(data: &[u8]) -> Result<(Self, usize), Error> {
-        let mut offset = 0;
-        let mut attributes = WriteAttributeRecordVec::new();
-        loop {
-            if offset == data.len() {
-                break;
-            }
-            let (record, used) = WriteAttributeRecord::unpack(&data[offset..])?;
-            attributes.push(record);
-            offset += used;
-        }
-        Ok((Self { attributes }, offset))
+        Self::unpack_partial(data).map_err(|partial| partial.error)
     }
 }
 
@@ -470,6 +506,30 @@ mod tests {
         assert_eq!(cmd.attributes[2].value, AttributeValue::Unsigned16(0x01c6));
     }
 
+    #[test]
+    fn unpack_report_attributes_unknown_data_type() {
+        use crate::cluster_library::AttributeValue;
+
+        // Unsigned 16-bit value followed by the unknown data type 0x5f
+        let data = [
+            0x03, 0x00, 0x21, 0xba, 0x75, 0x04, 0x00, 0x5f, 0x1d, 0x69, 0x07, 0x00, 0x21, 0xc6,
+            0x01,
+        ];
+        assert_eq!(
+            ReportAttributes::unpack(&data).unwrap_err(),
+            Error::UnknownDataType(0x5f)
+        );
+        let partial = ReportAttributes::unpack_partial(&data).unwrap_err();
+        assert_eq!(partial.error, Error::UnknownDataType(0x5f));
+        assert_eq!(partial.used, 5);
+        assert_eq!(partial.report.attributes.len(), 1);
+        assert_eq!(partial.report.attributes[0].identifier, 0x0003);
+        assert_eq!(
+            partial.report.attributes[0].value,
+            AttributeValue::Unsigned16(0x75ba)
+        );
+    }
+
     #[test]
     fn unpack_discover_attributes() {
         let data = [0x00, 0x00, 0xf0];
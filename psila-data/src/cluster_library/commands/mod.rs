@@ -6,11 +6,13 @@ use core::convert::TryFrom;
 use crate::pack::Pack;
 use crate::Error;
 
-use attributes::{
-    DiscoverAttributes, DiscoverAttributesResponse, ReadAttributes, ReadAttributesResponse,
-    ReportAttributes, WriteAttributes, WriteAttributesResponse,
+pub use attributes::{
+    AttributeIdentifierVec, AttributeStatus, AttributeStatusVec, DiscoverAttributeVec,
+    DiscoverAttributes, DiscoverAttributesResponse, PartialReportAttributes, ReadAttributes,
+    ReadAttributesResponse, ReportAttributes, WriteAttributeRecord, WriteAttributeRecordVec,
+    WriteAttributeStatus, WriteAttributeStatusVec, WriteAttributes, WriteAttributesResponse,
 };
-use default_response::DefaultResponse;
+pub use default_response::DefaultResponse;
 
 extended_enum!(
     /// Cluster library general command identifiers
@@ -40,6 +42,12 @@ extended_enum!(
     DiscoverAttributesExtendedResponse => 0x16,
 );
 
+/// Cluster library global command identifier
+///
+/// The global commands share the same identifiers regardless of cluster, so
+/// these can be recognised before any cluster-specific knowledge is applied.
+pub type GlobalCommand = GeneralCommandIdentifier;
+
 /// Cluster library general command
 #[derive(Clone, Debug, PartialEq)]
 pub enum Command {
@@ -238,3 +246,47 @@ impl Command {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn global_command_identifiers() {
+        let commands = [
+            (0x00, GlobalCommand::ReadAttributes),
+            (0x01, GlobalCommand::ReadAttributesResponse),
+            (0x02, GlobalCommand::WriteAttributes),
+            (0x03, GlobalCommand::WriteAttributesUndivided),
+            (0x04, GlobalCommand::WriteAttributesResponse),
+            (0x05, GlobalCommand::WriteAttributesNoResponse),
+            (0x06, GlobalCommand::ConfigureReporting),
+            (0x07, GlobalCommand::ConfigureReportingResponse),
+            (0x08, GlobalCommand::ReadReportingConfiguration),
+            (0x09, GlobalCommand::ReadReportingConfigurationResponse),
+            (0x0a, GlobalCommand::ReportAttributes),
+            (0x0b, GlobalCommand::DefaultResponse),
+            (0x0c, GlobalCommand::DiscoverAttributes),
+            (0x0d, GlobalCommand::DiscoverAttributesResponse),
+            (0x0e, GlobalCommand::ReadAttributesStructured),
+            (0x0f, GlobalCommand::WriteAttributesStructured),
+            (0x10, GlobalCommand::WriteAttributesStructuredResponse),
+            (0x11, GlobalCommand::DiscoverCommandsReceived),
+            (0x12, GlobalCommand::DiscoverCommandsReceivedResponse),
+            (0x13, GlobalCommand::DiscoverCommandsGenerated),
+            (0x14, GlobalCommand::DiscoverCommandsGeneratedResponse),
+            (0x15, GlobalCommand::DiscoverAttributesExtended),
+            (0x16, GlobalCommand::DiscoverAttributesExtendedResponse),
+        ];
+        for (identifier, command) in commands.iter() {
+            assert_eq!(GlobalCommand::try_from(*identifier), Ok(*command));
+            assert_eq!(u8::from(*command), *identifier);
+        }
+        for identifier in 0x17..=0xff {
+            assert_eq!(
+                GlobalCommand::try_from(identifier),
+                Err(Error::InvalidValue)
+            );
+        }
+    }
+}
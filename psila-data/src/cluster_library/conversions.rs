@@ -0,0 +1,224 @@
+//! # Attribute value conversions
+//!
+//! Conversions from attribute values to the natural Rust types, e.g. for
+//! consuming attribute reports, `let temperature: i16 = value.try_into()?`.
+//!
+//! The conversions fail with `Error::WrongDataType` if the value has another
+//! data type. Values holding the invalid value of the data type, e.g. 0xff
+//! for a unsigned 8-bit integer, fail with `Error::InvalidValue`. Strings are
+//! borrowed from the value.
+
+use core::convert::TryFrom;
+
+use crate::cluster_library::AttributeValue;
+use crate::Error;
+
+/// `v` if `value` isn't the invalid value of its data type
+fn valid<T>(value: &AttributeValue, v: T) -> Result<T, Error> {
+    if value.is_valid() {
+        Ok(v)
+    } else {
+        Err(Error::InvalidValue)
+    }
+}
+
+impl TryFrom<&AttributeValue> for u8 {
+    type Error = Error;
+
+    /// Unsigned 8-bit integer, 8-bit enumeration, data or bitmap, the
+    /// invalid value fails with `Error::InvalidValue`
+    fn try_from(value: &AttributeValue) -> Result<Self, Self::Error> {
+        let v = match value {
+            AttributeValue::Unsigned8(v)
+            | AttributeValue::Enumeration8(v)
+            | AttributeValue::Data8(v)
+            | AttributeValue::Bitmap8(v) => *v,
+            _ => return Err(Error::WrongDataType),
+        };
+        valid(value, v)
+    }
+}
+
+impl TryFrom<AttributeValue> for u8 {
+    type Error = Error;
+
+    fn try_from(value: AttributeValue) -> Result<Self, Self::Error> {
+        Self::try_from(&value)
+    }
+}
+
+impl TryFrom<&AttributeValue> for u16 {
+    type Error = Error;
+
+    /// Unsigned 16-bit integer, 16-bit enumeration, data or bitmap, cluster
+    /// or attribute identifier, the invalid value fails with
+    /// `Error::InvalidValue`
+    fn try_from(value: &AttributeValue) -> Result<Self, Self::Error> {
+        let v = match value {
+            AttributeValue::Unsigned16(v)
+            | AttributeValue::Enumeration16(v)
+            | AttributeValue::Data16(v)
+            | AttributeValue::Bitmap16(v)
+            | AttributeValue::ClusterIdentifier(v)
+            | AttributeValue::AttributeIdentifier(v) => *v,
+            _ => return Err(Error::WrongDataType),
+        };
+        valid(value, v)
+    }
+}
+
+impl TryFrom<AttributeValue> for u16 {
+    type Error = Error;
+
+    fn try_from(value: AttributeValue) -> Result<Self, Self::Error> {
+        Self::try_from(&value)
+    }
+}
+
+impl TryFrom<&AttributeValue> for i16 {
+    type Error = Error;
+
+    /// Signed 16-bit integer, the invalid value fails with
+    /// `Error::InvalidValue`
+    fn try_from(value: &AttributeValue) -> Result<Self, Self::Error> {
+        let v = match value {
+            AttributeValue::Signed16(v) => *v,
+            _ => return Err(Error::WrongDataType),
+        };
+        valid(value, v)
+    }
+}
+
+impl TryFrom<AttributeValue> for i16 {
+    type Error = Error;
+
+    fn try_from(value: AttributeValue) -> Result<Self, Self::Error> {
+        Self::try_from(&value)
+    }
+}
+
+impl TryFrom<&AttributeValue> for bool {
+    type Error = Error;
+
+    /// Boolean, the invalid boolean fails with `Error::InvalidValue`
+    fn try_from(value: &AttributeValue) -> Result<Self, Self::Error> {
+        match value {
+            AttributeValue::Boolean(_) => value.as_bool().ok_or(Error::InvalidValue),
+            _ => Err(Error::WrongDataType),
+        }
+    }
+}
+
+impl TryFrom<AttributeValue> for bool {
+    type Error = Error;
+
+    fn try_from(value: AttributeValue) -> Result<Self, Self::Error> {
+        Self::try_from(&value)
+    }
+}
+
+impl<'a> TryFrom<&'a AttributeValue> for &'a str {
+    type Error = Error;
+
+    /// Character string or long character string, the invalid string fails
+    /// with `Error::InvalidValue`
+    fn try_from(value: &'a AttributeValue) -> Result<Self, Self::Error> {
+        match value {
+            AttributeValue::CharacterString(v) | AttributeValue::LongCharacterString(v) => {
+                v.as_ref().map(|v| v.as_str()).ok_or(Error::InvalidValue)
+            }
+            _ => Err(Error::WrongDataType),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cluster_library::{ClusterLibraryHeader, ReportAttributes};
+    use crate::common::types::character_string;
+    use crate::pack::Pack;
+    use core::convert::TryInto;
+
+    #[test]
+    fn convert_report() {
+        // Report attributes, measured temperature 21.50 °C
+        let data = [0x18, 0x01, 0x0a, 0x00, 0x00, 0x29, 0x66, 0x08];
+        let (_, used) = ClusterLibraryHeader::unpack(&data).unwrap();
+        let (report, _) = ReportAttributes::unpack(&data[used..]).unwrap();
+        let record = &report.attributes[0];
+        let temperature: i16 = (&record.value).try_into().unwrap();
+        assert_eq!(temperature, 2150);
+        let temperature: Result<u16, Error> = record.value.clone().try_into();
+        assert_eq!(temperature, Err(Error::WrongDataType));
+
+        assert_eq!(u8::try_from(AttributeValue::Enumeration8(0x04)), Ok(0x04));
+        assert_eq!(
+            u16::try_from(AttributeValue::Unsigned16(0x1234)),
+            Ok(0x1234)
+        );
+        assert_eq!(bool::try_from(AttributeValue::Boolean(0x01)), Ok(true));
+        assert_eq!(
+            bool::try_from(AttributeValue::Boolean(0xff)),
+            Err(Error::InvalidValue)
+        );
+        assert_eq!(
+            u8::try_from(AttributeValue::Signed8(-1)),
+            Err(Error::WrongDataType)
+        );
+    }
+
+    #[test]
+    fn convert_invalid_value() {
+        assert_eq!(
+            u8::try_from(AttributeValue::Unsigned8(0xff)),
+            Err(Error::InvalidValue)
+        );
+        assert_eq!(
+            u8::try_from(AttributeValue::Enumeration8(0xff)),
+            Err(Error::InvalidValue)
+        );
+        assert_eq!(
+            u16::try_from(AttributeValue::Unsigned16(0xffff)),
+            Err(Error::InvalidValue)
+        );
+        assert_eq!(
+            u16::try_from(AttributeValue::Enumeration16(0xffff)),
+            Err(Error::InvalidValue)
+        );
+        assert_eq!(
+            u16::try_from(AttributeValue::ClusterIdentifier(0xffff)),
+            Err(Error::InvalidValue)
+        );
+        assert_eq!(
+            u16::try_from(AttributeValue::AttributeIdentifier(0xffff)),
+            Err(Error::InvalidValue)
+        );
+        assert_eq!(
+            i16::try_from(AttributeValue::Signed16(i16::min_value())),
+            Err(Error::InvalidValue)
+        );
+        // Data and bitmaps have no invalid value
+        assert_eq!(u8::try_from(AttributeValue::Data8(0xff)), Ok(0xff));
+        assert_eq!(u16::try_from(AttributeValue::Bitmap16(0xffff)), Ok(0xffff));
+        // Largest valid values
+        assert_eq!(u8::try_from(AttributeValue::Unsigned8(0xfe)), Ok(0xfe));
+        assert_eq!(
+            u16::try_from(AttributeValue::Unsigned16(0xfffe)),
+            Ok(0xfffe)
+        );
+        assert_eq!(i16::try_from(AttributeValue::Signed16(-32767)), Ok(-32767));
+    }
+
+    #[test]
+    fn convert_string() {
+        let value = AttributeValue::CharacterString(Some(character_string(b"psila").unwrap()));
+        let name: &str = (&value).try_into().unwrap();
+        assert_eq!(name, "psila");
+        let value = AttributeValue::CharacterString(None);
+        assert_eq!(<&str>::try_from(&value), Err(Error::InvalidValue));
+        let value = AttributeValue::Unsigned8(1);
+        assert_eq!(<&str>::try_from(&value), Err(Error::WrongDataType));
+        assert_eq!(bool::try_from(&value), Err(Error::WrongDataType));
+    }
+}
@@ -0,0 +1,252 @@
+//! # Door lock cluster
+//!
+//! Locks and unlocks a door, optionally authorized with a PIN code. The
+//! device answers each request with a response carrying the status.
+
+use core::convert::TryFrom;
+
+use crate::cluster_library::{
+    AttributeIdentifier, ClusterCommand, ClusterLibraryHeader, ClusterLibraryStatus, Direction,
+};
+use crate::common::types::{octet_string, OctetString};
+use crate::pack::Pack;
+use crate::Error;
+
+/// Door lock cluster identifier
+pub const CLUSTER_IDENTIFIER: u16 = 0x0101;
+
+extended_enum!(
+    /// Door lock cluster attribute identifiers
+    DoorLockAttribute, u16,
+    /// Lock state, 8-bit enumeration, see `LockState`
+    LockState => 0x0000,
+    /// Kind of lock, 8-bit enumeration
+    LockType => 0x0001,
+    /// The lock actuator is enabled, boolean
+    ActuatorEnabled => 0x0002,
+    /// Door state, 8-bit enumeration
+    DoorState => 0x0003,
+    /// Seconds before automatically locking again, unsigned 32-bit integer
+    AutoRelockTime => 0x0023,
+    /// PIN codes must be sent by the client, boolean
+    RequirePinForRfOperation => 0x0033,
+);
+
+impl From<DoorLockAttribute> for AttributeIdentifier {
+    fn from(value: DoorLockAttribute) -> Self {
+        AttributeIdentifier::new(u16::from(value))
+    }
+}
+
+extended_enum!(
+    /// Lock state
+    LockState, u8,
+    /// The lock is not fully locked
+    NotFullyLocked => 0x00,
+    /// Locked
+    Locked => 0x01,
+    /// Unlocked
+    Unlocked => 0x02,
+);
+
+/// Door lock cluster commands
+///
+/// The requests are sent by the client, the responses by the server. Each
+/// request and its response share command identifier.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DoorLockCommand {
+    /// Lock the door, see `LockRequest`
+    LockDoor,
+    /// Unlock the door, see `LockRequest`
+    UnlockDoor,
+    /// Toggle the lock, see `LockRequest`
+    Toggle,
+    /// Response to lock door, see `LockResponse`
+    LockDoorResponse,
+    /// Response to unlock door, see `LockResponse`
+    UnlockDoorResponse,
+    /// Response to toggle, see `LockResponse`
+    ToggleResponse,
+}
+
+impl DoorLockCommand {
+    /// Direction of the command
+    pub fn direction(self) -> Direction {
+        match self {
+            DoorLockCommand::LockDoor | DoorLockCommand::UnlockDoor | DoorLockCommand::Toggle => {
+                Direction::ToServer
+            }
+            _ => Direction::ToClient,
+        }
+    }
+
+    /// Command identifier
+    pub fn identifier(self) -> u8 {
+        match self {
+            DoorLockCommand::LockDoor | DoorLockCommand::LockDoorResponse => 0x00,
+            DoorLockCommand::UnlockDoor | DoorLockCommand::UnlockDoorResponse => 0x01,
+            DoorLockCommand::Toggle | DoorLockCommand::ToggleResponse => 0x02,
+        }
+    }
+
+    /// Create the header of the command
+    pub fn header(self, transaction_sequence: u8) -> ClusterLibraryHeader {
+        ClusterLibraryHeader::new_cluster_command(
+            self.direction(),
+            transaction_sequence,
+            self.identifier(),
+            false,
+        )
+    }
+}
+
+impl ClusterCommand for DoorLockCommand {
+    fn from_identifier(direction: Direction, identifier: u8) -> Result<Self, Error> {
+        match (direction, identifier) {
+            (Direction::ToServer, 0x00) => Ok(DoorLockCommand::LockDoor),
+            (Direction::ToServer, 0x01) => Ok(DoorLockCommand::UnlockDoor),
+            (Direction::ToServer, 0x02) => Ok(DoorLockCommand::Toggle),
+            (Direction::ToClient, 0x00) => Ok(DoorLockCommand::LockDoorResponse),
+            (Direction::ToClient, 0x01) => Ok(DoorLockCommand::UnlockDoorResponse),
+            (Direction::ToClient, 0x02) => Ok(DoorLockCommand::ToggleResponse),
+            _ => Err(Error::InvalidValue),
+        }
+    }
+}
+
+// ZCL, 7.3.2.16.1 Lock Door Command
+/// Lock door, unlock door and toggle requests, with a optional PIN code
+#[derive(Clone, Debug, PartialEq)]
+pub struct LockRequest {
+    /// PIN or RFID code, `None` if the lock doesn't require a code
+    pub pin_code: Option<OctetString>,
+}
+
+impl LockRequest {
+    /// Create a request with the optional `pin_code`
+    pub fn new(pin_code: Option<&[u8]>) -> Result<Self, Error> {
+        let pin_code = match pin_code {
+            Some(code) => Some(octet_string(code)?),
+            None => None,
+        };
+        Ok(Self { pin_code })
+    }
+}
+
+impl Pack<LockRequest, Error> for LockRequest {
+    fn pack(&self, data: &mut [u8]) -> Result<usize, Error> {
+        match self.pin_code {
+            Some(ref code) => code.pack(data),
+            None => Ok(0),
+        }
+    }
+
+    fn unpack(data: &[u8]) -> Result<(Self, usize), Error> {
+        if data.is_empty() {
+            return Ok((Self { pin_code: None }, 0));
+        }
+        let (code, used) = OctetString::unpack(data)?;
+        Ok((
+            Self {
+                pin_code: Some(code),
+            },
+            used,
+        ))
+    }
+}
+
+// ZCL, 7.3.2.17.1 Lock Door Response Command
+/// Lock door, unlock door and toggle responses
+#[derive(Clone, Debug, PartialEq)]
+pub struct LockResponse {
+    /// Result of the request
+    pub status: ClusterLibraryStatus,
+}
+
+impl Pack<LockResponse, Error> for LockResponse {
+    fn pack(&self, data: &mut [u8]) -> Result<usize, Error> {
+        if data.is_empty() {
+            return Err(Error::WrongNumberOfBytes);
+        }
+        data[0] = u8::from(self.status);
+        Ok(1)
+    }
+
+    fn unpack(data: &[u8]) -> Result<(Self, usize), Error> {
+        if data.is_empty() {
+            return Err(Error::WrongNumberOfBytes);
+        }
+        let status = ClusterLibraryStatus::try_from(data[0])?;
+        Ok((Self { status }, 1))
+    }
+}
+
+/// Create the header and payload of a lock door command, with the optional
+/// `pin_code`
+pub fn lock_door(
+    transaction_sequence: u8,
+    pin_code: Option<&[u8]>,
+) -> Result<(ClusterLibraryHeader, LockRequest), Error> {
+    Ok((
+        DoorLockCommand::LockDoor.header(transaction_sequence),
+        LockRequest::new(pin_code)?,
+    ))
+}
+
+/// Create the header and payload of a unlock door command, with the
+/// optional `pin_code`
+pub fn unlock_door(
+    transaction_sequence: u8,
+    pin_code: Option<&[u8]>,
+) -> Result<(ClusterLibraryHeader, LockRequest), Error> {
+    Ok((
+        DoorLockCommand::UnlockDoor.header(transaction_sequence),
+        LockRequest::new(pin_code)?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pack_unlock_door_with_pin() {
+        let (header, request) = unlock_door(0x51, Some(b"1234")).unwrap();
+        let mut buffer = [0u8; 8];
+        let used = header.pack(&mut buffer).unwrap();
+        let used = used + request.pack(&mut buffer[used..]).unwrap();
+        assert_eq!(
+            buffer[..used],
+            [0x01, 0x51, 0x01, 0x04, 0x31, 0x32, 0x33, 0x34]
+        );
+
+        let (header, used) = ClusterLibraryHeader::unpack(&buffer).unwrap();
+        assert_eq!(
+            header.cluster_command::<DoorLockCommand>(),
+            Ok(DoorLockCommand::UnlockDoor)
+        );
+        let (unpacked, _) = LockRequest::unpack(&buffer[used..]).unwrap();
+        assert_eq!(unpacked, request);
+
+        // Without PIN code
+        let (header, request) = lock_door(0x52, None).unwrap();
+        let used = header.pack(&mut buffer).unwrap();
+        let used = used + request.pack(&mut buffer[used..]).unwrap();
+        assert_eq!(buffer[..used], [0x01, 0x52, 0x00]);
+        assert_eq!(LockRequest::unpack(&[]).unwrap().0.pin_code, None);
+    }
+
+    #[test]
+    fn unpack_lock_response() {
+        // Unlock door response, failure
+        let data = [0x19, 0x51, 0x01, 0x01];
+        let (header, used) = ClusterLibraryHeader::unpack(&data).unwrap();
+        assert_eq!(
+            header.cluster_command::<DoorLockCommand>(),
+            Ok(DoorLockCommand::UnlockDoorResponse)
+        );
+        let (response, _) = LockResponse::unpack(&data[used..]).unwrap();
+        assert_eq!(response.status, ClusterLibraryStatus::Failure);
+        assert_eq!(LockResponse::unpack(&[]), Err(Error::WrongNumberOfBytes));
+    }
+}
@@ -0,0 +1,117 @@
+//! # Electrical measurement cluster
+//!
+//! Electrical properties measured by a device, e.g. a smart plug. The
+//! measurements are converted into volts, amperes and watts using the
+//! multiplier and divisor attributes of each kind.
+
+use crate::cluster_library::{AttributeIdentifier, AttributeValue};
+
+/// Electrical measurement cluster identifier
+pub const CLUSTER_IDENTIFIER: u16 = 0x0b04;
+
+extended_enum!(
+    /// Electrical measurement cluster attribute identifiers
+    ElectricalMeasurementAttribute, u16,
+    /// Kinds of measurements supported, 32-bit bitmap
+    MeasurementType => 0x0000,
+    /// Root mean square voltage, unsigned 16-bit integer
+    RmsVoltage => 0x0505,
+    /// Root mean square current, unsigned 16-bit integer
+    RmsCurrent => 0x0508,
+    /// Active power, signed 16-bit integer
+    ActivePower => 0x050b,
+    /// Reactive power, signed 16-bit integer
+    ReactivePower => 0x050e,
+    /// Apparent power, unsigned 16-bit integer
+    ApparentPower => 0x050f,
+    /// Power factor, signed 8-bit integer
+    PowerFactor => 0x0510,
+    /// Multiplier of the voltage, unsigned 16-bit integer
+    AcVoltageMultiplier => 0x0600,
+    /// Divisor of the voltage, unsigned 16-bit integer
+    AcVoltageDivisor => 0x0601,
+    /// Multiplier of the current, unsigned 16-bit integer
+    AcCurrentMultiplier => 0x0602,
+    /// Divisor of the current, unsigned 16-bit integer
+    AcCurrentDivisor => 0x0603,
+    /// Multiplier of the power, unsigned 16-bit integer
+    AcPowerMultiplier => 0x0604,
+    /// Divisor of the power, unsigned 16-bit integer
+    AcPowerDivisor => 0x0605,
+);
+
+impl From<ElectricalMeasurementAttribute> for AttributeIdentifier {
+    fn from(value: ElectricalMeasurementAttribute) -> Self {
+        AttributeIdentifier::new(u16::from(value))
+    }
+}
+
+fn unsigned16(value: &AttributeValue) -> Option<u16> {
+    match value {
+        AttributeValue::Unsigned16(v) if value.is_valid() => Some(*v),
+        _ => None,
+    }
+}
+
+fn signed16(value: &AttributeValue) -> Option<i16> {
+    match value {
+        AttributeValue::Signed16(v) if value.is_valid() => Some(*v),
+        _ => None,
+    }
+}
+
+/// Get the RMS voltage from a attribute value, `None` if the value isn't a
+/// valid unsigned 16-bit integer
+pub fn rms_voltage(value: &AttributeValue) -> Option<u16> {
+    unsigned16(value)
+}
+
+/// Get the RMS current from a attribute value, `None` if the value isn't a
+/// valid unsigned 16-bit integer
+pub fn rms_current(value: &AttributeValue) -> Option<u16> {
+    unsigned16(value)
+}
+
+/// Get the active power from a attribute value, `None` if the value isn't a
+/// valid signed 16-bit integer
+pub fn active_power(value: &AttributeValue) -> Option<i16> {
+    signed16(value)
+}
+
+/// Get a multiplier or divisor from a attribute value, `None` if the value
+/// isn't a valid unsigned 16-bit integer
+pub fn formatting_factor(value: &AttributeValue) -> Option<u16> {
+    unsigned16(value)
+}
+
+#[cfg(all(test, not(feature = "core")))]
+mod tests {
+    use super::*;
+    use crate::cluster_library::ReportAttributes;
+    use crate::pack::Pack;
+    use core::convert::TryFrom;
+
+    #[test]
+    fn unpack_active_power_report() {
+        // Active power of 30.0 W, in tenths of watts
+        let data = [0x0b, 0x05, 0x29, 0x2c, 0x01];
+        let (report, used) = ReportAttributes::unpack(&data).unwrap();
+        assert_eq!(used, 5);
+        let record = &report.attributes[0];
+        assert_eq!(
+            ElectricalMeasurementAttribute::try_from(u16::from(record.identifier)),
+            Ok(ElectricalMeasurementAttribute::ActivePower)
+        );
+        assert_eq!(active_power(&record.value), Some(300));
+        assert_eq!(active_power(&AttributeValue::Signed16(-15)), Some(-15));
+
+        // Invalid and mismatching values
+        assert_eq!(
+            active_power(&AttributeValue::Signed16(i16::min_value())),
+            None
+        );
+        assert_eq!(active_power(&AttributeValue::Unsigned16(300)), None);
+        assert_eq!(rms_voltage(&AttributeValue::Unsigned16(2301)), Some(2301));
+        assert_eq!(rms_current(&AttributeValue::Unsigned16(0xffff)), None);
+    }
+}
@@ -1,5 +1,6 @@
 use core::convert::TryFrom;
 
+use crate::cluster_library::GeneralCommandIdentifier;
 use crate::error::Error;
 use crate::pack::{Pack, PackFixed};
 
@@ -30,9 +31,9 @@ impl TryFrom<u8> for FrameType {
 /// Direction of the command
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub enum Direction {
-    /// Sent from the server side to the client side
-    ToServer = 0,
     /// Sent from the client side to the server side
+    ToServer = 0,
+    /// Sent from the server side to the client side
     ToClient = 1,
 }
 
@@ -58,6 +59,15 @@ impl From<Direction> for u8 {
     }
 }
 
+/// Cluster specific command identifiers
+///
+/// The same command identifier can have different meaning depending on the
+/// direction of the command.
+pub trait ClusterCommand: Sized {
+    /// Get the command from the direction and command identifier
+    fn from_identifier(direction: Direction, identifier: u8) -> Result<Self, Error>;
+}
+
 // ZCL, 2.4.1.1 Frame Control Field
 /// Cluster library frame control field
 #[derive(Copy, Clone, Debug)]
@@ -118,6 +128,61 @@ pub struct ClusterLibraryHeader {
     pub command: u8,
 }
 
+impl ClusterLibraryHeader {
+    /// Create a header for a cluster specific command
+    pub fn new_cluster_command(
+        direction: Direction,
+        transaction_sequence: u8,
+        command: u8,
+        disable_default_response: bool,
+    ) -> Self {
+        Self {
+            control: FrameControl {
+                frame_type: FrameType::Local,
+                manufacturer_specific: false,
+                direction,
+                disable_default_response,
+            },
+            manufacturer: None,
+            transaction_sequence,
+            command,
+        }
+    }
+
+    /// Create a header for a global command
+    pub fn new_global_command(
+        direction: Direction,
+        transaction_sequence: u8,
+        command: GeneralCommandIdentifier,
+        disable_default_response: bool,
+    ) -> Self {
+        Self {
+            control: FrameControl {
+                frame_type: FrameType::Global,
+                manufacturer_specific: false,
+                direction,
+                disable_default_response,
+            },
+            manufacturer: None,
+            transaction_sequence,
+            command: u8::from(command),
+        }
+    }
+
+    /// Direction of the command
+    pub fn direction(&self) -> Direction {
+        self.control.direction
+    }
+
+    /// Get the cluster specific command, using the direction of the frame
+    pub fn cluster_command<C: ClusterCommand>(&self) -> Result<C, Error> {
+        if self.control.frame_type != FrameType::Local {
+            return Err(Error::UnknownFrameType);
+        }
+        C::from_identifier(self.control.direction, self.command)
+    }
+}
+
 impl Pack<ClusterLibraryHeader, Error> for ClusterLibraryHeader {
     fn pack(&self, data: &mut [u8]) -> Result<usize, Error> {
         let length = if self.manufacturer.is_some() { 5 } else { 3 };
@@ -0,0 +1,203 @@
+//! # Identify cluster
+//!
+//! Puts a device in identification mode, e.g. blinking a light, used while
+//! commissioning. All times are in seconds.
+
+use core::convert::TryFrom;
+
+use byteorder::{ByteOrder, LittleEndian};
+
+use crate::cluster_library::{
+    AttributeIdentifier, ClusterCommand, ClusterLibraryHeader, Direction,
+};
+use crate::pack::Pack;
+use crate::Error;
+
+/// Identify cluster identifier
+pub const CLUSTER_IDENTIFIER: u16 = 0x0003;
+
+extended_enum!(
+    /// Identify cluster attribute identifiers
+    IdentifyAttribute, u16,
+    /// Remaining time in identification mode, unsigned 16-bit integer
+    IdentifyTime => 0x0000,
+);
+
+impl From<IdentifyAttribute> for AttributeIdentifier {
+    fn from(value: IdentifyAttribute) -> Self {
+        AttributeIdentifier::new(u16::from(value))
+    }
+}
+
+/// Identify cluster commands
+///
+/// The identify query response is sent by the server, the others by the
+/// client. The identify and identify query response share command
+/// identifier.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum IdentifyCommand {
+    /// Start or stop identifying, see `Identify`
+    Identify,
+    /// Ask devices in identification mode to respond, no payload
+    IdentifyQuery,
+    /// Trigger a identification effect
+    TriggerEffect,
+    /// Response from a device in identification mode, see
+    /// `IdentifyQueryResponse`
+    IdentifyQueryResponse,
+}
+
+impl IdentifyCommand {
+    /// Direction of the command
+    pub fn direction(self) -> Direction {
+        match self {
+            IdentifyCommand::IdentifyQueryResponse => Direction::ToClient,
+            _ => Direction::ToServer,
+        }
+    }
+
+    /// Command identifier
+    pub fn identifier(self) -> u8 {
+        match self {
+            IdentifyCommand::Identify | IdentifyCommand::IdentifyQueryResponse => 0x00,
+            IdentifyCommand::IdentifyQuery => 0x01,
+            IdentifyCommand::TriggerEffect => 0x40,
+        }
+    }
+
+    /// Create the header of the command
+    pub fn header(self, transaction_sequence: u8) -> ClusterLibraryHeader {
+        ClusterLibraryHeader::new_cluster_command(
+            self.direction(),
+            transaction_sequence,
+            self.identifier(),
+            false,
+        )
+    }
+}
+
+impl ClusterCommand for IdentifyCommand {
+    fn from_identifier(direction: Direction, identifier: u8) -> Result<Self, Error> {
+        match (direction, identifier) {
+            (Direction::ToServer, 0x00) => Ok(IdentifyCommand::Identify),
+            (Direction::ToServer, 0x01) => Ok(IdentifyCommand::IdentifyQuery),
+            (Direction::ToServer, 0x40) => Ok(IdentifyCommand::TriggerEffect),
+            (Direction::ToClient, 0x00) => Ok(IdentifyCommand::IdentifyQueryResponse),
+            _ => Err(Error::InvalidValue),
+        }
+    }
+}
+
+/// Create the header of a identify query command, the command has no
+/// payload
+pub fn identify_query(transaction_sequence: u8) -> ClusterLibraryHeader {
+    IdentifyCommand::IdentifyQuery.header(transaction_sequence)
+}
+
+// ZCL, 3.5.2.3.1 Identify Command
+/// Identify command, start identifying for a time or stop
+#[derive(Clone, Debug, PartialEq)]
+pub struct Identify {
+    /// Time to identify, zero to stop identifying
+    pub identify_time: u16,
+}
+
+impl Identify {
+    /// Identify for `seconds`
+    pub fn seconds(seconds: u16) -> Self {
+        Self {
+            identify_time: seconds,
+        }
+    }
+
+    /// Stop identifying
+    pub fn stop() -> Self {
+        Self { identify_time: 0 }
+    }
+}
+
+impl Pack<Identify, Error> for Identify {
+    fn pack(&self, data: &mut [u8]) -> Result<usize, Error> {
+        if data.len() < 2 {
+            return Err(Error::WrongNumberOfBytes);
+        }
+        LittleEndian::write_u16(&mut data[0..2], self.identify_time);
+        Ok(2)
+    }
+
+    fn unpack(data: &[u8]) -> Result<(Self, usize), Error> {
+        if data.len() < 2 {
+            return Err(Error::WrongNumberOfBytes);
+        }
+        let identify_time = LittleEndian::read_u16(&data[0..2]);
+        Ok((Self { identify_time }, 2))
+    }
+}
+
+// ZCL, 3.5.2.4.1 Identify Query Response Command
+/// Identify query response, with the remaining time in identification mode
+#[derive(Clone, Debug, PartialEq)]
+pub struct IdentifyQueryResponse {
+    /// Remaining time to identify
+    pub timeout: u16,
+}
+
+impl Pack<IdentifyQueryResponse, Error> for IdentifyQueryResponse {
+    fn pack(&self, data: &mut [u8]) -> Result<usize, Error> {
+        if data.len() < 2 {
+            return Err(Error::WrongNumberOfBytes);
+        }
+        LittleEndian::write_u16(&mut data[0..2], self.timeout);
+        Ok(2)
+    }
+
+    fn unpack(data: &[u8]) -> Result<(Self, usize), Error> {
+        if data.len() < 2 {
+            return Err(Error::WrongNumberOfBytes);
+        }
+        let timeout = LittleEndian::read_u16(&data[0..2]);
+        Ok((Self { timeout }, 2))
+    }
+}
+
+#[cfg(all(test, not(feature = "core")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pack_identify() {
+        let mut buffer = [0u8; 5];
+        let used = IdentifyCommand::Identify
+            .header(0x21)
+            .pack(&mut buffer)
+            .unwrap();
+        let used = used + Identify::seconds(10).pack(&mut buffer[used..]).unwrap();
+        assert_eq!(buffer[..used], [0x01, 0x21, 0x00, 0x0a, 0x00]);
+
+        let (header, used) = ClusterLibraryHeader::unpack(&buffer).unwrap();
+        assert_eq!(
+            header.cluster_command::<IdentifyCommand>(),
+            Ok(IdentifyCommand::Identify)
+        );
+        let (identify, _) = Identify::unpack(&buffer[used..]).unwrap();
+        assert_eq!(identify.identify_time, 10);
+
+        let used = identify_query(0x22).pack(&mut buffer).unwrap();
+        assert_eq!(buffer[..used], [0x01, 0x22, 0x01]);
+    }
+
+    #[test]
+    fn unpack_identify_query_response() {
+        // Identify query response, 7 seconds remaining
+        let data = [0x19, 0x22, 0x00, 0x07, 0x00];
+        let (header, used) = ClusterLibraryHeader::unpack(&data).unwrap();
+        assert_eq!(
+            header.cluster_command::<IdentifyCommand>(),
+            Ok(IdentifyCommand::IdentifyQueryResponse)
+        );
+        let (response, used) = IdentifyQueryResponse::unpack(&data[used..]).unwrap();
+        assert_eq!(used, 2);
+        assert_eq!(response.timeout, 7);
+        assert!(IdentifyQueryResponse::unpack(&data[..1]).is_err());
+    }
+}
@@ -0,0 +1,101 @@
+//! # Metering cluster
+//!
+//! Consumption reported by a meter, e.g. electricity, gas or water. The
+//! summations are unsigned 48-bit integers which are converted into the unit
+//! of measure using the multiplier and divisor attributes.
+
+use byteorder::{ByteOrder, LittleEndian};
+
+use crate::cluster_library::{AttributeIdentifier, AttributeValue};
+
+/// Metering cluster identifier
+pub const CLUSTER_IDENTIFIER: u16 = 0x0702;
+
+extended_enum!(
+    /// Metering cluster attribute identifiers
+    MeteringAttribute, u16,
+    /// Summation of the delivered consumption, unsigned 48-bit integer
+    CurrentSummationDelivered => 0x0000,
+    /// Summation of the received production, unsigned 48-bit integer
+    CurrentSummationReceived => 0x0001,
+    /// Unit of measure of the summations and demand, 8-bit enumeration
+    UnitOfMeasure => 0x0300,
+    /// Multiplier of the summations and demand, unsigned 24-bit integer
+    Multiplier => 0x0301,
+    /// Divisor of the summations and demand, unsigned 24-bit integer
+    Divisor => 0x0302,
+    /// Formatting of the summations when displayed, 8-bit bitmap
+    SummationFormatting => 0x0303,
+    /// Kind of meter, 8-bit bitmap
+    MeteringDeviceType => 0x0306,
+    /// Current demand, signed 24-bit integer
+    InstantaneousDemand => 0x0400,
+);
+
+impl From<MeteringAttribute> for AttributeIdentifier {
+    fn from(value: MeteringAttribute) -> Self {
+        AttributeIdentifier::new(u16::from(value))
+    }
+}
+
+/// Get a summation from a attribute value, `None` if the value isn't a
+/// valid unsigned 48-bit integer
+pub fn summation(value: &AttributeValue) -> Option<u64> {
+    match value {
+        AttributeValue::Unsigned48(octets) if value.is_valid() => {
+            Some(LittleEndian::read_uint(octets, 6))
+        }
+        _ => None,
+    }
+}
+
+/// Get the multiplier or divisor from a attribute value, `None` if the value
+/// isn't a valid unsigned 24-bit integer
+pub fn formatting_factor(value: &AttributeValue) -> Option<u32> {
+    match value {
+        AttributeValue::Unsigned24(factor) if value.is_valid() => Some(*factor),
+        _ => None,
+    }
+}
+
+/// Get the instantaneous demand from a attribute value, `None` if the value
+/// isn't a valid signed 24-bit integer
+pub fn instantaneous_demand(value: &AttributeValue) -> Option<i32> {
+    match value {
+        AttributeValue::Signed24(demand) if value.is_valid() => Some(*demand),
+        _ => None,
+    }
+}
+
+#[cfg(all(test, not(feature = "core")))]
+mod tests {
+    use super::*;
+    use crate::cluster_library::ReportAttributes;
+    use crate::pack::Pack;
+    use core::convert::TryFrom;
+
+    #[test]
+    fn unpack_summation_report() {
+        // Current summation delivered 1234567, divisor 1000
+        let data = [
+            0x00, 0x00, 0x25, 0x87, 0xd6, 0x12, 0x00, 0x00, 0x00, 0x02, 0x03, 0x22, 0xe8, 0x03,
+            0x00,
+        ];
+        let (report, used) = ReportAttributes::unpack(&data).unwrap();
+        assert_eq!(used, data.len());
+        let record = &report.attributes[0];
+        assert_eq!(
+            MeteringAttribute::try_from(u16::from(record.identifier)),
+            Ok(MeteringAttribute::CurrentSummationDelivered)
+        );
+        assert_eq!(summation(&record.value), Some(1_234_567));
+        assert_eq!(formatting_factor(&report.attributes[1].value), Some(1000));
+
+        assert_eq!(summation(&AttributeValue::Unsigned48([0xff; 6])), None);
+        assert_eq!(summation(&AttributeValue::Unsigned32(1)), None);
+        assert_eq!(
+            instantaneous_demand(&AttributeValue::Signed24(-250)),
+            Some(-250)
+        );
+    }
+}
@@ -6,12 +6,31 @@ use crate::common::address::ShortAddress;
 use crate::Error;
 
 mod attribute;
+pub mod basic;
 mod commands;
+mod conversions;
+pub mod door_lock;
+pub mod electrical_measurement;
 mod frame;
+pub mod identify;
+pub mod metering;
+pub mod on_off;
+pub mod ota;
+pub mod poll_control;
+pub mod scenes;
+pub mod thermostat;
+pub mod time;
+pub mod window_covering;
 
 pub use attribute::{AttributeDataType, AttributeValue};
-pub use commands::{Command, GeneralCommandIdentifier};
-pub use frame::{ClusterLibraryHeader, Direction, FrameType};
+pub use commands::{
+    AttributeIdentifierVec, AttributeStatus, AttributeStatusVec, Command, DefaultResponse,
+    DiscoverAttributeVec, DiscoverAttributes, DiscoverAttributesResponse, GeneralCommandIdentifier,
+    GlobalCommand, PartialReportAttributes, ReadAttributes, ReadAttributesResponse,
+    ReportAttributes, WriteAttributeRecord, WriteAttributeRecordVec, WriteAttributeStatus,
+    WriteAttributeStatusVec, WriteAttributes, WriteAttributesResponse,
+};
+pub use frame::{ClusterCommand, ClusterLibraryHeader, Direction, FrameType};
 
 /// 16-bit attribute identifier
 pub type AttributeIdentifier = ShortAddress;
@@ -0,0 +1,62 @@
+//! # On/Off cluster
+//!
+//! Commands for turning a device on and off.
+
+use core::convert::TryFrom;
+
+use crate::cluster_library::{ClusterCommand, Direction};
+use crate::Error;
+
+/// On/Off cluster identifier
+pub const CLUSTER_IDENTIFIER: u16 = 0x0006;
+
+extended_enum!(
+    /// On/Off cluster command identifiers, all sent from client to server
+    OnOffCommand, u8,
+    /// Turn off
+    Off => 0x00,
+    /// Turn on
+    On => 0x01,
+    /// Toggle between on and off
+    Toggle => 0x02,
+    /// Turn off with a effect
+    OffWithEffect => 0x40,
+    /// Turn on and recall the global scene
+    OnWithRecallGlobalScene => 0x41,
+    /// Turn on for a period of time
+    OnWithTimedOff => 0x42,
+);
+
+impl ClusterCommand for OnOffCommand {
+    fn from_identifier(direction: Direction, identifier: u8) -> Result<Self, Error> {
+        match direction {
+            Direction::ToServer => Self::try_from(identifier),
+            Direction::ToClient => Err(Error::InvalidValue),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cluster_library::ClusterLibraryHeader;
+    use crate::pack::Pack;
+
+    #[test]
+    fn on_off_command_direction() {
+        // Off with effect, client to server
+        let (header, _) = ClusterLibraryHeader::unpack(&[0x01, 0x10, 0x40]).unwrap();
+        assert_eq!(header.direction(), Direction::ToServer);
+        assert_eq!(
+            header.cluster_command::<OnOffCommand>(),
+            Ok(OnOffCommand::OffWithEffect)
+        );
+        // Same command identifier, server to client
+        let (header, _) = ClusterLibraryHeader::unpack(&[0x09, 0x10, 0x40]).unwrap();
+        assert_eq!(header.direction(), Direction::ToClient);
+        assert!(header.cluster_command::<OnOffCommand>().is_err());
+        // Global command
+        let (header, _) = ClusterLibraryHeader::unpack(&[0x00, 0x10, 0x01]).unwrap();
+        assert!(header.cluster_command::<OnOffCommand>().is_err());
+    }
+}
@@ -0,0 +1,367 @@
+//! # Over-the-air (OTA) upgrade cluster
+//!
+//! Image block request and response, used to transfer a firmware image from
+//! an upgrade server to a client, one block at a time.
+
+use core::convert::TryFrom;
+
+use crate::cluster_library::{ClusterCommand, ClusterLibraryStatus, Direction};
+use crate::common::types::OctetString;
+use crate::pack::{Pack, PackFixed};
+use crate::{Error, ExtendedAddress};
+
+use byteorder::{ByteOrder, LittleEndian};
+
+/// OTA upgrade cluster identifier
+pub const CLUSTER_IDENTIFIER: u16 = 0x0019;
+
+const FIELD_REQUEST_NODE_ADDRESS: u8 = 0x01;
+const FIELD_MINIMUM_BLOCK_PERIOD: u8 = 0x02;
+
+extended_enum!(
+    /// OTA upgrade cluster command identifiers
+    OtaCommand, u8,
+    ImageNotify => 0x00,
+    QueryNextImageRequest => 0x01,
+    QueryNextImageResponse => 0x02,
+    ImageBlockRequest => 0x03,
+    ImagePageRequest => 0x04,
+    ImageBlockResponse => 0x05,
+    UpgradeEndRequest => 0x06,
+    UpgradeEndResponse => 0x07,
+    QuerySpecificFileRequest => 0x08,
+    QuerySpecificFileResponse => 0x09,
+);
+
+impl ClusterCommand for OtaCommand {
+    fn from_identifier(direction: Direction, identifier: u8) -> Result<Self, Error> {
+        let command = Self::try_from(identifier)?;
+        let to_server = match command {
+            OtaCommand::QueryNextImageRequest
+            | OtaCommand::ImageBlockRequest
+            | OtaCommand::ImagePageRequest
+            | OtaCommand::UpgradeEndRequest
+            | OtaCommand::QuerySpecificFileRequest => true,
+            OtaCommand::ImageNotify
+            | OtaCommand::QueryNextImageResponse
+            | OtaCommand::ImageBlockResponse
+            | OtaCommand::UpgradeEndResponse
+            | OtaCommand::QuerySpecificFileResponse => false,
+        };
+        if to_server == (direction == Direction::ToServer) {
+            Ok(command)
+        } else {
+            Err(Error::InvalidValue)
+        }
+    }
+}
+
+// ZCL, 11.13.8 Image Block Request Command
+/// Image block request, sent by the client to request a block of the image
+#[derive(Clone, Debug, PartialEq)]
+pub struct ImageBlockRequest {
+    /// Manufacturer code of the image
+    pub manufacturer_code: u16,
+    /// Image type of the image
+    pub image_type: u16,
+    /// File version of the image
+    pub file_version: u32,
+    /// Offset into the image file
+    pub file_offset: u32,
+    /// Maximum number of data octets the client can receive in a block
+    pub maximum_data_size: u8,
+    /// Optional IEEE address of the requesting node
+    pub request_node_address: Option<ExtendedAddress>,
+    /// Optional minimum delay between blocks, in milliseconds
+    pub minimum_block_period: Option<u16>,
+}
+
+impl Pack<ImageBlockRequest, Error> for ImageBlockRequest {
+    fn pack(&self, data: &mut [u8]) -> Result<usize, Error> {
+        let mut length = 14;
+        let mut field_control = 0;
+        if self.request_node_address.is_some() {
+            field_control |= FIELD_REQUEST_NODE_ADDRESS;
+            length += 8;
+        }
+        if self.minimum_block_period.is_some() {
+            field_control |= FIELD_MINIMUM_BLOCK_PERIOD;
+            length += 2;
+        }
+        if data.len() < length {
+            return Err(Error::WrongNumberOfBytes);
+        }
+        data[0] = field_control;
+        LittleEndian::write_u16(&mut data[1..3], self.manufacturer_code);
+        LittleEndian::write_u16(&mut data[3..5], self.image_type);
+        LittleEndian::write_u32(&mut data[5..9], self.file_version);
+        LittleEndian::write_u32(&mut data[9..13], self.file_offset);
+        data[13] = self.maximum_data_size;
+        let mut offset = 14;
+        if let Some(address) = self.request_node_address {
+            address.pack(&mut data[offset..offset + 8])?;
+            offset += 8;
+        }
+        if let Some(period) = self.minimum_block_period {
+            LittleEndian::write_u16(&mut data[offset..offset + 2], period);
+            offset += 2;
+        }
+        Ok(offset)
+    }
+
+    fn unpack(data: &[u8]) -> Result<(Self, usize), Error> {
+        if data.len() < 14 {
+            return Err(Error::WrongNumberOfBytes);
+        }
+        let field_control = data[0];
+        let manufacturer_code = LittleEndian::read_u16(&data[1..3]);
+        let image_type = LittleEndian::read_u16(&data[3..5]);
+        let file_version = LittleEndian::read_u32(&data[5..9]);
+        let file_offset = LittleEndian::read_u32(&data[9..13]);
+        let maximum_data_size = data[13];
+        let mut offset = 14;
+        let request_node_address = if field_control & FIELD_REQUEST_NODE_ADDRESS != 0 {
+            if data.len() < offset + 8 {
+                return Err(Error::WrongNumberOfBytes);
+            }
+            let address = ExtendedAddress::unpack(&data[offset..offset + 8])?;
+            offset += 8;
+            Some(address)
+        } else {
+            None
+        };
+        let minimum_block_period = if field_control & FIELD_MINIMUM_BLOCK_PERIOD != 0 {
+            if data.len() < offset + 2 {
+                return Err(Error::WrongNumberOfBytes);
+            }
+            let period = LittleEndian::read_u16(&data[offset..offset + 2]);
+            offset += 2;
+            Some(period)
+        } else {
+            None
+        };
+        Ok((
+            Self {
+                manufacturer_code,
+                image_type,
+                file_version,
+                file_offset,
+                maximum_data_size,
+                request_node_address,
+                minimum_block_period,
+            },
+            offset,
+        ))
+    }
+}
+
+/// A block of image data
+#[derive(Clone, Debug, PartialEq)]
+pub struct ImageBlock {
+    /// Manufacturer code of the image
+    pub manufacturer_code: u16,
+    /// Image type of the image
+    pub image_type: u16,
+    /// File version of the image
+    pub file_version: u32,
+    /// Offset into the image file where the data starts
+    pub file_offset: u32,
+    /// Image data
+    pub data: OctetString,
+}
+
+// ZCL, 11.13.8.2 Image Block Response Command
+/// Image block response, sent by the server
+#[derive(Clone, Debug, PartialEq)]
+pub enum ImageBlockResponse {
+    /// The requested block of image data
+    Success(ImageBlock),
+    /// The server is not ready, the client shall retry at `request_time`
+    WaitForData {
+        /// Current time of the server, in UTC
+        current_time: u32,
+        /// Time when the client shall retry the request, in UTC
+        request_time: u32,
+        /// Minimum delay between blocks, in milliseconds
+        minimum_block_period: u16,
+    },
+    /// The server wishes to abort the upgrade
+    Abort,
+}
+
+impl Pack<ImageBlockResponse, Error> for ImageBlockResponse {
+    fn pack(&self, data: &mut [u8]) -> Result<usize, Error> {
+        if data.is_empty() {
+            return Err(Error::WrongNumberOfBytes);
+        }
+        match self {
+            ImageBlockResponse::Success(block) => {
+                if block.data.len() > usize::from(u8::max_value()) {
+                    return Err(Error::InvalidValue);
+                }
+                let length = 14 + block.data.len();
+                if data.len() < length {
+                    return Err(Error::WrongNumberOfBytes);
+                }
+                data[0] = u8::from(ClusterLibraryStatus::Success);
+                LittleEndian::write_u16(&mut data[1..3], block.manufacturer_code);
+                LittleEndian::write_u16(&mut data[3..5], block.image_type);
+                LittleEndian::write_u32(&mut data[5..9], block.file_version);
+                LittleEndian::write_u32(&mut data[9..13], block.file_offset);
+                data[13] = block.data.len() as u8;
+                data[14..length].copy_from_slice(&block.data);
+                Ok(length)
+            }
+            ImageBlockResponse::WaitForData {
+                current_time,
+                request_time,
+                minimum_block_period,
+            } => {
+                if data.len() < 11 {
+                    return Err(Error::WrongNumberOfBytes);
+                }
+                data[0] = u8::from(ClusterLibraryStatus::WaitForData);
+                LittleEndian::write_u32(&mut data[1..5], *current_time);
+                LittleEndian::write_u32(&mut data[5..9], *request_time);
+                LittleEndian::write_u16(&mut data[9..11], *minimum_block_period);
+                Ok(11)
+            }
+            ImageBlockResponse::Abort => {
+                data[0] = u8::from(ClusterLibraryStatus::Abort);
+                Ok(1)
+            }
+        }
+    }
+
+    fn unpack(data: &[u8]) -> Result<(Self, usize), Error> {
+        if data.is_empty() {
+            return Err(Error::WrongNumberOfBytes);
+        }
+        match ClusterLibraryStatus::try_from(data[0])? {
+            ClusterLibraryStatus::Success => {
+                if data.len() < 14 {
+                    return Err(Error::WrongNumberOfBytes);
+                }
+                let manufacturer_code = LittleEndian::read_u16(&data[1..3]);
+                let image_type = LittleEndian::read_u16(&data[3..5]);
+                let file_version = LittleEndian::read_u32(&data[5..9]);
+                let file_offset = LittleEndian::read_u32(&data[9..13]);
+                let (block_data, used) = OctetString::unpack(&data[13..])?;
+                Ok((
+                    ImageBlockResponse::Success(ImageBlock {
+                        manufacturer_code,
+                        image_type,
+                        file_version,
+                        file_offset,
+                        data: block_data,
+                    }),
+                    13 + used,
+                ))
+            }
+            ClusterLibraryStatus::WaitForData => {
+                if data.len() < 11 {
+                    return Err(Error::WrongNumberOfBytes);
+                }
+                let current_time = LittleEndian::read_u32(&data[1..5]);
+                let request_time = LittleEndian::read_u32(&data[5..9]);
+                let minimum_block_period = LittleEndian::read_u16(&data[9..11]);
+                Ok((
+                    ImageBlockResponse::WaitForData {
+                        current_time,
+                        request_time,
+                        minimum_block_period,
+                    },
+                    11,
+                ))
+            }
+            ClusterLibraryStatus::Abort => Ok((ImageBlockResponse::Abort, 1)),
+            _ => Err(Error::InvalidValue),
+        }
+    }
+}
+
+#[cfg(all(test, not(feature = "core")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unpack_image_block_request() {
+        let data = [
+            0x00, 0x7c, 0x11, 0x01, 0x21, 0x23, 0x01, 0x00, 0x20, 0x40, 0x02, 0x00, 0x00, 0x40,
+        ];
+        let (request, used) = ImageBlockRequest::unpack(&data).unwrap();
+        assert_eq!(used, 14);
+        assert_eq!(request.manufacturer_code, 0x117c);
+        assert_eq!(request.image_type, 0x2101);
+        assert_eq!(request.file_version, 0x2000_0123);
+        assert_eq!(request.file_offset, 0x0000_0240);
+        assert_eq!(request.maximum_data_size, 64);
+        assert_eq!(request.request_node_address, None);
+        assert_eq!(request.minimum_block_period, None);
+
+        let data = [
+            0x03, 0x7c, 0x11, 0x01, 0x21, 0x23, 0x01, 0x00, 0x20, 0x00, 0x00, 0x00, 0x00, 0x30,
+            0x99, 0x88, 0x77, 0x66, 0x55, 0x44, 0x33, 0x22, 0xe8, 0x03,
+        ];
+        let (request, used) = ImageBlockRequest::unpack(&data).unwrap();
+        assert_eq!(used, 24);
+        assert_eq!(request.file_offset, 0);
+        assert_eq!(request.maximum_data_size, 48);
+        assert_eq!(
+            request.request_node_address,
+            Some(ExtendedAddress::new(0x2233_4455_6677_8899))
+        );
+        assert_eq!(request.minimum_block_period, Some(1000));
+
+        let mut buffer = [0u8; 24];
+        let used = request.pack(&mut buffer).unwrap();
+        assert_eq!(used, 24);
+        assert_eq!(buffer, data);
+    }
+
+    #[test]
+    fn pack_image_block_response() {
+        let image: Vec<u8> = (0..64).collect();
+        let response = ImageBlockResponse::Success(ImageBlock {
+            manufacturer_code: 0x117c,
+            image_type: 0x2101,
+            file_version: 0x2000_0123,
+            file_offset: 0x0000_0240,
+            data: image.clone(),
+        });
+        let mut buffer = [0u8; 128];
+        let used = response.pack(&mut buffer).unwrap();
+        assert_eq!(used, 78);
+        assert_eq!(
+            buffer[..14],
+            [0x00, 0x7c, 0x11, 0x01, 0x21, 0x23, 0x01, 0x00, 0x20, 0x40, 0x02, 0x00, 0x00, 0x40]
+        );
+        assert_eq!(buffer[14..78], image[..]);
+
+        let (unpacked, used) = ImageBlockResponse::unpack(&buffer[..used]).unwrap();
+        assert_eq!(used, 78);
+        assert_eq!(unpacked, response);
+
+        let mut buffer = [0u8; 77];
+        assert_eq!(response.pack(&mut buffer), Err(Error::WrongNumberOfBytes));
+    }
+
+    #[test]
+    fn image_block_response_wait_for_data() {
+        let response = ImageBlockResponse::WaitForData {
+            current_time: 0x1000_0000,
+            request_time: 0x1000_0010,
+            minimum_block_period: 250,
+        };
+        let mut buffer = [0u8; 11];
+        let used = response.pack(&mut buffer).unwrap();
+        assert_eq!(used, 11);
+        assert_eq!(
+            buffer,
+            [0x97, 0x00, 0x00, 0x00, 0x10, 0x10, 0x00, 0x00, 0x10, 0xfa, 0x00]
+        );
+        let (unpacked, used) = ImageBlockResponse::unpack(&buffer).unwrap();
+        assert_eq!(used, 11);
+        assert_eq!(unpacked, response);
+    }
+}
@@ -0,0 +1,267 @@
+//! # Poll control cluster
+//!
+//! Lets a coordinator manage the polling of a sleepy end device. The device
+//! checks in periodically and the coordinator can ask it to poll fast, e.g.
+//! while transferring data. All intervals are in quarter seconds.
+
+use core::convert::TryFrom;
+
+use byteorder::{ByteOrder, LittleEndian};
+
+use crate::cluster_library::{
+    AttributeIdentifier, ClusterCommand, ClusterLibraryHeader, Direction,
+};
+use crate::pack::Pack;
+use crate::Error;
+
+/// Poll control cluster identifier
+pub const CLUSTER_IDENTIFIER: u16 = 0x0020;
+
+extended_enum!(
+    /// Poll control cluster attribute identifiers
+    PollControlAttribute, u16,
+    /// Interval between check-ins, unsigned 32-bit integer
+    CheckInInterval => 0x0000,
+    /// Interval between polls when not fast polling, unsigned 32-bit integer
+    LongPollInterval => 0x0001,
+    /// Interval between polls when fast polling, unsigned 16-bit integer
+    ShortPollInterval => 0x0002,
+    /// Default time spent fast polling, unsigned 16-bit integer
+    FastPollTimeout => 0x0003,
+    /// Minimum check-in interval, unsigned 32-bit integer
+    CheckInIntervalMin => 0x0004,
+    /// Minimum long poll interval, unsigned 32-bit integer
+    LongPollIntervalMin => 0x0005,
+    /// Maximum fast poll timeout, unsigned 16-bit integer
+    FastPollTimeoutMax => 0x0006,
+);
+
+impl From<PollControlAttribute> for AttributeIdentifier {
+    fn from(value: PollControlAttribute) -> Self {
+        AttributeIdentifier::new(u16::from(value))
+    }
+}
+
+/// Poll control cluster commands
+///
+/// The check-in command is sent by the server, the device, the others by
+/// the client. The check-in and check-in response share command identifier.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PollControlCommand {
+    /// The device is ready to be managed, server to client
+    CheckIn,
+    /// Response to a check-in, see `CheckInResponse`
+    CheckInResponse,
+    /// Stop fast polling
+    FastPollStop,
+    /// Set the long poll interval, see `SetLongPollInterval`
+    SetLongPollInterval,
+    /// Set the short poll interval, see `SetShortPollInterval`
+    SetShortPollInterval,
+}
+
+impl PollControlCommand {
+    /// Direction of the command
+    pub fn direction(self) -> Direction {
+        match self {
+            PollControlCommand::CheckIn => Direction::ToClient,
+            _ => Direction::ToServer,
+        }
+    }
+
+    /// Command identifier
+    pub fn identifier(self) -> u8 {
+        match self {
+            PollControlCommand::CheckIn | PollControlCommand::CheckInResponse => 0x00,
+            PollControlCommand::FastPollStop => 0x01,
+            PollControlCommand::SetLongPollInterval => 0x02,
+            PollControlCommand::SetShortPollInterval => 0x03,
+        }
+    }
+
+    /// Create the header of the command
+    pub fn header(self, transaction_sequence: u8) -> ClusterLibraryHeader {
+        ClusterLibraryHeader::new_cluster_command(
+            self.direction(),
+            transaction_sequence,
+            self.identifier(),
+            false,
+        )
+    }
+}
+
+impl ClusterCommand for PollControlCommand {
+    fn from_identifier(direction: Direction, identifier: u8) -> Result<Self, Error> {
+        match (direction, identifier) {
+            (Direction::ToClient, 0x00) => Ok(PollControlCommand::CheckIn),
+            (Direction::ToServer, 0x00) => Ok(PollControlCommand::CheckInResponse),
+            (Direction::ToServer, 0x01) => Ok(PollControlCommand::FastPollStop),
+            (Direction::ToServer, 0x02) => Ok(PollControlCommand::SetLongPollInterval),
+            (Direction::ToServer, 0x03) => Ok(PollControlCommand::SetShortPollInterval),
+            _ => Err(Error::InvalidValue),
+        }
+    }
+}
+
+// ZCL, 3.16.5.1 Check-in Response Command
+/// Check-in response, tells the device whether to start fast polling
+#[derive(Clone, Debug, PartialEq)]
+pub struct CheckInResponse {
+    /// Start fast polling
+    pub start_fast_polling: bool,
+    /// Time to fast poll, zero for the default of the device
+    pub fast_poll_timeout: u16,
+}
+
+impl CheckInResponse {
+    /// Ask the device to fast poll for `timeout`, zero for the default of
+    /// the device
+    pub fn fast_poll(timeout: u16) -> Self {
+        Self {
+            start_fast_polling: true,
+            fast_poll_timeout: timeout,
+        }
+    }
+
+    /// Let the device continue polling as before
+    pub fn no_fast_poll() -> Self {
+        Self {
+            start_fast_polling: false,
+            fast_poll_timeout: 0,
+        }
+    }
+}
+
+impl Pack<CheckInResponse, Error> for CheckInResponse {
+    fn pack(&self, data: &mut [u8]) -> Result<usize, Error> {
+        if data.len() < 3 {
+            return Err(Error::WrongNumberOfBytes);
+        }
+        data[0] = u8::from(self.start_fast_polling);
+        LittleEndian::write_u16(&mut data[1..3], self.fast_poll_timeout);
+        Ok(3)
+    }
+
+    fn unpack(data: &[u8]) -> Result<(Self, usize), Error> {
+        if data.len() < 3 {
+            return Err(Error::WrongNumberOfBytes);
+        }
+        let start_fast_polling = match data[0] {
+            0x00 => false,
+            0x01 => true,
+            _ => return Err(Error::InvalidValue),
+        };
+        let fast_poll_timeout = LittleEndian::read_u16(&data[1..3]);
+        Ok((
+            Self {
+                start_fast_polling,
+                fast_poll_timeout,
+            },
+            3,
+        ))
+    }
+}
+
+// ZCL, 3.16.5.3 Set Long Poll Interval Command
+/// Set the long poll interval of the device
+#[derive(Clone, Debug, PartialEq)]
+pub struct SetLongPollInterval {
+    /// The new long poll interval
+    pub interval: u32,
+}
+
+impl Pack<SetLongPollInterval, Error> for SetLongPollInterval {
+    fn pack(&self, data: &mut [u8]) -> Result<usize, Error> {
+        if data.len() < 4 {
+            return Err(Error::WrongNumberOfBytes);
+        }
+        LittleEndian::write_u32(&mut data[0..4], self.interval);
+        Ok(4)
+    }
+
+    fn unpack(data: &[u8]) -> Result<(Self, usize), Error> {
+        if data.len() < 4 {
+            return Err(Error::WrongNumberOfBytes);
+        }
+        let interval = LittleEndian::read_u32(&data[0..4]);
+        Ok((Self { interval }, 4))
+    }
+}
+
+// ZCL, 3.16.5.4 Set Short Poll Interval Command
+/// Set the short poll interval of the device
+#[derive(Clone, Debug, PartialEq)]
+pub struct SetShortPollInterval {
+    /// The new short poll interval
+    pub interval: u16,
+}
+
+impl Pack<SetShortPollInterval, Error> for SetShortPollInterval {
+    fn pack(&self, data: &mut [u8]) -> Result<usize, Error> {
+        if data.len() < 2 {
+            return Err(Error::WrongNumberOfBytes);
+        }
+        LittleEndian::write_u16(&mut data[0..2], self.interval);
+        Ok(2)
+    }
+
+    fn unpack(data: &[u8]) -> Result<(Self, usize), Error> {
+        if data.len() < 2 {
+            return Err(Error::WrongNumberOfBytes);
+        }
+        let interval = LittleEndian::read_u16(&data[0..2]);
+        Ok((Self { interval }, 2))
+    }
+}
+
+#[cfg(all(test, not(feature = "core")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn check_in_response_fast_poll() {
+        // Check-in response, start fast polling for 10 seconds
+        let data = [0x01, 0x05, 0x00, 0x01, 0x28, 0x00];
+        let (header, used) = ClusterLibraryHeader::unpack(&data).unwrap();
+        assert_eq!(
+            header.cluster_command::<PollControlCommand>(),
+            Ok(PollControlCommand::CheckInResponse)
+        );
+        let (response, _) = CheckInResponse::unpack(&data[used..]).unwrap();
+        assert_eq!(response, CheckInResponse::fast_poll(40));
+
+        let mut buffer = [0u8; 6];
+        let used = PollControlCommand::CheckInResponse
+            .header(0x05)
+            .pack(&mut buffer)
+            .unwrap();
+        let used = used + response.pack(&mut buffer[used..]).unwrap();
+        assert_eq!(buffer[..used], data);
+
+        // Same command identifier from the device
+        let (header, _) = ClusterLibraryHeader::unpack(&[0x09, 0x06, 0x00]).unwrap();
+        assert_eq!(
+            header.cluster_command::<PollControlCommand>(),
+            Ok(PollControlCommand::CheckIn)
+        );
+    }
+
+    #[test]
+    fn poll_intervals() {
+        let mut data = [0u8; 4];
+        let command = SetLongPollInterval { interval: 7200 };
+        assert_eq!(command.pack(&mut data).unwrap(), 4);
+        assert_eq!(data, [0x20, 0x1c, 0x00, 0x00]);
+        assert_eq!(SetLongPollInterval::unpack(&data).unwrap(), (command, 4));
+
+        let command = SetShortPollInterval { interval: 2 };
+        assert_eq!(command.pack(&mut data).unwrap(), 2);
+        assert_eq!(data[..2], [0x02, 0x00]);
+        assert_eq!(SetShortPollInterval::unpack(&data).unwrap(), (command, 2));
+
+        assert_eq!(
+            CheckInResponse::unpack(&[0x02, 0x00, 0x00]),
+            Err(Error::InvalidValue)
+        );
+    }
+}
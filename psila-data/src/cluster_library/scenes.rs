@@ -0,0 +1,571 @@
+//! # Scenes cluster
+//!
+//! Stores and recalls the state of a device, e.g. the level and color of a
+//! light, as scenes. A scene is identified by a group identifier and a scene
+//! identifier. Transition times are in seconds.
+
+use core::convert::TryFrom;
+
+use byteorder::{ByteOrder, LittleEndian};
+
+use crate::cluster_library::{
+    AttributeIdentifier, ClusterCommand, ClusterLibraryHeader, ClusterLibraryStatus, Direction,
+};
+use crate::common::address::{GroupIdentifier, GROUP_IDENTIFIER_SIZE};
+use crate::common::types::{CharacterString, OctetString};
+use crate::pack::{Pack, PackFixed};
+use crate::Error;
+
+/// Scenes cluster identifier
+pub const CLUSTER_IDENTIFIER: u16 = 0x0005;
+
+extended_enum!(
+    /// Scenes cluster attribute identifiers
+    ScenesAttribute, u16,
+    /// Number of scenes in the scene table, unsigned 8-bit integer
+    SceneCount => 0x0000,
+    /// Scene last invoked, unsigned 8-bit integer
+    CurrentScene => 0x0001,
+    /// Group of the scene last invoked, unsigned 16-bit integer
+    CurrentGroup => 0x0002,
+    /// The state of the device matches the current scene, boolean
+    SceneValid => 0x0003,
+    /// Scene names are supported, 8-bit bitmap
+    NameSupport => 0x0004,
+    /// Device which last configured the scene table, IEEE address
+    LastConfiguredBy => 0x0005,
+);
+
+impl From<ScenesAttribute> for AttributeIdentifier {
+    fn from(value: ScenesAttribute) -> Self {
+        AttributeIdentifier::new(u16::from(value))
+    }
+}
+
+/// Scenes cluster commands
+///
+/// The responses are sent by the server, the others by the client. Each
+/// response shares command identifier with its command.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ScenesCommand {
+    /// Add a scene, see `AddScene`
+    AddScene,
+    /// Ask for the content of a scene, see `SceneIdentifier`
+    ViewScene,
+    /// Remove a scene, see `SceneIdentifier`
+    RemoveScene,
+    /// Remove all scenes of a group, see `RemoveAllScenes`
+    RemoveAllScenes,
+    /// Store the current state as a scene, see `SceneIdentifier`
+    StoreScene,
+    /// Recall a scene, see `SceneIdentifier`
+    RecallScene,
+    /// Response to a add scene, see `SceneResponse`
+    AddSceneResponse,
+    /// Response to a view scene, see `ViewSceneResponse`
+    ViewSceneResponse,
+    /// Response to a remove scene, see `SceneResponse`
+    RemoveSceneResponse,
+    /// Response to a remove all scenes, see `RemoveAllScenesResponse`
+    RemoveAllScenesResponse,
+    /// Response to a store scene, see `SceneResponse`
+    StoreSceneResponse,
+}
+
+impl ScenesCommand {
+    /// Direction of the command
+    pub fn direction(self) -> Direction {
+        match self {
+            ScenesCommand::AddSceneResponse
+            | ScenesCommand::ViewSceneResponse
+            | ScenesCommand::RemoveSceneResponse
+            | ScenesCommand::RemoveAllScenesResponse
+            | ScenesCommand::StoreSceneResponse => Direction::ToClient,
+            _ => Direction::ToServer,
+        }
+    }
+
+    /// Command identifier
+    pub fn identifier(self) -> u8 {
+        match self {
+            ScenesCommand::AddScene | ScenesCommand::AddSceneResponse => 0x00,
+            ScenesCommand::ViewScene | ScenesCommand::ViewSceneResponse => 0x01,
+            ScenesCommand::RemoveScene | ScenesCommand::RemoveSceneResponse => 0x02,
+            ScenesCommand::RemoveAllScenes | ScenesCommand::RemoveAllScenesResponse => 0x03,
+            ScenesCommand::StoreScene | ScenesCommand::StoreSceneResponse => 0x04,
+            ScenesCommand::RecallScene => 0x05,
+        }
+    }
+
+    /// Create the header of the command
+    pub fn header(self, transaction_sequence: u8) -> ClusterLibraryHeader {
+        ClusterLibraryHeader::new_cluster_command(
+            self.direction(),
+            transaction_sequence,
+            self.identifier(),
+            false,
+        )
+    }
+}
+
+impl ClusterCommand for ScenesCommand {
+    fn from_identifier(direction: Direction, identifier: u8) -> Result<Self, Error> {
+        match (direction, identifier) {
+            (Direction::ToServer, 0x00) => Ok(ScenesCommand::AddScene),
+            (Direction::ToServer, 0x01) => Ok(ScenesCommand::ViewScene),
+            (Direction::ToServer, 0x02) => Ok(ScenesCommand::RemoveScene),
+            (Direction::ToServer, 0x03) => Ok(ScenesCommand::RemoveAllScenes),
+            (Direction::ToServer, 0x04) => Ok(ScenesCommand::StoreScene),
+            (Direction::ToServer, 0x05) => Ok(ScenesCommand::RecallScene),
+            (Direction::ToClient, 0x00) => Ok(ScenesCommand::AddSceneResponse),
+            (Direction::ToClient, 0x01) => Ok(ScenesCommand::ViewSceneResponse),
+            (Direction::ToClient, 0x02) => Ok(ScenesCommand::RemoveSceneResponse),
+            (Direction::ToClient, 0x03) => Ok(ScenesCommand::RemoveAllScenesResponse),
+            (Direction::ToClient, 0x04) => Ok(ScenesCommand::StoreSceneResponse),
+            _ => Err(Error::InvalidValue),
+        }
+    }
+}
+
+/// Extension field set of a scene, the state of one cluster
+///
+/// The content is specific to the cluster, e.g. the on/off attribute for
+/// the on/off cluster, and is kept as is.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExtensionFieldSet {
+    /// Cluster identifier
+    pub cluster: u16,
+    /// Attribute values of the cluster
+    pub data: OctetString,
+}
+
+impl Pack<ExtensionFieldSet, Error> for ExtensionFieldSet {
+    fn pack(&self, data: &mut [u8]) -> Result<usize, Error> {
+        if data.len() < 2 {
+            return Err(Error::WrongNumberOfBytes);
+        }
+        LittleEndian::write_u16(&mut data[0..2], self.cluster);
+        let used = self.data.pack(&mut data[2..])?;
+        Ok(2 + used)
+    }
+
+    fn unpack(data: &[u8]) -> Result<(Self, usize), Error> {
+        if data.len() < 2 {
+            return Err(Error::WrongNumberOfBytes);
+        }
+        let cluster = LittleEndian::read_u16(&data[0..2]);
+        let (value, used) = OctetString::unpack(&data[2..])?;
+        Ok((
+            Self {
+                cluster,
+                data: value,
+            },
+            2 + used,
+        ))
+    }
+}
+
+#[cfg(not(feature = "core"))]
+pub type ExtensionFieldSetVec = std::vec::Vec<ExtensionFieldSet>;
+
+#[cfg(feature = "core")]
+pub type ExtensionFieldSetVec = heapless::Vec<ExtensionFieldSet, heapless::consts::U8>;
+
+pub(crate) fn pack_extensions(
+    extensions: &ExtensionFieldSetVec,
+    data: &mut [u8],
+) -> Result<usize, Error> {
+    let mut offset = 0;
+    for extension in extensions.iter() {
+        offset += extension.pack(&mut data[offset..])?;
+    }
+    Ok(offset)
+}
+
+/// Extension field sets fill the rest of the command
+pub(crate) fn unpack_extensions(data: &[u8]) -> Result<(ExtensionFieldSetVec, usize), Error> {
+    let mut offset = 0;
+    let mut extensions = ExtensionFieldSetVec::new();
+    while offset < data.len() {
+        let (extension, used) = ExtensionFieldSet::unpack(&data[offset..])?;
+        extensions.push(extension);
+        offset += used;
+    }
+    Ok((extensions, offset))
+}
+
+// ZCL, 3.7.2.4.2 View Scene Command
+/// Group and scene identifier, payload of the view, remove, store and
+/// recall scene commands
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SceneIdentifier {
+    /// Group identifier
+    pub group: GroupIdentifier,
+    /// Scene identifier
+    pub scene: u8,
+}
+
+impl SceneIdentifier {
+    /// Create a scene identifier for `scene` of `group`
+    pub fn new(group: GroupIdentifier, scene: u8) -> Self {
+        Self { group, scene }
+    }
+}
+
+impl Pack<SceneIdentifier, Error> for SceneIdentifier {
+    fn pack(&self, data: &mut [u8]) -> Result<usize, Error> {
+        if data.len() < 3 {
+            return Err(Error::WrongNumberOfBytes);
+        }
+        self.group.pack(&mut data[0..GROUP_IDENTIFIER_SIZE])?;
+        data[2] = self.scene;
+        Ok(3)
+    }
+
+    fn unpack(data: &[u8]) -> Result<(Self, usize), Error> {
+        if data.len() < 3 {
+            return Err(Error::WrongNumberOfBytes);
+        }
+        let group = GroupIdentifier::unpack(&data[0..GROUP_IDENTIFIER_SIZE])?;
+        Ok((
+            Self {
+                group,
+                scene: data[2],
+            },
+            3,
+        ))
+    }
+}
+
+// ZCL, 3.7.2.4.1 Add Scene Command
+/// Add a scene with the state given by the extension field sets
+#[derive(Clone, Debug, PartialEq)]
+pub struct AddScene {
+    /// Group and scene identifier
+    pub identifier: SceneIdentifier,
+    /// Time to transition to the scene when recalled
+    pub transition_time: u16,
+    /// Name of the scene, empty if names aren't supported
+    pub name: CharacterString,
+    /// State of the clusters of the scene
+    pub extensions: ExtensionFieldSetVec,
+}
+
+impl Pack<AddScene, Error> for AddScene {
+    fn pack(&self, data: &mut [u8]) -> Result<usize, Error> {
+        if data.len() < 5 {
+            return Err(Error::WrongNumberOfBytes);
+        }
+        let mut offset = self.identifier.pack(data)?;
+        LittleEndian::write_u16(&mut data[offset..offset + 2], self.transition_time);
+        offset += 2;
+        offset += self.name.pack(&mut data[offset..])?;
+        offset += pack_extensions(&self.extensions, &mut data[offset..])?;
+        Ok(offset)
+    }
+
+    fn unpack(data: &[u8]) -> Result<(Self, usize), Error> {
+        if data.len() < 5 {
+            return Err(Error::WrongNumberOfBytes);
+        }
+        let (identifier, mut offset) = SceneIdentifier::unpack(data)?;
+        let transition_time = LittleEndian::read_u16(&data[offset..offset + 2]);
+        offset += 2;
+        let (name, used) = CharacterString::unpack(&data[offset..])?;
+        offset += used;
+        let (extensions, used) = unpack_extensions(&data[offset..])?;
+        Ok((
+            Self {
+                identifier,
+                transition_time,
+                name,
+                extensions,
+            },
+            offset + used,
+        ))
+    }
+}
+
+// ZCL, 3.7.2.4.4 Remove All Scenes Command
+/// Remove all scenes of a group
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RemoveAllScenes {
+    /// Group identifier
+    pub group: GroupIdentifier,
+}
+
+impl Pack<RemoveAllScenes, Error> for RemoveAllScenes {
+    fn pack(&self, data: &mut [u8]) -> Result<usize, Error> {
+        if data.len() < GROUP_IDENTIFIER_SIZE {
+            return Err(Error::WrongNumberOfBytes);
+        }
+        self.group.pack(&mut data[0..GROUP_IDENTIFIER_SIZE])?;
+        Ok(GROUP_IDENTIFIER_SIZE)
+    }
+
+    fn unpack(data: &[u8]) -> Result<(Self, usize), Error> {
+        if data.len() < GROUP_IDENTIFIER_SIZE {
+            return Err(Error::WrongNumberOfBytes);
+        }
+        let group = GroupIdentifier::unpack(&data[0..GROUP_IDENTIFIER_SIZE])?;
+        Ok((Self { group }, GROUP_IDENTIFIER_SIZE))
+    }
+}
+
+/// Create the header and payload of a recall scene command for `scene` of
+/// `group`
+pub fn recall_scene(
+    transaction_sequence: u8,
+    group: GroupIdentifier,
+    scene: u8,
+) -> (ClusterLibraryHeader, SceneIdentifier) {
+    (
+        ScenesCommand::RecallScene.header(transaction_sequence),
+        SceneIdentifier::new(group, scene),
+    )
+}
+
+/// Create the header and payload of a store scene command for `scene` of
+/// `group`
+pub fn store_scene(
+    transaction_sequence: u8,
+    group: GroupIdentifier,
+    scene: u8,
+) -> (ClusterLibraryHeader, SceneIdentifier) {
+    (
+        ScenesCommand::StoreScene.header(transaction_sequence),
+        SceneIdentifier::new(group, scene),
+    )
+}
+
+/// Create the header and payload of a remove scene command for `scene` of
+/// `group`
+pub fn remove_scene(
+    transaction_sequence: u8,
+    group: GroupIdentifier,
+    scene: u8,
+) -> (ClusterLibraryHeader, SceneIdentifier) {
+    (
+        ScenesCommand::RemoveScene.header(transaction_sequence),
+        SceneIdentifier::new(group, scene),
+    )
+}
+
+// ZCL, 3.7.2.5.1 Add Scene Response Command
+/// Response to the add, remove and store scene commands
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SceneResponse {
+    /// Status of the command
+    pub status: ClusterLibraryStatus,
+    /// Group and scene identifier
+    pub identifier: SceneIdentifier,
+}
+
+impl Pack<SceneResponse, Error> for SceneResponse {
+    fn pack(&self, data: &mut [u8]) -> Result<usize, Error> {
+        if data.len() < 4 {
+            return Err(Error::WrongNumberOfBytes);
+        }
+        data[0] = u8::from(self.status);
+        let used = self.identifier.pack(&mut data[1..])?;
+        Ok(1 + used)
+    }
+
+    fn unpack(data: &[u8]) -> Result<(Self, usize), Error> {
+        if data.len() < 4 {
+            return Err(Error::WrongNumberOfBytes);
+        }
+        let status = ClusterLibraryStatus::try_from(data[0])?;
+        let (identifier, used) = SceneIdentifier::unpack(&data[1..])?;
+        Ok((Self { status, identifier }, 1 + used))
+    }
+}
+
+// ZCL, 3.7.2.5.2 View Scene Response Command
+/// Response to the view scene command
+///
+/// The scene content is only present when the status is success.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ViewSceneResponse {
+    /// Status of the command
+    pub status: ClusterLibraryStatus,
+    /// Group and scene identifier
+    pub identifier: SceneIdentifier,
+    /// Time to transition to the scene when recalled
+    pub transition_time: u16,
+    /// Name of the scene
+    pub name: CharacterString,
+    /// State of the clusters of the scene
+    pub extensions: ExtensionFieldSetVec,
+}
+
+impl Pack<ViewSceneResponse, Error> for ViewSceneResponse {
+    fn pack(&self, data: &mut [u8]) -> Result<usize, Error> {
+        if data.len() < 4 {
+            return Err(Error::WrongNumberOfBytes);
+        }
+        data[0] = u8::from(self.status);
+        let mut offset = 1 + self.identifier.pack(&mut data[1..])?;
+        if self.status != ClusterLibraryStatus::Success {
+            return Ok(offset);
+        }
+        if data.len() < offset + 2 {
+            return Err(Error::WrongNumberOfBytes);
+        }
+        LittleEndian::write_u16(&mut data[offset..offset + 2], self.transition_time);
+        offset += 2;
+        offset += self.name.pack(&mut data[offset..])?;
+        offset += pack_extensions(&self.extensions, &mut data[offset..])?;
+        Ok(offset)
+    }
+
+    fn unpack(data: &[u8]) -> Result<(Self, usize), Error> {
+        if data.len() < 4 {
+            return Err(Error::WrongNumberOfBytes);
+        }
+        let status = ClusterLibraryStatus::try_from(data[0])?;
+        let (identifier, used) = SceneIdentifier::unpack(&data[1..])?;
+        let mut offset = 1 + used;
+        let mut response = Self {
+            status,
+            identifier,
+            transition_time: 0,
+            name: CharacterString::new(),
+            extensions: ExtensionFieldSetVec::new(),
+        };
+        if status != ClusterLibraryStatus::Success {
+            return Ok((response, offset));
+        }
+        if data.len() < offset + 2 {
+            return Err(Error::WrongNumberOfBytes);
+        }
+        response.transition_time = LittleEndian::read_u16(&data[offset..offset + 2]);
+        offset += 2;
+        let (name, used) = CharacterString::unpack(&data[offset..])?;
+        response.name = name;
+        offset += used;
+        let (extensions, used) = unpack_extensions(&data[offset..])?;
+        response.extensions = extensions;
+        Ok((response, offset + used))
+    }
+}
+
+// ZCL, 3.7.2.5.4 Remove All Scenes Response Command
+/// Response to the remove all scenes command
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RemoveAllScenesResponse {
+    /// Status of the command
+    pub status: ClusterLibraryStatus,
+    /// Group identifier
+    pub group: GroupIdentifier,
+}
+
+impl Pack<RemoveAllScenesResponse, Error> for RemoveAllScenesResponse {
+    fn pack(&self, data: &mut [u8]) -> Result<usize, Error> {
+        if data.len() < 3 {
+            return Err(Error::WrongNumberOfBytes);
+        }
+        data[0] = u8::from(self.status);
+        self.group.pack(&mut data[1..3])?;
+        Ok(3)
+    }
+
+    fn unpack(data: &[u8]) -> Result<(Self, usize), Error> {
+        if data.len() < 3 {
+            return Err(Error::WrongNumberOfBytes);
+        }
+        let status = ClusterLibraryStatus::try_from(data[0])?;
+        let group = GroupIdentifier::unpack(&data[1..3])?;
+        Ok((Self { status, group }, 3))
+    }
+}
+
+#[cfg(all(test, not(feature = "core")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pack_recall_scene() {
+        let (header, payload) = recall_scene(0x31, GroupIdentifier::new(0x0001), 2);
+        let mut buffer = [0u8; 6];
+        let used = header.pack(&mut buffer).unwrap();
+        let used = used + payload.pack(&mut buffer[used..]).unwrap();
+        assert_eq!(buffer[..used], [0x01, 0x31, 0x05, 0x01, 0x00, 0x02]);
+
+        let (header, used) = ClusterLibraryHeader::unpack(&buffer).unwrap();
+        assert_eq!(
+            header.cluster_command::<ScenesCommand>(),
+            Ok(ScenesCommand::RecallScene)
+        );
+        let (identifier, _) = SceneIdentifier::unpack(&buffer[used..]).unwrap();
+        assert_eq!(identifier, payload);
+
+        let (header, payload) = store_scene(0x32, GroupIdentifier::new(0x0001), 2);
+        let used = header.pack(&mut buffer).unwrap();
+        let used = used + payload.pack(&mut buffer[used..]).unwrap();
+        assert_eq!(buffer[..used], [0x01, 0x32, 0x04, 0x01, 0x00, 0x02]);
+
+        let (header, _) = remove_scene(0x33, GroupIdentifier::new(0x0001), 2);
+        assert_eq!(
+            header.cluster_command::<ScenesCommand>(),
+            Ok(ScenesCommand::RemoveScene)
+        );
+    }
+
+    #[test]
+    fn add_scene() {
+        // Group 1, scene 2, 1 second transition, "up", on/off on
+        let data = [
+            0x01, 0x00, 0x02, 0x01, 0x00, 0x02, 0x75, 0x70, 0x06, 0x00, 0x01, 0x01,
+        ];
+        let (command, used) = AddScene::unpack(&data).unwrap();
+        assert_eq!(used, data.len());
+        assert_eq!(
+            command.identifier,
+            SceneIdentifier::new(GroupIdentifier::new(0x0001), 2)
+        );
+        assert_eq!(command.transition_time, 1);
+        assert_eq!(command.name, "up");
+        assert_eq!(
+            command.extensions,
+            [ExtensionFieldSet {
+                cluster: 0x0006,
+                data: vec![0x01],
+            }]
+        );
+        let mut buffer = [0u8; 12];
+        assert_eq!(command.pack(&mut buffer), Ok(12));
+        assert_eq!(buffer, data);
+
+        // Truncated extension field set
+        assert!(AddScene::unpack(&data[..11]).is_err());
+    }
+
+    #[test]
+    fn scene_responses() {
+        let data = [0x00, 0x01, 0x00, 0x02];
+        let (response, used) = SceneResponse::unpack(&data).unwrap();
+        assert_eq!(used, 4);
+        assert_eq!(response.status, ClusterLibraryStatus::Success);
+        assert_eq!(response.identifier.scene, 2);
+
+        // Unsuccessful view scene response carries no scene content
+        let data = [0x8b, 0x01, 0x00, 0x03];
+        let (response, used) = ViewSceneResponse::unpack(&data).unwrap();
+        assert_eq!(used, 4);
+        assert_eq!(response.status, ClusterLibraryStatus::NotFound);
+        assert!(response.extensions.is_empty());
+        let mut buffer = [0u8; 8];
+        assert_eq!(response.pack(&mut buffer), Ok(4));
+        assert_eq!(buffer[..4], data);
+
+        let data = [0x00, 0x01, 0x00, 0x05, 0x05, 0x00, 0x00];
+        let (response, used) = ViewSceneResponse::unpack(&data).unwrap();
+        assert_eq!(used, 7);
+        assert_eq!(response.transition_time, 5);
+        assert!(response.name.is_empty());
+
+        let (response, _) = RemoveAllScenesResponse::unpack(&[0x00, 0x01, 0x00]).unwrap();
+        assert_eq!(response.group, GroupIdentifier::new(0x0001));
+    }
+}
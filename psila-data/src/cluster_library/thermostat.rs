@@ -0,0 +1,274 @@
+//! # Thermostat cluster
+//!
+//! Heating and cooling control. Temperatures are signed 16-bit integers in
+//! hundredths of degrees Celsius, the setpoint adjustments in tenths of
+//! degrees Celsius.
+
+use core::convert::TryFrom;
+
+use crate::cluster_library::{
+    AttributeIdentifier, AttributeValue, ClusterCommand, ClusterLibraryHeader, Direction,
+};
+use crate::pack::Pack;
+use crate::Error;
+
+/// Thermostat cluster identifier
+pub const CLUSTER_IDENTIFIER: u16 = 0x0201;
+
+extended_enum!(
+    /// Thermostat cluster attribute identifiers
+    ThermostatAttribute, u16,
+    /// Measured temperature, signed 16-bit integer
+    LocalTemperature => 0x0000,
+    /// Outdoor temperature, signed 16-bit integer
+    OutdoorTemperature => 0x0001,
+    /// Occupancy sensed, 8-bit bitmap
+    Occupancy => 0x0002,
+    /// Cooling setpoint when occupied, signed 16-bit integer
+    OccupiedCoolingSetpoint => 0x0011,
+    /// Heating setpoint when occupied, signed 16-bit integer
+    OccupiedHeatingSetpoint => 0x0012,
+    /// Cooling setpoint when unoccupied, signed 16-bit integer
+    UnoccupiedCoolingSetpoint => 0x0013,
+    /// Heating setpoint when unoccupied, signed 16-bit integer
+    UnoccupiedHeatingSetpoint => 0x0014,
+    /// Supported operation, 8-bit enumeration
+    ControlSequenceOfOperation => 0x001b,
+    /// Current system mode, 8-bit enumeration, see `SystemMode`
+    SystemMode => 0x001c,
+    /// Mode the thermostat is running in, 8-bit enumeration
+    ThermostatRunningMode => 0x001e,
+    /// Outputs currently on, 16-bit bitmap, see `RunningState`
+    ThermostatRunningState => 0x0029,
+);
+
+impl From<ThermostatAttribute> for AttributeIdentifier {
+    fn from(value: ThermostatAttribute) -> Self {
+        AttributeIdentifier::new(u16::from(value))
+    }
+}
+
+extended_enum!(
+    /// Thermostat system mode
+    SystemMode, u8,
+    /// Off
+    Off => 0x00,
+    /// Automatic heating and cooling
+    Auto => 0x01,
+    /// Cooling
+    Cool => 0x03,
+    /// Heating
+    Heat => 0x04,
+    /// Emergency heating
+    EmergencyHeating => 0x05,
+    /// Pre-cooling
+    Precooling => 0x06,
+    /// Fan only
+    FanOnly => 0x07,
+    /// Drying
+    Dry => 0x08,
+    /// Sleep
+    Sleep => 0x09,
+);
+
+bitflags! {
+    /// Outputs of the thermostat which are currently on
+    pub struct RunningState: u16 {
+        const HEAT              = 0b0000_0000_0000_0001;
+        const COOL              = 0b0000_0000_0000_0010;
+        const FAN               = 0b0000_0000_0000_0100;
+        const HEAT_SECOND_STAGE = 0b0000_0000_0000_1000;
+        const COOL_SECOND_STAGE = 0b0000_0000_0001_0000;
+        const FAN_SECOND_STAGE  = 0b0000_0000_0010_0000;
+        const FAN_THIRD_STAGE   = 0b0000_0000_0100_0000;
+    }
+}
+
+/// Get a temperature or setpoint from a attribute value, `None` if the value
+/// isn't a valid signed 16-bit integer
+pub fn temperature(value: &AttributeValue) -> Option<i16> {
+    match value {
+        AttributeValue::Signed16(temperature) if value.is_valid() => Some(*temperature),
+        _ => None,
+    }
+}
+
+/// Get the system mode from a attribute value, `None` if the value isn't a
+/// known system mode
+pub fn system_mode(value: &AttributeValue) -> Option<SystemMode> {
+    match value {
+        AttributeValue::Enumeration8(mode) => SystemMode::try_from(*mode).ok(),
+        _ => None,
+    }
+}
+
+/// Get the running state from a attribute value, `None` if the value isn't
+/// a 16-bit bitmap
+pub fn running_state(value: &AttributeValue) -> Option<RunningState> {
+    match value {
+        AttributeValue::Bitmap16(state) => Some(RunningState::from_bits_truncate(*state)),
+        _ => None,
+    }
+}
+
+/// Thermostat cluster commands, sent by the client
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ThermostatCommand {
+    /// Adjust the setpoints, see `SetpointRaiseLower`
+    SetpointRaiseLower,
+    /// Remove the weekly schedule, no payload
+    ClearWeeklySchedule,
+}
+
+impl ThermostatCommand {
+    /// Direction of the command
+    pub fn direction(self) -> Direction {
+        Direction::ToServer
+    }
+
+    /// Command identifier
+    pub fn identifier(self) -> u8 {
+        match self {
+            ThermostatCommand::SetpointRaiseLower => 0x00,
+            ThermostatCommand::ClearWeeklySchedule => 0x03,
+        }
+    }
+
+    /// Create the header of the command
+    pub fn header(self, transaction_sequence: u8) -> ClusterLibraryHeader {
+        ClusterLibraryHeader::new_cluster_command(
+            self.direction(),
+            transaction_sequence,
+            self.identifier(),
+            false,
+        )
+    }
+}
+
+impl ClusterCommand for ThermostatCommand {
+    fn from_identifier(direction: Direction, identifier: u8) -> Result<Self, Error> {
+        match (direction, identifier) {
+            (Direction::ToServer, 0x00) => Ok(ThermostatCommand::SetpointRaiseLower),
+            (Direction::ToServer, 0x03) => Ok(ThermostatCommand::ClearWeeklySchedule),
+            _ => Err(Error::InvalidValue),
+        }
+    }
+}
+
+extended_enum!(
+    /// Setpoints adjusted by a setpoint raise/lower command
+    SetpointMode, u8,
+    /// Heating setpoint
+    Heat => 0x00,
+    /// Cooling setpoint
+    Cool => 0x01,
+    /// Both heating and cooling setpoints
+    Both => 0x02,
+);
+
+// ZCL, 6.3.2.3.1 Setpoint Raise/Lower Command
+/// Setpoint raise/lower, adjust the setpoints by a amount
+#[derive(Clone, Debug, PartialEq)]
+pub struct SetpointRaiseLower {
+    /// The setpoints to adjust
+    pub mode: SetpointMode,
+    /// Adjustment in tenths of degrees Celsius, negative to lower
+    pub amount: i8,
+}
+
+impl Pack<SetpointRaiseLower, Error> for SetpointRaiseLower {
+    fn pack(&self, data: &mut [u8]) -> Result<usize, Error> {
+        if data.len() < 2 {
+            return Err(Error::WrongNumberOfBytes);
+        }
+        data[0] = u8::from(self.mode);
+        data[1] = self.amount as u8;
+        Ok(2)
+    }
+
+    fn unpack(data: &[u8]) -> Result<(Self, usize), Error> {
+        if data.len() < 2 {
+            return Err(Error::WrongNumberOfBytes);
+        }
+        let mode = SetpointMode::try_from(data[0])?;
+        let amount = data[1] as i8;
+        Ok((Self { mode, amount }, 2))
+    }
+}
+
+/// Create the header and payload of a setpoint raise/lower command,
+/// adjusting the `mode` setpoints by `amount` tenths of degrees Celsius
+pub fn setpoint_raise_lower(
+    transaction_sequence: u8,
+    mode: SetpointMode,
+    amount: i8,
+) -> (ClusterLibraryHeader, SetpointRaiseLower) {
+    (
+        ThermostatCommand::SetpointRaiseLower.header(transaction_sequence),
+        SetpointRaiseLower { mode, amount },
+    )
+}
+
+/// Create the header of a clear weekly schedule command, the command has no
+/// payload
+pub fn clear_weekly_schedule(transaction_sequence: u8) -> ClusterLibraryHeader {
+    ThermostatCommand::ClearWeeklySchedule.header(transaction_sequence)
+}
+
+#[cfg(all(test, not(feature = "core")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pack_raise_heating_setpoint() {
+        // Raise the heating setpoint by 2 degrees
+        let (header, command) = setpoint_raise_lower(0x31, SetpointMode::Heat, 20);
+        let mut buffer = [0u8; 5];
+        let used = header.pack(&mut buffer).unwrap();
+        let used = used + command.pack(&mut buffer[used..]).unwrap();
+        assert_eq!(buffer[..used], [0x01, 0x31, 0x00, 0x00, 0x14]);
+
+        let (header, used) = ClusterLibraryHeader::unpack(&buffer).unwrap();
+        assert_eq!(
+            header.cluster_command::<ThermostatCommand>(),
+            Ok(ThermostatCommand::SetpointRaiseLower)
+        );
+        let (unpacked, _) = SetpointRaiseLower::unpack(&buffer[used..]).unwrap();
+        assert_eq!(unpacked, command);
+
+        // Lower both setpoints by half a degree
+        let (command, _) = SetpointRaiseLower::unpack(&[0x02, 0xfb]).unwrap();
+        assert_eq!(command.mode, SetpointMode::Both);
+        assert_eq!(command.amount, -5);
+        assert_eq!(
+            SetpointRaiseLower::unpack(&[0x03, 0x00]),
+            Err(Error::InvalidValue)
+        );
+        assert_eq!(
+            SetpointRaiseLower::unpack(&[0x00]),
+            Err(Error::WrongNumberOfBytes)
+        );
+
+        let used = clear_weekly_schedule(0x32).pack(&mut buffer).unwrap();
+        assert_eq!(buffer[..used], [0x01, 0x32, 0x03]);
+    }
+
+    #[test]
+    fn attribute_values() {
+        // 21.5 degrees
+        assert_eq!(temperature(&AttributeValue::Signed16(2150)), Some(2150));
+        assert_eq!(
+            temperature(&AttributeValue::Signed16(i16::min_value())),
+            None
+        );
+        assert_eq!(
+            system_mode(&AttributeValue::Enumeration8(0x04)),
+            Some(SystemMode::Heat)
+        );
+        assert_eq!(system_mode(&AttributeValue::Enumeration8(0x02)), None);
+        assert_eq!(
+            running_state(&AttributeValue::Bitmap16(0x0005)),
+            Some(RunningState::HEAT | RunningState::FAN)
+        );
+    }
+}
@@ -0,0 +1,155 @@
+//! # Time cluster
+//!
+//! The time cluster keeps time as the number of seconds since 1st of January
+//! 2000 00:00:00 UTC, the Zigbee epoch.
+
+use core::convert::TryFrom;
+
+use crate::cluster_library::{
+    AttributeIdentifier, AttributeIdentifierVec, AttributeValue, ClusterLibraryStatus,
+    ReadAttributes, ReadAttributesResponse, WriteAttributeRecord, WriteAttributeRecordVec,
+    WriteAttributes,
+};
+use crate::Error;
+
+/// Time cluster identifier
+pub const CLUSTER_IDENTIFIER: u16 = 0x000a;
+
+/// Seconds between the Unix epoch (1970) and the Zigbee epoch (2000)
+pub const ZIGBEE_EPOCH_OFFSET: u64 = 946_684_800;
+
+extended_enum!(
+    /// Time cluster attribute identifiers
+    TimeAttribute, u16,
+    /// UTC time, seconds since the Zigbee epoch
+    Time => 0x0000,
+    /// Time status bitmap
+    TimeStatus => 0x0001,
+    /// Offset from UTC in seconds
+    TimeZone => 0x0002,
+    /// Start of daylight saving time
+    DstStart => 0x0003,
+    /// End of daylight saving time
+    DstEnd => 0x0004,
+    /// Daylight saving time offset in seconds
+    DstShift => 0x0005,
+    /// Local standard time
+    StandardTime => 0x0006,
+    /// Local time
+    LocalTime => 0x0007,
+    /// Last time the time was set
+    LastSetTime => 0x0008,
+    /// Time until which the time is considered valid
+    ValidUntilTime => 0x0009,
+);
+
+impl From<TimeAttribute> for AttributeIdentifier {
+    fn from(value: TimeAttribute) -> Self {
+        AttributeIdentifier::new(u16::from(value))
+    }
+}
+
+/// Convert Zigbee time into Unix time
+pub fn zigbee_to_unix(time: u32) -> u64 {
+    u64::from(time) + ZIGBEE_EPOCH_OFFSET
+}
+
+/// Convert Unix time into Zigbee time
+///
+/// Returns `None` if the time is before the Zigbee epoch or cannot be
+/// represented, 0xffffffff is reserved as invalid time.
+pub fn unix_to_zigbee(time: u64) -> Option<u32> {
+    if time < ZIGBEE_EPOCH_OFFSET {
+        return None;
+    }
+    match u32::try_from(time - ZIGBEE_EPOCH_OFFSET) {
+        Ok(time) if time != u32::max_value() => Some(time),
+        _ => None,
+    }
+}
+
+/// Create a read attributes command reading the time attribute
+pub fn read_time() -> ReadAttributes {
+    let mut attributes = AttributeIdentifierVec::new();
+    attributes.push(AttributeIdentifier::from(TimeAttribute::Time));
+    ReadAttributes { attributes }
+}
+
+/// Create a write attributes command setting the time attribute
+pub fn write_time(time: u32) -> WriteAttributes {
+    let mut attributes = WriteAttributeRecordVec::new();
+    attributes.push(WriteAttributeRecord {
+        identifier: AttributeIdentifier::from(TimeAttribute::Time),
+        value: AttributeValue::UtcTime(time),
+    });
+    WriteAttributes { attributes }
+}
+
+/// Get the time attribute from a read attributes response
+///
+/// Returns `None` if the response does not successfully carry a valid time
+pub fn time_from_response(response: &ReadAttributesResponse) -> Option<u32> {
+    response
+        .attributes
+        .iter()
+        .filter(|a| a.identifier == u16::from(TimeAttribute::Time))
+        .filter(|a| a.status == ClusterLibraryStatus::Success)
+        .find_map(|a| match a.value {
+            Some(AttributeValue::UtcTime(time)) if time != u32::max_value() => Some(time),
+            _ => None,
+        })
+}
+
+#[cfg(all(test, not(feature = "core")))]
+mod tests {
+    use super::*;
+    use crate::cluster_library::AttributeStatus;
+    use crate::pack::Pack;
+
+    #[test]
+    fn epoch_conversion() {
+        assert_eq!(zigbee_to_unix(0), 946_684_800);
+        // 2020-01-01 00:00:00 UTC
+        assert_eq!(zigbee_to_unix(631_152_000), 1_577_836_800);
+        assert_eq!(unix_to_zigbee(1_577_836_800), Some(631_152_000));
+        assert_eq!(unix_to_zigbee(946_684_800), Some(0));
+        assert_eq!(unix_to_zigbee(946_684_799), None);
+        assert_eq!(unix_to_zigbee(0), None);
+        assert_eq!(unix_to_zigbee(ZIGBEE_EPOCH_OFFSET + 0xffff_ffff), None);
+    }
+
+    #[test]
+    fn pack_write_time() {
+        let cmd = write_time(631_152_000);
+        let mut data = [0u8; 7];
+        let used = cmd.pack(&mut data[..]).unwrap();
+        assert_eq!(used, 7);
+        assert_eq!(data, [0x00, 0x00, 0xe2, 0x80, 0x9d, 0x9e, 0x25]);
+    }
+
+    #[test]
+    fn pack_read_time() {
+        let cmd = read_time();
+        let mut data = [0u8; 2];
+        let used = cmd.pack(&mut data[..]).unwrap();
+        assert_eq!(used, 2);
+        assert_eq!(data, [0x00, 0x00]);
+    }
+
+    #[test]
+    fn unpack_time_response() {
+        let data = [0x00, 0x00, 0x00, 0xe2, 0x80, 0x9d, 0x9e, 0x25];
+        let (response, used) = ReadAttributesResponse::unpack(&data).unwrap();
+        assert_eq!(used, 8);
+        assert_eq!(time_from_response(&response), Some(631_152_000));
+
+        let response = ReadAttributesResponse {
+            attributes: vec![AttributeStatus {
+                identifier: AttributeIdentifier::from(TimeAttribute::Time),
+                status: ClusterLibraryStatus::UnsupportedAttribute,
+                value: None,
+            }],
+        };
+        assert_eq!(time_from_response(&response), None);
+    }
+}
@@ -0,0 +1,225 @@
+//! # Window covering cluster
+//!
+//! Controls blinds, shades and other window coverings. The lift is the
+//! vertical position of the covering, the tilt the angle of the slats.
+//! Percentages are zero for fully open and 100 for fully closed.
+
+use core::convert::TryFrom;
+
+use crate::cluster_library::{
+    AttributeIdentifier, ClusterCommand, ClusterLibraryHeader, Direction,
+};
+use crate::pack::Pack;
+use crate::Error;
+
+/// Window covering cluster identifier
+pub const CLUSTER_IDENTIFIER: u16 = 0x0102;
+
+/// Highest position percentage, fully closed
+pub const MAX_PERCENTAGE: u8 = 100;
+
+extended_enum!(
+    /// Window covering cluster attribute identifiers
+    WindowCoveringAttribute, u16,
+    /// Kind of window covering, 8-bit enumeration
+    WindowCoveringType => 0x0000,
+    /// Current lift position, unsigned 16-bit integer
+    CurrentPositionLift => 0x0003,
+    /// Current tilt position, unsigned 16-bit integer
+    CurrentPositionTilt => 0x0004,
+    /// Operational status and configuration, 8-bit bitmap
+    ConfigStatus => 0x0007,
+    /// Current lift position in percent, unsigned 8-bit integer
+    CurrentPositionLiftPercentage => 0x0008,
+    /// Current tilt position in percent, unsigned 8-bit integer
+    CurrentPositionTiltPercentage => 0x0009,
+    /// Operating mode, 8-bit bitmap
+    Mode => 0x0017,
+);
+
+impl From<WindowCoveringAttribute> for AttributeIdentifier {
+    fn from(value: WindowCoveringAttribute) -> Self {
+        AttributeIdentifier::new(u16::from(value))
+    }
+}
+
+/// Window covering cluster commands, sent by the client
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum WindowCoveringCommand {
+    /// Open the covering, no payload
+    UpOpen,
+    /// Close the covering, no payload
+    DownClose,
+    /// Stop moving, no payload
+    Stop,
+    /// Move to a lift percentage, see `GoToPercentage`
+    GoToLiftPercentage,
+    /// Move to a tilt percentage, see `GoToPercentage`
+    GoToTiltPercentage,
+}
+
+impl WindowCoveringCommand {
+    /// Direction of the command
+    pub fn direction(self) -> Direction {
+        Direction::ToServer
+    }
+
+    /// Command identifier
+    pub fn identifier(self) -> u8 {
+        match self {
+            WindowCoveringCommand::UpOpen => 0x00,
+            WindowCoveringCommand::DownClose => 0x01,
+            WindowCoveringCommand::Stop => 0x02,
+            WindowCoveringCommand::GoToLiftPercentage => 0x05,
+            WindowCoveringCommand::GoToTiltPercentage => 0x08,
+        }
+    }
+
+    /// Create the header of the command
+    pub fn header(self, transaction_sequence: u8) -> ClusterLibraryHeader {
+        ClusterLibraryHeader::new_cluster_command(
+            self.direction(),
+            transaction_sequence,
+            self.identifier(),
+            false,
+        )
+    }
+}
+
+impl ClusterCommand for WindowCoveringCommand {
+    fn from_identifier(direction: Direction, identifier: u8) -> Result<Self, Error> {
+        match (direction, identifier) {
+            (Direction::ToServer, 0x00) => Ok(WindowCoveringCommand::UpOpen),
+            (Direction::ToServer, 0x01) => Ok(WindowCoveringCommand::DownClose),
+            (Direction::ToServer, 0x02) => Ok(WindowCoveringCommand::Stop),
+            (Direction::ToServer, 0x05) => Ok(WindowCoveringCommand::GoToLiftPercentage),
+            (Direction::ToServer, 0x08) => Ok(WindowCoveringCommand::GoToTiltPercentage),
+            _ => Err(Error::InvalidValue),
+        }
+    }
+}
+
+// ZCL, 7.4.2.2.5 Go To Lift Percentage Command
+// ZCL, 7.4.2.2.7 Go To Tilt Percentage Command
+/// Go to lift percentage and go to tilt percentage, move the covering to a
+/// position
+#[derive(Clone, Debug, PartialEq)]
+pub struct GoToPercentage {
+    /// Position to move to, 0 to 100
+    pub percentage: u8,
+}
+
+impl GoToPercentage {
+    /// Move to `percentage`, fails with `Error::InvalidValue` if above
+    /// `MAX_PERCENTAGE`
+    pub fn new(percentage: u8) -> Result<Self, Error> {
+        if percentage > MAX_PERCENTAGE {
+            return Err(Error::InvalidValue);
+        }
+        Ok(Self { percentage })
+    }
+}
+
+impl Pack<GoToPercentage, Error> for GoToPercentage {
+    fn pack(&self, data: &mut [u8]) -> Result<usize, Error> {
+        if data.is_empty() {
+            return Err(Error::WrongNumberOfBytes);
+        }
+        if self.percentage > MAX_PERCENTAGE {
+            return Err(Error::InvalidValue);
+        }
+        data[0] = self.percentage;
+        Ok(1)
+    }
+
+    fn unpack(data: &[u8]) -> Result<(Self, usize), Error> {
+        if data.is_empty() {
+            return Err(Error::WrongNumberOfBytes);
+        }
+        Ok((Self::new(data[0])?, 1))
+    }
+}
+
+/// Create the header of a up/open command, the command has no payload
+pub fn up_open(transaction_sequence: u8) -> ClusterLibraryHeader {
+    WindowCoveringCommand::UpOpen.header(transaction_sequence)
+}
+
+/// Create the header of a down/close command, the command has no payload
+pub fn down_close(transaction_sequence: u8) -> ClusterLibraryHeader {
+    WindowCoveringCommand::DownClose.header(transaction_sequence)
+}
+
+/// Create the header of a stop command, the command has no payload
+pub fn stop(transaction_sequence: u8) -> ClusterLibraryHeader {
+    WindowCoveringCommand::Stop.header(transaction_sequence)
+}
+
+/// Create the header and payload of a go to lift percentage command, fails
+/// with `Error::InvalidValue` if `percentage` is above `MAX_PERCENTAGE`
+pub fn go_to_lift_percentage(
+    transaction_sequence: u8,
+    percentage: u8,
+) -> Result<(ClusterLibraryHeader, GoToPercentage), Error> {
+    Ok((
+        WindowCoveringCommand::GoToLiftPercentage.header(transaction_sequence),
+        GoToPercentage::new(percentage)?,
+    ))
+}
+
+/// Create the header and payload of a go to tilt percentage command, fails
+/// with `Error::InvalidValue` if `percentage` is above `MAX_PERCENTAGE`
+pub fn go_to_tilt_percentage(
+    transaction_sequence: u8,
+    percentage: u8,
+) -> Result<(ClusterLibraryHeader, GoToPercentage), Error> {
+    Ok((
+        WindowCoveringCommand::GoToTiltPercentage.header(transaction_sequence),
+        GoToPercentage::new(percentage)?,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pack_go_to_lift_percentage() {
+        let (header, command) = go_to_lift_percentage(0x41, 50).unwrap();
+        let mut buffer = [0u8; 4];
+        let used = header.pack(&mut buffer).unwrap();
+        let used = used + command.pack(&mut buffer[used..]).unwrap();
+        assert_eq!(buffer[..used], [0x01, 0x41, 0x05, 0x32]);
+
+        let (header, used) = ClusterLibraryHeader::unpack(&buffer).unwrap();
+        assert_eq!(
+            header.cluster_command::<WindowCoveringCommand>(),
+            Ok(WindowCoveringCommand::GoToLiftPercentage)
+        );
+        let (unpacked, _) = GoToPercentage::unpack(&buffer[used..]).unwrap();
+        assert_eq!(unpacked.percentage, 50);
+
+        assert!(matches!(
+            go_to_tilt_percentage(0x42, 101),
+            Err(Error::InvalidValue)
+        ));
+        assert_eq!(GoToPercentage::unpack(&[0xff]), Err(Error::InvalidValue));
+    }
+
+    #[test]
+    fn pack_movement_commands() {
+        let mut buffer = [0u8; 3];
+        let used = up_open(0x01).pack(&mut buffer).unwrap();
+        assert_eq!(buffer[..used], [0x01, 0x01, 0x00]);
+        let used = down_close(0x02).pack(&mut buffer).unwrap();
+        assert_eq!(buffer[..used], [0x01, 0x02, 0x01]);
+        let used = stop(0x03).pack(&mut buffer).unwrap();
+        assert_eq!(buffer[..used], [0x01, 0x03, 0x02]);
+
+        let (header, _) = ClusterLibraryHeader::unpack(&[0x01, 0x04, 0x08]).unwrap();
+        assert_eq!(
+            header.cluster_command::<WindowCoveringCommand>(),
+            Ok(WindowCoveringCommand::GoToTiltPercentage)
+        );
+    }
+}
@@ -1,5 +1,6 @@
 //! # Addresses used in the network
 
+use core::convert::TryFrom;
 use core::default::Default;
 
 use crate::pack::PackFixed;
@@ -15,9 +16,12 @@ pub const SHORT_ADDRESS_BROADCAST: u16 = 0xffff;
 /// The device has associated to a network but has not been assigned a address.
 /// The extended address should be used.
 pub const SHORT_ADDRESS_UNASSIGNED: u16 = 0xfffe;
+/// Short address, broadcast to all devices with the receiver on when idle
+/// Used as network destination for group addressed frames.
+pub const SHORT_ADDRESS_BROADCAST_RX_ON_WHEN_IDLE: u16 = 0xfffd;
 
 /// 16-bit short address
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct ShortAddress(u16);
 
 impl ShortAddress {
@@ -81,6 +85,15 @@ impl From<ShortAddress> for u16 {
     }
 }
 
+/// The bytes are in over the air order, little-endian
+impl TryFrom<&[u8]> for ShortAddress {
+    type Error = Error;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        Self::unpack(value)
+    }
+}
+
 impl From<ieee802154::mac::frame::ShortAddress> for ShortAddress {
     fn from(value: ieee802154::mac::frame::ShortAddress) -> Self {
         ShortAddress(value.0)
@@ -125,12 +138,16 @@ impl core::fmt::Display for ShortAddress {
     }
 }
 
+impl core::fmt::Debug for ShortAddress {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "ShortAddress(0x{:04x})", self.0)
+    }
+}
+
 /// 16-bit network address
 pub type NetworkAddress = ShortAddress;
 /// 16-bit personal area network (PAN) identifier
 pub type PanIdentifier = ShortAddress;
-/// 16-bit group identifier
-pub type GroupIdentifier = ShortAddress;
 
 impl From<ieee802154::mac::frame::PanId> for PanIdentifier {
     fn from(value: ieee802154::mac::frame::PanId) -> Self {
@@ -150,13 +167,107 @@ impl PartialEq<ieee802154::mac::frame::PanId> for PanIdentifier {
     }
 }
 
+/// Group identifier size
+pub const GROUP_IDENTIFIER_SIZE: usize = 2;
+/// Group identifiers from this and up are reserved
+pub const GROUP_IDENTIFIER_RESERVED: u16 = 0xfff8;
+
+/// 16-bit group identifier
+#[derive(Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct GroupIdentifier(u16);
+
+impl GroupIdentifier {
+    pub fn new(value: u16) -> Self {
+        Self(value)
+    }
+
+    /// The group identifier is in the reserved range, 0xfff8 to 0xffff
+    pub fn is_reserved(self) -> bool {
+        self.0 >= GROUP_IDENTIFIER_RESERVED
+    }
+}
+
+impl PackFixed<GroupIdentifier, Error> for GroupIdentifier {
+    fn pack(&self, mut data: &mut [u8]) -> Result<(), Error> {
+        if data.len() == GROUP_IDENTIFIER_SIZE {
+            LittleEndian::write_u16(&mut data, self.0);
+            Ok(())
+        } else {
+            Err(Error::NotEnoughSpace)
+        }
+    }
+
+    fn unpack(data: &[u8]) -> Result<Self, Error> {
+        if data.len() == GROUP_IDENTIFIER_SIZE {
+            Ok(GroupIdentifier(LittleEndian::read_u16(&data)))
+        } else {
+            Err(Error::WrongNumberOfBytes)
+        }
+    }
+}
+
+impl From<u16> for GroupIdentifier {
+    fn from(value: u16) -> Self {
+        GroupIdentifier(value)
+    }
+}
+
+impl From<GroupIdentifier> for u16 {
+    fn from(value: GroupIdentifier) -> Self {
+        value.0
+    }
+}
+
+/// The bytes are in over the air order, little-endian
+impl TryFrom<&[u8]> for GroupIdentifier {
+    type Error = Error;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        Self::unpack(value)
+    }
+}
+
+/// Multicast network frames carry the group identifier as destination
+/// address
+impl From<GroupIdentifier> for NetworkAddress {
+    fn from(value: GroupIdentifier) -> Self {
+        NetworkAddress::new(value.0)
+    }
+}
+
+impl PartialEq<u16> for GroupIdentifier {
+    fn eq(&self, other: &u16) -> bool {
+        self.0 == *other
+    }
+}
+
+#[cfg(not(feature = "core"))]
+impl std::fmt::Display for GroupIdentifier {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "0x{:04x}", self.0)
+    }
+}
+
+#[cfg(feature = "core")]
+impl core::fmt::Display for GroupIdentifier {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "0x{:04x}", self.0)
+    }
+}
+
+impl core::fmt::Debug for GroupIdentifier {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "GroupIdentifier(0x{:04x})", self.0)
+    }
+}
+
 /// Extended IEEE address size
 pub const EXTENDED_ADDRESS_SIZE: usize = 8;
 /// Extended IEEE address, broadcast address
 pub const EXTENDED_ADDRESS_BROADCAST: u64 = 0xffff_ffff_ffff_ffffu64;
 
 /// 64-bit extended IEEE address
-#[derive(Copy, Clone, Debug, PartialEq)]
+#[derive(Copy, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct ExtendedAddress(u64);
 
 impl ExtendedAddress {
@@ -171,6 +282,30 @@ impl ExtendedAddress {
     pub fn is_broadcast(self) -> bool {
         self.0 == EXTENDED_ADDRESS_BROADCAST
     }
+
+    /// Organizationally unique identifier (OUI), the three most significant
+    /// bytes of the address
+    pub fn oui(&self) -> [u8; 3] {
+        [
+            ((self.0 >> 56) & 0xff) as u8,
+            ((self.0 >> 48) & 0xff) as u8,
+            ((self.0 >> 40) & 0xff) as u8,
+        ]
+    }
+
+    /// Look up the vendor name of the address using the provided lookup
+    pub fn vendor<'a, L: OuiLookup>(&self, lookup: &'a L) -> Option<&'a str> {
+        lookup.vendor(self.oui())
+    }
+}
+
+/// Map a organizationally unique identifier (OUI) to a vendor name
+///
+/// No vendor database is provided, the user implements this with the
+/// database of their choice.
+pub trait OuiLookup {
+    /// Vendor name for the OUI, if known
+    fn vendor(&self, oui: [u8; 3]) -> Option<&str>;
 }
 
 impl PackFixed<ExtendedAddress, Error> for ExtendedAddress {
@@ -200,6 +335,8 @@ impl PartialEq<[u8; EXTENDED_ADDRESS_SIZE]> for ExtendedAddress {
     }
 }
 
+/// The most significant byte of the value is the first byte of the address
+/// as displayed, which is the last byte sent over the air
 impl From<u64> for ExtendedAddress {
     fn from(value: u64) -> Self {
         ExtendedAddress(value)
@@ -212,6 +349,31 @@ impl From<ExtendedAddress> for u64 {
     }
 }
 
+/// The bytes are in over the air order, little-endian
+impl From<[u8; EXTENDED_ADDRESS_SIZE]> for ExtendedAddress {
+    fn from(value: [u8; EXTENDED_ADDRESS_SIZE]) -> Self {
+        ExtendedAddress(LittleEndian::read_u64(&value[..]))
+    }
+}
+
+/// The bytes are in over the air order, little-endian
+impl TryFrom<&[u8]> for ExtendedAddress {
+    type Error = Error;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        Self::unpack(value)
+    }
+}
+
+/// The bytes are in over the air order, little-endian
+impl From<ExtendedAddress> for [u8; EXTENDED_ADDRESS_SIZE] {
+    fn from(value: ExtendedAddress) -> Self {
+        let mut data = [0u8; EXTENDED_ADDRESS_SIZE];
+        LittleEndian::write_u64(&mut data[..], value.0);
+        data
+    }
+}
+
 impl From<ieee802154::mac::frame::ExtendedAddress> for ExtendedAddress {
     fn from(value: ieee802154::mac::frame::ExtendedAddress) -> Self {
         ExtendedAddress(value.0)
@@ -259,6 +421,12 @@ impl core::fmt::Display for ExtendedAddress {
     }
 }
 
+impl core::fmt::Debug for ExtendedAddress {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "ExtendedAddress({})", self)
+    }
+}
+
 /// 64-bit extended personal area network (PAN) identifier
 pub type ExtendedPanIdentifier = ExtendedAddress;
 
@@ -320,6 +488,43 @@ mod tests {
         assert_eq!(buf, [0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99]);
     }
 
+    #[test]
+    fn extended_address_conversions() {
+        let bytes = [0x99, 0x88, 0x77, 0x66, 0x55, 0x44, 0x33, 0x22];
+        let a = ExtendedAddress::from(bytes);
+        assert_eq!(a, ExtendedAddress::from(0x2233_4455_6677_8899));
+        assert_eq!(u64::from(a), 0x2233_4455_6677_8899);
+        let b: [u8; 8] = a.into();
+        assert_eq!(b, bytes);
+        // Matches the packed, over the air, layout
+        let mut packed = [0u8; 8];
+        a.pack(&mut packed).unwrap();
+        assert_eq!(packed, bytes);
+        let c: u64 = ExtendedAddress::from(0x0011_2233_4455_6677).into();
+        assert_eq!(c, 0x0011_2233_4455_6677);
+    }
+
+    #[test]
+    fn extended_address_oui() {
+        struct Vendors;
+
+        impl OuiLookup for Vendors {
+            fn vendor(&self, oui: [u8; 3]) -> Option<&str> {
+                match oui {
+                    [0x00, 0x15, 0x8d] => Some("Xiaomi"),
+                    _ => None,
+                }
+            }
+        }
+
+        let a = ExtendedAddress::new(0x0015_8d00_0123_4567);
+        assert_eq!(a.oui(), [0x00, 0x15, 0x8d]);
+        assert_eq!(a.vendor(&Vendors), Some("Xiaomi"));
+        let a = ExtendedAddress::new(0x8899_aabb_ccdd_eeff);
+        assert_eq!(a.oui(), [0x88, 0x99, 0xaa]);
+        assert_eq!(a.vendor(&Vendors), None);
+    }
+
     #[test]
     fn ieee802154_extended_address_interop() {
         let mac_address = ieee802154::mac::frame::ExtendedAddress(0x2233_4455_6677_8899);
@@ -332,4 +537,160 @@ mod tests {
             ieee802154::mac::frame::ExtendedAddress(0x8899_aabb_ccdd_eeff)
         );
     }
+
+    #[test]
+    fn address_ordering() {
+        use std::collections::{HashMap, HashSet};
+
+        let mut addresses = vec![
+            ExtendedAddress::new(0x8899_aabb_ccdd_eeff),
+            ExtendedAddress::new(0x0011_2233_4455_6677),
+            ExtendedAddress::broadcast(),
+            ExtendedAddress::new(0x0000_0000_0000_0001),
+        ];
+        addresses.sort();
+        assert_eq!(
+            addresses,
+            [
+                ExtendedAddress::new(0x0000_0000_0000_0001),
+                ExtendedAddress::new(0x0011_2233_4455_6677),
+                ExtendedAddress::new(0x8899_aabb_ccdd_eeff),
+                ExtendedAddress::broadcast(),
+            ]
+        );
+
+        let mut map = HashMap::new();
+        map.insert(ExtendedAddress::new(0x0011_2233_4455_6677), 1);
+        map.insert(ExtendedAddress::new(0x8899_aabb_ccdd_eeff), 2);
+        assert_eq!(map[&ExtendedAddress::new(0x8899_aabb_ccdd_eeff)], 2);
+
+        assert!(PanIdentifier::new(0x0001) < PanIdentifier::broadcast());
+        let groups: HashSet<GroupIdentifier> = [0x0001, 0x0002, 0x0001]
+            .iter()
+            .map(|g| GroupIdentifier::new(*g))
+            .collect();
+        assert_eq!(groups.len(), 2);
+    }
+
+    #[test]
+    fn group_identifier() {
+        let group = GroupIdentifier::from(0x1234);
+        assert_eq!(group, 0x1234);
+        assert_eq!(u16::from(group), 0x1234);
+        assert_eq!(format!("{}", group), "0x1234");
+        assert_eq!(format!("{}", GroupIdentifier::new(0x000a)), "0x000a");
+        assert!(!group.is_reserved());
+        assert!(!GroupIdentifier::new(0xfff7).is_reserved());
+        assert!(GroupIdentifier::new(0xfff8).is_reserved());
+        assert!(GroupIdentifier::new(0xffff).is_reserved());
+
+        let mut data = [0u8; 2];
+        group.pack(&mut data).unwrap();
+        assert_eq!(data, [0x34, 0x12]);
+        assert_eq!(GroupIdentifier::unpack(&data).unwrap(), group);
+        assert_eq!(NetworkAddress::from(group), 0x1234);
+    }
+
+    #[test]
+    fn debug_hex() {
+        assert_eq!(
+            format!("{:?}", ExtendedAddress::new(0x0012_4b00_0102_03ff)),
+            "ExtendedAddress(00:12:4b:00:01:02:03:ff)"
+        );
+        assert_eq!(
+            format!("{:?}", ShortAddress::new(0x00af)),
+            "ShortAddress(0x00af)"
+        );
+        assert_eq!(
+            format!("{:?}", GroupIdentifier::new(0xfff8)),
+            "GroupIdentifier(0xfff8)"
+        );
+        assert_eq!(
+            format!("{:?}", Some(PanIdentifier::broadcast())),
+            "Some(ShortAddress(0xffff))"
+        );
+    }
+
+    #[test]
+    fn try_from_slice() {
+        let data = [0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09];
+        assert_eq!(
+            ExtendedAddress::try_from(&data[..8]),
+            Ok(ExtendedAddress::new(0x0807_0605_0403_0201))
+        );
+        assert_eq!(
+            ExtendedAddress::try_from(&data[..7]),
+            Err(Error::WrongNumberOfBytes)
+        );
+        assert_eq!(
+            ExtendedAddress::try_from(&data[..]),
+            Err(Error::WrongNumberOfBytes)
+        );
+        assert_eq!(
+            ShortAddress::try_from(&data[..2]),
+            Ok(ShortAddress::new(0x0201))
+        );
+        assert_eq!(
+            PanIdentifier::try_from(&data[1..3]),
+            Ok(PanIdentifier::new(0x0302))
+        );
+        assert_eq!(
+            NetworkAddress::try_from(&data[..1]),
+            Err(Error::WrongNumberOfBytes)
+        );
+        assert_eq!(
+            GroupIdentifier::try_from(&data[..2]),
+            Ok(GroupIdentifier::new(0x0201))
+        );
+        assert_eq!(
+            GroupIdentifier::try_from(&data[..3]),
+            Err(Error::WrongNumberOfBytes)
+        );
+    }
+
+    #[test]
+    fn on_air_byte_order() {
+        // All 16-bit addresses and identifiers are little-endian
+        let mut data = [0u8; 2];
+        ShortAddress::new(0xa1b2).pack(&mut data).unwrap();
+        assert_eq!(data, [0xb2, 0xa1]);
+        NetworkAddress::new(0x1f96).pack(&mut data).unwrap();
+        assert_eq!(data, [0x96, 0x1f]);
+        assert_eq!(NetworkAddress::unpack(&data).unwrap(), 0x1f96);
+        PanIdentifier::new(0xabcd).pack(&mut data).unwrap();
+        assert_eq!(data, [0xcd, 0xab]);
+        assert_eq!(PanIdentifier::unpack(&data).unwrap(), 0xabcd);
+        GroupIdentifier::new(0x0102).pack(&mut data).unwrap();
+        assert_eq!(data, [0x02, 0x01]);
+        assert_eq!(GroupIdentifier::unpack(&data).unwrap(), 0x0102);
+
+        // All 64-bit addresses and identifiers are little-endian
+        let on_air = [0x08, 0x07, 0x06, 0x05, 0x04, 0x03, 0x02, 0x01];
+        let mut data = [0u8; 8];
+        ExtendedAddress::new(0x0102_0304_0506_0708)
+            .pack(&mut data)
+            .unwrap();
+        assert_eq!(data, on_air);
+        assert_eq!(
+            ExtendedAddress::unpack(&on_air).unwrap(),
+            ExtendedAddress::new(0x0102_0304_0506_0708)
+        );
+        assert_eq!(ExtendedAddress::from(on_air), 0x0102_0304_0506_0708);
+        let bytes: [u8; 8] = ExtendedAddress::new(0x0102_0304_0506_0708).into();
+        assert_eq!(bytes, on_air);
+        ExtendedPanIdentifier::new(0xdead_beef_0000_0001)
+            .pack(&mut data)
+            .unwrap();
+        assert_eq!(data, [0x01, 0x00, 0x00, 0x00, 0xef, 0xbe, 0xad, 0xde]);
+        assert_eq!(
+            ExtendedPanIdentifier::unpack(&data).unwrap(),
+            ExtendedPanIdentifier::new(0xdead_beef_0000_0001)
+        );
+
+        // Slices of the wrong size are rejected
+        assert!(ShortAddress::new(0x0001).pack(&mut [0u8; 3]).is_err());
+        assert!(GroupIdentifier::unpack(&[0u8; 1]).is_err());
+        assert!(ExtendedAddress::new(1).pack(&mut [0u8; 7]).is_err());
+        assert!(ExtendedAddress::unpack(&[0u8; 9]).is_err());
+    }
 }
@@ -26,6 +26,13 @@ pub use psila_crypto::KEY_SIZE;
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub struct Key([u8; KEY_SIZE]);
 
+impl Key {
+    /// Wipe the key material, see `clear`
+    pub fn clear(&mut self) {
+        crate::utils::clear(&mut self.0);
+    }
+}
+
 impl PackFixed<Key, Error> for Key {
     fn pack(&self, data: &mut [u8]) -> Result<(), Error> {
         if data.len() != KEY_SIZE {
@@ -142,4 +149,11 @@ mod tests {
         ]);
         assert_eq!(format!("{}", a), "f0e1d2c3b4a5968778695a4b3c2d1e0f");
     }
+
+    #[test]
+    fn key_clear() {
+        let mut a = Key::from([0xa5; KEY_SIZE]);
+        a.clear();
+        assert_eq!(a, [0x00; KEY_SIZE]);
+    }
 }
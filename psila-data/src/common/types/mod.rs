@@ -5,7 +5,7 @@ mod types_core;
 mod types_std;
 
 #[cfg(feature = "core")]
-pub use types_core::{CharacterString, OctetString};
+pub use types_core::{character_string, octet_string, CharacterString, OctetString};
 
 #[cfg(not(feature = "core"))]
-pub use types_std::{CharacterString, OctetString};
+pub use types_std::{character_string, octet_string, CharacterString, OctetString};
@@ -5,13 +5,22 @@ use crate::Error;
 
 pub type OctetString = Vec<u8, U256>;
 
+/// Create a octet string from `data`, at most 256 octets
+pub fn octet_string(data: &[u8]) -> Result<OctetString, Error> {
+    let mut value = OctetString::new();
+    value
+        .extend_from_slice(data)
+        .map_err(|_| Error::InvalidValue)?;
+    Ok(value)
+}
+
 impl Pack<OctetString, Error> for OctetString {
     fn pack(&self, data: &mut [u8]) -> Result<usize, Error> {
         if data.len() <= self.len() {
             return Err(Error::WrongNumberOfBytes);
         }
         data[0] = self.len() as u8;
-        data.copy_from_slice(self.as_ref());
+        data[1..=self.len()].copy_from_slice(self.as_ref());
         Ok(self.len() + 1)
     }
 
@@ -33,13 +42,19 @@ impl Pack<OctetString, Error> for OctetString {
 
 pub type CharacterString = String<U256>;
 
+/// Create a character string from `data`, which must be valid UTF-8 and at
+/// most 256 octets
+pub fn character_string(data: &[u8]) -> Result<CharacterString, Error> {
+    CharacterString::from_utf8(octet_string(data)?).map_err(|_| Error::InvalidValue)
+}
+
 impl Pack<CharacterString, Error> for CharacterString {
     fn pack(&self, data: &mut [u8]) -> Result<usize, Error> {
         if data.len() <= self.len() {
             return Err(Error::WrongNumberOfBytes);
         }
         data[0] = self.len() as u8;
-        data.copy_from_slice(self.as_ref());
+        data[1..=self.len()].copy_from_slice(self.as_ref());
         Ok(self.len() + 1)
     }
 
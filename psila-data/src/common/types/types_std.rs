@@ -5,13 +5,18 @@ use crate::Error;
 
 pub type OctetString = std::vec::Vec<u8>;
 
+/// Create a octet string from `data`
+pub fn octet_string(data: &[u8]) -> Result<OctetString, Error> {
+    Ok(data.to_vec())
+}
+
 impl Pack<OctetString, Error> for OctetString {
     fn pack(&self, data: &mut [u8]) -> Result<usize, Error> {
         if data.len() <= self.len() {
             return Err(Error::WrongNumberOfBytes);
         }
         data[0] = self.len() as u8;
-        data.copy_from_slice(self.as_ref());
+        data[1..=self.len()].copy_from_slice(self.as_ref());
         Ok(self.len() + 1)
     }
 
@@ -30,13 +35,18 @@ impl Pack<OctetString, Error> for OctetString {
 
 pub type CharacterString = String;
 
+/// Create a character string from `data`, which must be valid UTF-8
+pub fn character_string(data: &[u8]) -> Result<CharacterString, Error> {
+    String::from_utf8(data.to_vec()).map_err(|_| Error::InvalidValue)
+}
+
 impl Pack<CharacterString, Error> for CharacterString {
     fn pack(&self, data: &mut [u8]) -> Result<usize, Error> {
         if data.len() <= self.len() {
             return Err(Error::WrongNumberOfBytes);
         }
         data[0] = self.len() as u8;
-        data.copy_from_slice(self.as_ref());
+        data[1..=self.len()].copy_from_slice(self.as_ref());
         Ok(self.len() + 1)
     }
 
@@ -0,0 +1,195 @@
+//! # Conformance captures
+//!
+//! Frames captured from real networks, and a few synthetic frames covering
+//! layers without a capture, all shall decode through the layered decoder
+//! without error.
+//!
+//! To add a capture, append a entry to `CAPTURES` with the hexadecimal
+//! representation of the frame and the layer the capture starts at.
+//! Synthetic frames are marked as such, and shall be replaced when a
+//! capture becomes available.
+
+use crate::application_service::{self, ApplicationServiceHeader};
+use crate::cluster_library::{self, ClusterLibraryHeader};
+use crate::decode::{decode, decode_from, device_profile_cluster, Layer, Payload};
+use crate::device_profile::DeviceProfileFrame;
+use crate::green_power;
+use crate::network::{self, BeaconInformation, NetworkHeader};
+use crate::pack::{Pack, PackFixed};
+use crate::security::{SecurityControl, SecurityHeader};
+use crate::Error;
+
+/// A captured frame
+struct Capture {
+    /// Description of the capture
+    name: &'static str,
+    /// The outermost layer of the capture
+    layer: Layer,
+    /// The frame as hexadecimal, without footer (FCS)
+    data: &'static str,
+    /// The frame was built by hand, not captured
+    synthetic: bool,
+}
+
+const CAPTURES: &[Capture] = &[
+    Capture {
+        name: "MAC data, On/Off",
+        layer: Layer::Mac,
+        data: "41881f45673eed000008003eed000001874001060004010112aabb",
+        synthetic: true,
+    },
+    // Captured beacon payload in a synthetic MAC header
+    Capture {
+        name: "MAC beacon, Zigbee PRO",
+        layer: Layer::Mac,
+        data: "00800145670000ffcf0000002284382e03ffff2e2100ffffff00",
+        synthetic: true,
+    },
+    Capture {
+        name: "NWK data, secured",
+        layer: Layer::Network,
+        data: "0802000034121e422801000000776655443322110000423c405ed9d744ee2950b9c5",
+        synthetic: true,
+    },
+    Capture {
+        name: "APS data, secured",
+        layer: Layer::ApplicationService,
+        data: "2872300000637d6103008d150000c257c59b87a2",
+        synthetic: false,
+    },
+    Capture {
+        name: "APS command, secured 1",
+        layer: Layer::ApplicationService,
+        data: "21d33006000000b54124740300b5b403ffff2e210000ea6a2a9b696251297141a58c3378c59bf8c2111710e4008ebcbcf576153e2a2e",
+        synthetic: false,
+    },
+    Capture {
+        name: "APS command, secured 2",
+        layer: Layer::ApplicationService,
+        data: "21993001000000b5b403ffff2e2100e7df99c981223d67293145b32ba21174cbccbde5e476b06e05ee35fd5fd1d8088e4dc170a152a9",
+        synthetic: false,
+    },
+    Capture {
+        name: "APS command, secured 3",
+        layer: Layer::ApplicationService,
+        data: "21a73003000000b5b403ffff2e210062d33dca7f86aa158f4c7beea7f03b1d89e87c203dc263b97f7eeb6d39136b7f367383668dc193",
+        synthetic: false,
+    },
+    Capture {
+        name: "APS command, secured 4",
+        layer: Layer::ApplicationService,
+        data: "21413001000000382e03ffc8535f02132bff2e21000061007527e5943e3fd9473c81fa7ada823451813f000000000000000000000000",
+        synthetic: false,
+    },
+    Capture {
+        name: "APS command, secured 5",
+        layer: Layer::ApplicationService,
+        data: "21453002000000382e03ffff2e2100ae5e9f46a640cde7902fd60e432317484b4c5a9b4cde1ce70707b6fb1a0be9997e0af80fdf5dcf",
+        synthetic: false,
+    },
+    Capture {
+        name: "APS data, device announce",
+        layer: Layer::ApplicationService,
+        data: "0800130000000006817bc085ae21feff6f0d0080",
+        synthetic: false,
+    },
+    Capture {
+        name: "APS inter-PAN, touchlink 1",
+        layer: Layer::ApplicationService,
+        data: "0b00105ec0118000161fb45b0212",
+        synthetic: false,
+    },
+    Capture {
+        name: "APS inter-PAN, touchlink 2",
+        layer: Layer::ApplicationService,
+        data: "0b00105ec0119800ea7853b90212",
+        synthetic: false,
+    },
+];
+
+/// Parse hexadecimal into `output`, white-space is ignored
+fn from_hex(hex: &str, output: &mut [u8]) -> usize {
+    let mut digits = hex
+        .bytes()
+        .filter(|c| !c.is_ascii_whitespace())
+        .map(|c| match c {
+            b'0'..=b'9' => c - b'0',
+            b'a'..=b'f' => c - b'a' + 10,
+            b'A'..=b'F' => c - b'A' + 10,
+            _ => panic!("Invalid hexadecimal digit {}", c as char),
+        });
+    let mut length = 0;
+    while let Some(high) = digits.next() {
+        let low = digits.next().expect("Odd number of hexadecimal digits");
+        output[length] = high << 4 | low;
+        length += 1;
+    }
+    length
+}
+
+/// Decode the capture starting at `layer` with the layered decoder
+///
+/// Device profile messages without a parser fail with
+/// `Error::NotImplemented`.
+fn decode_capture(layer: Layer, data: &[u8]) -> Result<(), Error> {
+    let decoded = decode_from(layer, data).map_err(|partial| partial.error)?;
+    if let Some(header) = decoded.application_service.as_ref() {
+        if decoded.payload_kind == Payload::Plain
+            && device_profile_cluster(header).is_some()
+            && decoded.device_profile.is_none()
+        {
+            return Err(Error::NotImplemented);
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn captures_decode() {
+    for capture in CAPTURES.iter() {
+        let mut data = [0u8; 128];
+        let length = from_hex(capture.data, &mut data);
+        if let Err(error) = decode_capture(capture.layer, &data[..length]) {
+            panic!(
+                "{} \"{}\" failed to decode, {:?}",
+                if capture.synthetic {
+                    "Synthetic frame"
+                } else {
+                    "Capture"
+                },
+                capture.name,
+                error
+            );
+        }
+    }
+}
+
+#[test]
+fn empty_input() {
+    // All public parsers shall fail on empty input, not panic
+    let data: &[u8] = &[];
+    match decode(data) {
+        Ok(_) => panic!("Empty frame decoded"),
+        Err(partial) => assert_eq!(partial.layer, Layer::Mac),
+    }
+    let expected = Some(Error::WrongNumberOfBytes);
+    assert_eq!(network::header::FrameControl::unpack(data).err(), expected);
+    assert_eq!(NetworkHeader::unpack(data).err(), expected);
+    assert_eq!(network::Command::unpack(data).err(), expected);
+    assert_eq!(BeaconInformation::unpack(data).err(), expected);
+    assert_eq!(
+        application_service::header::FrameControl::unpack(data).err(),
+        expected
+    );
+    assert_eq!(ApplicationServiceHeader::unpack(data).err(), expected);
+    assert_eq!(application_service::Command::unpack(data).err(), expected);
+    assert_eq!(SecurityControl::unpack(data).err(), expected);
+    assert_eq!(SecurityHeader::unpack(data).err(), expected);
+    assert_eq!(
+        cluster_library::frame::FrameControl::unpack(data).err(),
+        expected
+    );
+    assert_eq!(ClusterLibraryHeader::unpack(data).err(), expected);
+    assert_eq!(DeviceProfileFrame::unpack(data, 0x0013).err(), expected);
+    assert_eq!(green_power::Command::unpack(data).err(), expected);
+}
@@ -0,0 +1,771 @@
+//! # Layered decoding
+//!
+//! Decode the MAC, NWK and APS layers of a received frame. Decoding stops at
+//! the first layer that fails to decode, the layers decoded up to that point
+//! are returned together with the error.
+//!
+//! Application data to the device profile is decoded as a device profile
+//! (ZDP) frame, using the parser matching the cluster of the APS frame.
+//!
+//! `decode_from` starts decoding at a inner layer, for frames captured
+//! without the outer layers.
+//!
+//! `from_mac_frame` decodes the layers of a already parsed MAC data frame,
+//! removing the network and application service security.
+//!
+//! `summarize` provides a owned summary of a layered decode, which can be
+//! kept after the frame is gone.
+
+use ieee802154::mac;
+use psila_crypto::CryptoBackend;
+
+use crate::application_service::{self, ApplicationServiceHeader};
+use crate::cluster_library::ClusterLibraryHeader;
+use crate::common::{
+    address::{ExtendedAddress, NetworkAddress},
+    ProfileIdentifier,
+};
+use crate::device_profile::{DeviceProfileFrame, ZdpCluster};
+use crate::network::{self, header::FrameType, KeyStore, NetworkHeader};
+use crate::pack::Pack;
+use crate::security::SecurityHeader;
+use crate::Error;
+
+/// Protocol layer
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Layer {
+    /// IEEE 802.15.4 MAC layer
+    Mac,
+    /// Network layer (NWK)
+    Network,
+    /// Application service layer (APS)
+    ApplicationService,
+    /// Device profile (ZDP)
+    DeviceProfile,
+}
+
+/// Kind of the payload following the innermost decoded layer
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Payload {
+    /// Plain text
+    Plain,
+    /// Secured and not decrypted, the auxiliary security header followed by
+    /// the cipher text and the message integrity code (MIC)
+    ///
+    /// The layer following the innermost decoded layer is secured and no
+    /// key was available, or the decoder doesn't decrypt. Distinguishes a
+    /// frame which couldn't be decrypted from a frame which failed to parse.
+    Encrypted,
+}
+
+/// Decoded layers of a frame
+#[derive(Clone, Debug)]
+pub struct DecodedFrame<'a> {
+    /// MAC frame
+    pub mac: Option<mac::Frame<'a>>,
+    /// Network header, if the MAC frame carried data
+    pub network: Option<NetworkHeader>,
+    /// Application service header, if the network frame carried
+    /// unsecured data
+    pub application_service: Option<ApplicationServiceHeader>,
+    /// Device profile frame, if the application service frame carried
+    /// device profile data with a supported cluster
+    pub device_profile: Option<DeviceProfileFrame>,
+    /// Payload following the innermost decoded layer
+    pub payload: &'a [u8],
+    /// Kind of the payload
+    pub payload_kind: Payload,
+    /// Link quality indicator reported by the radio, not part of the frame
+    pub lqi: Option<u8>,
+    /// Received signal strength in dBm reported by the radio, not part of
+    /// the frame
+    pub rssi: Option<i8>,
+}
+
+/// A frame which failed to decode at `layer`
+#[derive(Clone, Debug)]
+pub struct PartialDecode<'a> {
+    /// The layers decoded before the failure
+    pub decoded: DecodedFrame<'a>,
+    /// The layer which failed to decode
+    pub layer: Layer,
+    /// The error of the failed layer
+    pub error: Error,
+}
+
+/// Result of a layered decode
+pub type DecodeResult<'a> = Result<DecodedFrame<'a>, PartialDecode<'a>>;
+
+/// Summary of a layered decode, see `summarize`
+///
+/// Unlike `DecodedFrame` the summary doesn't borrow the frame, so it can be
+/// stored, e.g. in a `DecodeCache`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DecodeSummary {
+    /// MAC frame type, `None` if the MAC layer failed to decode
+    pub frame_type: Option<mac::FrameType>,
+    /// MAC sequence number
+    pub sequence: Option<u8>,
+    /// Innermost decoded layer, `None` if the MAC layer failed to decode
+    pub layer: Option<Layer>,
+    /// Network source address
+    pub source: Option<NetworkAddress>,
+    /// Network destination address
+    pub destination: Option<NetworkAddress>,
+    /// The network frame is secured
+    pub secured: bool,
+    /// Cluster of the application service frame
+    pub cluster: Option<u16>,
+    /// Profile of the application service frame
+    pub profile: Option<u16>,
+    /// Size of the payload following the innermost decoded layer
+    pub payload_length: usize,
+    /// The layer which failed to decode and its error
+    pub error: Option<(Layer, Error)>,
+}
+
+impl DecodeSummary {
+    fn new(decoded: &DecodedFrame, error: Option<(Layer, Error)>) -> Self {
+        let layer = if decoded.device_profile.is_some() {
+            Some(Layer::DeviceProfile)
+        } else if decoded.application_service.is_some() {
+            Some(Layer::ApplicationService)
+        } else if decoded.network.is_some() {
+            Some(Layer::Network)
+        } else if decoded.mac.is_some() {
+            Some(Layer::Mac)
+        } else {
+            None
+        };
+        let network = decoded.network.as_ref();
+        let application_service = decoded.application_service.as_ref();
+        Self {
+            frame_type: decoded.mac.as_ref().map(|frame| frame.header.frame_type),
+            sequence: decoded.mac.as_ref().map(|frame| frame.header.seq),
+            layer,
+            source: network.map(|header| header.source_address),
+            destination: network.map(|header| header.destination_address),
+            secured: network.map_or(false, |header| header.control.security),
+            cluster: application_service.and_then(|header| header.cluster),
+            profile: application_service.and_then(|header| header.profile),
+            payload_length: decoded.payload.len(),
+            error,
+        }
+    }
+}
+
+impl<'a> From<&DecodeResult<'a>> for DecodeSummary {
+    fn from(result: &DecodeResult<'a>) -> Self {
+        match result {
+            Ok(decoded) => Self::new(decoded, None),
+            Err(partial) => Self::new(&partial.decoded, Some((partial.layer, partial.error))),
+        }
+    }
+}
+
+/// Decode a frame without footer (FCS) into a summary, see `decode`
+pub fn summarize(data: &[u8]) -> DecodeSummary {
+    DecodeSummary::from(&decode(data))
+}
+
+/// Smallest network header, frame control, destination, source, radius and
+/// sequence number
+const NETWORK_HEADER_MIN_SIZE: usize = 8;
+/// Smallest application service header, frame control and counter
+const APPLICATION_SERVICE_HEADER_MIN_SIZE: usize = 2;
+
+/// Check if the network frame (NWK) in `data` is secured, without parsing
+/// the header
+///
+/// Only the security bit of the frame control is inspected. Fails with
+/// `WrongNumberOfBytes` if `data` is shorter than the smallest network
+/// header.
+pub fn nwk_is_secured(data: &[u8]) -> Result<bool, Error> {
+    if data.len() < NETWORK_HEADER_MIN_SIZE {
+        return Err(Error::WrongNumberOfBytes);
+    }
+    Ok(data[1] & 0b0000_0010 == 0b0000_0010)
+}
+
+/// Check if the application service frame (APS) in `data` is secured,
+/// without parsing the header
+///
+/// Only the security bit of the frame control is inspected. Fails with
+/// `WrongNumberOfBytes` if `data` is shorter than the smallest application
+/// service header.
+pub fn aps_is_secured(data: &[u8]) -> Result<bool, Error> {
+    if data.len() < APPLICATION_SERVICE_HEADER_MIN_SIZE {
+        return Err(Error::WrongNumberOfBytes);
+    }
+    Ok(data[0] & 0b0010_0000 == 0b0010_0000)
+}
+
+/// Decode a frame without footer (FCS)
+///
+/// Layers protected by security are not decoded, the secured payload is
+/// provided as `payload`.
+pub fn decode(data: &[u8]) -> DecodeResult {
+    decode_with_link_quality(data, None, None)
+}
+
+/// Decode a frame without footer (FCS), see `decode`
+///
+/// The link quality indicator `lqi` and signal strength `rssi` measured by
+/// the radio when receiving the frame are carried along with the decoded
+/// layers, also when decoding fails.
+pub fn decode_with_link_quality(data: &[u8], lqi: Option<u8>, rssi: Option<i8>) -> DecodeResult {
+    let mut decoded = DecodedFrame::new(data);
+    decoded.lqi = lqi;
+    decoded.rssi = rssi;
+    decode_mac(decoded)
+}
+
+/// Decode a frame without footer (FCS) starting at `layer`, e.g. a network
+/// frame captured without the MAC header, see `decode`
+///
+/// Device profile frames can't be decoded without the application service
+/// header, decoding from `Layer::DeviceProfile` fails with
+/// `Error::InvalidValue`.
+pub fn decode_from(layer: Layer, data: &[u8]) -> DecodeResult {
+    let decoded = DecodedFrame::new(data);
+    match layer {
+        Layer::Mac => decode_mac(decoded),
+        Layer::Network => decode_network(decoded),
+        Layer::ApplicationService => decode_application_service(decoded),
+        Layer::DeviceProfile => Err(PartialDecode {
+            decoded,
+            layer,
+            error: Error::InvalidValue,
+        }),
+    }
+}
+
+impl<'a> DecodedFrame<'a> {
+    /// Nothing decoded yet, all of `data` is payload
+    fn new(data: &'a [u8]) -> Self {
+        Self {
+            mac: None,
+            network: None,
+            application_service: None,
+            device_profile: None,
+            payload: data,
+            payload_kind: Payload::Plain,
+            lqi: None,
+            rssi: None,
+        }
+    }
+}
+
+/// Decode the MAC frame in the payload of `decoded` and the following layers
+fn decode_mac(mut decoded: DecodedFrame) -> DecodeResult {
+    let frame = match mac::Frame::decode(decoded.payload, false) {
+        Ok(frame) => frame,
+        Err(error) => {
+            return Err(PartialDecode {
+                decoded,
+                layer: Layer::Mac,
+                error: Error::from(error),
+            })
+        }
+    };
+    let has_network = frame.header.frame_type == mac::FrameType::Data;
+    decoded.payload = frame.payload;
+    decoded.mac = Some(frame);
+    if !has_network {
+        return Ok(decoded);
+    }
+    decode_network(decoded)
+}
+
+/// Decode the network frame in the payload of `decoded` and the following
+/// layers
+fn decode_network(mut decoded: DecodedFrame) -> DecodeResult {
+    let (network, used) = match NetworkHeader::unpack(decoded.payload) {
+        Ok(result) => result,
+        Err(error) => {
+            return Err(PartialDecode {
+                decoded,
+                layer: Layer::Network,
+                error,
+            })
+        }
+    };
+    decoded.payload = &decoded.payload[used..];
+    let has_application_service = !network.control.security
+        && (network.control.frame_type == FrameType::Data
+            || network.control.frame_type == FrameType::InterPan);
+    if network.control.security {
+        decoded.payload_kind = Payload::Encrypted;
+    }
+    decoded.network = Some(network);
+    if !has_application_service {
+        return Ok(decoded);
+    }
+    decode_application_service(decoded)
+}
+
+/// Decode the application service frame in the payload of `decoded` and
+/// the device profile frame it carries
+fn decode_application_service(mut decoded: DecodedFrame) -> DecodeResult {
+    let (application_service, used) = match ApplicationServiceHeader::unpack(decoded.payload) {
+        Ok(result) => result,
+        Err(error) => {
+            return Err(PartialDecode {
+                decoded,
+                layer: Layer::ApplicationService,
+                error,
+            })
+        }
+    };
+    decoded.payload = &decoded.payload[used..];
+    if application_service.control.security {
+        decoded.payload_kind = Payload::Encrypted;
+        decoded.application_service = Some(application_service);
+        return Ok(decoded);
+    }
+    let cluster = device_profile_cluster(&application_service);
+    decoded.application_service = Some(application_service);
+    let cluster = match cluster {
+        Some(cluster) => cluster,
+        None => return Ok(decoded),
+    };
+
+    match DeviceProfileFrame::unpack(decoded.payload, u16::from(cluster)) {
+        Ok((device_profile, used)) => {
+            decoded.payload = &decoded.payload[used..];
+            decoded.device_profile = Some(device_profile);
+        }
+        // Messages without a parser are left as payload
+        Err(Error::NotImplemented) => (),
+        Err(error) => {
+            return Err(PartialDecode {
+                decoded,
+                layer: Layer::DeviceProfile,
+                error,
+            })
+        }
+    }
+
+    Ok(decoded)
+}
+
+/// The device profile cluster of a application service data frame with
+/// `header`, `None` for other frames
+pub(crate) fn device_profile_cluster(header: &ApplicationServiceHeader) -> Option<ZdpCluster> {
+    if header.control.frame_type == application_service::header::FrameType::Data {
+        ZdpCluster::from_header(header)
+    } else {
+        None
+    }
+}
+
+/// Largest payload of a MAC frame
+const MAX_PAYLOAD_SIZE: usize = 127;
+
+/// The network, application service and cluster library layers of a MAC
+/// data frame, with the security removed
+#[derive(Clone, Debug)]
+pub struct Frame {
+    /// Network header
+    pub network: NetworkHeader,
+    /// Application service header, if the network frame carried data
+    pub application_service: Option<ApplicationServiceHeader>,
+    /// Cluster library header, if the application service frame carried
+    /// cluster library data
+    pub cluster_library: Option<ClusterLibraryHeader>,
+    /// Device profile frame, if the application service frame carried
+    /// device profile data with a supported cluster
+    pub device_profile: Option<DeviceProfileFrame>,
+    /// Kind of the payload, encrypted if no key was available for the
+    /// application service security
+    pub payload_kind: Payload,
+    payload: [u8; MAX_PAYLOAD_SIZE],
+    payload_length: usize,
+}
+
+impl Frame {
+    /// Payload following the innermost decoded layer, in plain text
+    pub fn payload(&self) -> &[u8] {
+        &self.payload[..self.payload_length]
+    }
+
+    fn set_payload(&mut self, payload: &[u8]) {
+        self.payload[..payload.len()].copy_from_slice(payload);
+        self.payload_length = payload.len();
+    }
+
+    /// Remove the first `used` bytes of the payload, decoded by a inner
+    /// layer
+    fn consume_payload(&mut self, used: usize) {
+        self.payload.copy_within(used..self.payload_length, 0);
+        self.payload_length -= used;
+    }
+}
+
+/// Extended source address of the network frame in `data`, used for the
+/// application service security
+///
+/// Taken from the network header if present, otherwise from the auxiliary
+/// header of a secured network frame.
+fn network_source(network: &NetworkHeader, data: &[u8]) -> Option<ExtendedAddress> {
+    if network.source_ieee_address.is_some() || !network.control.security {
+        return network.source_ieee_address;
+    }
+    let (_, used) = NetworkHeader::unpack(data).ok()?;
+    let (security, _) = SecurityHeader::unpack(&data[used..]).ok()?;
+    security.source
+}
+
+/// Decode the network, application service and cluster library layers of
+/// `frame`
+///
+/// The frame shall be a MAC data frame. Secured network and application
+/// service frames are decrypted with the keys from `keys`, see
+/// `network::decrypt_frame` and `application_service::decrypt_frame`. The
+/// device profile frame is decoded for application data to the device
+/// profile, otherwise the cluster library header is decoded.
+///
+/// Fails with the error of the first layer that fails to decode or decrypt.
+/// A secured application service frame without a matching key isn't a
+/// failure, the header is decoded and the payload marked
+/// `Payload::Encrypted`.
+pub fn from_mac_frame<Backend, Keys>(
+    backend: &mut Backend,
+    frame: &mac::Frame,
+    keys: &Keys,
+) -> Result<Frame, Error>
+where
+    Backend: CryptoBackend,
+    Keys: KeyStore,
+{
+    if frame.header.frame_type != mac::FrameType::Data {
+        return Err(Error::UnknownFrameType);
+    }
+    if frame.payload.len() > MAX_PAYLOAD_SIZE {
+        return Err(Error::WrongNumberOfBytes);
+    }
+    let mut buffer = [0u8; MAX_PAYLOAD_SIZE];
+    let buffer = &mut buffer[..frame.payload.len()];
+    buffer.copy_from_slice(frame.payload);
+    let (network, payload) = network::decrypt_frame(backend, keys, buffer)?;
+    let mut decoded = Frame {
+        network,
+        application_service: None,
+        cluster_library: None,
+        device_profile: None,
+        payload_kind: Payload::Plain,
+        payload: [0u8; MAX_PAYLOAD_SIZE],
+        payload_length: 0,
+    };
+    decoded.set_payload(payload);
+    if decoded.network.control.frame_type == FrameType::Command {
+        return Ok(decoded);
+    }
+
+    // Frames where neither layer carries the extended source address fail
+    // the integrity check of the application service security
+    let source =
+        network_source(&decoded.network, frame.payload).unwrap_or_else(|| ExtendedAddress::new(0));
+    let mut buffer = [0u8; MAX_PAYLOAD_SIZE];
+    let buffer = &mut buffer[..decoded.payload_length];
+    buffer.copy_from_slice(decoded.payload());
+    let (application_service, payload) =
+        match application_service::decrypt_frame(backend, keys, source, buffer) {
+            Ok(result) => result,
+            Err(Error::MissingKey) => {
+                // No key for the application service security, keep the
+                // header and the secured payload
+                let (application_service, used) =
+                    ApplicationServiceHeader::unpack(decoded.payload())?;
+                decoded.consume_payload(used);
+                decoded.application_service = Some(application_service);
+                decoded.payload_kind = Payload::Encrypted;
+                return Ok(decoded);
+            }
+            Err(error) => return Err(error),
+        };
+    decoded.set_payload(payload);
+    let profile = application_service.profile;
+    let is_data =
+        application_service.control.frame_type == application_service::header::FrameType::Data;
+    let cluster = device_profile_cluster(&application_service);
+    decoded.application_service = Some(application_service);
+    if let Some(cluster) = cluster {
+        let used = match DeviceProfileFrame::unpack(decoded.payload(), u16::from(cluster)) {
+            Ok((device_profile, used)) => {
+                decoded.device_profile = Some(device_profile);
+                used
+            }
+            Err(Error::NotImplemented) => 0,
+            Err(error) => return Err(error),
+        };
+        decoded.consume_payload(used);
+        return Ok(decoded);
+    }
+    if !is_data || profile.is_none() || profile == Some(ProfileIdentifier::DeviceProfile.into()) {
+        return Ok(decoded);
+    }
+
+    let (cluster_library, used) = ClusterLibraryHeader::unpack(decoded.payload())?;
+    decoded.consume_payload(used);
+    decoded.cluster_library = Some(cluster_library);
+    Ok(decoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decode_data_frame() {
+        let data = [
+            0x41, 0x88, 0x1f, 0x45, 0x67, 0x3e, 0xed, 0x00, 0x00, 0x08, 0x00, 0x3e, 0xed, 0x00,
+            0x00, 0x01, 0x87, 0x40, 0x01, 0x06, 0x00, 0x04, 0x01, 0x01, 0x12, 0xaa, 0xbb,
+        ];
+        let decoded = decode(&data).unwrap();
+        assert_eq!(decoded.mac.unwrap().header.seq, 0x1f);
+        let network = decoded.network.unwrap();
+        assert_eq!(network.destination_address, [0x3e, 0xed]);
+        assert_eq!(network.sequence_number, 0x87);
+        let application_service = decoded.application_service.unwrap();
+        assert_eq!(application_service.destination, Some(0x01));
+        assert_eq!(application_service.cluster, Some(0x0006));
+        assert_eq!(application_service.profile, Some(0x0104));
+        assert_eq!(application_service.source, Some(0x01));
+        assert_eq!(application_service.counter, 0x12);
+        assert_eq!(decoded.payload, [0xaa, 0xbb]);
+    }
+
+    #[test]
+    fn decode_link_quality() {
+        let data = [
+            0x41, 0x88, 0x1f, 0x45, 0x67, 0x3e, 0xed, 0x00, 0x00, 0x08, 0x00, 0x3e, 0xed, 0x00,
+            0x00, 0x01, 0x87, 0x40, 0x01, 0x06, 0x00, 0x04, 0x01, 0x01, 0x12, 0xaa, 0xbb,
+        ];
+        let decoded = decode_with_link_quality(&data, Some(0xb4), Some(-62)).unwrap();
+        assert_eq!(decoded.lqi, Some(0xb4));
+        assert_eq!(decoded.rssi, Some(-62));
+        assert!(decoded.application_service.is_some());
+
+        let decoded = decode(&data).unwrap();
+        assert_eq!(decoded.lqi, None);
+        assert_eq!(decoded.rssi, None);
+
+        // Kept when decoding fails
+        let partial = decode_with_link_quality(&data[..20], Some(0x20), None).unwrap_err();
+        assert_eq!(partial.layer, Layer::ApplicationService);
+        assert_eq!(partial.decoded.lqi, Some(0x20));
+        assert_eq!(partial.decoded.rssi, None);
+    }
+
+    #[test]
+    fn decode_device_announce() {
+        use crate::device_profile::DeviceProfileMessage;
+
+        let data = [
+            0x41, 0x88, 0x1f, 0x45, 0x67, 0xff, 0xff, 0x7b, 0xc0, 0x08, 0x00, 0xfd, 0xff, 0x7b,
+            0xc0, 0x1e, 0x87, 0x08, 0x00, 0x13, 0x00, 0x00, 0x00, 0x00, 0x06, 0x81, 0x7b, 0xc0,
+            0x85, 0xae, 0x21, 0xfe, 0xff, 0x6f, 0x0d, 0x00, 0x80,
+        ];
+        let decoded = decode(&data).unwrap();
+        let application_service = decoded.application_service.unwrap();
+        assert_eq!(application_service.cluster, Some(0x0013));
+        let device_profile = decoded.device_profile.unwrap();
+        assert_eq!(device_profile.transaction_sequence, 0x81);
+        match device_profile.message {
+            DeviceProfileMessage::DeviceAnnounce(announce) => {
+                assert_eq!(announce.network_address, [0x7b, 0xc0]);
+                assert_eq!(
+                    announce.ieee_address,
+                    [0x85, 0xae, 0x21, 0xfe, 0xff, 0x6f, 0x0d, 0x00]
+                );
+            }
+            _ => unreachable!(),
+        }
+        assert!(decoded.payload.is_empty());
+
+        // Truncated device announce
+        let partial = decode(&data[..30]).unwrap_err();
+        assert_eq!(partial.layer, Layer::DeviceProfile);
+        assert!(partial.decoded.application_service.is_some());
+        assert!(partial.decoded.device_profile.is_none());
+
+        // Other profiles are not decoded as device profile
+        let mut data = data;
+        data[21] = 0x04;
+        data[22] = 0x01;
+        let decoded = decode(&data).unwrap();
+        assert!(decoded.device_profile.is_none());
+        assert_eq!(decoded.payload.len(), 12);
+    }
+
+    #[test]
+    fn decode_truncated_application_service() {
+        let data = [
+            0x41, 0x88, 0x1f, 0x45, 0x67, 0x3e, 0xed, 0x00, 0x00, 0x08, 0x00, 0x3e, 0xed, 0x00,
+            0x00, 0x01, 0x87, 0x40, 0x01, 0x06,
+        ];
+        let partial = decode(&data).unwrap_err();
+        assert_eq!(partial.layer, Layer::ApplicationService);
+        assert_eq!(partial.error, Error::NotEnoughSpace);
+        assert_eq!(partial.decoded.mac.unwrap().header.seq, 0x1f);
+        let network = partial.decoded.network.unwrap();
+        assert_eq!(network.source_address, [0x00, 0x00]);
+        assert_eq!(network.radius, 1);
+        assert!(partial.decoded.application_service.is_none());
+        assert_eq!(partial.decoded.payload, [0x40, 0x01, 0x06]);
+    }
+
+    #[test]
+    fn frame_is_secured() {
+        // Secured network data frame
+        let data = [
+            0x08, 0x02, 0x00, 0x00, 0x34, 0x12, 0x1e, 0x42, 0x28, 0x01, 0x00, 0x00, 0x00, 0x77,
+            0x66, 0x55, 0x44, 0x33, 0x22, 0x11, 0x00, 0x00,
+        ];
+        assert_eq!(nwk_is_secured(&data), Ok(true));
+        // Unsecured network data frame carrying a unsecured APS frame
+        let data = [
+            0x08, 0x00, 0x3e, 0xed, 0x00, 0x00, 0x01, 0x87, 0x40, 0x01, 0x06, 0x00, 0x04, 0x01,
+            0x01, 0x12,
+        ];
+        assert_eq!(nwk_is_secured(&data), Ok(false));
+        assert_eq!(aps_is_secured(&data[8..]), Ok(false));
+        assert_eq!(nwk_is_secured(&data[..7]), Err(Error::WrongNumberOfBytes));
+
+        // Secured APS data frame
+        let data = [
+            0x28, 0x72, 0x30, 0x00, 0x00, 0x63, 0x7d, 0x61, 0x03, 0x00, 0x8d, 0x15, 0x00, 0x00,
+        ];
+        assert_eq!(aps_is_secured(&data), Ok(true));
+        assert_eq!(aps_is_secured(&data[..1]), Err(Error::WrongNumberOfBytes));
+    }
+
+    #[test]
+    fn decode_truncated_mac() {
+        let data = [0x41, 0x88, 0x1f, 0x45];
+        let partial = decode(&data).unwrap_err();
+        assert_eq!(partial.layer, Layer::Mac);
+        assert_eq!(partial.error, Error::WrongNumberOfBytes);
+        assert!(partial.decoded.mac.is_none());
+        assert!(partial.decoded.network.is_none());
+    }
+
+    #[test]
+    fn decode_from_mac_frame() {
+        use psila_crypto::{AesEngine, EngineBackend, BLOCK_SIZE, KEY_SIZE};
+
+        /// Only unsecured frames are decoded
+        struct NoEngine;
+
+        impl AesEngine for NoEngine {
+            fn set_key(&mut self, _key: &[u8; KEY_SIZE]) -> Result<(), psila_crypto::Error> {
+                Err(psila_crypto::Error::InvalidKey)
+            }
+            fn encrypt_block(&self, _block: &mut [u8; BLOCK_SIZE]) {}
+        }
+
+        struct NoKeys;
+
+        impl KeyStore for NoKeys {
+            fn network_key(&self, _sequence: u8) -> Option<crate::Key> {
+                None
+            }
+        }
+
+        /// A link key shared with all devices
+        struct LinkKeys;
+
+        impl KeyStore for LinkKeys {
+            fn network_key(&self, _sequence: u8) -> Option<crate::Key> {
+                None
+            }
+            fn link_key(&self, _address: ExtendedAddress) -> Option<crate::Key> {
+                Some(crate::Key::from([0u8; 16]))
+            }
+        }
+
+        let mut backend = EngineBackend::new(NoEngine);
+        // On/Off cluster, Off command
+        let data = [
+            0x41, 0x88, 0x1f, 0x45, 0x67, 0x3e, 0xed, 0x00, 0x00, 0x08, 0x00, 0x3e, 0xed, 0x00,
+            0x00, 0x01, 0x87, 0x40, 0x01, 0x06, 0x00, 0x04, 0x01, 0x01, 0x12, 0x11, 0x2a, 0x00,
+        ];
+        let frame = mac::Frame::decode(&data, false).unwrap();
+        let decoded = from_mac_frame(&mut backend, &frame, &NoKeys).unwrap();
+        assert_eq!(decoded.network.sequence_number, 0x87);
+        let application_service = decoded.application_service.unwrap();
+        assert_eq!(application_service.cluster, Some(0x0006));
+        let cluster_library = decoded.cluster_library.unwrap();
+        assert_eq!(cluster_library.transaction_sequence, 0x2a);
+        assert_eq!(cluster_library.command, 0x00);
+        assert!(decoded.payload().is_empty());
+
+        // Device announce
+        let data = [
+            0x41, 0x88, 0x1f, 0x45, 0x67, 0xff, 0xff, 0x7b, 0xc0, 0x08, 0x00, 0xfd, 0xff, 0x7b,
+            0xc0, 0x1e, 0x87, 0x08, 0x00, 0x13, 0x00, 0x00, 0x00, 0x00, 0x06, 0x81, 0x7b, 0xc0,
+            0x85, 0xae, 0x21, 0xfe, 0xff, 0x6f, 0x0d, 0x00, 0x80,
+        ];
+        let frame = mac::Frame::decode(&data, false).unwrap();
+        let decoded = from_mac_frame(&mut backend, &frame, &NoKeys).unwrap();
+        assert!(decoded.cluster_library.is_none());
+        assert!(decoded.payload().is_empty());
+        assert_eq!(decoded.device_profile.unwrap().transaction_sequence, 0x81);
+
+        // Secured network frame without a key
+        let data = [
+            0x41, 0x88, 0x1f, 0x45, 0x67, 0x3e, 0xed, 0x00, 0x00, 0x08, 0x02, 0x00, 0x00, 0x34,
+            0x12, 0x1e, 0x42, 0x28, 0x01, 0x00, 0x00, 0x00, 0x77, 0x66, 0x55, 0x44, 0x33, 0x22,
+            0x11, 0x00, 0x00, 0x42, 0x3c, 0x40, 0x5e,
+        ];
+        let frame = mac::Frame::decode(&data, false).unwrap();
+        assert_eq!(
+            from_mac_frame(&mut backend, &frame, &NoKeys).unwrap_err(),
+            Error::MissingKey
+        );
+
+        // Secured application service frame without a link key
+        let data = [
+            0x41, 0x88, 0x1f, 0x45, 0x67, 0x3e, 0xed, 0x00, 0x00, 0x08, 0x00, 0x3e, 0xed, 0x00,
+            0x00, 0x01, 0x87, 0x60, 0x01, 0x06, 0x00, 0x04, 0x01, 0x01, 0x12, 0x20, 0x01, 0x00,
+            0x00, 0x00, 0x77, 0x66, 0x55, 0x44, 0x33, 0x22, 0x11, 0x00, 0xaa, 0xbb, 0xcc, 0x01,
+            0x02, 0x03, 0x04,
+        ];
+        let frame = mac::Frame::decode(&data, false).unwrap();
+        let decoded = from_mac_frame(&mut backend, &frame, &NoKeys).unwrap();
+        assert_eq!(decoded.payload_kind, Payload::Encrypted);
+        let application_service = decoded.application_service.unwrap();
+        assert!(application_service.control.security);
+        assert_eq!(application_service.cluster, Some(0x0006));
+        assert_eq!(application_service.counter, 0x12);
+        assert!(decoded.cluster_library.is_none());
+        assert_eq!(decoded.payload(), &data[25..]);
+
+        let decoded = decode(&data).unwrap();
+        assert_eq!(decoded.payload_kind, Payload::Encrypted);
+        assert_eq!(decoded.application_service.unwrap().profile, Some(0x0104));
+        assert_eq!(decoded.payload, &data[25..]);
+        // A key rejected by the crypto backend is a failure, not a missing key
+        assert_eq!(
+            from_mac_frame(&mut backend, &frame, &LinkKeys).unwrap_err(),
+            Error::CryptoError(psila_crypto::Error::InvalidKey)
+        );
+        // A truncated frame is still a parse error
+        assert_eq!(
+            decode(&data[..20]).unwrap_err().layer,
+            Layer::ApplicationService
+        );
+
+        // Not a data frame
+        let data = [
+            0x00, 0x80, 0x01, 0x45, 0x67, 0x00, 0x00, 0xff, 0xcf, 0x00, 0x00, 0x00, 0x22, 0x84,
+            0x38, 0x2e, 0x03, 0xff, 0xff, 0x2e, 0x21, 0x00, 0xff, 0xff, 0xff, 0x00,
+        ];
+        let frame = mac::Frame::decode(&data, false).unwrap();
+        assert_eq!(
+            from_mac_frame(&mut backend, &frame, &NoKeys).unwrap_err(),
+            Error::UnknownFrameType
+        );
+    }
+}
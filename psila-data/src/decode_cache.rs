@@ -0,0 +1,173 @@
+//! # Decode cache
+//!
+//! Remembers the summaries of recently decoded frames, so that frames which
+//! are received over and over, such as beacons and link status, aren't
+//! decoded again when sniffing. The least recently used summary is evicted
+//! when the cache is full.
+
+use alloc::collections::VecDeque;
+use alloc::vec::Vec;
+
+use crate::decode::{summarize, DecodeSummary};
+
+/// FNV-1a offset basis
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+/// FNV-1a prime
+const FNV_PRIME: u64 = 0x0000_0100_0000_01b3;
+
+/// Hash of the raw frame bytes, FNV-1a
+fn frame_hash(data: &[u8]) -> u64 {
+    data.iter().fold(FNV_OFFSET_BASIS, |hash, octet| {
+        (hash ^ u64::from(*octet)).wrapping_mul(FNV_PRIME)
+    })
+}
+
+struct Entry {
+    hash: u64,
+    frame: Vec<u8>,
+    summary: DecodeSummary,
+}
+
+/// Least recently used cache of decode summaries, keyed by the raw frame
+pub struct DecodeCache {
+    capacity: usize,
+    entries: VecDeque<Entry>,
+    hits: usize,
+    misses: usize,
+}
+
+impl DecodeCache {
+    /// Create a cache holding at most `capacity` summaries, at least one
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            capacity,
+            entries: VecDeque::with_capacity(capacity),
+            hits: 0,
+            misses: 0,
+        }
+    }
+
+    /// Decode a frame without footer (FCS) into a summary, see
+    /// `decode::summarize`
+    ///
+    /// The summary of a identical frame decoded before is reused if still
+    /// in the cache.
+    pub fn decode(&mut self, data: &[u8]) -> DecodeSummary {
+        let hash = frame_hash(data);
+        let position = self
+            .entries
+            .iter()
+            .position(|entry| entry.hash == hash && entry.frame == data);
+        if let Some(entry) = position.and_then(|index| self.entries.remove(index)) {
+            self.hits += 1;
+            let summary = entry.summary.clone();
+            self.entries.push_back(entry);
+            return summary;
+        }
+        self.misses += 1;
+        let summary = summarize(data);
+        if self.entries.len() >= self.capacity {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(Entry {
+            hash,
+            frame: data.to_vec(),
+            summary: summary.clone(),
+        });
+        summary
+    }
+
+    /// Maximum number of summaries held
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Number of summaries held
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Check if the cache is empty
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Number of decodes answered from the cache
+    pub fn hits(&self) -> usize {
+        self.hits
+    }
+
+    /// Number of decodes which had to decode the frame
+    pub fn misses(&self) -> usize {
+        self.misses
+    }
+
+    /// Remove all summaries
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::decode::Layer;
+
+    #[test]
+    fn cache_hit() {
+        // Beacon request
+        let beacon_request = [0x03, 0x08, 0x01, 0xff, 0xff, 0xff, 0xff, 0x07];
+        // Beacon of a Zigbee PRO network
+        let beacon = [
+            0x00, 0x80, 0x8a, 0x45, 0x67, 0x00, 0x00, 0xff, 0xcf, 0x00, 0x00, 0x00, 0x22, 0x84,
+            0x38, 0x2e, 0x03, 0xff, 0xff, 0x2e, 0x21, 0x00, 0xff, 0xff, 0xff, 0x00,
+        ];
+        let mut cache = DecodeCache::new(1);
+
+        let summary = cache.decode(&beacon);
+        assert_eq!(summary.layer, Some(Layer::Mac));
+        assert_eq!(summary.sequence, Some(0x8a));
+        assert_eq!((cache.hits(), cache.misses()), (0, 1));
+
+        let cached = cache.decode(&beacon);
+        assert_eq!(cached, summary);
+        assert_eq!((cache.hits(), cache.misses()), (1, 1));
+
+        // The beacon request evicts the beacon
+        let other = cache.decode(&beacon_request);
+        assert_ne!(other, summary);
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache.decode(&beacon), summary);
+        assert_eq!((cache.hits(), cache.misses()), (1, 3));
+    }
+
+    #[test]
+    fn least_recently_used_evicted() {
+        let frames = [
+            [0x03, 0x08, 0x01, 0xff, 0xff, 0xff, 0xff, 0x07],
+            [0x03, 0x08, 0x02, 0xff, 0xff, 0xff, 0xff, 0x07],
+            [0x03, 0x08, 0x03, 0xff, 0xff, 0xff, 0xff, 0x07],
+        ];
+        let mut cache = DecodeCache::new(2);
+        cache.decode(&frames[0]);
+        cache.decode(&frames[1]);
+        // Use the first frame, the second is now the least recently used
+        cache.decode(&frames[0]);
+        cache.decode(&frames[2]);
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.hits(), 1);
+
+        cache.decode(&frames[0]);
+        assert_eq!(cache.hits(), 2);
+        cache.decode(&frames[1]);
+        assert_eq!(cache.misses(), 4);
+
+        cache.clear();
+        assert!(cache.is_empty());
+        // Truncated frame, the summary carries the error
+        let summary = cache.decode(&frames[0][..2]);
+        assert_eq!(summary.layer, None);
+        assert!(summary.error.is_some());
+    }
+}
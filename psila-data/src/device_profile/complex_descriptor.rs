@@ -0,0 +1,198 @@
+use core::convert::TryFrom;
+
+use crate::common::address::NetworkAddress;
+use crate::common::types::OctetString;
+use crate::device_profile::Status;
+use crate::pack::Pack;
+use crate::Error;
+
+// 2.3.2.6 Complex Descriptor
+extended_enum!(
+    /// Complex descriptor field, identified by the compressed XML tag
+    ComplexDescriptorTag, u8,
+    /// Language and character set, ISO 639-1 language code and character
+    /// set identifier
+    LanguageCharacterSet => 0x01,
+    /// Manufacturer name, character string
+    ManufacturerName => 0x02,
+    /// Model name, character string
+    ModelName => 0x03,
+    /// Serial number, character string
+    SerialNumber => 0x04,
+    /// Device URL, character string
+    DeviceUrl => 0x05,
+    /// Icon, octet string
+    Icon => 0x06,
+    /// Icon URL, character string
+    IconUrl => 0x07,
+);
+
+/// Length of the language and character set field
+const LANGUAGE_CHARACTER_SET_SIZE: usize = 3;
+
+/// Iterator over the fields of a complex descriptor
+///
+/// Each item is the tag and the field data. Strings are provided without
+/// the length prefix. Iteration stops at the first malformed field.
+pub struct ComplexDescriptorFields<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Iterator for ComplexDescriptorFields<'a> {
+    type Item = (ComplexDescriptorTag, &'a [u8]);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.data.is_empty() {
+            return None;
+        }
+        let tag = match ComplexDescriptorTag::try_from(self.data[0]) {
+            Ok(tag) => tag,
+            Err(_) => {
+                self.data = &[];
+                return None;
+            }
+        };
+        let (start, length) = if tag == ComplexDescriptorTag::LanguageCharacterSet {
+            (1, LANGUAGE_CHARACTER_SET_SIZE)
+        } else if self.data.len() > 1 {
+            (2, self.data[1] as usize)
+        } else {
+            self.data = &[];
+            return None;
+        };
+        if self.data.len() < start + length {
+            self.data = &[];
+            return None;
+        }
+        let field = &self.data[start..start + length];
+        self.data = &self.data[start + length..];
+        Some((tag, field))
+    }
+}
+
+// 2.4.3.1.5 Complex_Desc_req
+/// Complex descriptor request
+/// Requests the complex descriptor for a remote device
+#[derive(Clone, Debug, PartialEq)]
+pub struct ComplexDescriptorRequest {
+    pub address: NetworkAddress,
+}
+
+impl Pack<ComplexDescriptorRequest, Error> for ComplexDescriptorRequest {
+    fn pack(&self, data: &mut [u8]) -> Result<usize, Error> {
+        if data.len() != 2 {
+            return Err(Error::WrongNumberOfBytes);
+        }
+        self.address.pack(&mut data[0..2])?;
+        Ok(2)
+    }
+
+    fn unpack(data: &[u8]) -> Result<(Self, usize), Error> {
+        if data.len() != 2 {
+            return Err(Error::WrongNumberOfBytes);
+        }
+        let address = NetworkAddress::unpack(&data[0..2])?;
+        Ok((Self { address }, 2))
+    }
+}
+
+// 2.4.4.2.5 Complex_Desc_rsp
+/// Complex descriptor response
+/// Response to a complex descriptor request
+#[derive(Clone, Debug, PartialEq)]
+pub struct ComplexDescriptorResponse {
+    pub status: Status,
+    pub address: NetworkAddress,
+    /// The complex descriptor, a set of compressed XML fields
+    pub descriptor: OctetString,
+}
+
+impl ComplexDescriptorResponse {
+    /// Iterate over the fields of the complex descriptor
+    pub fn fields(&self) -> ComplexDescriptorFields {
+        ComplexDescriptorFields {
+            data: &self.descriptor[..],
+        }
+    }
+}
+
+impl Pack<ComplexDescriptorResponse, Error> for ComplexDescriptorResponse {
+    fn pack(&self, data: &mut [u8]) -> Result<usize, Error> {
+        let length = self.descriptor.len();
+        if length > usize::from(u8::max_value()) {
+            return Err(Error::InvalidValue);
+        }
+        let size = 4 + length;
+        if data.len() < size {
+            return Err(Error::WrongNumberOfBytes);
+        }
+        data[0] = u8::from(self.status);
+        self.address.pack(&mut data[1..3])?;
+        data[3] = length as u8;
+        data[4..size].copy_from_slice(&self.descriptor[..]);
+        Ok(size)
+    }
+
+    fn unpack(data: &[u8]) -> Result<(Self, usize), Error> {
+        if data.len() < 4 {
+            return Err(Error::WrongNumberOfBytes);
+        }
+        let status = Status::try_from(data[0])?;
+        let address = NetworkAddress::unpack(&data[1..3])?;
+        let (descriptor, used) = OctetString::unpack(&data[3..])?;
+        Ok((
+            Self {
+                status,
+                address,
+                descriptor,
+            },
+            3 + used,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unpack_complex_descriptor_response() {
+        let data = [
+            0x00, 0x96, 0x1f, 0x0d, 0x01, b'e', b'n', 0x00, 0x02, 0x05, b'P', b's', b'i', b'l',
+            b'a', 0x03, 0x00,
+        ];
+        let (rsp, used) = ComplexDescriptorResponse::unpack(&data[..]).unwrap();
+        assert_eq!(used, 17);
+        assert_eq!(rsp.status, Status::Success);
+        assert_eq!(rsp.address, 0x1f96);
+        let mut fields = rsp.fields();
+        assert_eq!(
+            fields.next(),
+            Some((ComplexDescriptorTag::LanguageCharacterSet, &b"en\x00"[..]))
+        );
+        assert_eq!(
+            fields.next(),
+            Some((ComplexDescriptorTag::ManufacturerName, &b"Psila"[..]))
+        );
+        assert_eq!(
+            fields.next(),
+            Some((ComplexDescriptorTag::ModelName, &b""[..]))
+        );
+        assert_eq!(fields.next(), None);
+
+        let mut packed = [0u8; 32];
+        let size = rsp.pack(&mut packed[..]).unwrap();
+        assert_eq!(packed[..size], data);
+    }
+
+    #[test]
+    fn pack_complex_descriptor_response_too_long() {
+        let rsp = ComplexDescriptorResponse {
+            status: Status::Success,
+            address: NetworkAddress::new(0x1f96),
+            descriptor: crate::common::types::octet_string(&[0u8; 256]).unwrap(),
+        };
+        let mut packed = [0u8; 300];
+        assert_eq!(rsp.pack(&mut packed[..]), Err(Error::InvalidValue));
+    }
+}
@@ -0,0 +1,215 @@
+use core::convert::TryFrom;
+
+use crate::common::address::{ExtendedAddress, NetworkAddress};
+use crate::device_profile::Status;
+use crate::pack::{Pack, PackFixed};
+use crate::Error;
+
+use byteorder::{ByteOrder, LittleEndian};
+
+// 2.4.3.2.1 End_Device_Bind_req
+/// End device bind request
+/// Sent to the coordinator when the user requests a binding, the coordinator
+/// matches two requests to create the binding
+#[derive(Clone, Debug, PartialEq)]
+pub struct EndDeviceBindRequest {
+    pub target: NetworkAddress,
+    pub source_address: ExtendedAddress,
+    pub source_endpoint: u8,
+    pub profile: u16,
+    num_input_clusters: u8,
+    input_clusters: [u16; 32],
+    num_output_clusters: u8,
+    output_clusters: [u16; 32],
+}
+
+impl EndDeviceBindRequest {
+    /// Create a new end device bind request, at most 32 input and 32 output
+    /// clusters can be provided
+    pub fn new(
+        target: NetworkAddress,
+        source_address: ExtendedAddress,
+        source_endpoint: u8,
+        profile: u16,
+        input: &[u16],
+        output: &[u16],
+    ) -> Result<Self, Error> {
+        if input.len() > 32 || output.len() > 32 {
+            return Err(Error::NotEnoughSpace);
+        }
+        let mut input_clusters = [0u16; 32];
+        input_clusters[..input.len()].copy_from_slice(input);
+        let mut output_clusters = [0u16; 32];
+        output_clusters[..output.len()].copy_from_slice(output);
+        Ok(Self {
+            target,
+            source_address,
+            source_endpoint,
+            profile,
+            num_input_clusters: input.len() as u8,
+            input_clusters,
+            num_output_clusters: output.len() as u8,
+            output_clusters,
+        })
+    }
+
+    pub fn input_clusters_len(&self) -> usize {
+        self.num_input_clusters as usize
+    }
+
+    pub fn input_clusters_entries(&self) -> &[u16] {
+        &self.input_clusters[..self.num_input_clusters as usize]
+    }
+
+    pub fn output_clusters_len(&self) -> usize {
+        self.num_output_clusters as usize
+    }
+
+    pub fn output_clusters_entries(&self) -> &[u16] {
+        &self.output_clusters[..self.num_output_clusters as usize]
+    }
+}
+
+impl Pack<EndDeviceBindRequest, Error> for EndDeviceBindRequest {
+    fn pack(&self, data: &mut [u8]) -> Result<usize, Error> {
+        let num_clusters = self.input_clusters_len() + self.output_clusters_len();
+        if data.len() < 15 + (num_clusters * 2) {
+            return Err(Error::WrongNumberOfBytes);
+        }
+        self.target.pack(&mut data[0..2])?;
+        self.source_address.pack(&mut data[2..10])?;
+        data[10] = self.source_endpoint;
+        LittleEndian::write_u16(&mut data[11..13], self.profile);
+        data[13] = self.num_input_clusters;
+        let mut offset = 14;
+        for cluster in self.input_clusters_entries().iter() {
+            LittleEndian::write_u16(&mut data[offset..offset + 2], *cluster);
+            offset += 2;
+        }
+        data[offset] = self.num_output_clusters;
+        offset += 1;
+        for cluster in self.output_clusters_entries().iter() {
+            LittleEndian::write_u16(&mut data[offset..offset + 2], *cluster);
+            offset += 2;
+        }
+        Ok(offset)
+    }
+
+    fn unpack(data: &[u8]) -> Result<(Self, usize), Error> {
+        if data.len() < 15 {
+            return Err(Error::WrongNumberOfBytes);
+        }
+        let target = NetworkAddress::unpack(&data[0..2])?;
+        let source_address = ExtendedAddress::unpack(&data[2..10])?;
+        let source_endpoint = data[10];
+        let profile = LittleEndian::read_u16(&data[11..13]);
+        let num_input_clusters = data[13];
+        let num_clusters = num_input_clusters as usize;
+        if num_clusters > 32 {
+            return Err(Error::NotEnoughSpace);
+        }
+        if data.len() < 15 + (num_clusters * 2) {
+            return Err(Error::WrongNumberOfBytes);
+        }
+        let mut offset = 14;
+        let mut input_clusters = [0u16; 32];
+        for cluster in input_clusters[..num_clusters].iter_mut() {
+            *cluster = LittleEndian::read_u16(&data[offset..offset + 2]);
+            offset += 2;
+        }
+        let num_output_clusters = data[offset];
+        let num_clusters = num_output_clusters as usize;
+        offset += 1;
+        if num_clusters > 32 {
+            return Err(Error::NotEnoughSpace);
+        }
+        if data.len() < offset + (num_clusters * 2) {
+            return Err(Error::WrongNumberOfBytes);
+        }
+        let mut output_clusters = [0u16; 32];
+        for cluster in output_clusters[..num_clusters].iter_mut() {
+            *cluster = LittleEndian::read_u16(&data[offset..offset + 2]);
+            offset += 2;
+        }
+        Ok((
+            Self {
+                target,
+                source_address,
+                source_endpoint,
+                profile,
+                num_input_clusters,
+                input_clusters,
+                num_output_clusters,
+                output_clusters,
+            },
+            offset,
+        ))
+    }
+}
+
+// 2.4.4.3.1 End_Device_Bind_rsp
+/// End device bind response
+#[derive(Clone, Debug, PartialEq)]
+pub struct EndDeviceBindResponse {
+    pub status: Status,
+}
+
+impl Pack<EndDeviceBindResponse, Error> for EndDeviceBindResponse {
+    fn pack(&self, data: &mut [u8]) -> Result<usize, Error> {
+        if data.is_empty() {
+            return Err(Error::WrongNumberOfBytes);
+        }
+        data[0] = u8::from(self.status);
+        Ok(1)
+    }
+
+    fn unpack(data: &[u8]) -> Result<(Self, usize), Error> {
+        if data.is_empty() {
+            return Err(Error::WrongNumberOfBytes);
+        }
+        let status = Status::try_from(data[0])?;
+        Ok((Self { status }, 1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pack_end_device_bind_request() {
+        // Light switch, on/off client on endpoint 1
+        let request = EndDeviceBindRequest::new(
+            NetworkAddress::new(0x0000),
+            ExtendedAddress::new(0x0011_2233_4455_6677),
+            0x01,
+            0x0104,
+            &[0x0000, 0x0003],
+            &[0x0006],
+        )
+        .unwrap();
+        let mut data = [0u8; 64];
+        let used = request.pack(&mut data).unwrap();
+        assert_eq!(used, 21);
+        assert_eq!(
+            data[..used],
+            [
+                0x00, 0x00, 0x77, 0x66, 0x55, 0x44, 0x33, 0x22, 0x11, 0x00, 0x01, 0x04, 0x01, 0x02,
+                0x00, 0x00, 0x03, 0x00, 0x01, 0x06, 0x00
+            ]
+        );
+        let (unpacked, used) = EndDeviceBindRequest::unpack(&data[..used]).unwrap();
+        assert_eq!(used, 21);
+        assert_eq!(unpacked, request);
+        assert_eq!(unpacked.input_clusters_entries(), [0x0000, 0x0003]);
+        assert_eq!(unpacked.output_clusters_entries(), [0x0006]);
+    }
+
+    #[test]
+    fn unpack_end_device_bind_response() {
+        let data = [0x86];
+        let (response, used) = EndDeviceBindResponse::unpack(&data[..]).unwrap();
+        assert_eq!(used, 1);
+        assert_eq!(response.status, Status::NoMatch);
+    }
+}
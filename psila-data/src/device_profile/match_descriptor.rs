@@ -21,6 +21,31 @@ pub struct MatchDescriptorRequest {
 }
 
 impl MatchDescriptorRequest {
+    /// Create a new match descriptor request, at most 32 input and 32 output
+    /// clusters can be provided
+    pub fn new(
+        address: NetworkAddress,
+        profile: u16,
+        input: &[u16],
+        output: &[u16],
+    ) -> Result<Self, Error> {
+        if input.len() > 32 || output.len() > 32 {
+            return Err(Error::NotEnoughSpace);
+        }
+        let mut input_clusters = [0u16; 32];
+        input_clusters[..input.len()].copy_from_slice(input);
+        let mut output_clusters = [0u16; 32];
+        output_clusters[..output.len()].copy_from_slice(output);
+        Ok(Self {
+            address,
+            profile,
+            num_input_clusters: input.len() as u8,
+            input_clusters,
+            num_output_clusters: output.len() as u8,
+            output_clusters,
+        })
+    }
+
     pub fn input_clusters_len(&self) -> usize {
         self.num_input_clusters as usize
     }
@@ -40,23 +65,21 @@ impl MatchDescriptorRequest {
 
 impl Pack<MatchDescriptorRequest, Error> for MatchDescriptorRequest {
     fn pack(&self, data: &mut [u8]) -> Result<usize, Error> {
-        assert!(self.input_clusters.len() <= 255);
-        assert!(self.output_clusters.len() <= 255);
-        let num_clusters = self.input_clusters.len() + self.output_clusters.len();
+        let num_clusters = self.input_clusters_len() + self.output_clusters_len();
         if data.len() < 6 + (num_clusters * 2) {
             return Err(Error::WrongNumberOfBytes);
         }
         self.address.pack(&mut data[0..2])?;
         LittleEndian::write_u16(&mut data[2..4], self.profile);
-        data[4] = self.input_clusters.len() as u8;
+        data[4] = self.num_input_clusters;
         let mut offset = 5;
-        for cluster in self.input_clusters.iter() {
+        for cluster in self.input_clusters_entries().iter() {
             LittleEndian::write_u16(&mut data[offset..offset + 2], *cluster);
             offset += 2;
         }
-        data[offset] = self.output_clusters.len() as u8;
+        data[offset] = self.num_output_clusters;
         offset += 1;
-        for cluster in self.output_clusters.iter() {
+        for cluster in self.output_clusters_entries().iter() {
             LittleEndian::write_u16(&mut data[offset..offset + 2], *cluster);
             offset += 2;
         }
@@ -71,6 +94,9 @@ impl Pack<MatchDescriptorRequest, Error> for MatchDescriptorRequest {
         let profile = LittleEndian::read_u16(&data[2..4]);
         let num_input_clusters = data[4];
         let num_clusters = num_input_clusters as usize;
+        if num_clusters > 32 {
+            return Err(Error::NotEnoughSpace);
+        }
         if data.len() < 6 + (num_clusters * 2) {
             return Err(Error::WrongNumberOfBytes);
         }
@@ -83,6 +109,9 @@ impl Pack<MatchDescriptorRequest, Error> for MatchDescriptorRequest {
         let num_output_clusters = data[offset];
         let num_clusters = num_output_clusters as usize;
         offset += 1;
+        if num_clusters > 32 {
+            return Err(Error::NotEnoughSpace);
+        }
         if data.len() < offset + (num_clusters * 2) {
             return Err(Error::WrongNumberOfBytes);
         }
@@ -105,9 +134,9 @@ impl Pack<MatchDescriptorRequest, Error> for MatchDescriptorRequest {
     }
 }
 
-// 2.4.3.1.7 Match_Desc_req
-/// Match descriptor request
-/// Requests simple descriptor for devices matching the requested requirements
+// 2.4.4.2.7 Match_Desc_rsp
+/// Match descriptor response
+/// Response listing the endpoints matching the requested requirements
 #[derive(Clone, Debug, PartialEq)]
 pub struct MatchDescriptorResponse {
     pub status: Status,
@@ -117,6 +146,32 @@ pub struct MatchDescriptorResponse {
 }
 
 impl MatchDescriptorResponse {
+    pub fn success_response(address: NetworkAddress, endpoints: &[u8]) -> Self {
+        let count = if endpoints.len() > 32 {
+            32
+        } else {
+            endpoints.len()
+        };
+        let mut entries = [0u8; 32];
+        entries[..count].copy_from_slice(&endpoints[..count]);
+        Self {
+            status: Status::Success,
+            address,
+            num_entries: count as u8,
+            entries,
+        }
+    }
+
+    pub fn failure_response(status: Status, address: NetworkAddress) -> Self {
+        assert!(status != Status::Success);
+        Self {
+            status,
+            address,
+            num_entries: 0,
+            entries: [0u8; 32],
+        }
+    }
+
     pub fn is_empty(&self) -> bool {
         self.num_entries == 0
     }
@@ -136,7 +191,7 @@ impl Pack<MatchDescriptorResponse, Error> for MatchDescriptorResponse {
         if data.len() < 4 + num_entries {
             return Err(Error::WrongNumberOfBytes);
         }
-        data[0] = self.status as u8;
+        data[0] = u8::from(self.status);
         self.address.pack(&mut data[1..=2])?;
         data[3] = self.num_entries;
         let mut offset = 4;
@@ -152,6 +207,9 @@ impl Pack<MatchDescriptorResponse, Error> for MatchDescriptorResponse {
         let status = Status::try_from(data[0])?;
         let address = NetworkAddress::unpack(&data[1..=2])?;
         let num_entries = data[3] as usize;
+        if num_entries > 32 {
+            return Err(Error::NotEnoughSpace);
+        }
         if data.len() < 4 + num_entries {
             return Err(Error::WrongNumberOfBytes);
         }
@@ -195,4 +253,43 @@ mod tests {
         assert_eq!(req.len(), 1);
         assert_eq!(req.entries()[0], 0x01);
     }
+
+    #[test]
+    fn pack_match_descriptor_request_on_off() {
+        let request =
+            MatchDescriptorRequest::new(NetworkAddress::new(0xfffd), 0x0104, &[0x0006], &[])
+                .unwrap();
+        let mut data = [0u8; 64];
+        let used = request.pack(&mut data).unwrap();
+        assert_eq!(used, 8);
+        assert_eq!(
+            data[..used],
+            [0xfd, 0xff, 0x04, 0x01, 0x01, 0x06, 0x00, 0x00]
+        );
+        let (unpacked, used) = MatchDescriptorRequest::unpack(&data[..used]).unwrap();
+        assert_eq!(used, 8);
+        assert_eq!(unpacked, request);
+        assert_eq!(unpacked.input_clusters_entries(), [0x0006]);
+        assert!(unpacked.output_clusters_entries().is_empty());
+    }
+
+    #[test]
+    fn pack_match_descriptor_response_on_off() {
+        let response =
+            MatchDescriptorResponse::success_response(NetworkAddress::new(0x1234), &[0x0b]);
+        let mut data = [0u8; 64];
+        let used = response.pack(&mut data).unwrap();
+        assert_eq!(used, 5);
+        assert_eq!(data[..used], [0x00, 0x34, 0x12, 0x01, 0x0b]);
+        let (unpacked, _) = MatchDescriptorResponse::unpack(&data[..used]).unwrap();
+        assert_eq!(unpacked, response);
+
+        let response = MatchDescriptorResponse::failure_response(
+            Status::DeviceNotFound,
+            NetworkAddress::new(0x1234),
+        );
+        let used = response.pack(&mut data).unwrap();
+        assert_eq!(used, 4);
+        assert_eq!(data[..used], [0x81, 0x34, 0x12, 0x00]);
+    }
 }
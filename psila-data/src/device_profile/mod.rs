@@ -1,25 +1,37 @@
 //! # Device Profile (ZDP)
 
 mod active_endpoints;
+pub mod complex_descriptor;
 mod device_announce;
+mod end_device_bind;
 pub mod link_quality;
 mod match_descriptor;
 mod network_address;
 pub mod node_descriptor;
 pub mod power_descriptor;
+pub mod server_mask;
 mod simple_descriptor;
+mod system_server_discovery;
 
 pub use active_endpoints::{ActiveEndpointRequest, ActiveEndpointResponse};
+pub use complex_descriptor::{ComplexDescriptorRequest, ComplexDescriptorResponse};
 pub use device_announce::DeviceAnnounce;
+pub use end_device_bind::{EndDeviceBindRequest, EndDeviceBindResponse};
 pub use link_quality::{DeviceType, ManagementLinkQualityIndicatorResponse};
 pub use match_descriptor::{MatchDescriptorRequest, MatchDescriptorResponse};
 pub use network_address::{AddressResponse, IeeeAddressRequest, NetworkAddressRequest};
 pub use node_descriptor::{NodeDescriptor, NodeDescriptorRequest, NodeDescriptorResponse};
 pub use power_descriptor::{NodePowerDescriptor, PowerDescriptorRequest, PowerDescriptorResponse};
-pub use simple_descriptor::{SimpleDescriptor, SimpleDescriptorRequest, SimpleDescriptorResponse};
+pub use server_mask::{ServerFlags, ServerMask};
+pub use simple_descriptor::{
+    SimpleDescriptor, SimpleDescriptorRequest, SimpleDescriptorResponse, MAX_CLUSTERS,
+};
+pub use system_server_discovery::{SystemServerDiscoveryRequest, SystemServerDiscoveryResponse};
 
 use core::convert::TryFrom;
 
+use crate::application_service::ApplicationServiceHeader;
+use crate::common::profile_identifier::ProfileIdentifier;
 use crate::error::Error;
 use crate::pack::Pack;
 
@@ -43,6 +55,7 @@ extended_enum!(
     ActiveEndpointRequest => 0x0005,
     /// Find other devices that match the criteria
     MatchDescriptorRequest => 0x0006,
+    /// Request the complex descriptor of another device
     ComplexDescriptorRequest => 0x0010,
     UserDescriptorRequest => 0x0011,
     DiscoveryCacheRequest => 0x0012,
@@ -83,6 +96,67 @@ extended_enum!(
     ManagementNetworkUpdateRequest => 0x0038,
 );
 
+/// Device profile cluster of a APS frame
+///
+/// The cluster identifier of a response is the one of the request with the
+/// `RESPONSE` bit set.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ZdpCluster {
+    /// Request, or notification such as the device announcement
+    Request(ClusterIdentifier),
+    /// Response to a request
+    Response(ClusterIdentifier),
+}
+
+impl ZdpCluster {
+    /// Get the device profile cluster of a APS frame with `header`
+    ///
+    /// Returns `None` if the frame is not for the device profile or the
+    /// cluster is unknown.
+    pub fn from_header(header: &ApplicationServiceHeader) -> Option<Self> {
+        if header.profile != Some(u16::from(ProfileIdentifier::DeviceProfile)) {
+            return None;
+        }
+        header
+            .cluster
+            .and_then(|cluster| ZdpCluster::try_from(cluster).ok())
+    }
+
+    /// The cluster identifier, without the response bit
+    pub fn identifier(self) -> ClusterIdentifier {
+        match self {
+            ZdpCluster::Request(identifier) | ZdpCluster::Response(identifier) => identifier,
+        }
+    }
+
+    /// Check if this is a response
+    pub fn is_response(self) -> bool {
+        matches!(self, ZdpCluster::Response(_))
+    }
+}
+
+impl TryFrom<u16> for ZdpCluster {
+    type Error = Error;
+
+    fn try_from(value: u16) -> Result<Self, Self::Error> {
+        let identifier = ClusterIdentifier::try_from(value & !RESPONSE)?;
+        if (value & RESPONSE) == RESPONSE {
+            Ok(ZdpCluster::Response(identifier))
+        } else {
+            Ok(ZdpCluster::Request(identifier))
+        }
+    }
+}
+
+impl From<ZdpCluster> for u16 {
+    fn from(value: ZdpCluster) -> Self {
+        match value {
+            ZdpCluster::Request(identifier) => u16::from(identifier),
+            ZdpCluster::Response(identifier) => u16::from(identifier) | RESPONSE,
+        }
+    }
+}
+
 // 2.4.5 ZDP Enumeration Description
 extended_enum!(
     /// Response status codes
@@ -117,9 +191,13 @@ extended_enum!(
     NotAuthorised => 0x8d,
     /// The device could not complete the operation because the device binding table is full
     DeviceBindingTableFull => 0x8e,
+    /// The provided index is out of range
     InvalidIndex => 0x8f,
 );
 
+/// ZDP response status, the status byte that starts every ZDP response
+pub type ZdpStatus = Status;
+
 #[derive(Clone, Debug, PartialEq)]
 pub enum DeviceProfileMessage {
     /// Request the network address of another device
@@ -138,6 +216,10 @@ pub enum DeviceProfileMessage {
     PowerDescriptorRequest(PowerDescriptorRequest),
     /// Response to a power descriptor request
     PowerDescriptorResponse(PowerDescriptorResponse),
+    /// Request the complex descriptor of another device
+    ComplexDescriptorRequest(ComplexDescriptorRequest),
+    /// Response to a complex descriptor request
+    ComplexDescriptorResponse(ComplexDescriptorResponse),
     /// Request the endpoint simple desciptor of another device
     SimpleDescriptorRequest(SimpleDescriptorRequest),
     /// Response to a endpoint simple descriptor request
@@ -152,6 +234,14 @@ pub enum DeviceProfileMessage {
     MatchDescriptorResponse(MatchDescriptorResponse),
     /// Device announcement notification
     DeviceAnnounce(DeviceAnnounce),
+    /// Find devices providing system services, e.g. the trust center
+    SystemServerDiscoveryRequest(SystemServerDiscoveryRequest),
+    /// Response to a system server discovery request
+    SystemServerDiscoveryResponse(SystemServerDiscoveryResponse),
+    /// Request a binding from the coordinator
+    EndDeviceBindRequest(EndDeviceBindRequest),
+    /// Response to a end device bind request
+    EndDeviceBindResponse(EndDeviceBindResponse),
     /// Management link quality indicator (LQI) request
     /// Message contains the start index as u8
     ManagementLinkQualityIndicatorRequest(u8),
@@ -170,6 +260,8 @@ impl DeviceProfileMessage {
             DeviceProfileMessage::NodeDescriptorResponse(ref m) => m.pack(data),
             DeviceProfileMessage::PowerDescriptorRequest(ref m) => m.pack(data),
             DeviceProfileMessage::PowerDescriptorResponse(ref m) => m.pack(data),
+            DeviceProfileMessage::ComplexDescriptorRequest(ref m) => m.pack(data),
+            DeviceProfileMessage::ComplexDescriptorResponse(ref m) => m.pack(data),
             DeviceProfileMessage::SimpleDescriptorRequest(ref m) => m.pack(data),
             DeviceProfileMessage::SimpleDescriptorResponse(ref m) => m.pack(data),
             DeviceProfileMessage::ActiveEndpointRequest(ref m) => m.pack(data),
@@ -177,6 +269,10 @@ impl DeviceProfileMessage {
             DeviceProfileMessage::MatchDescriptorRequest(ref m) => m.pack(data),
             DeviceProfileMessage::MatchDescriptorResponse(ref m) => m.pack(data),
             DeviceProfileMessage::DeviceAnnounce(ref m) => m.pack(data),
+            DeviceProfileMessage::SystemServerDiscoveryRequest(ref m) => m.pack(data),
+            DeviceProfileMessage::SystemServerDiscoveryResponse(ref m) => m.pack(data),
+            DeviceProfileMessage::EndDeviceBindRequest(ref m) => m.pack(data),
+            DeviceProfileMessage::EndDeviceBindResponse(ref m) => m.pack(data),
             DeviceProfileMessage::ManagementLinkQualityIndicatorResponse(ref m) => m.pack(data),
             DeviceProfileMessage::ManagementLinkQualityIndicatorRequest(ref m) => {
                 data[0] = *m;
@@ -186,9 +282,9 @@ impl DeviceProfileMessage {
     }
 
     pub fn unpack(data: &[u8], cluster_identifier: u16) -> Result<(Self, usize), Error> {
-        let response = (cluster_identifier & RESPONSE) == RESPONSE;
-        let cluster_identifier = ClusterIdentifier::try_from(cluster_identifier & 0x7fff)?;
-        if response {
+        let cluster = ZdpCluster::try_from(cluster_identifier)?;
+        let cluster_identifier = cluster.identifier();
+        if cluster.is_response() {
             match cluster_identifier {
                 ClusterIdentifier::NetworkAddressRequest => {
                     let (rsp, used) = AddressResponse::unpack(&data)?;
@@ -206,6 +302,10 @@ impl DeviceProfileMessage {
                     let (rsp, used) = PowerDescriptorResponse::unpack(&data)?;
                     Ok((DeviceProfileMessage::PowerDescriptorResponse(rsp), used))
                 }
+                ClusterIdentifier::ComplexDescriptorRequest => {
+                    let (rsp, used) = ComplexDescriptorResponse::unpack(&data)?;
+                    Ok((DeviceProfileMessage::ComplexDescriptorResponse(rsp), used))
+                }
                 ClusterIdentifier::SimpleDescriptorRequest => {
                     let (rsp, used) = SimpleDescriptorResponse::unpack(&data)?;
                     Ok((DeviceProfileMessage::SimpleDescriptorResponse(rsp), used))
@@ -218,6 +318,17 @@ impl DeviceProfileMessage {
                     let (rsp, used) = MatchDescriptorResponse::unpack(&data)?;
                     Ok((DeviceProfileMessage::MatchDescriptorResponse(rsp), used))
                 }
+                ClusterIdentifier::SystemServerDiscoveryRequest => {
+                    let (rsp, used) = SystemServerDiscoveryResponse::unpack(&data)?;
+                    Ok((
+                        DeviceProfileMessage::SystemServerDiscoveryResponse(rsp),
+                        used,
+                    ))
+                }
+                ClusterIdentifier::EndDeviceBindRequest => {
+                    let (rsp, used) = EndDeviceBindResponse::unpack(&data)?;
+                    Ok((DeviceProfileMessage::EndDeviceBindResponse(rsp), used))
+                }
                 ClusterIdentifier::ManagementLinkQualityIndicatorRequest => {
                     let (rsp, used) = ManagementLinkQualityIndicatorResponse::unpack(&data)?;
                     Ok((
@@ -246,6 +357,10 @@ impl DeviceProfileMessage {
                     let (req, used) = PowerDescriptorRequest::unpack(&data)?;
                     Ok((DeviceProfileMessage::PowerDescriptorRequest(req), used))
                 }
+                ClusterIdentifier::ComplexDescriptorRequest => {
+                    let (req, used) = ComplexDescriptorRequest::unpack(&data)?;
+                    Ok((DeviceProfileMessage::ComplexDescriptorRequest(req), used))
+                }
                 ClusterIdentifier::SimpleDescriptorRequest => {
                     let (req, used) = SimpleDescriptorRequest::unpack(&data)?;
                     Ok((DeviceProfileMessage::SimpleDescriptorRequest(req), used))
@@ -262,6 +377,17 @@ impl DeviceProfileMessage {
                     let (req, used) = DeviceAnnounce::unpack(&data)?;
                     Ok((DeviceProfileMessage::DeviceAnnounce(req), used))
                 }
+                ClusterIdentifier::SystemServerDiscoveryRequest => {
+                    let (req, used) = SystemServerDiscoveryRequest::unpack(&data)?;
+                    Ok((
+                        DeviceProfileMessage::SystemServerDiscoveryRequest(req),
+                        used,
+                    ))
+                }
+                ClusterIdentifier::EndDeviceBindRequest => {
+                    let (req, used) = EndDeviceBindRequest::unpack(&data)?;
+                    Ok((DeviceProfileMessage::EndDeviceBindRequest(req), used))
+                }
                 ClusterIdentifier::ManagementLinkQualityIndicatorRequest => {
                     if data.is_empty() {
                         return Err(Error::WrongNumberOfBytes);
@@ -313,6 +439,75 @@ impl DeviceProfileFrame {
 mod tests {
     use super::*;
 
+    #[test]
+    fn zdp_status_codes() {
+        let codes = [
+            (0x00, ZdpStatus::Success),
+            (0x80, ZdpStatus::InvalidRequestType),
+            (0x81, ZdpStatus::DeviceNotFound),
+            (0x82, ZdpStatus::InvalidEndpoint),
+            (0x83, ZdpStatus::NotActive),
+            (0x84, ZdpStatus::NotSupported),
+            (0x85, ZdpStatus::Timeout),
+            (0x86, ZdpStatus::NoMatch),
+            (0x88, ZdpStatus::NoEntry),
+            (0x89, ZdpStatus::NoDescriptor),
+            (0x8a, ZdpStatus::InsufficientSpace),
+            (0x8b, ZdpStatus::NotPermitted),
+            (0x8c, ZdpStatus::TableFull),
+            (0x8d, ZdpStatus::NotAuthorised),
+            (0x8e, ZdpStatus::DeviceBindingTableFull),
+            (0x8f, ZdpStatus::InvalidIndex),
+        ];
+        for (code, status) in codes.iter() {
+            assert_eq!(ZdpStatus::try_from(*code), Ok(*status));
+            assert_eq!(u8::from(*status), *code);
+        }
+        assert_eq!(ZdpStatus::try_from(0x01), Err(Error::InvalidValue));
+        assert_eq!(ZdpStatus::try_from(0x87), Err(Error::InvalidValue));
+        assert_eq!(ZdpStatus::try_from(0x90), Err(Error::InvalidValue));
+    }
+
+    #[test]
+    fn zdp_cluster_device_announce() {
+        assert_eq!(
+            ZdpCluster::try_from(0x0013),
+            Ok(ZdpCluster::Request(ClusterIdentifier::DeviceAnnounce))
+        );
+        let cluster = ZdpCluster::try_from(0x8005).unwrap();
+        assert!(cluster.is_response());
+        assert_eq!(
+            cluster.identifier(),
+            ClusterIdentifier::ActiveEndpointRequest
+        );
+        assert_eq!(u16::from(cluster), 0x8005);
+        assert!(ZdpCluster::try_from(0x00ff).is_err());
+
+        // Device announce, broadcast to the device profile
+        let data = [
+            0x08, 0x00, 0x13, 0x00, 0x00, 0x00, 0x00, 0x81, 0x81, 0x6a, 0x6a, 0xc1, 0xe9, 0x1f,
+            0x00, 0x00, 0xff, 0x0f, 0x00, 0x8e,
+        ];
+        let (header, used) = ApplicationServiceHeader::unpack(&data).unwrap();
+        let cluster = ZdpCluster::from_header(&header).unwrap();
+        assert_eq!(
+            cluster,
+            ZdpCluster::Request(ClusterIdentifier::DeviceAnnounce)
+        );
+        let (frame, _) = DeviceProfileFrame::unpack(&data[used..], u16::from(cluster)).unwrap();
+        assert!(matches!(
+            frame.message,
+            DeviceProfileMessage::DeviceAnnounce(_)
+        ));
+
+        // Same cluster on the home automation profile
+        let mut data = data;
+        data[4] = 0x04;
+        data[5] = 0x01;
+        let (header, _) = ApplicationServiceHeader::unpack(&data).unwrap();
+        assert_eq!(ZdpCluster::from_header(&header), None);
+    }
+
     #[test]
     fn unpack_device_announce() {
         let cluster_identifier = 0x0013;
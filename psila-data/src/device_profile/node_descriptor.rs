@@ -2,6 +2,7 @@ use byteorder::{ByteOrder, LittleEndian};
 use core::convert::TryFrom;
 
 use crate::common::{address::NetworkAddress, capability_information::CapabilityInformation};
+pub use crate::device_profile::server_mask::{ServerFlags, ServerMask};
 use crate::device_profile::{DeviceType, Status};
 use crate::pack::{Pack, PackFixed};
 use crate::Error;
@@ -14,53 +15,6 @@ bitflags! {
     }
 }
 
-bitflags! {
-    pub struct ServerFlags: u8 {
-        const PRIMARY_TRUST_CENTER      = 0b0000_0001;
-        const BACKUP_TRUST_CENTER       = 0b0000_0010;
-        const PRIMARY_BINDING_TABLE     = 0b0000_0100;
-        const BACKUP_BINDING_TABLE      = 0b0000_1000;
-        const PRIMARY_DISCOVERY_CACHE   = 0b0001_0000;
-        const BACKUP_DISCOVERY_CACHE    = 0b0010_0000;
-        const NETWORK_MANAGER           = 0b0100_0000;
-    }
-}
-
-#[derive(Clone, Debug, PartialEq)]
-pub struct ServerMask {
-    pub flags: ServerFlags,
-    pub stack_complience_version: u8,
-}
-
-impl PackFixed<ServerMask, Error> for ServerMask {
-    fn pack(&self, data: &mut [u8]) -> Result<(), Error> {
-        if data.len() != 2 {
-            return Err(Error::WrongNumberOfBytes);
-        }
-        data[0] = self.flags.bits();
-        data[1] = (self.stack_complience_version & 0x7f) << 1;
-        Ok(())
-    }
-    fn unpack(data: &[u8]) -> Result<Self, Error> {
-        if data.len() != 2 {
-            return Err(Error::WrongNumberOfBytes);
-        }
-        Ok(Self {
-            flags: ServerFlags::from_bits_truncate(data[0]),
-            stack_complience_version: (data[1] & 0xfe) >> 1,
-        })
-    }
-}
-
-impl Default for ServerMask {
-    fn default() -> Self {
-        Self {
-            flags: ServerFlags::empty(),
-            stack_complience_version: 0,
-        }
-    }
-}
-
 bitflags! {
     pub struct DescriptorCapability: u8 {
         const EXTENDED_ACTIVE_END_POINT_LIST_AVAILABLE  = 0b0000_0001;
@@ -73,7 +27,7 @@ const USER_DESCRIPTOR: u8 = 0b0001_0000;
 
 // 2.3.2.3 Node Descriptor
 /// Node descriptor
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct NodeDescriptor {
     /// Node device type
     pub device_type: DeviceType,
@@ -268,85 +222,6 @@ impl Pack<NodeDescriptorResponse, Error> for NodeDescriptorResponse {
 mod tests {
     use super::*;
 
-    #[test]
-    fn unpack_server_mask() {
-        let data = [0x55, 0x42];
-        let server_mask = ServerMask::unpack(&data[..]).unwrap();
-        assert_eq!(
-            server_mask.flags,
-            ServerFlags::PRIMARY_TRUST_CENTER
-                | ServerFlags::PRIMARY_BINDING_TABLE
-                | ServerFlags::PRIMARY_DISCOVERY_CACHE
-                | ServerFlags::NETWORK_MANAGER
-        );
-        assert_eq!(server_mask.stack_complience_version, 0x21);
-
-        let data = [0x2a, 0xf0];
-        let server_mask = ServerMask::unpack(&data[..]).unwrap();
-        assert_eq!(
-            server_mask.flags,
-            ServerFlags::BACKUP_TRUST_CENTER
-                | ServerFlags::BACKUP_BINDING_TABLE
-                | ServerFlags::BACKUP_DISCOVERY_CACHE
-        );
-        assert_eq!(server_mask.stack_complience_version, 0x78);
-
-        let data = [0x00, 0x00];
-        let server_mask = ServerMask::unpack(&data[..]).unwrap();
-        assert_eq!(server_mask.flags, ServerFlags::empty());
-        assert_eq!(server_mask.stack_complience_version, 0x00);
-
-        let data = [0xff, 0xff];
-        let server_mask = ServerMask::unpack(&data[..]).unwrap();
-        assert_eq!(server_mask.flags, ServerFlags::all());
-        assert_eq!(server_mask.stack_complience_version, 0x7f);
-
-        let data = [0x7f, 0xfe];
-        let server_mask = ServerMask::unpack(&data[..]).unwrap();
-        assert_eq!(server_mask.flags, ServerFlags::all());
-        assert_eq!(server_mask.stack_complience_version, 0x7f);
-    }
-
-    #[test]
-    fn pack_server_mask() {
-        let server_mask = ServerMask {
-            flags: ServerFlags::PRIMARY_TRUST_CENTER
-                | ServerFlags::PRIMARY_BINDING_TABLE
-                | ServerFlags::PRIMARY_DISCOVERY_CACHE
-                | ServerFlags::NETWORK_MANAGER,
-            stack_complience_version: 0x21,
-        };
-        let mut data = [0u8; 2];
-        server_mask.pack(&mut data[..]).unwrap();
-        assert_eq!(data, [0x55, 0x42]);
-
-        let server_mask = ServerMask {
-            flags: ServerFlags::BACKUP_TRUST_CENTER
-                | ServerFlags::BACKUP_BINDING_TABLE
-                | ServerFlags::BACKUP_DISCOVERY_CACHE,
-            stack_complience_version: 0x78,
-        };
-        let mut data = [0u8; 2];
-        server_mask.pack(&mut data[..]).unwrap();
-        assert_eq!(data, [0x2a, 0xf0]);
-
-        let server_mask = ServerMask {
-            flags: ServerFlags::empty(),
-            stack_complience_version: 0,
-        };
-        let mut data = [0u8; 2];
-        server_mask.pack(&mut data[..]).unwrap();
-        assert_eq!(data, [0, 0]);
-
-        let server_mask = ServerMask {
-            flags: ServerFlags::all(),
-            stack_complience_version: 0xff,
-        };
-        let mut data = [0u8; 2];
-        server_mask.pack(&mut data[..]).unwrap();
-        assert_eq!(data, [0x7f, 0xfe]);
-    }
-
     #[test]
     fn unpack_node_descriptor_request() {
         let data = [0x96, 0x1f];
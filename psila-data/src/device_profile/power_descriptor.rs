@@ -17,16 +17,28 @@ extended_enum!(
 );
 
 extended_enum!(
-    /// Power source
+    /// Power source, encoded as a single bit of `PowerSources`
     PowerSource, u8,
     /// Constant (mains) power
-    MainsPower => 0x00,
+    MainsPower => 0x01,
     /// Rechargeable battery power
-    RechargeableBattery => 0x01,
+    RechargeableBattery => 0x02,
     /// Disposable battery power
-    DisposableBattery => 0x02,
+    DisposableBattery => 0x04,
 );
 
+bitflags! {
+    /// Available power sources
+    pub struct PowerSources: u8 {
+        /// Constant (mains) power
+        const MAINS_POWER          = 0b0000_0001;
+        /// Rechargeable battery power
+        const RECHARGEABLE_BATTERY = 0b0000_0010;
+        /// Disposable battery power
+        const DISPOSABLE_BATTERY   = 0b0000_0100;
+    }
+}
+
 extended_enum!(
     /// Power level for power source
     PowerLevel, u8,
@@ -42,11 +54,17 @@ extended_enum!(
 
 // 2.3.2.4 Node Power Descriptor
 /// Power descriptor for a node
-#[derive(Clone, Debug, PartialEq)]
+///
+/// Packed as four nibbles in two bytes, the power mode, the available power
+/// sources, the current power source and the current power source level.
+#[derive(Clone, Copy, Debug, PartialEq)]
 pub struct NodePowerDescriptor {
     pub mode: PowerMode,
-    pub available_sources: PowerSource,
+    /// Power sources available to the node
+    pub available_sources: PowerSources,
+    /// Power source currently used by the node
     pub current_sources: PowerSource,
+    /// Level of the current power source
     pub level: PowerLevel,
 }
 
@@ -55,7 +73,7 @@ impl PackFixed<NodePowerDescriptor, Error> for NodePowerDescriptor {
         if data.len() != 2 {
             return Err(Error::WrongNumberOfBytes);
         }
-        data[0] = u8::from(self.mode) & 0x0f | (u8::from(self.available_sources) & 0x0f) << 4;
+        data[0] = u8::from(self.mode) & 0x0f | (self.available_sources.bits() & 0x0f) << 4;
         data[1] = u8::from(self.current_sources) & 0x0f | (u8::from(self.level) & 0x0f) << 4;
         Ok(())
     }
@@ -64,7 +82,8 @@ impl PackFixed<NodePowerDescriptor, Error> for NodePowerDescriptor {
             return Err(Error::WrongNumberOfBytes);
         }
         let mode = PowerMode::try_from(data[0] & 0x0f)?;
-        let available_sources = PowerSource::try_from((data[0] >> 4) & 0x0f)?;
+        let available_sources =
+            PowerSources::from_bits((data[0] >> 4) & 0x0f).ok_or(Error::InvalidValue)?;
         let current_sources = PowerSource::try_from(data[1] & 0x0f)?;
         let level = PowerLevel::try_from((data[1] >> 4) & 0x0f)?;
         Ok(Self {
@@ -80,7 +99,7 @@ impl Default for NodePowerDescriptor {
     fn default() -> Self {
         Self {
             mode: PowerMode::OnWhenIdle,
-            available_sources: PowerSource::MainsPower,
+            available_sources: PowerSources::MAINS_POWER,
             current_sources: PowerSource::MainsPower,
             level: PowerLevel::Level100Percent,
         }
@@ -155,6 +174,9 @@ impl Pack<PowerDescriptorResponse, Error> for PowerDescriptorResponse {
         let status = Status::try_from(data[0])?;
         let address = NetworkAddress::unpack(&data[1..3])?;
         let (descriptor, size) = if status == Status::Success {
+            if data.len() < 5 {
+                return Err(Error::WrongNumberOfBytes);
+            }
             (NodePowerDescriptor::unpack(&data[3..5])?, 5)
         } else {
             (NodePowerDescriptor::default(), 3)
@@ -190,15 +212,62 @@ mod tests {
         assert_eq!(req.status, Status::Success);
         assert_eq!(req.address, 0x1f96);
         assert_eq!(req.descriptor.mode, PowerMode::OnWhenIdle);
+        assert_eq!(req.descriptor.available_sources, PowerSources::MAINS_POWER);
+        assert_eq!(req.descriptor.current_sources, PowerSource::MainsPower);
+        assert_eq!(req.descriptor.level, PowerLevel::Level100Percent);
+    }
+
+    #[test]
+    fn unpack_power_descriptor_response_battery() {
+        // Wakes up when stimulated, disposable battery at 66%
+        let data = [0x00, 0x34, 0x12, 0x42, 0x84];
+        let (rsp, used) = PowerDescriptorResponse::unpack(&data[..]).unwrap();
+        assert_eq!(used, 5);
+        assert_eq!(rsp.status, Status::Success);
+        assert_eq!(rsp.address, 0x1234);
+        assert_eq!(rsp.descriptor.mode, PowerMode::WhenStimulated);
         assert_eq!(
-            req.descriptor.available_sources,
-            PowerSource::RechargeableBattery
+            rsp.descriptor.available_sources,
+            PowerSources::DISPOSABLE_BATTERY
         );
         assert_eq!(
-            req.descriptor.current_sources,
-            PowerSource::RechargeableBattery
+            rsp.descriptor.current_sources,
+            PowerSource::DisposableBattery
+        );
+        assert_eq!(rsp.descriptor.level, PowerLevel::Level66Percent);
+
+        let mut packed = [0u8; 5];
+        assert_eq!(rsp.pack(&mut packed[..]).unwrap(), 5);
+        assert_eq!(packed, data);
+
+        // Successful response without the descriptor
+        assert_eq!(
+            PowerDescriptorResponse::unpack(&data[..4]).err(),
+            Some(Error::WrongNumberOfBytes)
+        );
+
+        // Mains powered with a rechargeable battery as backup
+        let descriptor = NodePowerDescriptor::unpack(&[0x30, 0xc1]).unwrap();
+        assert_eq!(
+            descriptor.available_sources,
+            PowerSources::MAINS_POWER | PowerSources::RECHARGEABLE_BATTERY
+        );
+        assert_eq!(descriptor.current_sources, PowerSource::MainsPower);
+        // Reserved power source
+        assert_eq!(
+            NodePowerDescriptor::unpack(&[0x80, 0xc1]),
+            Err(Error::InvalidValue)
+        );
+        // No current power source
+        assert_eq!(
+            NodePowerDescriptor::unpack(&[0x30, 0xc0]),
+            Err(Error::InvalidValue)
+        );
+        // More than one current power source
+        assert_eq!(
+            NodePowerDescriptor::unpack(&[0x30, 0xc3]),
+            Err(Error::InvalidValue)
         );
-        assert_eq!(req.descriptor.level, PowerLevel::Level100Percent);
     }
 
     #[test]
@@ -214,7 +283,7 @@ mod tests {
     fn pack_node_descriptor_response_success() {
         let descriptor = NodePowerDescriptor {
             mode: PowerMode::OnWhenIdle,
-            available_sources: PowerSource::DisposableBattery,
+            available_sources: PowerSources::RECHARGEABLE_BATTERY,
             current_sources: PowerSource::MainsPower,
             level: PowerLevel::Critical,
         };
@@ -226,7 +295,7 @@ mod tests {
         let mut data = [0u8; 5];
         let used = response.pack(&mut data[..]).unwrap();
         assert_eq!(used, 5);
-        assert_eq!(data, [0x00, 0x65, 0x87, 0x20, 0x00]);
+        assert_eq!(data, [0x00, 0x65, 0x87, 0x20, 0x01]);
     }
 
     #[test]
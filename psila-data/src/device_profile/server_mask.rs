@@ -0,0 +1,236 @@
+//! # Server mask
+//!
+//! Services provided by a device, shared by the node descriptor and the
+//! system server discovery.
+
+use crate::pack::PackFixed;
+use crate::Error;
+
+bitflags! {
+    /// Services provided by a device
+    pub struct ServerFlags: u8 {
+        const PRIMARY_TRUST_CENTER      = 0b0000_0001;
+        const BACKUP_TRUST_CENTER       = 0b0000_0010;
+        const PRIMARY_BINDING_TABLE     = 0b0000_0100;
+        const BACKUP_BINDING_TABLE      = 0b0000_1000;
+        const PRIMARY_DISCOVERY_CACHE   = 0b0001_0000;
+        const BACKUP_DISCOVERY_CACHE    = 0b0010_0000;
+        const NETWORK_MANAGER           = 0b0100_0000;
+    }
+}
+
+// 2.3.2.3.10 Server Mask Field
+/// Server mask
+///
+/// Used by the node descriptor and the system server discovery. The lower
+/// byte carries the services provided by the device, the upper seven bits
+/// the stack compliance revision.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ServerMask {
+    pub flags: ServerFlags,
+    pub stack_complience_version: u8,
+}
+
+impl ServerMask {
+    /// Create a server mask for a device providing the services in `flags`,
+    /// compliant with stack revision `revision`
+    pub fn new(flags: ServerFlags, revision: u8) -> Self {
+        Self {
+            flags,
+            stack_complience_version: revision & 0x7f,
+        }
+    }
+
+    /// Device is the primary trust center
+    pub fn is_primary_trust_center(&self) -> bool {
+        self.flags.contains(ServerFlags::PRIMARY_TRUST_CENTER)
+    }
+
+    /// Device is a backup trust center
+    pub fn is_backup_trust_center(&self) -> bool {
+        self.flags.contains(ServerFlags::BACKUP_TRUST_CENTER)
+    }
+
+    /// Device holds the primary binding table cache
+    pub fn is_primary_binding_table_cache(&self) -> bool {
+        self.flags.contains(ServerFlags::PRIMARY_BINDING_TABLE)
+    }
+
+    /// Device holds a backup binding table cache
+    pub fn is_backup_binding_table_cache(&self) -> bool {
+        self.flags.contains(ServerFlags::BACKUP_BINDING_TABLE)
+    }
+
+    /// Device holds the primary discovery cache
+    pub fn is_primary_discovery_cache(&self) -> bool {
+        self.flags.contains(ServerFlags::PRIMARY_DISCOVERY_CACHE)
+    }
+
+    /// Device holds a backup discovery cache
+    pub fn is_backup_discovery_cache(&self) -> bool {
+        self.flags.contains(ServerFlags::BACKUP_DISCOVERY_CACHE)
+    }
+
+    /// Device is the network manager
+    pub fn is_network_manager(&self) -> bool {
+        self.flags.contains(ServerFlags::NETWORK_MANAGER)
+    }
+
+    /// Stack compliance revision of the device, zero for devices older than
+    /// revision 21
+    pub fn stack_compliance_revision(&self) -> u8 {
+        self.stack_complience_version
+    }
+}
+
+impl From<u16> for ServerMask {
+    fn from(value: u16) -> Self {
+        Self {
+            flags: ServerFlags::from_bits_truncate(value as u8),
+            stack_complience_version: (value >> 9) as u8,
+        }
+    }
+}
+
+impl From<ServerMask> for u16 {
+    fn from(mask: ServerMask) -> Self {
+        u16::from(mask.flags.bits()) | u16::from(mask.stack_complience_version & 0x7f) << 9
+    }
+}
+
+impl PackFixed<ServerMask, Error> for ServerMask {
+    fn pack(&self, data: &mut [u8]) -> Result<(), Error> {
+        if data.len() != 2 {
+            return Err(Error::WrongNumberOfBytes);
+        }
+        data[0] = self.flags.bits();
+        data[1] = (self.stack_complience_version & 0x7f) << 1;
+        Ok(())
+    }
+    fn unpack(data: &[u8]) -> Result<Self, Error> {
+        if data.len() != 2 {
+            return Err(Error::WrongNumberOfBytes);
+        }
+        Ok(Self {
+            flags: ServerFlags::from_bits_truncate(data[0]),
+            stack_complience_version: (data[1] & 0xfe) >> 1,
+        })
+    }
+}
+
+impl Default for ServerMask {
+    fn default() -> Self {
+        Self {
+            flags: ServerFlags::empty(),
+            stack_complience_version: 0,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unpack_server_mask() {
+        let data = [0x55, 0x42];
+        let server_mask = ServerMask::unpack(&data[..]).unwrap();
+        assert_eq!(
+            server_mask.flags,
+            ServerFlags::PRIMARY_TRUST_CENTER
+                | ServerFlags::PRIMARY_BINDING_TABLE
+                | ServerFlags::PRIMARY_DISCOVERY_CACHE
+                | ServerFlags::NETWORK_MANAGER
+        );
+        assert_eq!(server_mask.stack_complience_version, 0x21);
+
+        let data = [0x2a, 0xf0];
+        let server_mask = ServerMask::unpack(&data[..]).unwrap();
+        assert_eq!(
+            server_mask.flags,
+            ServerFlags::BACKUP_TRUST_CENTER
+                | ServerFlags::BACKUP_BINDING_TABLE
+                | ServerFlags::BACKUP_DISCOVERY_CACHE
+        );
+        assert_eq!(server_mask.stack_complience_version, 0x78);
+
+        let data = [0x00, 0x00];
+        let server_mask = ServerMask::unpack(&data[..]).unwrap();
+        assert_eq!(server_mask.flags, ServerFlags::empty());
+        assert_eq!(server_mask.stack_complience_version, 0x00);
+
+        let data = [0xff, 0xff];
+        let server_mask = ServerMask::unpack(&data[..]).unwrap();
+        assert_eq!(server_mask.flags, ServerFlags::all());
+        assert_eq!(server_mask.stack_complience_version, 0x7f);
+
+        let data = [0x7f, 0xfe];
+        let server_mask = ServerMask::unpack(&data[..]).unwrap();
+        assert_eq!(server_mask.flags, ServerFlags::all());
+        assert_eq!(server_mask.stack_complience_version, 0x7f);
+    }
+
+    #[test]
+    fn pack_server_mask() {
+        let server_mask = ServerMask {
+            flags: ServerFlags::PRIMARY_TRUST_CENTER
+                | ServerFlags::PRIMARY_BINDING_TABLE
+                | ServerFlags::PRIMARY_DISCOVERY_CACHE
+                | ServerFlags::NETWORK_MANAGER,
+            stack_complience_version: 0x21,
+        };
+        let mut data = [0u8; 2];
+        server_mask.pack(&mut data[..]).unwrap();
+        assert_eq!(data, [0x55, 0x42]);
+
+        let server_mask = ServerMask {
+            flags: ServerFlags::BACKUP_TRUST_CENTER
+                | ServerFlags::BACKUP_BINDING_TABLE
+                | ServerFlags::BACKUP_DISCOVERY_CACHE,
+            stack_complience_version: 0x78,
+        };
+        let mut data = [0u8; 2];
+        server_mask.pack(&mut data[..]).unwrap();
+        assert_eq!(data, [0x2a, 0xf0]);
+
+        let server_mask = ServerMask {
+            flags: ServerFlags::empty(),
+            stack_complience_version: 0,
+        };
+        let mut data = [0u8; 2];
+        server_mask.pack(&mut data[..]).unwrap();
+        assert_eq!(data, [0, 0]);
+
+        let server_mask = ServerMask {
+            flags: ServerFlags::all(),
+            stack_complience_version: 0xff,
+        };
+        let mut data = [0u8; 2];
+        server_mask.pack(&mut data[..]).unwrap();
+        assert_eq!(data, [0x7f, 0xfe]);
+    }
+
+    #[test]
+    fn coordinator_server_mask() {
+        // Coordinator acting trust center and network manager, revision 22
+        let server_mask = ServerMask::new(
+            ServerFlags::PRIMARY_TRUST_CENTER | ServerFlags::NETWORK_MANAGER,
+            22,
+        );
+        assert!(server_mask.is_primary_trust_center());
+        assert!(!server_mask.is_backup_trust_center());
+        assert!(!server_mask.is_primary_binding_table_cache());
+        assert!(!server_mask.is_backup_binding_table_cache());
+        assert!(!server_mask.is_primary_discovery_cache());
+        assert!(!server_mask.is_backup_discovery_cache());
+        assert!(server_mask.is_network_manager());
+        assert_eq!(server_mask.stack_compliance_revision(), 22);
+        assert_eq!(u16::from(server_mask), 0x2c41);
+        assert_eq!(ServerMask::from(0x2c41), server_mask);
+
+        let mut data = [0u8; 2];
+        server_mask.pack(&mut data[..]).unwrap();
+        assert_eq!(data, [0x41, 0x2c]);
+        assert_eq!(ServerMask::unpack(&data[..]).unwrap(), server_mask);
+    }
+}
@@ -6,9 +6,12 @@ use crate::device_profile::Status;
 use crate::pack::{Pack, PackFixed};
 use crate::Error;
 
+/// Maximum number of input or output clusters in a simple descriptor
+pub const MAX_CLUSTERS: usize = 32;
+
 // 2.3.2.5 Simple Descriptor
 /// Simple descriptor for a node endpoint
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug)]
 pub struct SimpleDescriptor {
     pub endpoint: u8,
     pub profile: u16,
@@ -16,10 +19,10 @@ pub struct SimpleDescriptor {
     pub device_version: u8,
     /// Server clusters implemented by the device
     pub input_cluster_count: u8,
-    pub input_clusters: [u16; 32],
+    pub input_clusters: [u16; MAX_CLUSTERS],
     /// Client clusters implemented by the device
     pub output_cluster_count: u8,
-    pub output_clusters: [u16; 32],
+    pub output_clusters: [u16; MAX_CLUSTERS],
 }
 
 impl SimpleDescriptor {
@@ -31,19 +34,19 @@ impl SimpleDescriptor {
         input_clusters: &[u16],
         output_clusters: &[u16],
     ) -> Self {
-        let icc = if input_clusters.len() > 32 {
-            32
+        let icc = if input_clusters.len() > MAX_CLUSTERS {
+            MAX_CLUSTERS
         } else {
             input_clusters.len()
         };
-        let occ = if output_clusters.len() > 32 {
-            32
+        let occ = if output_clusters.len() > MAX_CLUSTERS {
+            MAX_CLUSTERS
         } else {
             output_clusters.len()
         };
-        let mut ic = [0u16; 32];
+        let mut ic = [0u16; MAX_CLUSTERS];
         ic[..icc].copy_from_slice(&input_clusters[..icc]);
-        let mut oc = [0u16; 32];
+        let mut oc = [0u16; MAX_CLUSTERS];
         oc[..occ].copy_from_slice(&output_clusters[..occ]);
         Self {
             endpoint,
@@ -67,6 +70,19 @@ impl SimpleDescriptor {
     }
 }
 
+/// Descriptors are equal when the used part of the cluster lists are equal,
+/// any trailing entries beyond the cluster counts are ignored
+impl PartialEq for SimpleDescriptor {
+    fn eq(&self, other: &Self) -> bool {
+        self.endpoint == other.endpoint
+            && self.profile == other.profile
+            && self.device == other.device
+            && self.device_version == other.device_version
+            && self.input_clusters() == other.input_clusters()
+            && self.output_clusters() == other.output_clusters()
+    }
+}
+
 impl Pack<SimpleDescriptor, Error> for SimpleDescriptor {
     fn pack(&self, data: &mut [u8]) -> Result<usize, Error> {
         let size =
@@ -104,8 +120,15 @@ impl Pack<SimpleDescriptor, Error> for SimpleDescriptor {
         let device_version = data[5] & 0x0f;
         let input_cluster_count = data[6];
         let count = input_cluster_count as usize;
+        if count > MAX_CLUSTERS {
+            return Err(Error::NotEnoughSpace);
+        }
+        // The input clusters are followed by the output cluster count
+        if data.len() < 8 + (count * 2) {
+            return Err(Error::WrongNumberOfBytes);
+        }
         let mut offset = 7;
-        let mut input_clusters = [0u16; 32];
+        let mut input_clusters = [0u16; MAX_CLUSTERS];
         for cluster in &mut input_clusters[..count] {
             *cluster = LittleEndian::read_u16(&data[offset..offset + 2]);
             offset += 2;
@@ -113,7 +136,13 @@ impl Pack<SimpleDescriptor, Error> for SimpleDescriptor {
         let output_cluster_count = data[offset];
         let count = output_cluster_count as usize;
         offset += 1;
-        let mut output_clusters = [0u16; 32];
+        if count > MAX_CLUSTERS {
+            return Err(Error::NotEnoughSpace);
+        }
+        if data.len() < offset + (count * 2) {
+            return Err(Error::WrongNumberOfBytes);
+        }
+        let mut output_clusters = [0u16; MAX_CLUSTERS];
         for cluster in &mut output_clusters[..count] {
             *cluster = LittleEndian::read_u16(&data[offset..offset + 2]);
             offset += 2;
@@ -142,9 +171,9 @@ impl Default for SimpleDescriptor {
             device: 0,
             device_version: 0,
             input_cluster_count: 0,
-            input_clusters: [0; 32],
+            input_clusters: [0; MAX_CLUSTERS],
             output_cluster_count: 0,
-            output_clusters: [0; 32],
+            output_clusters: [0; MAX_CLUSTERS],
         }
     }
 }
@@ -281,6 +310,59 @@ mod tests {
         assert_eq!(clusters[3], 0x0ff0);
     }
 
+    #[test]
+    fn unpack_simple_descriptor_short() {
+        // Three input clusters, but only one present
+        let data = [0x01, 0x04, 0x01, 0x00, 0x01, 0x00, 0x03, 0x00, 0x00];
+        assert_eq!(
+            SimpleDescriptor::unpack(&data[..]),
+            Err(Error::WrongNumberOfBytes)
+        );
+        // Output cluster count missing
+        let data = [0x01, 0x04, 0x01, 0x00, 0x01, 0x00, 0x01, 0x00, 0x00];
+        assert_eq!(
+            SimpleDescriptor::unpack(&data[..]),
+            Err(Error::WrongNumberOfBytes)
+        );
+        // Two output clusters, but only one present
+        let data = [0x01, 0x04, 0x01, 0x00, 0x01, 0x00, 0x00, 0x02, 0x06, 0x00];
+        assert_eq!(
+            SimpleDescriptor::unpack(&data[..]),
+            Err(Error::WrongNumberOfBytes)
+        );
+        // More clusters than can be stored
+        let data = [0x01, 0x04, 0x01, 0x00, 0x01, 0x00, 0xff, 0x00, 0x00];
+        assert_eq!(
+            SimpleDescriptor::unpack(&data[..]),
+            Err(Error::NotEnoughSpace)
+        );
+        // The descriptor length of the response exceeds the clusters
+        let data = [
+            0x00, 0x96, 0x1f, 0x0a, 0x01, 0x04, 0x01, 0x00, 0x01, 0x00, 0x02, 0x00, 0x00, 0x00,
+        ];
+        assert_eq!(
+            SimpleDescriptorResponse::unpack(&data[..]),
+            Err(Error::WrongNumberOfBytes)
+        );
+    }
+
+    #[test]
+    fn simple_descriptor_equality() {
+        let first = SimpleDescriptor::new(0x01, 0x0104, 0x0100, 0x01, &[0x0000, 0x0006], &[0x0019]);
+        let second =
+            SimpleDescriptor::new(0x01, 0x0104, 0x0100, 0x01, &[0x0000, 0x0006], &[0x0019]);
+        assert_eq!(first, second);
+        assert_eq!(first, first.clone());
+
+        // Entries beyond the cluster count are not compared
+        let mut stale = second.clone();
+        stale.input_clusters[5] = 0x1234;
+        assert_eq!(first, stale);
+
+        let other = SimpleDescriptor::new(0x01, 0x0104, 0x0100, 0x01, &[0x0000], &[0x0019]);
+        assert_ne!(first, other);
+    }
+
     #[test]
     fn unpack_simple_descriptor_request() {
         let data = [0x96, 0x1f, 0x01];
@@ -0,0 +1,134 @@
+use core::convert::TryFrom;
+
+use crate::device_profile::server_mask::{ServerFlags, ServerMask};
+use crate::device_profile::Status;
+use crate::pack::{Pack, PackFixed};
+use crate::Error;
+
+// 2.4.3.1.13 System_Server_Discovery_req
+/// System server discovery request
+/// Broadcast to find devices providing the services in the server mask,
+/// e.g. the trust center or network manager
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SystemServerDiscoveryRequest {
+    pub server_mask: ServerMask,
+}
+
+impl SystemServerDiscoveryRequest {
+    /// Create a request for the servers in `flags`
+    pub fn new(flags: ServerFlags) -> Self {
+        Self {
+            server_mask: ServerMask::new(flags, 0),
+        }
+    }
+
+    /// Build the response of a device with `server_mask`
+    ///
+    /// Only devices providing any of the requested services respond, the
+    /// response carries the requested services provided by the device.
+    pub fn response(&self, server_mask: ServerMask) -> Option<SystemServerDiscoveryResponse> {
+        let flags = self.server_mask.flags & server_mask.flags;
+        if flags.is_empty() {
+            None
+        } else {
+            Some(SystemServerDiscoveryResponse {
+                status: Status::Success,
+                server_mask: ServerMask::new(flags, server_mask.stack_compliance_revision()),
+            })
+        }
+    }
+}
+
+impl Pack<SystemServerDiscoveryRequest, Error> for SystemServerDiscoveryRequest {
+    fn pack(&self, data: &mut [u8]) -> Result<usize, Error> {
+        if data.len() < 2 {
+            return Err(Error::WrongNumberOfBytes);
+        }
+        self.server_mask.pack(&mut data[0..2])?;
+        Ok(2)
+    }
+
+    fn unpack(data: &[u8]) -> Result<(Self, usize), Error> {
+        if data.len() < 2 {
+            return Err(Error::WrongNumberOfBytes);
+        }
+        let server_mask = ServerMask::unpack(&data[0..2])?;
+        Ok((Self { server_mask }, 2))
+    }
+}
+
+// 2.4.4.2.10 System_Server_Discovery_rsp
+/// System server discovery response
+/// Response to a system server discovery request, with the requested
+/// services provided by the responding device
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SystemServerDiscoveryResponse {
+    pub status: Status,
+    pub server_mask: ServerMask,
+}
+
+impl Pack<SystemServerDiscoveryResponse, Error> for SystemServerDiscoveryResponse {
+    fn pack(&self, data: &mut [u8]) -> Result<usize, Error> {
+        if data.len() < 3 {
+            return Err(Error::WrongNumberOfBytes);
+        }
+        data[0] = u8::from(self.status);
+        self.server_mask.pack(&mut data[1..3])?;
+        Ok(3)
+    }
+
+    fn unpack(data: &[u8]) -> Result<(Self, usize), Error> {
+        if data.len() < 3 {
+            return Err(Error::WrongNumberOfBytes);
+        }
+        let status = Status::try_from(data[0])?;
+        let server_mask = ServerMask::unpack(&data[1..3])?;
+        Ok((
+            Self {
+                status,
+                server_mask,
+            },
+            3,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn discover_primary_trust_center() {
+        let request = SystemServerDiscoveryRequest::new(ServerFlags::PRIMARY_TRUST_CENTER);
+        let mut data = [0u8; 3];
+        let used = request.pack(&mut data).unwrap();
+        assert_eq!(data[..used], [0x01, 0x00]);
+        let (unpacked, used) = SystemServerDiscoveryRequest::unpack(&data[..2]).unwrap();
+        assert_eq!(used, 2);
+        assert_eq!(unpacked, request);
+
+        // Coordinator, trust center and network manager, stack revision 22
+        let coordinator = ServerMask {
+            flags: ServerFlags::PRIMARY_TRUST_CENTER | ServerFlags::NETWORK_MANAGER,
+            stack_complience_version: 22,
+        };
+        let response = request.response(coordinator).unwrap();
+        assert_eq!(response.status, Status::Success);
+        assert_eq!(
+            response.server_mask.flags,
+            ServerFlags::PRIMARY_TRUST_CENTER
+        );
+        let used = response.pack(&mut data).unwrap();
+        assert_eq!(data[..used], [0x00, 0x01, 0x2c]);
+        let (unpacked, used) = SystemServerDiscoveryResponse::unpack(&data).unwrap();
+        assert_eq!(used, 3);
+        assert_eq!(unpacked, response);
+
+        // Devices not providing the service don't respond
+        let router = ServerMask {
+            flags: ServerFlags::PRIMARY_BINDING_TABLE,
+            stack_complience_version: 22,
+        };
+        assert_eq!(request.response(router), None);
+    }
+}
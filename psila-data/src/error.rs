@@ -25,6 +25,8 @@ pub enum Error {
     UnknownNetworkCommand,
     /// The delivery mode is unknown
     UnknownDeliveryMode,
+    /// The delivery mode is reserved for the frame type
+    ReservedDeliveryMode,
     /// The security level is unknown
     UnknownSecurityLevel,
     /// The Key indetifier is unknown
@@ -37,6 +39,14 @@ pub enum Error {
     UnknownClusterIdentifier,
     /// The attribute value is unsupported
     UnsupportedAttributeValue,
+    /// The attribute data type is unknown, the value is provided
+    UnknownDataType(u8),
+    /// The attribute value has another data type than the requested
+    WrongDataType,
+    /// The outgoing frame counter is exhausted, a new key is needed
+    FrameCounterExhausted,
+    /// No key is available for the secured frame
+    MissingKey,
     /// A crypto error has occurred
     CryptoError(psila_crypto::Error),
 }
@@ -46,3 +56,17 @@ impl From<psila_crypto::Error> for Error {
         Self::CryptoError(error)
     }
 }
+
+impl From<ieee802154::mac::DecodeError> for Error {
+    fn from(error: ieee802154::mac::DecodeError) -> Self {
+        use ieee802154::mac::DecodeError;
+        match error {
+            DecodeError::NotEnoughBytes => Self::WrongNumberOfBytes,
+            DecodeError::InvalidFrameType(_) => Self::UnknownFrameType,
+            DecodeError::SecurityNotSupported => Self::NotImplemented,
+            DecodeError::InvalidAddressMode(_)
+            | DecodeError::InvalidFrameVersion(_)
+            | DecodeError::InvalidValue => Self::InvalidValue,
+        }
+    }
+}
@@ -0,0 +1,309 @@
+use core::convert::TryFrom;
+
+use byteorder::{ByteOrder, LittleEndian};
+
+use crate::common::address::{PanIdentifier, SHORT_ADDRESS_SIZE};
+use crate::common::key::{Key, KEY_SIZE};
+use crate::error::Error;
+use crate::green_power::{KeyType, SecurityLevel};
+use crate::pack::{Pack, PackFixed};
+use crate::security::{FrameCounter, FRAME_COUNTER_SIZE};
+
+const PAN_IDENTIFIER_PRESENT: u8 = 0b0000_0001;
+const KEY_PRESENT: u8 = 0b0000_0010;
+const KEY_ENCRYPTION: u8 = 0b0000_0100;
+const SECURITY_LEVEL_MASK: u8 = 0b0001_1000;
+const KEY_TYPE_MASK: u8 = 0b1110_0000;
+
+const CHANNEL_MASK: u8 = 0b0000_1111;
+const BASIC: u8 = 0b0001_0000;
+
+/// Parameters of a encrypted GPD key
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct KeyEncryption {
+    /// Message integrity code of the encrypted key
+    pub mic: u32,
+    /// Outgoing frame counter used when encrypting the key
+    pub frame_counter: FrameCounter,
+}
+
+/// Commissioning reply
+///
+/// Sent to the GPD by the proxy or sink to finish commissioning. Carries
+/// the PAN identifier and the key which the GPD shall use.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CommissioningReply {
+    /// Security level to use
+    pub security_level: SecurityLevel,
+    /// Type of the key
+    pub key_type: KeyType,
+    /// PAN identifier of the network
+    pub pan_identifier: Option<PanIdentifier>,
+    /// GPD key, encrypted if `key_encryption` is set
+    pub key: Option<Key>,
+    /// Encryption parameters of the GPD key
+    pub key_encryption: Option<KeyEncryption>,
+}
+
+impl CommissioningReply {
+    /// Create a commissioning reply without key
+    pub fn new(
+        pan_identifier: Option<PanIdentifier>,
+        security_level: SecurityLevel,
+        key_type: KeyType,
+    ) -> Self {
+        Self {
+            security_level,
+            key_type,
+            pan_identifier,
+            key: None,
+            key_encryption: None,
+        }
+    }
+
+    /// Add a key sent in the clear
+    pub fn with_key(mut self, key: Key) -> Self {
+        self.key = Some(key);
+        self.key_encryption = None;
+        self
+    }
+
+    /// Add a encrypted key with its MIC and the frame counter used for
+    /// encrypting it
+    pub fn with_encrypted_key(mut self, key: Key, mic: u32, frame_counter: FrameCounter) -> Self {
+        self.key = Some(key);
+        self.key_encryption = Some(KeyEncryption { mic, frame_counter });
+        self
+    }
+
+    fn options(&self) -> u8 {
+        let mut options = (u8::from(self.security_level) << 3) & SECURITY_LEVEL_MASK;
+        options |= (u8::from(self.key_type) << 5) & KEY_TYPE_MASK;
+        if self.pan_identifier.is_some() {
+            options |= PAN_IDENTIFIER_PRESENT;
+        }
+        if self.key.is_some() {
+            options |= KEY_PRESENT;
+            if self.key_encryption.is_some() {
+                options |= KEY_ENCRYPTION;
+            }
+        }
+        options
+    }
+
+    fn packed_length(&self) -> usize {
+        let mut length = 1;
+        if self.pan_identifier.is_some() {
+            length += SHORT_ADDRESS_SIZE;
+        }
+        if self.key.is_some() {
+            length += KEY_SIZE;
+            if self.key_encryption.is_some() {
+                length += 4 + FRAME_COUNTER_SIZE;
+            }
+        }
+        length
+    }
+}
+
+impl Pack<CommissioningReply, Error> for CommissioningReply {
+    fn pack(&self, data: &mut [u8]) -> Result<usize, Error> {
+        if data.len() < self.packed_length() {
+            return Err(Error::NotEnoughSpace);
+        }
+        data[0] = self.options();
+        let mut offset = 1;
+        if let Some(pan_identifier) = self.pan_identifier {
+            pan_identifier.pack(&mut data[offset..offset + SHORT_ADDRESS_SIZE])?;
+            offset += SHORT_ADDRESS_SIZE;
+        }
+        if let Some(key) = self.key {
+            key.pack(&mut data[offset..offset + KEY_SIZE])?;
+            offset += KEY_SIZE;
+            if let Some(encryption) = self.key_encryption {
+                LittleEndian::write_u32(&mut data[offset..offset + 4], encryption.mic);
+                offset += 4;
+                encryption
+                    .frame_counter
+                    .pack(&mut data[offset..offset + FRAME_COUNTER_SIZE])?;
+                offset += FRAME_COUNTER_SIZE;
+            }
+        }
+        Ok(offset)
+    }
+
+    fn unpack(data: &[u8]) -> Result<(Self, usize), Error> {
+        if data.is_empty() {
+            return Err(Error::WrongNumberOfBytes);
+        }
+        let options = data[0];
+        let security_level = SecurityLevel::try_from((options & SECURITY_LEVEL_MASK) >> 3)?;
+        let key_type = KeyType::try_from((options & KEY_TYPE_MASK) >> 5)?;
+        let mut offset = 1;
+        let pan_identifier = if options & PAN_IDENTIFIER_PRESENT == PAN_IDENTIFIER_PRESENT {
+            if data.len() < offset + SHORT_ADDRESS_SIZE {
+                return Err(Error::WrongNumberOfBytes);
+            }
+            let pan_identifier = PanIdentifier::unpack(&data[offset..offset + SHORT_ADDRESS_SIZE])?;
+            offset += SHORT_ADDRESS_SIZE;
+            Some(pan_identifier)
+        } else {
+            None
+        };
+        let (key, key_encryption) = if options & KEY_PRESENT == KEY_PRESENT {
+            if data.len() < offset + KEY_SIZE {
+                return Err(Error::WrongNumberOfBytes);
+            }
+            let key = Key::unpack(&data[offset..offset + KEY_SIZE])?;
+            offset += KEY_SIZE;
+            let key_encryption = if options & KEY_ENCRYPTION == KEY_ENCRYPTION {
+                if data.len() < offset + 4 + FRAME_COUNTER_SIZE {
+                    return Err(Error::WrongNumberOfBytes);
+                }
+                let mic = LittleEndian::read_u32(&data[offset..offset + 4]);
+                offset += 4;
+                let frame_counter =
+                    FrameCounter::unpack(&data[offset..offset + FRAME_COUNTER_SIZE])?;
+                offset += FRAME_COUNTER_SIZE;
+                Some(KeyEncryption { mic, frame_counter })
+            } else {
+                None
+            };
+            (Some(key), key_encryption)
+        } else {
+            (None, None)
+        };
+        Ok((
+            Self {
+                security_level,
+                key_type,
+                pan_identifier,
+                key,
+                key_encryption,
+            },
+            offset,
+        ))
+    }
+}
+
+/// Channel configuration
+///
+/// Sent to the GPD to tell which channel the network operates on.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChannelConfiguration {
+    /// Operational channel, 11 to 26
+    pub channel: u8,
+    /// Set when sent by a basic proxy
+    pub basic: bool,
+}
+
+impl ChannelConfiguration {
+    /// Create a channel configuration for the provided channel
+    pub fn new(channel: u8, basic: bool) -> Result<Self, Error> {
+        if !(11..=26).contains(&channel) {
+            return Err(Error::InvalidValue);
+        }
+        Ok(Self { channel, basic })
+    }
+}
+
+impl Pack<ChannelConfiguration, Error> for ChannelConfiguration {
+    fn pack(&self, data: &mut [u8]) -> Result<usize, Error> {
+        if data.is_empty() {
+            return Err(Error::NotEnoughSpace);
+        }
+        if !(11..=26).contains(&self.channel) {
+            return Err(Error::InvalidValue);
+        }
+        data[0] = (self.channel - 11) & CHANNEL_MASK;
+        if self.basic {
+            data[0] |= BASIC;
+        }
+        Ok(1)
+    }
+
+    fn unpack(data: &[u8]) -> Result<(Self, usize), Error> {
+        if data.is_empty() {
+            return Err(Error::WrongNumberOfBytes);
+        }
+        Ok((
+            Self {
+                channel: (data[0] & CHANNEL_MASK) + 11,
+                basic: data[0] & BASIC == BASIC,
+            },
+            1,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::green_power::Command;
+
+    #[test]
+    fn pack_commissioning_reply_basic_sink() {
+        let key = Key::from([
+            0xc0, 0xc1, 0xc2, 0xc3, 0xc4, 0xc5, 0xc6, 0xc7, 0xc8, 0xc9, 0xca, 0xcb, 0xcc, 0xcd,
+            0xce, 0xcf,
+        ]);
+        let reply = CommissioningReply::new(
+            Some(PanIdentifier::new(0x1a62)),
+            SecurityLevel::EncryptionFullFrameCounterAndMic,
+            KeyType::IndividualKey,
+        )
+        .with_key(key);
+        let command = Command::CommissioningReply(reply);
+        let mut data = [0u8; 32];
+        let used = command.pack(&mut data).unwrap();
+        assert_eq!(used, 20);
+        assert_eq!(
+            data[..used],
+            [
+                0xf0, 0x9b, 0x62, 0x1a, 0xc0, 0xc1, 0xc2, 0xc3, 0xc4, 0xc5, 0xc6, 0xc7, 0xc8, 0xc9,
+                0xca, 0xcb, 0xcc, 0xcd, 0xce, 0xcf
+            ]
+        );
+        let (unpacked, unpacked_used) = Command::unpack(&data[..used]).unwrap();
+        assert_eq!(unpacked_used, used);
+        assert_eq!(unpacked, command);
+    }
+
+    #[test]
+    fn pack_commissioning_reply_encrypted_key() {
+        let reply = CommissioningReply::new(
+            None,
+            SecurityLevel::FullFrameCounterAndMic,
+            KeyType::GroupKey,
+        )
+        .with_encrypted_key(
+            Key::from([0x55; 16]),
+            0x0403_0201,
+            FrameCounter::new(0x0000_0010),
+        );
+        let mut data = [0u8; 32];
+        let used = reply.pack(&mut data).unwrap();
+        assert_eq!(used, 25);
+        assert_eq!(data[0], 0x56);
+        assert_eq!(
+            data[17..25],
+            [0x01, 0x02, 0x03, 0x04, 0x10, 0x00, 0x00, 0x00]
+        );
+        assert!(reply.pack(&mut data[..24]).is_err());
+        let (unpacked, unpacked_used) = CommissioningReply::unpack(&data[..used]).unwrap();
+        assert_eq!(unpacked_used, used);
+        assert_eq!(
+            unpacked.key_encryption.unwrap().frame_counter,
+            FrameCounter::new(0x10)
+        );
+    }
+
+    #[test]
+    fn pack_channel_configuration() {
+        let command = Command::ChannelConfiguration(ChannelConfiguration::new(15, true).unwrap());
+        let mut data = [0u8; 2];
+        let used = command.pack(&mut data).unwrap();
+        assert_eq!(data[..used], [0xf3, 0x14]);
+        assert!(ChannelConfiguration::new(27, true).is_err());
+    }
+}
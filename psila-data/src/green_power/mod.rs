@@ -0,0 +1,109 @@
+//! # Green Power
+//!
+//! Green Power device (GPD) frames, as used by the Green Power proxy and sink.
+
+use core::convert::TryFrom;
+
+use crate::error::Error;
+use crate::pack::Pack;
+
+mod commissioning;
+mod security;
+
+pub use commissioning::{ChannelConfiguration, CommissioningReply, KeyEncryption};
+pub use security::{decrypt, nonce, Direction, MIC_SIZE};
+
+extended_enum!(
+    /// Green Power device command identifier
+    CommandIdentifier, u8,
+    /// Commissioning, sent by the GPD
+    Commissioning => 0xe0,
+    /// Decommissioning, sent by the GPD
+    Decommissioning => 0xe1,
+    /// Success, sent by the GPD
+    Success => 0xe2,
+    /// Channel request, sent by the GPD
+    ChannelRequest => 0xe3,
+    /// Commissioning reply, sent to the GPD
+    CommissioningReply => 0xf0,
+    /// Write attributes, sent to the GPD
+    WriteAttributes => 0xf1,
+    /// Read attributes, sent to the GPD
+    ReadAttributes => 0xf2,
+    /// Channel configuration, sent to the GPD
+    ChannelConfiguration => 0xf3,
+);
+
+extended_enum!(
+    /// Green Power security level
+    SecurityLevel, u8,
+    /// No security
+    NoSecurity => 0b00,
+    /// 1 byte frame counter and 2 byte MIC, deprecated
+    ShortFrameCounterAndMic => 0b01,
+    /// 4 byte frame counter and 4 byte MIC
+    FullFrameCounterAndMic => 0b10,
+    /// Encryption, 4 byte frame counter and 4 byte MIC
+    EncryptionFullFrameCounterAndMic => 0b11,
+);
+
+extended_enum!(
+    /// Green Power security key type
+    KeyType, u8,
+    /// No key
+    NoKey => 0b000,
+    /// Zigbee network key
+    NetworkKey => 0b001,
+    /// GPD group key
+    GroupKey => 0b010,
+    /// Network key derived GPD group key
+    NetworkDerivedGroupKey => 0b011,
+    /// Out-of-the-box individual GPD key
+    IndividualKey => 0b100,
+    /// Derived individual GPD key
+    DerivedIndividualKey => 0b111,
+);
+
+/// Green Power device commands sent to the GPD during commissioning
+#[derive(Clone, Debug, PartialEq)]
+pub enum Command {
+    CommissioningReply(CommissioningReply),
+    ChannelConfiguration(ChannelConfiguration),
+}
+
+impl Pack<Command, Error> for Command {
+    fn pack(&self, data: &mut [u8]) -> Result<usize, Error> {
+        if data.is_empty() {
+            return Err(Error::NotEnoughSpace);
+        }
+        let used = match self {
+            Command::CommissioningReply(cmd) => {
+                data[0] = u8::from(CommandIdentifier::CommissioningReply);
+                cmd.pack(&mut data[1..])?
+            }
+            Command::ChannelConfiguration(cmd) => {
+                data[0] = u8::from(CommandIdentifier::ChannelConfiguration);
+                cmd.pack(&mut data[1..])?
+            }
+        };
+        Ok(1 + used)
+    }
+
+    fn unpack(data: &[u8]) -> Result<(Self, usize), Error> {
+        if data.is_empty() {
+            return Err(Error::WrongNumberOfBytes);
+        }
+        let (command, used) = match CommandIdentifier::try_from(data[0])? {
+            CommandIdentifier::CommissioningReply => {
+                let (cmd, used) = CommissioningReply::unpack(&data[1..])?;
+                (Command::CommissioningReply(cmd), used)
+            }
+            CommandIdentifier::ChannelConfiguration => {
+                let (cmd, used) = ChannelConfiguration::unpack(&data[1..])?;
+                (Command::ChannelConfiguration(cmd), used)
+            }
+            _ => return Err(Error::NotImplemented),
+        };
+        Ok((command, 1 + used))
+    }
+}
@@ -0,0 +1,97 @@
+use byteorder::{ByteOrder, LittleEndian};
+use psila_crypto::CryptoBackend;
+
+use crate::common::key::{Key, KEY_SIZE};
+use crate::error::Error;
+use crate::security::{FrameCounter, NONCE_SIZE};
+
+/// Length of the message integrity code (MIC) of secured GPD frames
+pub const MIC_SIZE: usize = 4;
+
+/// Direction of a Green Power device frame
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Direction {
+    /// Frame sent by the GPD
+    FromDevice,
+    /// Frame sent to the GPD
+    ToDevice,
+}
+
+/// Create the CCM* nonce for a GPD frame using the GPD source identifier
+///
+/// The GPD source identifier is used in place of the extended address. For
+/// frames from the GPD the source identifier is repeated in the four most
+/// significant bytes, for frames to the GPD those bytes are zero.
+pub fn nonce(
+    source_identifier: u32,
+    frame_counter: FrameCounter,
+    direction: Direction,
+) -> [u8; NONCE_SIZE] {
+    let mut nonce = [0u8; NONCE_SIZE];
+    LittleEndian::write_u32(&mut nonce[0..4], source_identifier);
+    if direction == Direction::FromDevice {
+        LittleEndian::write_u32(&mut nonce[4..8], source_identifier);
+    }
+    LittleEndian::write_u32(&mut nonce[8..12], frame_counter.value());
+    nonce[12] = match direction {
+        Direction::FromDevice => 0x05,
+        Direction::ToDevice => 0xc5,
+    };
+    nonce
+}
+
+/// Decrypt the payload of a secured frame sent by a GPD
+///
+/// The frame shall use security level 0b11, encryption with a full frame
+/// counter and MIC. `frame` is the complete GPD frame, the first
+/// `header_length` bytes are the header up to and including the frame counter
+/// which is used as additional data. The frame ends with the MIC.
+///
+/// Returns the number of bytes written to `output`, the GPD command identifier
+/// followed by the command payload. Zero bytes are written if the MIC check
+/// fails.
+pub fn decrypt<Backend: CryptoBackend>(
+    backend: &mut Backend,
+    key: &Key,
+    source_identifier: u32,
+    frame_counter: FrameCounter,
+    frame: &[u8],
+    header_length: usize,
+    output: &mut [u8],
+) -> Result<usize, Error> {
+    if frame.len() < header_length + MIC_SIZE {
+        return Err(Error::WrongNumberOfBytes);
+    }
+    let mic_offset = frame.len() - MIC_SIZE;
+    if output.len() < mic_offset - header_length {
+        return Err(Error::NotEnoughSpace);
+    }
+    let key: [u8; KEY_SIZE] = (*key).into();
+    let nonce = nonce(source_identifier, frame_counter, Direction::FromDevice);
+    let used = backend.ccmstar_decrypt(
+        &key,
+        &nonce,
+        &frame[header_length..mic_offset],
+        &frame[mic_offset..],
+        &frame[..header_length],
+        output,
+    )?;
+    Ok(used)
+}
+
+#[cfg(all(test, not(feature = "core")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gpd_nonce() {
+        assert_eq!(
+            nonce(0x8765_4321, FrameCounter::new(2), Direction::FromDevice),
+            [0x21, 0x43, 0x65, 0x87, 0x21, 0x43, 0x65, 0x87, 0x02, 0x00, 0x00, 0x00, 0x05]
+        );
+        assert_eq!(
+            nonce(0x8765_4321, FrameCounter::new(2), Direction::ToDevice),
+            [0x21, 0x43, 0x65, 0x87, 0x00, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0xc5]
+        );
+    }
+}
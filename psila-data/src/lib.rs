@@ -6,6 +6,9 @@
 
 #![cfg_attr(feature = "core", no_std)]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
 #[macro_use]
 extern crate bitflags;
 
@@ -15,18 +18,28 @@ mod utils;
 pub mod application_service; // APS
 pub mod cluster_library; // ZCL
 pub mod common;
+pub mod decode;
+#[cfg(feature = "alloc")]
+pub mod decode_cache;
 pub mod device_profile; // ZDP
 pub mod error;
+pub mod green_power; // GP
+pub mod mac; // MAC
 pub mod network; // NWK
 pub mod pack;
 pub mod security;
+#[cfg(feature = "testing")]
+pub mod testing;
+
+#[cfg(test)]
+mod conformance;
 
 pub use common::address::{
-    ExtendedAddress, ExtendedPanIdentifier, GroupIdentifier, NetworkAddress, PanIdentifier,
-    ShortAddress,
+    ExtendedAddress, ExtendedPanIdentifier, GroupIdentifier, NetworkAddress, OuiLookup,
+    PanIdentifier, ShortAddress,
 };
 pub use common::capability_information::CapabilityInformation;
 pub use common::key::Key;
 pub use error::Error;
 
-pub use utils::clear;
+pub use utils::{clear, hex_into};
@@ -0,0 +1,85 @@
+//! # MAC commands
+//!
+//! The IEEE 802.15.4 MAC commands used by Zigbee.
+
+use core::convert::TryFrom;
+
+use ieee802154::mac::{self, command::Command};
+
+use crate::error::Error;
+
+extended_enum!(
+    /// MAC command frame identifier
+    CommandIdentifier, u8,
+    /// Association request, sent by a device joining
+    AssociationRequest => 0x01,
+    /// Association response, sent by the coordinator
+    AssociationResponse => 0x02,
+    /// Disassociation notification
+    DisassociationNotification => 0x03,
+    /// Data request, polls the coordinator for pending data
+    DataRequest => 0x04,
+    /// PAN identifier conflict notification
+    PanIdConflictNotification => 0x05,
+    /// Orphan notification, sent by a device which lost its coordinator
+    OrphanNotification => 0x06,
+    /// Beacon request
+    BeaconRequest => 0x07,
+    /// Coordinator realignment
+    CoordinatorRealignment => 0x08,
+    /// Guaranteed time slot (GTS) request
+    GuaranteedTimeSlotRequest => 0x09,
+);
+
+impl CommandIdentifier {
+    /// Identify the command carried by a MAC frame
+    ///
+    /// Returns `None` if the frame isn't a MAC command frame.
+    pub fn from_frame(frame: &mac::Frame) -> Option<Self> {
+        match frame.content {
+            mac::FrameContent::Command(ref command) => Some(Self::from(command)),
+            _ => None,
+        }
+    }
+}
+
+impl From<&Command> for CommandIdentifier {
+    fn from(command: &Command) -> Self {
+        match command {
+            Command::AssociationRequest(..) => CommandIdentifier::AssociationRequest,
+            Command::AssociationResponse(..) => CommandIdentifier::AssociationResponse,
+            Command::DisassociationNotification(..) => {
+                CommandIdentifier::DisassociationNotification
+            }
+            Command::DataRequest => CommandIdentifier::DataRequest,
+            Command::PanIdConflictNotification => CommandIdentifier::PanIdConflictNotification,
+            Command::OrphanNotification => CommandIdentifier::OrphanNotification,
+            Command::BeaconRequest => CommandIdentifier::BeaconRequest,
+            Command::CoordinatorRealignment(..) => CommandIdentifier::CoordinatorRealignment,
+            Command::GuaranteedTimeSlotRequest(..) => CommandIdentifier::GuaranteedTimeSlotRequest,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn label_association_request() {
+        let data = [
+            0x23, 0xc8, 0x01, 0x45, 0x67, 0x87, 0xa9, 0xff, 0xff, 0xff, 0xee, 0xdd, 0xcc, 0xbb,
+            0xaa, 0x99, 0x88, 0x01, 0x8c,
+        ];
+        let frame = mac::Frame::decode(&data, false).unwrap();
+        assert_eq!(
+            CommandIdentifier::from_frame(&frame),
+            Some(CommandIdentifier::AssociationRequest)
+        );
+        assert_eq!(
+            CommandIdentifier::try_from(data[17]),
+            Ok(CommandIdentifier::AssociationRequest)
+        );
+        assert_eq!(CommandIdentifier::try_from(0x0a), Err(Error::InvalidValue));
+    }
+}
@@ -5,13 +5,41 @@ const REJOIN: u8 = 0b0010_0000;
 const REQUEST: u8 = 0b0100_0000;
 const REMOVE_CHILDREN: u8 = 0b1000_0000;
 
+/// Network leave command
+///
+/// Sent by a device leaving the network, or with `request` set to ask
+/// another device to leave. Not to be confused with the MAC disassociation
+/// notification.
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub struct Leave {
+    /// The device will rejoin the network after leaving
     pub rejoin: bool,
+    /// The receiving device is asked to leave
     pub request: bool,
+    /// The children of the leaving device also leave
     pub remove_children: bool,
 }
 
+impl Leave {
+    /// Announce that the sending device leaves the network
+    pub fn new(rejoin: bool, remove_children: bool) -> Self {
+        Self {
+            rejoin,
+            request: false,
+            remove_children,
+        }
+    }
+
+    /// Ask the receiving device to leave the network
+    pub fn new_request(rejoin: bool, remove_children: bool) -> Self {
+        Self {
+            rejoin,
+            request: true,
+            remove_children,
+        }
+    }
+}
+
 impl Pack<Leave, Error> for Leave {
     fn pack(&self, data: &mut [u8]) -> Result<usize, Error> {
         if data.is_empty() {
@@ -188,4 +216,20 @@ mod tests {
         assert_eq!(used, 1);
         assert_eq!(data[0], 0xe0);
     }
+
+    #[test]
+    fn self_initiated_leave_with_rejoin() {
+        use crate::network::commands::Command;
+
+        let leave = Leave::new(true, false);
+        assert!(!leave.request);
+        let mut data = [0u8; 2];
+        let used = Command::Leave(leave).pack(&mut data).unwrap();
+        assert_eq!(used, 2);
+        assert_eq!(data, [0x04, 0x20]);
+        assert_eq!(Command::unpack(&data).unwrap(), (Command::Leave(leave), 2));
+
+        let (command, _) = Command::unpack(&[0x04, 0x60]).unwrap();
+        assert_eq!(command, Command::Leave(Leave::new_request(true, false)));
+    }
 }
@@ -13,43 +13,76 @@ extended_enum!(
 
 const NUMBER_OF_RECORDS_MASK: u8 = 0b0001_1111;
 const COMMAND_IDENTIFIER_MASK: u8 = 0b1110_0000;
+/// Largest number of records, the size of the 5-bit record count
+const MAX_RECORDS: usize = 31;
 
 /// Network report
 ///
 /// There is something to report about the network.
 ///
 /// This command actually has the potential to mean other things, but only one
-/// command identifier is standardised as of yet.
+/// command identifier is standardised as of yet. For a PAN identifier
+/// conflict report, the records lists the PAN identifiers in use by
+/// neighboring networks.
 #[derive(Clone, Debug, PartialEq)]
 pub struct NetworkReport {
     pub command_identifier: CommandIdentifier,
     pub extended_pan_identifier: ExtendedPanIdentifier,
-    pub pan_identifier: PanIdentifier,
+    num_pan_identifiers: u8,
+    pan_identifiers: [PanIdentifier; MAX_RECORDS],
+}
+
+impl NetworkReport {
+    /// Create a PAN identifier conflict report, at most 31 PAN identifiers
+    /// can be reported
+    pub fn pan_identifier_conflict(
+        extended_pan_identifier: ExtendedPanIdentifier,
+        pan_identifiers: &[PanIdentifier],
+    ) -> Result<Self, Error> {
+        if pan_identifiers.is_empty() || pan_identifiers.len() > MAX_RECORDS {
+            return Err(Error::InvalidValue);
+        }
+        let mut entries = [PanIdentifier::default(); MAX_RECORDS];
+        entries[..pan_identifiers.len()].copy_from_slice(pan_identifiers);
+        Ok(Self {
+            command_identifier: CommandIdentifier::PanIdentifierConflict,
+            extended_pan_identifier,
+            num_pan_identifiers: pan_identifiers.len() as u8,
+            pan_identifiers: entries,
+        })
+    }
+
+    /// The reported PAN identifiers
+    pub fn pan_identifiers(&self) -> &[PanIdentifier] {
+        &self.pan_identifiers[..self.num_pan_identifiers as usize]
+    }
 }
 
 impl Pack<NetworkReport, Error> for NetworkReport {
     fn pack(&self, data: &mut [u8]) -> Result<usize, Error> {
-        if data.len() < 11 {
+        let num_records = self.num_pan_identifiers as usize;
+        if data.len() < 1 + EXTENDED_ADDRESS_SIZE + num_records * SHORT_ADDRESS_SIZE {
             return Err(Error::WrongNumberOfBytes);
         }
-        data[0] = (self.command_identifier as u8) << 5 | 0x01;
+        data[0] = u8::from(self.command_identifier) << 5 | self.num_pan_identifiers;
         let mut offset = 1;
         self.extended_pan_identifier
             .pack(&mut data[offset..offset + EXTENDED_ADDRESS_SIZE])?;
         offset += EXTENDED_ADDRESS_SIZE;
-        self.pan_identifier
-            .pack(&mut data[offset..offset + SHORT_ADDRESS_SIZE])?;
-        offset += SHORT_ADDRESS_SIZE;
+        for pan_identifier in self.pan_identifiers() {
+            pan_identifier.pack(&mut data[offset..offset + SHORT_ADDRESS_SIZE])?;
+            offset += SHORT_ADDRESS_SIZE;
+        }
         Ok(offset)
     }
 
     fn unpack(data: &[u8]) -> Result<(Self, usize), Error> {
-        if data.len() < 11 {
+        if data.is_empty() {
             return Err(Error::WrongNumberOfBytes);
         }
         let num_records = (data[0] & NUMBER_OF_RECORDS_MASK) as usize;
-        if num_records != 1 {
-            return Err(Error::InvalidValue);
+        if data.len() < 1 + EXTENDED_ADDRESS_SIZE + num_records * SHORT_ADDRESS_SIZE {
+            return Err(Error::WrongNumberOfBytes);
         }
         let command_identifier =
             CommandIdentifier::try_from((data[0] & COMMAND_IDENTIFIER_MASK) >> 5)?;
@@ -57,15 +90,75 @@ impl Pack<NetworkReport, Error> for NetworkReport {
         let extended_pan_identifier =
             ExtendedPanIdentifier::unpack(&data[offset..offset + EXTENDED_ADDRESS_SIZE])?;
         offset += EXTENDED_ADDRESS_SIZE;
-        let pan_identifier = PanIdentifier::unpack(&data[offset..offset + SHORT_ADDRESS_SIZE])?;
-        offset += SHORT_ADDRESS_SIZE;
+        let mut pan_identifiers = [PanIdentifier::default(); MAX_RECORDS];
+        for pan_identifier in pan_identifiers[..num_records].iter_mut() {
+            *pan_identifier = PanIdentifier::unpack(&data[offset..offset + SHORT_ADDRESS_SIZE])?;
+            offset += SHORT_ADDRESS_SIZE;
+        }
         Ok((
             Self {
                 command_identifier,
                 extended_pan_identifier,
-                pan_identifier,
+                num_pan_identifiers: num_records as u8,
+                pan_identifiers,
             },
             offset,
         ))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unpack_pan_identifier_conflict() {
+        let data = [
+            0x02, 0x77, 0x66, 0x55, 0x44, 0x33, 0x22, 0x11, 0x00, 0x34, 0x12, 0xcd, 0xab,
+        ];
+        let (report, used) = NetworkReport::unpack(&data[..]).unwrap();
+        assert_eq!(used, 13);
+        assert_eq!(
+            report.command_identifier,
+            CommandIdentifier::PanIdentifierConflict
+        );
+        assert_eq!(
+            report.extended_pan_identifier,
+            ExtendedPanIdentifier::new(0x0011_2233_4455_6677)
+        );
+        assert_eq!(
+            report.pan_identifiers(),
+            [PanIdentifier::new(0x1234), PanIdentifier::new(0xabcd)]
+        );
+
+        let mut buffer = [0u8; 32];
+        let used = report.pack(&mut buffer).unwrap();
+        assert_eq!(buffer[..used], data);
+    }
+
+    #[test]
+    fn pan_identifier_conflict_limit() {
+        let extended_pan_identifier = ExtendedPanIdentifier::new(0x0011_2233_4455_6677);
+        let mut pan_identifiers = [PanIdentifier::default(); MAX_RECORDS + 1];
+        for (n, pan_identifier) in pan_identifiers.iter_mut().enumerate() {
+            *pan_identifier = PanIdentifier::new(0x1000 + n as u16);
+        }
+        assert_eq!(
+            NetworkReport::pan_identifier_conflict(extended_pan_identifier, &pan_identifiers),
+            Err(Error::InvalidValue)
+        );
+        let report = NetworkReport::pan_identifier_conflict(
+            extended_pan_identifier,
+            &pan_identifiers[..MAX_RECORDS],
+        )
+        .unwrap();
+
+        let mut buffer = [0u8; 128];
+        let used = report.pack(&mut buffer).unwrap();
+        assert_eq!(used, 1 + 8 + 31 * 2);
+        assert_eq!(buffer[0], 0x1f);
+        let (unpacked, unpacked_used) = NetworkReport::unpack(&buffer[..used]).unwrap();
+        assert_eq!(unpacked_used, used);
+        assert_eq!(unpacked.pan_identifiers(), &pan_identifiers[..MAX_RECORDS]);
+    }
+}
@@ -25,19 +25,41 @@ const COMMAND_IDENTIFIER_MASK: u8 = 0b1110_0000;
 pub struct NetworkUpdate {
     pub command_identifier: CommandIdentifier,
     pub extended_pan_identifier: ExtendedPanIdentifier,
+    /// Network update identifier, incremented for each update
+    pub update_identifier: u8,
     pub pan_identifier: PanIdentifier,
 }
 
+impl NetworkUpdate {
+    /// Create a PAN identifier update, moving the network to a new PAN
+    /// identifier. The update identifier is the current update identifier
+    /// incremented by one.
+    pub fn pan_identifier_update(
+        extended_pan_identifier: ExtendedPanIdentifier,
+        current_update_identifier: u8,
+        pan_identifier: PanIdentifier,
+    ) -> Self {
+        Self {
+            command_identifier: CommandIdentifier::PanIdentifierUpdate,
+            extended_pan_identifier,
+            update_identifier: current_update_identifier.wrapping_add(1),
+            pan_identifier,
+        }
+    }
+}
+
 impl Pack<NetworkUpdate, Error> for NetworkUpdate {
     fn pack(&self, data: &mut [u8]) -> Result<usize, Error> {
-        if data.len() < 11 {
+        if data.len() < 12 {
             return Err(Error::WrongNumberOfBytes);
         }
-        data[0] = (self.command_identifier as u8) << 5 | 0x01;
+        data[0] = u8::from(self.command_identifier) << 5 | 0x01;
         let mut offset = 1;
         self.extended_pan_identifier
             .pack(&mut data[offset..offset + EXTENDED_ADDRESS_SIZE])?;
         offset += EXTENDED_ADDRESS_SIZE;
+        data[offset] = self.update_identifier;
+        offset += 1;
         self.pan_identifier
             .pack(&mut data[offset..offset + SHORT_ADDRESS_SIZE])?;
         offset += SHORT_ADDRESS_SIZE;
@@ -45,7 +67,7 @@ impl Pack<NetworkUpdate, Error> for NetworkUpdate {
     }
 
     fn unpack(data: &[u8]) -> Result<(Self, usize), Error> {
-        if data.len() < 11 {
+        if data.len() < 12 {
             return Err(Error::WrongNumberOfBytes);
         }
         let num_records = (data[0] & NUMBER_OF_RECORDS_MASK) as usize;
@@ -58,15 +80,43 @@ impl Pack<NetworkUpdate, Error> for NetworkUpdate {
         let extended_pan_identifier =
             ExtendedPanIdentifier::unpack(&data[offset..offset + EXTENDED_ADDRESS_SIZE])?;
         offset += EXTENDED_ADDRESS_SIZE;
+        let update_identifier = data[offset];
+        offset += 1;
         let pan_identifier = PanIdentifier::unpack(&data[offset..offset + SHORT_ADDRESS_SIZE])?;
         offset += SHORT_ADDRESS_SIZE;
         Ok((
             Self {
                 command_identifier,
                 extended_pan_identifier,
+                update_identifier,
                 pan_identifier,
             },
             offset,
         ))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pack_pan_identifier_update() {
+        let update = NetworkUpdate::pan_identifier_update(
+            ExtendedPanIdentifier::new(0x0011_2233_4455_6677),
+            0x04,
+            PanIdentifier::new(0x5678),
+        );
+        assert_eq!(update.update_identifier, 0x05);
+        let mut data = [0u8; 16];
+        let used = update.pack(&mut data).unwrap();
+        assert_eq!(used, 12);
+        assert_eq!(
+            data[..used],
+            [0x01, 0x77, 0x66, 0x55, 0x44, 0x33, 0x22, 0x11, 0x00, 0x05, 0x78, 0x56]
+        );
+        let (unpacked, used) = NetworkUpdate::unpack(&data[..used]).unwrap();
+        assert_eq!(used, 12);
+        assert_eq!(unpacked, update);
+    }
+}
@@ -1,11 +1,12 @@
 use crate::common::address::{NetworkAddress, SHORT_ADDRESS_SIZE};
 use crate::error::Error;
+use crate::network::header::MAX_RELAY_COUNT;
 use crate::pack::{Pack, PackFixed};
 
 #[derive(Clone, Debug, PartialEq)]
 pub struct RouteRecord {
     num_entries: u8,
-    entries: [NetworkAddress; 32],
+    entries: [NetworkAddress; MAX_RELAY_COUNT],
 }
 
 impl RouteRecord {
@@ -42,11 +43,14 @@ impl Pack<RouteRecord, Error> for RouteRecord {
             return Err(Error::WrongNumberOfBytes);
         }
         let count = data[0] as usize;
+        if count > MAX_RELAY_COUNT {
+            return Err(Error::BrokenRelayList);
+        }
         if data.len() < 1 + (count * SHORT_ADDRESS_SIZE) {
             return Err(Error::WrongNumberOfBytes);
         }
         let mut offset = 1;
-        let mut entries = [NetworkAddress::default(); 32];
+        let mut entries = [NetworkAddress::default(); MAX_RELAY_COUNT];
         for entry in entries[..count].iter_mut() {
             *entry = NetworkAddress::unpack(&data[offset..offset + SHORT_ADDRESS_SIZE])?;
             offset += SHORT_ADDRESS_SIZE;
@@ -61,3 +65,28 @@ impl Pack<RouteRecord, Error> for RouteRecord {
         ))
     }
 }
+
+#[cfg(all(test, not(feature = "core")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unpack_route_record() {
+        let data = [0x02, 0x34, 0x12, 0x78, 0x56];
+        let (record, used) = RouteRecord::unpack(&data).unwrap();
+        assert_eq!(used, 5);
+        assert_eq!(
+            record.entries(),
+            [NetworkAddress::new(0x1234), NetworkAddress::new(0x5678)]
+        );
+        assert_eq!(
+            RouteRecord::unpack(&data[..4]),
+            Err(Error::WrongNumberOfBytes)
+        );
+        // Relay count beyond what can be stored, with the data present
+        let mut data = [0u8; 1 + 33 * SHORT_ADDRESS_SIZE];
+        data[0] = 33;
+        assert_eq!(RouteRecord::unpack(&data), Err(Error::BrokenRelayList));
+        assert_eq!(RouteRecord::unpack(&[0xff]), Err(Error::BrokenRelayList));
+    }
+}
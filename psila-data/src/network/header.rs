@@ -2,7 +2,7 @@ use core::convert::TryFrom;
 
 use crate::common::address::{EXTENDED_ADDRESS_SIZE, SHORT_ADDRESS_SIZE};
 use crate::pack::{Pack, PackFixed};
-use crate::{Error, ExtendedAddress, NetworkAddress};
+use crate::{Error, ExtendedAddress, GroupIdentifier, NetworkAddress};
 
 /// Frame Type Sub-Field
 #[derive(Copy, Clone, Debug, PartialEq)]
@@ -120,7 +120,7 @@ impl TryFrom<u8> for MulticastMode {
         match value & 0b0000_0011 {
             0b0000_0000 => Ok(MulticastMode::NonmemberMode),
             0b0000_0001 => Ok(MulticastMode::MemberMode),
-            _ => Err(Error::UnknownDiscoverRoute),
+            _ => Err(Error::InvalidValue),
         }
     }
 }
@@ -144,7 +144,7 @@ impl PackFixed<MulticastControl, Error> for MulticastControl {
         if data.len() != 1 {
             Err(Error::WrongNumberOfBytes)
         } else {
-            data[0] = self.mode as u8 & (self.radius << 2) & (self.max_radius << 5);
+            data[0] = self.mode as u8 | (self.radius & 0b111) << 2 | (self.max_radius & 0b111) << 5;
             Ok(())
         }
     }
@@ -163,11 +163,14 @@ impl PackFixed<MulticastControl, Error> for MulticastControl {
     }
 }
 
+/// Maximum number of relays in a source route
+pub const MAX_RELAY_COUNT: usize = 32;
+
 #[derive(Clone, Debug, PartialEq)]
 pub struct SourceRouteFrame {
     pub index: u8,
     num_entries: u8,
-    entries: [NetworkAddress; 32],
+    entries: [NetworkAddress; MAX_RELAY_COUNT],
 }
 
 impl SourceRouteFrame {
@@ -175,13 +178,27 @@ impl SourceRouteFrame {
         if relay_list.is_empty() {
             panic!("Relay list cannot be of length 0.");
         }
-        let mut entries = [NetworkAddress::default(); 32];
+        Self::try_new(relay_list).expect("Relay list too long.")
+    }
+
+    /// Create a source route with `relay_list`, failing with
+    /// `Error::BrokenRelayList` if the list is empty or longer than
+    /// `MAX_RELAY_COUNT`
+    ///
+    /// The relay list is ordered from the relay closest to the destination
+    /// to the relay closest to the source, the relay index starts at the
+    /// last relay.
+    pub fn try_new(relay_list: &[NetworkAddress]) -> Result<Self, Error> {
+        if relay_list.is_empty() || relay_list.len() > MAX_RELAY_COUNT {
+            return Err(Error::BrokenRelayList);
+        }
+        let mut entries = [NetworkAddress::default(); MAX_RELAY_COUNT];
         entries[..relay_list.len()].copy_from_slice(relay_list);
-        Self {
+        Ok(Self {
             index: relay_list.len() as u8 - 1,
             num_entries: relay_list.len() as u8,
             entries,
-        }
+        })
     }
 
     pub fn is_empty(&self) -> bool {
@@ -230,11 +247,11 @@ impl Pack<SourceRouteFrame, Error> for SourceRouteFrame {
         if data.len() < (count * SHORT_ADDRESS_SIZE) + 2 {
             return Err(Error::WrongNumberOfBytes);
         }
-        if count == 0 || index as usize >= count {
+        if count == 0 || count > MAX_RELAY_COUNT || index as usize >= count {
             return Err(Error::BrokenRelayList);
         }
         let end = 2 + (count * SHORT_ADDRESS_SIZE);
-        let mut entries = [NetworkAddress::default(); 32];
+        let mut entries = [NetworkAddress::default(); MAX_RELAY_COUNT];
         for (n, chunk) in data[2..end].chunks(SHORT_ADDRESS_SIZE).enumerate() {
             entries[n] = NetworkAddress::unpack(chunk)?;
         }
@@ -296,6 +313,87 @@ impl NetworkHeader {
             source_route_frame,
         }
     }
+
+    /// Create a header for a data frame sent to a multicast group
+    pub fn new_multicast_data_header(
+        protocol_version: u8,
+        security: bool,
+        group: GroupIdentifier,
+        source_address: NetworkAddress,
+        radius: u8,
+        sequence_number: u8,
+        multicast_control: MulticastControl,
+    ) -> Self {
+        Self {
+            control: FrameControl {
+                frame_type: FrameType::Data,
+                protocol_version,
+                discover_route: DiscoverRoute::SurpressDiscovery,
+                multicast: true,
+                security,
+                contains_source_route_frame: false,
+                contains_destination_ieee_address: false,
+                contains_source_ieee_address: false,
+            },
+            destination_address: group.into(),
+            source_address,
+            radius,
+            sequence_number,
+            destination_ieee_address: None,
+            source_ieee_address: None,
+            multicast_control: Some(multicast_control),
+            source_route_frame: None,
+        }
+    }
+
+    /// Create a header for a network command frame
+    ///
+    /// Route discovery is suppressed, commands are sent to neighbors or
+    /// along existing routes.
+    pub fn new_command_header(
+        protocol_version: u8,
+        security: bool,
+        destination_address: NetworkAddress,
+        source_address: NetworkAddress,
+        source_ieee_address: Option<ExtendedAddress>,
+        radius: u8,
+        sequence_number: u8,
+    ) -> Self {
+        Self {
+            control: FrameControl {
+                frame_type: FrameType::Command,
+                protocol_version,
+                discover_route: DiscoverRoute::SurpressDiscovery,
+                multicast: false,
+                security,
+                contains_source_route_frame: false,
+                contains_destination_ieee_address: false,
+                contains_source_ieee_address: source_ieee_address.is_some(),
+            },
+            destination_address,
+            source_address,
+            radius,
+            sequence_number,
+            destination_ieee_address: None,
+            source_ieee_address,
+            multicast_control: None,
+            source_route_frame: None,
+        }
+    }
+
+    /// Set the source route of the frame, or remove it with `None`
+    ///
+    /// The source route bit of the frame control is updated accordingly.
+    /// Fails with `Error::BrokenRelayList` if the relay list is empty or too
+    /// long, see `SourceRouteFrame::try_new`.
+    pub fn set_source_route(&mut self, relay_list: Option<&[NetworkAddress]>) -> Result<(), Error> {
+        self.source_route_frame = match relay_list {
+            Some(relay_list) => Some(SourceRouteFrame::try_new(relay_list)?),
+            None => None,
+        };
+        self.control.contains_source_route_frame = self.source_route_frame.is_some();
+        Ok(())
+    }
 }
 
 impl Pack<NetworkHeader, Error> for NetworkHeader {
@@ -305,6 +403,9 @@ impl Pack<NetworkHeader, Error> for NetworkHeader {
             control.pack(&mut data[0..2])?;
             return Ok(2);
         }
+        if data.len() < MIN_NUM_BYTES {
+            return Err(Error::NotEnoughSpace);
+        }
         let mut total_length = MIN_NUM_BYTES;
 
         self.destination_address.pack(&mut data[2..=3])?;
@@ -313,9 +414,9 @@ impl Pack<NetworkHeader, Error> for NetworkHeader {
         data[7] = self.sequence_number;
 
         control.contains_destination_ieee_address = if let Some(v) = self.destination_ieee_address {
-            if data.len() > total_length + 8 {
-                v.pack(&mut data[total_length..total_length + 8])?;
-                total_length += 8;
+            if data.len() >= total_length + EXTENDED_ADDRESS_SIZE {
+                v.pack(&mut data[total_length..total_length + EXTENDED_ADDRESS_SIZE])?;
+                total_length += EXTENDED_ADDRESS_SIZE;
                 true
             } else {
                 return Err(Error::NotEnoughSpace);
@@ -325,9 +426,9 @@ impl Pack<NetworkHeader, Error> for NetworkHeader {
         };
 
         control.contains_source_ieee_address = if let Some(v) = self.source_ieee_address {
-            if data.len() > total_length + 8 {
-                v.pack(&mut data[total_length..total_length + 8])?;
-                total_length += 8;
+            if data.len() >= total_length + EXTENDED_ADDRESS_SIZE {
+                v.pack(&mut data[total_length..total_length + EXTENDED_ADDRESS_SIZE])?;
+                total_length += EXTENDED_ADDRESS_SIZE;
                 true
             } else {
                 return Err(Error::NotEnoughSpace);
@@ -337,7 +438,7 @@ impl Pack<NetworkHeader, Error> for NetworkHeader {
         };
 
         control.multicast = if let Some(v) = self.multicast_control {
-            if data.len() > total_length + 8 {
+            if data.len() > total_length {
                 v.pack(&mut data[total_length..=total_length])?;
                 total_length += 1;
                 true
@@ -349,9 +450,10 @@ impl Pack<NetworkHeader, Error> for NetworkHeader {
         };
 
         control.contains_source_route_frame = if let Some(v) = &self.source_route_frame {
-            if data.len() > total_length + 8 {
-                let length = v.pack(&mut data[total_length..total_length + 8])?;
-                total_length += length as usize;
+            let length = 2 + v.len() * SHORT_ADDRESS_SIZE;
+            if data.len() >= total_length + length {
+                v.pack(&mut data[total_length..total_length + length])?;
+                total_length += length;
                 true
             } else {
                 return Err(Error::NotEnoughSpace);
@@ -881,4 +983,168 @@ mod tests {
         assert_eq!(used, 8);
         assert_eq!(data, correct_data);
     }
+
+    #[test]
+    fn pack_header_with_ieee_addresses() {
+        let control = FrameControl {
+            frame_type: FrameType::Data,
+            protocol_version: 2,
+            discover_route: DiscoverRoute::SurpressDiscovery,
+            multicast: false,
+            security: false,
+            contains_source_route_frame: false,
+            contains_destination_ieee_address: true,
+            contains_source_ieee_address: true,
+        };
+        let header = NetworkHeader {
+            control,
+            destination_address: NetworkAddress::new(0x1234),
+            source_address: NetworkAddress::new(0x5678),
+            radius: 1,
+            sequence_number: 2,
+            destination_ieee_address: Some(ExtendedAddress::new(0x0011_2233_4455_6677)),
+            source_ieee_address: Some(ExtendedAddress::new(0x8899_aabb_ccdd_eeff)),
+            multicast_control: None,
+            source_route_frame: None,
+        };
+        // Addresses are sent least significant octet first
+        let correct_data = [
+            0x08, 0x18, 0x34, 0x12, 0x78, 0x56, 0x01, 0x02, 0x77, 0x66, 0x55, 0x44, 0x33, 0x22,
+            0x11, 0x00, 0xff, 0xee, 0xdd, 0xcc, 0xbb, 0xaa, 0x99, 0x88,
+        ];
+        let mut data = [0; 24];
+        let used = header.pack(&mut data).unwrap();
+        assert_eq!(used, 24);
+        assert_eq!(data, correct_data);
+        let (unpacked, used) = NetworkHeader::unpack(&data).unwrap();
+        assert_eq!(used, 24);
+        assert_eq!(unpacked.destination_address, 0x1234);
+        assert_eq!(unpacked.source_address, 0x5678);
+        assert_eq!(
+            unpacked.destination_ieee_address,
+            header.destination_ieee_address
+        );
+        assert_eq!(unpacked.source_ieee_address, header.source_ieee_address);
+    }
+
+    #[test]
+    fn pack_source_routed_header() {
+        let mut header = NetworkHeader::new_data_header(
+            2,
+            DiscoverRoute::SurpressDiscovery,
+            false,
+            NetworkAddress::new(0x31a4),
+            NetworkAddress::new(0x0000),
+            10,
+            59,
+            None,
+        );
+        let relays = [NetworkAddress::new(0x1111), NetworkAddress::new(0x2222)];
+        header.set_source_route(Some(&relays)).unwrap();
+        assert!(header.control.contains_source_route_frame);
+
+        let mut data = [0u8; 14];
+        let used = header.pack(&mut data).unwrap();
+        assert_eq!(used, 14);
+        assert_eq!(
+            data,
+            [0x08, 0x04, 0xa4, 0x31, 0x00, 0x00, 0x0a, 0x3b, 0x02, 0x01, 0x11, 0x11, 0x22, 0x22]
+        );
+        let (unpacked, used) = NetworkHeader::unpack(&data).unwrap();
+        assert_eq!(used, 14);
+        assert!(unpacked.control.contains_source_route_frame);
+        assert_eq!(unpacked.destination_address, header.destination_address);
+        assert_eq!(unpacked.source_route_frame, header.source_route_frame);
+        let source_route = unpacked.source_route_frame.unwrap();
+        assert_eq!(source_route.entries(), relays);
+        assert_eq!(source_route.get_index(), 1);
+
+        assert_eq!(
+            header.pack(&mut data[..13]).unwrap_err(),
+            Error::NotEnoughSpace
+        );
+        header.set_source_route(None).unwrap();
+        assert!(!header.control.contains_source_route_frame);
+        assert_eq!(header.pack(&mut data).unwrap(), 8);
+        assert_eq!(
+            header.set_source_route(Some(&[])).unwrap_err(),
+            Error::BrokenRelayList
+        );
+    }
+
+    #[test]
+    fn unpack_multicast_header() {
+        let data = [
+            0x08, 0x01, 0x34, 0x12, 0x00, 0x00, 0x1e, 0x2a, 0xed, 0x01, 0x02,
+        ];
+        let (nwk, used) = NetworkHeader::unpack(&data[..]).unwrap();
+        assert_eq!(used, 9);
+        assert_eq!(nwk.control.frame_type, FrameType::Data);
+        assert_eq!(nwk.control.protocol_version, 2);
+        assert_eq!(nwk.control.multicast, true);
+        assert!(!nwk.control.security);
+        assert_eq!(
+            nwk.destination_address,
+            NetworkAddress::from(GroupIdentifier::new(0x1234))
+        );
+        assert_eq!(nwk.radius, 30);
+        assert_eq!(nwk.sequence_number, 42);
+        let multicast_control = nwk.multicast_control.unwrap();
+        assert_eq!(multicast_control.mode, MulticastMode::MemberMode);
+        assert_eq!(multicast_control.radius, 3);
+        assert_eq!(multicast_control.max_radius, 7);
+    }
+
+    #[test]
+    fn pack_multicast_header() {
+        let header = NetworkHeader::new_multicast_data_header(
+            2,
+            false,
+            GroupIdentifier::new(0x1234),
+            NetworkAddress::new(0x0000),
+            30,
+            42,
+            MulticastControl {
+                mode: MulticastMode::MemberMode,
+                radius: 3,
+                max_radius: 7,
+            },
+        );
+        let mut data = [0; 16];
+        let used = header.pack(&mut data).unwrap();
+        assert_eq!(used, 9);
+        assert_eq!(
+            data[..used],
+            [0x08, 0x01, 0x34, 0x12, 0x00, 0x00, 0x1e, 0x2a, 0xed]
+        );
+    }
+
+    #[test]
+    fn pack_command_header() {
+        let header = NetworkHeader::new_command_header(
+            2,
+            false,
+            NetworkAddress::new(0xfffd),
+            NetworkAddress::new(0x1234),
+            Some(ExtendedAddress::new(0x0011_2233_4455_6677)),
+            1,
+            5,
+        );
+        let mut data = [0; 24];
+        let used = header.pack(&mut data).unwrap();
+        assert_eq!(used, 16);
+        assert_eq!(
+            data[..used],
+            [
+                0x09, 0x10, 0xfd, 0xff, 0x34, 0x12, 0x01, 0x05, 0x77, 0x66, 0x55, 0x44, 0x33, 0x22,
+                0x11, 0x00
+            ]
+        );
+        let (unpacked, _) = NetworkHeader::unpack(&data[..used]).unwrap();
+        assert_eq!(unpacked.control.frame_type, FrameType::Command);
+        assert_eq!(
+            unpacked.source_ieee_address,
+            Some(ExtendedAddress::new(0x0011_2233_4455_6677))
+        );
+    }
 }
@@ -5,7 +5,11 @@
 pub mod beacon;
 pub mod commands;
 pub mod header;
+pub mod security;
+pub mod tree_addressing;
 
 pub use beacon::BeaconInformation;
 pub use commands::Command;
 pub use header::NetworkHeader;
+pub use security::{decrypt_frame, decrypt_frame_with_options, DecryptedFrame, KeyStore};
+pub use tree_addressing::TreeParameters;
@@ -0,0 +1,181 @@
+//! # Network layer security
+//!
+//! Decryption of frames secured on the network layer (NWK).
+
+use psila_crypto::{self, CryptoBackend};
+
+use crate::common::address::ExtendedAddress;
+use crate::common::key::{Key, KEY_SIZE};
+use crate::error::Error;
+use crate::network::NetworkHeader;
+use crate::pack::{Pack, PackFixed};
+use crate::security::{
+    Nonce, SecurityHeader, SecurityLevel, BLOCK_SIZE, LENGTH_FIELD_LENGTH, NONCE_SIZE,
+};
+
+/// Largest frame which can be decrypted
+const MAX_FRAME_SIZE: usize = 128;
+
+/// Provides the keys used for decrypting frames
+pub trait KeyStore {
+    /// The network key with the provided key sequence number
+    fn network_key(&self, sequence: u8) -> Option<Key>;
+
+    /// The link key shared with the device with the provided extended
+    /// address
+    ///
+    /// Used for frames secured on the application service layer (APS). No
+    /// link keys are known by default.
+    fn link_key(&self, _address: ExtendedAddress) -> Option<Key> {
+        None
+    }
+}
+
+/// A frame decrypted by `decrypt_frame_with_options`
+#[derive(Clone, Debug)]
+pub struct DecryptedFrame<'a> {
+    /// The network header
+    pub header: NetworkHeader,
+    /// The plain text payload
+    pub payload: &'a [u8],
+    /// The message integrity code (MIC) was verified, always true for frames
+    /// without security
+    pub mic_valid: bool,
+}
+
+/// Parse the network header of `frame` and decrypt the payload in place
+///
+/// If the security bit of the network header is set, the auxiliary security
+/// header is parsed and the network key is fetched from `keys`. The frame is
+/// decrypted and the MIC verified using the network security level,
+/// encryption with a 32-bit MIC.
+///
+/// Returns the network header and the plain text payload, typically a
+/// application service (APS) frame. Frames without security are returned as
+/// is. Frames failing the MIC check are rejected.
+pub fn decrypt_frame<'a, Backend, Keys>(
+    backend: &mut Backend,
+    keys: &Keys,
+    frame: &'a mut [u8],
+) -> Result<(NetworkHeader, &'a [u8]), Error>
+where
+    Backend: CryptoBackend,
+    Keys: KeyStore,
+{
+    let decrypted = decrypt_frame_with_options(backend, keys, frame, false)?;
+    Ok((decrypted.header, decrypted.payload))
+}
+
+/// Parse the network header of `frame` and decrypt the payload in place, see
+/// `decrypt_frame`
+///
+/// If `accept_invalid_mic` is set, a frame failing the MIC check is still
+/// decrypted and returned with `mic_valid` cleared, which is useful when
+/// sniffing. Otherwise such frames are rejected with
+/// `IntegrityCheckFailed`.
+pub fn decrypt_frame_with_options<'a, Backend, Keys>(
+    backend: &mut Backend,
+    keys: &Keys,
+    frame: &'a mut [u8],
+    accept_invalid_mic: bool,
+) -> Result<DecryptedFrame<'a>, Error>
+where
+    Backend: CryptoBackend,
+    Keys: KeyStore,
+{
+    let (header, header_length) = NetworkHeader::unpack(frame)?;
+    if !header.control.security {
+        let frame: &'a [u8] = frame;
+        return Ok(DecryptedFrame {
+            header,
+            payload: &frame[header_length..],
+            mic_valid: true,
+        });
+    }
+    if frame.len() > MAX_FRAME_SIZE {
+        return Err(Error::WrongNumberOfBytes);
+    }
+    let (security_header, security_length) = SecurityHeader::unpack(&frame[header_length..])?;
+    let level = SecurityLevel::EncryptedIntegrity32;
+    let nonce = Nonce::nwk(&security_header, level)?;
+    let sequence = security_header
+        .sequence
+        .ok_or(Error::UnknownKeyIdentifier)?;
+    let key: [u8; KEY_SIZE] = keys.network_key(sequence).ok_or(Error::MissingKey)?.into();
+
+    let payload_start = header_length + security_length;
+    let mic_bytes = level.mic_bytes();
+    if frame.len() < payload_start + mic_bytes {
+        return Err(Error::WrongNumberOfBytes);
+    }
+    let mic_offset = frame.len() - mic_bytes;
+
+    let mut buffer = [0u8; MAX_FRAME_SIZE];
+    let buffer = &mut buffer[..frame.len()];
+    buffer.copy_from_slice(frame);
+    // The security level is not sent over the air, the additional data uses
+    // the network security level
+    let mut control = security_header.control;
+    control.set_level(level);
+    control.pack(&mut buffer[header_length..=header_length])?;
+
+    let used = backend.ccmstar_decrypt(
+        &key,
+        nonce.as_ref(),
+        &buffer[payload_start..mic_offset],
+        &buffer[mic_offset..],
+        &buffer[..payload_start],
+        &mut frame[payload_start..mic_offset],
+    )?;
+    let mic_valid = used == mic_offset - payload_start;
+    if !mic_valid {
+        if !accept_invalid_mic {
+            return Err(Error::CryptoError(
+                psila_crypto::Error::IntegrityCheckFailed,
+            ));
+        }
+        decrypt_counter_mode(
+            backend,
+            &key,
+            nonce.as_ref(),
+            &buffer[payload_start..mic_offset],
+            &mut frame[payload_start..mic_offset],
+        )?;
+    }
+    let frame: &'a [u8] = frame;
+    Ok(DecryptedFrame {
+        header,
+        payload: &frame[payload_start..mic_offset],
+        mic_valid,
+    })
+}
+
+/// Decrypt the CCM* encrypted `message` without verifying the MIC
+///
+/// Applies the CCM* counter mode key stream, blocks A1, A2, ...
+fn decrypt_counter_mode<Backend: CryptoBackend>(
+    backend: &mut Backend,
+    key: &[u8; KEY_SIZE],
+    nonce: &[u8],
+    message: &[u8],
+    output: &mut [u8],
+) -> Result<(), Error> {
+    backend.aes128_ecb_encrypt_set_key(key)?;
+    let mut counter_block = [0u8; BLOCK_SIZE];
+    counter_block[0] = (LENGTH_FIELD_LENGTH - 1) as u8;
+    counter_block[1..=NONCE_SIZE].copy_from_slice(nonce);
+    for (index, (input, output)) in message
+        .chunks(BLOCK_SIZE)
+        .zip(output.chunks_mut(BLOCK_SIZE))
+        .enumerate()
+    {
+        let counter = (index + 1) as u16;
+        counter_block[BLOCK_SIZE - 2..].copy_from_slice(&counter.to_be_bytes());
+        let mut key_stream = [0u8; BLOCK_SIZE];
+        backend.aes128_ecb_encrypt_process_block(&counter_block, &mut key_stream)?;
+        for ((o, i), k) in output.iter_mut().zip(input.iter()).zip(key_stream.iter()) {
+            *o = i ^ k;
+        }
+    }
+    Ok(())
+}
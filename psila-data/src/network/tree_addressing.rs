@@ -0,0 +1,197 @@
+//! # Distributed address assignment
+//!
+//! Tree addressing, where each router is given a block of addresses to
+//! assign to its children. 3.6.1.6 Distributed Address Assignment Mechanism
+
+use crate::NetworkAddress;
+
+/// Parameters of a tree addressing network
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TreeParameters {
+    /// Maximum number of children of a parent (nwkMaxChildren, Cm)
+    pub max_children: u8,
+    /// Maximum number of router children of a parent (nwkMaxRouters, Rm)
+    pub max_routers: u8,
+    /// Maximum depth of the network (nwkMaxDepth, Lm)
+    pub max_depth: u8,
+}
+
+impl TreeParameters {
+    pub fn new(max_children: u8, max_routers: u8, max_depth: u8) -> Self {
+        Self {
+            max_children,
+            max_routers,
+            max_depth,
+        }
+    }
+
+    /// The size of the address sub-block given to each router child of a
+    /// parent at `depth`, Cskip(d)
+    ///
+    /// Returns zero at the maximum depth, where no children may join. `None`
+    /// is returned if the parameters are invalid or the sub-block does not
+    /// fit the address space.
+    pub fn cskip(&self, depth: u8) -> Option<u16> {
+        if self.max_routers > self.max_children {
+            return None;
+        }
+        if depth >= self.max_depth {
+            return Some(0);
+        }
+        let children = u64::from(self.max_children);
+        let routers = u64::from(self.max_routers);
+        let exponent = u32::from(self.max_depth - depth - 1);
+        let skip = if routers == 0 {
+            // Rm^0 is one
+            if exponent == 0 {
+                1
+            } else {
+                1 + children
+            }
+        } else if routers == 1 {
+            1 + children * u64::from(exponent)
+        } else {
+            // (1 + Cm - Rm - Cm * Rm^(Lm - d - 1)) / (1 - Rm), with the sign of
+            // numerator and denominator flipped to stay unsigned
+            let power = routers.checked_pow(exponent)?;
+            (children.checked_mul(power)? + routers - 1 - children) / (routers - 1)
+        };
+        if skip > u64::from(u16::max_value()) {
+            None
+        } else {
+            Some(skip as u16)
+        }
+    }
+
+    /// Address of the router child `n` (1 to `max_routers`) of the parent at
+    /// `depth`
+    pub fn router_address(
+        &self,
+        parent: NetworkAddress,
+        depth: u8,
+        n: u8,
+    ) -> Option<NetworkAddress> {
+        if n == 0 || n > self.max_routers {
+            return None;
+        }
+        let skip = self.cskip(depth)?;
+        if skip == 0 {
+            return None;
+        }
+        let offset = u32::from(skip) * u32::from(n - 1) + 1;
+        Self::offset_address(parent, offset)
+    }
+
+    /// Address of the end device child `n` (1 to `max_children -
+    /// max_routers`) of the parent at `depth`
+    pub fn end_device_address(
+        &self,
+        parent: NetworkAddress,
+        depth: u8,
+        n: u8,
+    ) -> Option<NetworkAddress> {
+        if n == 0 || n > self.max_children.saturating_sub(self.max_routers) {
+            return None;
+        }
+        let skip = self.cskip(depth)?;
+        if skip == 0 {
+            return None;
+        }
+        let offset = u32::from(skip) * u32::from(self.max_routers) + u32::from(n);
+        Self::offset_address(parent, offset)
+    }
+
+    /// The first and last address which may be assigned to children of the
+    /// parent at `depth`
+    pub fn child_address_range(
+        &self,
+        parent: NetworkAddress,
+        depth: u8,
+    ) -> Option<(NetworkAddress, NetworkAddress)> {
+        let skip = self.cskip(depth)?;
+        if skip == 0 || self.max_children == 0 {
+            return None;
+        }
+        let last = u32::from(skip) * u32::from(self.max_routers)
+            + u32::from(self.max_children - self.max_routers);
+        Some((
+            Self::offset_address(parent, 1)?,
+            Self::offset_address(parent, last)?,
+        ))
+    }
+
+    fn offset_address(parent: NetworkAddress, offset: u32) -> Option<NetworkAddress> {
+        let address = u32::from(u16::from(parent)) + offset;
+        // 0xfff8 and up are reserved for broadcast
+        if address >= 0xfff8 {
+            None
+        } else {
+            Some(NetworkAddress::new(address as u16))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cskip_example() {
+        // Example network, 6 children, 4 routers, depth 3
+        let tree = TreeParameters::new(6, 4, 3);
+        assert_eq!(tree.cskip(0), Some(31));
+        assert_eq!(tree.cskip(1), Some(7));
+        assert_eq!(tree.cskip(2), Some(1));
+        assert_eq!(tree.cskip(3), Some(0));
+
+        // Single router per parent
+        let tree = TreeParameters::new(6, 1, 3);
+        assert_eq!(tree.cskip(0), Some(13));
+        assert_eq!(tree.cskip(2), Some(1));
+
+        // More routers than children
+        assert_eq!(TreeParameters::new(2, 3, 3).cskip(0), None);
+    }
+
+    #[test]
+    fn child_addresses() {
+        let tree = TreeParameters::new(6, 4, 3);
+        let coordinator = NetworkAddress::new(0x0000);
+
+        let routers: [u16; 4] = [1, 32, 63, 94];
+        for (n, address) in routers.iter().enumerate() {
+            assert_eq!(
+                tree.router_address(coordinator, 0, n as u8 + 1),
+                Some(NetworkAddress::new(*address))
+            );
+        }
+        assert_eq!(tree.router_address(coordinator, 0, 5), None);
+        assert_eq!(
+            tree.end_device_address(coordinator, 0, 1),
+            Some(NetworkAddress::new(125))
+        );
+        assert_eq!(
+            tree.end_device_address(coordinator, 0, 2),
+            Some(NetworkAddress::new(126))
+        );
+        assert_eq!(tree.end_device_address(coordinator, 0, 3), None);
+        assert_eq!(
+            tree.child_address_range(coordinator, 0),
+            Some((NetworkAddress::new(1), NetworkAddress::new(126)))
+        );
+
+        // Second router of the coordinator, at depth 1
+        let router = NetworkAddress::new(32);
+        assert_eq!(
+            tree.router_address(router, 1, 2),
+            Some(NetworkAddress::new(40))
+        );
+        assert_eq!(
+            tree.child_address_range(router, 1),
+            Some((NetworkAddress::new(33), NetworkAddress::new(62)))
+        );
+
+        // No children at the maximum depth
+        assert_eq!(tree.child_address_range(NetworkAddress::new(3), 3), None);
+    }
+}
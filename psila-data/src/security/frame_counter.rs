@@ -0,0 +1,98 @@
+use core::fmt;
+
+use byteorder::{ByteOrder, LittleEndian};
+
+use crate::error::Error;
+use crate::pack::PackFixed;
+
+/// Size of the frame counter in octets
+pub const FRAME_COUNTER_SIZE: usize = 4;
+
+/// Frame counter of a auxiliary security header
+///
+/// The 32-bit outgoing frame counter which is part of the nonce, sent in
+/// little endian order. Not to be confused with the 8-bit APS counter of the
+/// application service header.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FrameCounter(u32);
+
+impl FrameCounter {
+    /// Create a frame counter with `value`
+    pub const fn new(value: u32) -> Self {
+        Self(value)
+    }
+
+    /// The value of the frame counter
+    pub const fn value(self) -> u32 {
+        self.0
+    }
+
+    /// The following frame counter, `None` if the counter is exhausted
+    pub fn next(self) -> Option<Self> {
+        self.0.checked_add(1).map(Self)
+    }
+}
+
+impl From<u32> for FrameCounter {
+    fn from(value: u32) -> Self {
+        Self(value)
+    }
+}
+
+impl From<FrameCounter> for u32 {
+    fn from(value: FrameCounter) -> Self {
+        value.0
+    }
+}
+
+impl fmt::Display for FrameCounter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl PackFixed<FrameCounter, Error> for FrameCounter {
+    fn pack(&self, data: &mut [u8]) -> Result<(), Error> {
+        if data.len() != FRAME_COUNTER_SIZE {
+            return Err(Error::WrongNumberOfBytes);
+        }
+        LittleEndian::write_u32(data, self.0);
+        Ok(())
+    }
+
+    fn unpack(data: &[u8]) -> Result<Self, Error> {
+        if data.len() != FRAME_COUNTER_SIZE {
+            return Err(Error::WrongNumberOfBytes);
+        }
+        Ok(Self(LittleEndian::read_u32(data)))
+    }
+}
+
+#[cfg(all(test, not(feature = "core")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pack_frame_counter() {
+        let counter = FrameCounter::new(0x0a0b_0c0d);
+        let mut data = [0u8; FRAME_COUNTER_SIZE];
+        counter.pack(&mut data).unwrap();
+        assert_eq!(data, [0x0d, 0x0c, 0x0b, 0x0a]);
+        assert_eq!(FrameCounter::unpack(&data).unwrap(), counter);
+        assert_eq!(u32::from(counter), 0x0a0b_0c0d);
+
+        assert_eq!(
+            FrameCounter::unpack(&data[..3]),
+            Err(Error::WrongNumberOfBytes)
+        );
+        let mut data = [0u8; 5];
+        assert_eq!(counter.pack(&mut data), Err(Error::WrongNumberOfBytes));
+    }
+
+    #[test]
+    fn next_frame_counter() {
+        assert_eq!(FrameCounter::new(1).next(), Some(FrameCounter::new(2)));
+        assert_eq!(FrameCounter::new(u32::max_value()).next(), None);
+        assert!(FrameCounter::new(1) < FrameCounter::new(0x100));
+    }
+}
@@ -1,10 +1,9 @@
 use core::convert::TryFrom;
 
-use byteorder::{ByteOrder, LittleEndian};
-
 use crate::common::address::{ExtendedAddress, EXTENDED_ADDRESS_SIZE};
 use crate::error::Error;
 use crate::pack::{Pack, PackFixed};
+use crate::security::frame_counter::{FrameCounter, FRAME_COUNTER_SIZE};
 
 pub const SECURITY_LEVEL_MASK: u8 = 0b0000_0111;
 
@@ -137,7 +136,7 @@ pub struct SecurityHeader {
     /// Security header flags
     pub control: SecurityControl,
     /// Securit header frame counter
-    pub counter: u32,
+    pub counter: FrameCounter,
     /// Source address as extended address
     pub source: Option<ExtendedAddress>,
     /// Sequence number for network keys
@@ -148,7 +147,7 @@ impl SecurityHeader {
     /// Create a new network security header
     pub fn network_header(
         security_level: SecurityLevel,
-        sequence: u32,
+        counter: FrameCounter,
         source_address: ExtendedAddress,
         key_sequence: u8,
     ) -> Self {
@@ -158,11 +157,28 @@ impl SecurityHeader {
                 identifier: KeyIdentifier::Network,
                 has_source_address: true,
             },
-            counter: sequence,
+            counter,
             source: Some(source_address),
             sequence: Some(key_sequence),
         }
     }
+    /// Create a new application service security header, for frames
+    /// secured with the data key (link key)
+    ///
+    /// The source address is not included, the receiver uses the extended
+    /// address of the network layer source.
+    pub fn data_header(security_level: SecurityLevel, counter: FrameCounter) -> Self {
+        SecurityHeader {
+            control: SecurityControl {
+                level: security_level,
+                identifier: KeyIdentifier::Data,
+                has_source_address: false,
+            },
+            counter,
+            source: None,
+            sequence: None,
+        }
+    }
     /// Generate nonce from the header
     pub fn get_nonce(&self, buf: &mut [u8]) -> Result<(), Error> {
         if let Some(source) = self.source {
@@ -170,12 +186,83 @@ impl SecurityHeader {
         } else {
             return Err(Error::NoExtendedAddress);
         }
-        LittleEndian::write_u32(&mut buf[8..12], self.counter);
+        self.counter.pack(&mut buf[8..12])?;
         self.control.pack(&mut buf[12..13]).unwrap();
         Ok(())
     }
 }
 
+/// Size of the CCM* nonce in octets
+pub const NONCE_SIZE: usize = 13;
+
+/// CCM* nonce
+///
+/// Source address, frame counter and security control octet, in that order.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Nonce([u8; NONCE_SIZE]);
+
+impl Nonce {
+    fn new(source: ExtendedAddress, counter: FrameCounter, control: SecurityControl) -> Self {
+        let mut nonce = [0u8; NONCE_SIZE];
+        source.pack(&mut nonce[0..8]).unwrap();
+        counter.pack(&mut nonce[8..12]).unwrap();
+        control.pack(&mut nonce[12..13]).unwrap();
+        Nonce(nonce)
+    }
+
+    /// Nonce for a frame secured on the network layer (NWK)
+    ///
+    /// The source address and frame counter are taken from the NWK auxiliary
+    /// header, which always carry the extended source address. The security
+    /// control octet is the one of the auxiliary header, with the key
+    /// identifier for the network key, but with the security level set to
+    /// `level`. The security level is not sent over the air, the network
+    /// security level shall be used.
+    pub fn nwk(header: &SecurityHeader, level: SecurityLevel) -> Result<Self, Error> {
+        if header.control.identifier != KeyIdentifier::Network {
+            return Err(Error::UnknownKeyIdentifier);
+        }
+        let source = header.source.ok_or(Error::NoExtendedAddress)?;
+        let mut control = header.control;
+        control.set_level(level);
+        Ok(Self::new(source, header.counter, control))
+    }
+
+    /// Nonce for a frame secured on the application service layer (APS)
+    ///
+    /// The frame counter is taken from the APS auxiliary header. The source
+    /// address is taken from the auxiliary header if present, otherwise
+    /// `source` is used, which shall be the extended address of the NWK
+    /// source. The security control octet is the one of the auxiliary header,
+    /// with the key identifier for a data, key-transport or key-load key,
+    /// but with the security level set to `level`.
+    pub fn aps(
+        header: &SecurityHeader,
+        level: SecurityLevel,
+        source: ExtendedAddress,
+    ) -> Result<Self, Error> {
+        if header.control.identifier == KeyIdentifier::Network {
+            return Err(Error::UnknownKeyIdentifier);
+        }
+        let source = header.source.unwrap_or(source);
+        let mut control = header.control;
+        control.set_level(level);
+        Ok(Self::new(source, header.counter, control))
+    }
+}
+
+impl AsRef<[u8]> for Nonce {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl From<Nonce> for [u8; NONCE_SIZE] {
+    fn from(value: Nonce) -> Self {
+        value.0
+    }
+}
+
 impl Pack<SecurityHeader, Error> for SecurityHeader {
     fn pack(&self, data: &mut [u8]) -> Result<usize, Error> {
         let length =
@@ -190,7 +277,7 @@ impl Pack<SecurityHeader, Error> for SecurityHeader {
         let mut control = self.control;
         control.has_source_address = self.source.is_some();
         control.pack(&mut data[0..=0])?;
-        LittleEndian::write_u32(&mut data[1..5], self.counter);
+        self.counter.pack(&mut data[1..=FRAME_COUNTER_SIZE])?;
         let mut offset = 5;
         if let Some(source) = self.source {
             source.pack(&mut data[offset..offset + EXTENDED_ADDRESS_SIZE])?;
@@ -208,7 +295,7 @@ impl Pack<SecurityHeader, Error> for SecurityHeader {
             return Err(Error::WrongNumberOfBytes);
         }
         let control = SecurityControl::unpack(&data[..1])?;
-        let counter = LittleEndian::read_u32(&data[1..5]);
+        let counter = FrameCounter::unpack(&data[1..=FRAME_COUNTER_SIZE])?;
         let mut offset = 5;
         let source = if control.has_source_address {
             if data.len() < (offset + 8) {
@@ -285,8 +372,69 @@ mod tests {
         assert_eq!(f.control.level, SecurityLevel::None);
         assert_eq!(f.control.identifier, KeyIdentifier::KeyTransport);
         assert_eq!(f.control.has_source_address, true);
-        assert_eq!(f.counter, 2);
+        assert_eq!(f.counter, FrameCounter::new(2));
         assert_eq!(f.source.unwrap(), 0x0021_2eff_ff03_2e38);
         assert_eq!(f.sequence, None);
     }
+
+    #[test]
+    fn network_nonce() {
+        let data = [
+            0x28, 0xa4, 0xde, 0x0a, 0x00, 0xb5, 0xb4, 0x03, 0xff, 0xff, 0x2e, 0x21, 0x00, 0x00,
+            0xb3, 0x5d, 0x06, 0xca, 0xec, 0x2c, 0xb3, 0xf3, 0x8a, 0x20, 0x4a, 0xb9,
+        ];
+        let (header, used) = SecurityHeader::unpack(&data[..]).unwrap();
+        assert_eq!(used, 14);
+        let nonce = Nonce::nwk(&header, SecurityLevel::EncryptedIntegrity32).unwrap();
+        assert_eq!(
+            nonce.as_ref(),
+            [0xb5, 0xb4, 0x03, 0xff, 0xff, 0x2e, 0x21, 0x00, 0xa4, 0xde, 0x0a, 0x00, 0x2d]
+        );
+        assert_eq!(
+            Nonce::aps(
+                &header,
+                SecurityLevel::EncryptedIntegrity32,
+                ExtendedAddress::new(0)
+            ),
+            Err(Error::UnknownKeyIdentifier)
+        );
+    }
+
+    #[test]
+    fn application_service_nonce() {
+        let data = [
+            0x30, 0x02, 0x00, 0x00, 0x00, 0x38, 0x2e, 0x03, 0xff, 0xff, 0x2e, 0x21, 0x00, 0xae,
+            0x5e, 0x9f, 0x46, 0xa6, 0x40, 0xcd, 0xe7, 0x90, 0x2f, 0xd6, 0x0e, 0x43, 0x23, 0x17,
+        ];
+        let (header, _) = SecurityHeader::unpack(&data[..]).unwrap();
+        let nonce = Nonce::aps(
+            &header,
+            SecurityLevel::EncryptedIntegrity32,
+            ExtendedAddress::new(0x1122_3344_5566_7788),
+        )
+        .unwrap();
+        assert_eq!(
+            nonce.as_ref(),
+            [0x38, 0x2e, 0x03, 0xff, 0xff, 0x2e, 0x21, 0x00, 0x02, 0x00, 0x00, 0x00, 0x35]
+        );
+        assert_eq!(
+            Nonce::nwk(&header, SecurityLevel::EncryptedIntegrity32),
+            Err(Error::UnknownKeyIdentifier)
+        );
+
+        // Without source address in the auxiliary header
+        let data = [0x00, 0x10, 0x32, 0x00, 0x00];
+        let (header, used) = SecurityHeader::unpack(&data[..]).unwrap();
+        assert_eq!(used, 5);
+        let nonce = Nonce::aps(
+            &header,
+            SecurityLevel::EncryptedIntegrity32,
+            ExtendedAddress::new(0x1122_3344_5566_7788),
+        )
+        .unwrap();
+        assert_eq!(
+            nonce.as_ref(),
+            [0x88, 0x77, 0x66, 0x55, 0x44, 0x33, 0x22, 0x11, 0x10, 0x32, 0x00, 0x00, 0x05]
+        );
+    }
 }
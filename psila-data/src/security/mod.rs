@@ -4,13 +4,22 @@ use psila_crypto::{self, CryptoBackend};
 
 use crate::error::Error;
 
+mod frame_counter;
 mod header;
+mod policy;
+mod sender;
 
+use crate::common::address::ExtendedAddress;
 use crate::common::key::KEY_SIZE;
 use crate::network::NetworkHeader;
 use crate::pack::{Pack, PackFixed};
 
-pub use header::{KeyIdentifier, SecurityControl, SecurityHeader, SecurityLevel};
+pub use frame_counter::{FrameCounter, FRAME_COUNTER_SIZE};
+pub use header::{
+    KeyIdentifier, Nonce, SecurityControl, SecurityHeader, SecurityLevel, NONCE_SIZE,
+};
+pub use policy::{SecurityPolicy, MAX_SECURED_CLUSTERS};
+pub use sender::SecureSender;
 
 pub use psila_crypto::BLOCK_SIZE;
 pub use psila_crypto::LENGTH_FIELD_LENGTH;
@@ -20,6 +29,189 @@ pub const DEFAULT_LINK_KEY: [u8; KEY_SIZE] = [
     0x5a, 0x69, 0x67, 0x42, 0x65, 0x65, 0x41, 0x6c, 0x6c, 0x69, 0x61, 0x6e, 0x63, 0x65, 0x30, 0x39,
 ];
 
+/// Process a block for the Key-hash hash function
+fn hash_key_process_block<Backend: CryptoBackend>(
+    backend: &mut Backend,
+    input: &[u8],
+    mut output: &mut [u8],
+    finish: bool,
+) -> Result<(), Error> {
+    backend.aes128_ecb_encrypt_set_key(&output)?;
+    if finish {
+        backend.aes128_ecb_encrypt_finish(&input, &mut output)?;
+    } else {
+        backend.aes128_ecb_encrypt_process_block(&input, &mut output)?;
+    }
+    // XOR the input into the hash block
+    for n in 0..BLOCK_SIZE {
+        output[n] ^= input[n];
+    }
+    Ok(())
+}
+
+/// Key-hash hash function
+fn hash_key_hash<Backend: CryptoBackend>(
+    backend: &mut Backend,
+    input: &[u8],
+    output: &mut [u8],
+) -> Result<(), Error> {
+    assert!(input.len() < 4096);
+
+    // Clear the first block of output
+    for b in output[..BLOCK_SIZE].iter_mut() {
+        *b = 0;
+    }
+
+    let mut blocks = input.chunks_exact(BLOCK_SIZE);
+
+    // Process input data in cipher block sized chunks
+    loop {
+        match blocks.next() {
+            Some(input_block) => {
+                hash_key_process_block(backend, &input_block, &mut output[..BLOCK_SIZE], false)?;
+            }
+            None => {
+                let mut block = [0u8; BLOCK_SIZE];
+                let remainder = blocks.remainder();
+                assert!(remainder.len() < BLOCK_SIZE - 3);
+                block[..remainder.len()].copy_from_slice(remainder);
+                // Pad the message M by right-concatenating to M the bit ‘1’ followed by the
+                // smallest non-negative number of ‘0’ bits, such that the resulting string has
+                // length 14 (mod 16) octets:
+                block[remainder.len()] = 0x80;
+                let input_len = input.len() as u16 * 8;
+                // Form the padded message M' by right-concatenating to the resulting string the
+                // 16-bit string that is equal to the binary representation of the integer l:
+                block[BLOCK_SIZE - 2] = (input_len >> 8) as u8;
+                block[BLOCK_SIZE - 1] = (input_len & 0xff) as u8;
+                hash_key_process_block(backend, &block, &mut output[..BLOCK_SIZE], true)?;
+                break;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Keyed hash function for message authentication (HMAC)
+///
+/// Appearently as specified in FIPS Pub 198
+pub(crate) fn keyed_hash<Backend: CryptoBackend>(
+    backend: &mut Backend,
+    key: &[u8; KEY_SIZE],
+    input: u8,
+    result: &mut [u8],
+) -> Result<(), Error> {
+    const HASH_INNER_PAD: u8 = 0x36;
+    const HASH_OUTER_PAD: u8 = 0x5c;
+    let mut hash_in = [0; BLOCK_SIZE * 2];
+    let mut hash_out = [0; BLOCK_SIZE + 1];
+
+    {
+        // XOR the key with the outer padding
+        for n in 0..KEY_SIZE {
+            hash_in[n] = key[n] ^ HASH_OUTER_PAD;
+        }
+        // XOR the key with the inner padding
+        for n in 0..KEY_SIZE {
+            hash_out[n] = key[n] ^ HASH_INNER_PAD;
+        }
+        // Append the input byte
+        hash_out[BLOCK_SIZE] = input;
+        // Hash hash_out to form (Key XOR opad) || H((Key XOR ipad) || text)
+        hash_key_hash(
+            backend,
+            &hash_out[..=BLOCK_SIZE],
+            &mut hash_in[BLOCK_SIZE..],
+        )?;
+        // Hash hash_in to get the result
+        hash_key_hash(backend, &hash_in, &mut hash_out)?;
+    }
+    {
+        // Take the key
+        let (output_key, _) = result.split_at_mut(KEY_SIZE);
+        output_key.copy_from_slice(&hash_out[..KEY_SIZE]);
+    }
+
+    Ok(())
+}
+
+/// Derive the key for `identifier` from `key`
+///
+/// The key-transport and key-load keys are derived from the link key, other
+/// keys are used as is.
+pub(crate) fn derive_key<Backend>(
+    backend: &mut Backend,
+    key: &[u8; KEY_SIZE],
+    identifier: KeyIdentifier,
+) -> Result<[u8; KEY_SIZE], Error>
+where
+    Backend: CryptoBackend,
+{
+    let hash_input = match identifier {
+        KeyIdentifier::KeyTransport => 0x00,
+        KeyIdentifier::KeyLoad => 0x02,
+        _ => return Ok(*key),
+    };
+    let mut derived = [0u8; KEY_SIZE];
+    keyed_hash(backend, key, hash_input, &mut derived)?;
+    Ok(derived)
+}
+
+/// Decrypt the payload of the secured `frame` into `output`
+///
+/// The auxiliary security header starts at `secure_header_offset`, the part
+/// of the frame before the payload is the additional data. The security
+/// level is not sent over the air, `level` is written to the security
+/// control octet of `frame` and used for the MIC size and the nonce. The
+/// key-transport and key-load keys are derived from `key`. `source` is used
+/// for the nonce when the auxiliary header doesn't carry the source address.
+///
+/// Returns the offset of the payload in `frame` and the number of bytes
+/// decrypted, zero if the MIC check failed.
+pub(crate) fn decrypt_secured<Backend>(
+    backend: &mut Backend,
+    key: &[u8; KEY_SIZE],
+    level: SecurityLevel,
+    frame: &mut [u8],
+    secure_header_offset: usize,
+    source: Option<ExtendedAddress>,
+    output: &mut [u8],
+) -> Result<(usize, usize), Error>
+where
+    Backend: CryptoBackend,
+{
+    if secure_header_offset >= frame.len() {
+        return Err(Error::WrongNumberOfBytes);
+    }
+    let (mut header, used) = SecurityHeader::unpack(&frame[secure_header_offset..])?;
+    header.control.set_level(level);
+    header
+        .control
+        .pack(&mut frame[secure_header_offset..=secure_header_offset])?;
+
+    let payload_start = secure_header_offset + used;
+    let mic_bytes = level.mic_bytes();
+    if frame.len() < payload_start + mic_bytes {
+        return Err(Error::WrongNumberOfBytes);
+    }
+    let mic_offset = frame.len() - mic_bytes;
+
+    let key = derive_key(backend, key, header.control.identifier)?;
+    header.source = header.source.or(source);
+    let mut nonce = [0; NONCE_SIZE];
+    header.get_nonce(&mut nonce)?;
+
+    let used = backend.ccmstar_decrypt(
+        &key,
+        &nonce,
+        &frame[payload_start..mic_offset],
+        &frame[mic_offset..],
+        &frame[..payload_start],
+        output,
+    )?;
+    Ok((payload_start, used))
+}
+
 pub struct CryptoProvider<Backend> {
     backend: Backend,
     buffer: [u8; 256],
@@ -36,65 +228,9 @@ where
         }
     }
 
-    /// Process a block for the Key-hash hash function
-    fn hash_key_process_block(
-        &mut self,
-        input: &[u8],
-        mut output: &mut [u8],
-        finish: bool,
-    ) -> Result<(), Error> {
-        self.backend.aes128_ecb_encrypt_set_key(&output)?;
-        if finish {
-            self.backend
-                .aes128_ecb_encrypt_finish(&input, &mut output)?;
-        } else {
-            self.backend
-                .aes128_ecb_encrypt_process_block(&input, &mut output)?;
-        }
-        // XOR the input into the hash block
-        for n in 0..BLOCK_SIZE {
-            output[n] ^= input[n];
-        }
-        Ok(())
-    }
-
-    /// Key-hash hash function
-    fn hash_key_hash(&mut self, input: &[u8], output: &mut [u8]) -> Result<(), Error> {
-        assert!(input.len() < 4096);
-
-        // Clear the first block of output
-        for b in output[..BLOCK_SIZE].iter_mut() {
-            *b = 0;
-        }
-
-        let mut blocks = input.chunks_exact(BLOCK_SIZE);
-
-        // Process input data in cipher block sized chunks
-        loop {
-            match blocks.next() {
-                Some(input_block) => {
-                    self.hash_key_process_block(&input_block, &mut output[..BLOCK_SIZE], false)?;
-                }
-                None => {
-                    let mut block = [0u8; BLOCK_SIZE];
-                    let remainder = blocks.remainder();
-                    assert!(remainder.len() < BLOCK_SIZE - 3);
-                    block[..remainder.len()].copy_from_slice(remainder);
-                    // Pad the message M by right-concatenating to M the bit ‘1’ followed by the
-                    // smallest non-negative number of ‘0’ bits, such that the resulting string has
-                    // length 14 (mod 16) octets:
-                    block[remainder.len()] = 0x80;
-                    let input_len = input.len() as u16 * 8;
-                    // Form the padded message M' by right-concatenating to the resulting string the
-                    // 16-bit string that is equal to the binary representation of the integer l:
-                    block[BLOCK_SIZE - 2] = (input_len >> 8) as u8;
-                    block[BLOCK_SIZE - 1] = (input_len & 0xff) as u8;
-                    self.hash_key_process_block(&block, &mut output[..BLOCK_SIZE], true)?;
-                    break;
-                }
-            }
-        }
-        Ok(())
+    /// The crypto backend used by the provider
+    pub fn backend(&mut self) -> &mut Backend {
+        &mut self.backend
     }
 
     /// Keyed hash function for message authentication (HMAC)
@@ -106,34 +242,7 @@ where
         input: u8,
         result: &mut [u8],
     ) -> Result<(), Error> {
-        const HASH_INNER_PAD: u8 = 0x36;
-        const HASH_OUTER_PAD: u8 = 0x5c;
-        let mut hash_in = [0; BLOCK_SIZE * 2];
-        let mut hash_out = [0; BLOCK_SIZE + 1];
-
-        {
-            // XOR the key with the outer padding
-            for n in 0..KEY_SIZE {
-                hash_in[n] = key[n] ^ HASH_OUTER_PAD;
-            }
-            // XOR the key with the inner padding
-            for n in 0..KEY_SIZE {
-                hash_out[n] = key[n] ^ HASH_INNER_PAD;
-            }
-            // Append the input byte
-            hash_out[BLOCK_SIZE] = input;
-            // Hash hash_out to form (Key XOR opad) || H((Key XOR ipad) || text)
-            self.hash_key_hash(&hash_out[..=BLOCK_SIZE], &mut hash_in[BLOCK_SIZE..])?;
-            // Hash hash_in to get the result
-            self.hash_key_hash(&hash_in, &mut hash_out)?;
-        }
-        {
-            // Take the key
-            let (output_key, _) = result.split_at_mut(KEY_SIZE);
-            output_key.copy_from_slice(&hash_out[..KEY_SIZE]);
-        }
-
-        Ok(())
+        keyed_hash(&mut self.backend, key, input, result)
     }
 
     pub fn decrypt_payload(
@@ -142,63 +251,26 @@ where
         security_level: SecurityLevel,
         payload: &[u8],
         secure_header_offset: usize,
-        mut output_payload: &mut [u8],
+        output_payload: &mut [u8],
     ) -> Result<usize, Error> {
-        let (mut header, used) = SecurityHeader::unpack(&payload[secure_header_offset..])?;
-        header.control.set_level(security_level);
-
-        self.buffer[..payload.len()].copy_from_slice(&payload);
-
-        header
-            .control
-            .pack(&mut self.buffer[secure_header_offset..=secure_header_offset])?;
-
-        let mic_bytes = header.control.level.mic_bytes();
-
-        if payload.len() - used < mic_bytes {
-            return Err(Error::WrongNumberOfBytes);
+        if payload.len() > self.buffer.len() {
+            return Err(Error::NotEnoughSpace);
         }
-
-        let mut updated_key = [0; KEY_SIZE];
-
-        match header.control.identifier {
-            KeyIdentifier::KeyTransport => {
-                self.hash_key(&key, 0x00, &mut updated_key)?;
-            }
-            KeyIdentifier::KeyLoad => {
-                self.hash_key(&key, 0x02, &mut updated_key)?;
-            }
-            _ => {
-                updated_key.copy_from_slice(&key[..]);
-            }
-        }
-
-        let payload_start = secure_header_offset + used;
-
-        let mut nonce = [0; 13];
-        header.get_nonce(&mut nonce)?;
-
-        let aad = &self.buffer[..payload_start];
+        let buffer = &mut self.buffer[..payload.len()];
+        buffer.copy_from_slice(payload);
+        let (payload_start, used) = decrypt_secured(
+            &mut self.backend,
+            key,
+            security_level,
+            buffer,
+            secure_header_offset,
+            None,
+            output_payload,
+        )?;
         // Payload == a with length l(a), 0 < l(a) < 2^64
-        let mic_offset = payload.len() - mic_bytes;
-        let payload_length = payload.len();
-
-        if mic_offset <= payload_start {
+        if payload_start + security_level.mic_bytes() == payload.len() {
             return Err(Error::WrongNumberOfBytes);
         }
-
-        let payload = &self.buffer[payload_start..mic_offset];
-        let mic = &self.buffer[mic_offset..payload_length];
-
-        let used = self.backend.ccmstar_decrypt(
-            &updated_key,
-            &nonce,
-            &payload,
-            &mic,
-            &aad,
-            &mut output_payload,
-        )?;
-
         Ok(used)
     }
 
@@ -213,15 +285,14 @@ where
         let nwk_used = header.pack(&mut self.buffer[..])?;
         let sec_used = security_header.pack(&mut self.buffer[nwk_used..])?;
         let aad_length = nwk_used + sec_used;
-        let mut nonce = [0u8; 13];
-        security_header.get_nonce(&mut nonce)?;
+        let nonce = Nonce::nwk(&security_header, security_header.control.level)?;
         let mut mic = [0u8; 16];
         let mic_length = security_header.control.level.mic_bytes();
         output_payload[..aad_length].copy_from_slice(&self.buffer[..aad_length]);
 
         let encrypted_length = self.backend.ccmstar_encrypt(
             key,
-            &nonce,
+            nonce.as_ref(),
             payload,
             &mut mic[..mic_length],
             &self.buffer[..aad_length],
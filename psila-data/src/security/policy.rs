@@ -0,0 +1,120 @@
+//! # Security policy
+//!
+//! Decides how outgoing application service frames are secured.
+
+use crate::error::Error;
+use crate::security::SecurityLevel;
+
+/// Maximum number of clusters requiring application service security
+pub const MAX_SECURED_CLUSTERS: usize = 8;
+
+/// Trust center security policy for application service (APS) frames
+///
+/// Frames for the required clusters are secured with the link key at the
+/// level of the policy, all other frames only rely on the network security.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SecurityPolicy {
+    level: SecurityLevel,
+    clusters: [Option<(u16, u16)>; MAX_SECURED_CLUSTERS],
+}
+
+impl SecurityPolicy {
+    /// Create a policy securing the required clusters with `level`, no
+    /// clusters are required initially
+    pub fn new(level: SecurityLevel) -> Self {
+        Self {
+            level,
+            clusters: [None; MAX_SECURED_CLUSTERS],
+        }
+    }
+
+    /// Security level of the required clusters
+    pub fn level(&self) -> SecurityLevel {
+        self.level
+    }
+
+    /// Require APS security for `cluster` in `profile`
+    ///
+    /// Fails with `Error::NotEnoughSpace` if `MAX_SECURED_CLUSTERS` already
+    /// are required.
+    pub fn require(&mut self, cluster: u16, profile: u16) -> Result<(), Error> {
+        if self.is_required(cluster, profile) {
+            return Ok(());
+        }
+        let slot = self
+            .clusters
+            .iter_mut()
+            .find(|entry| entry.is_none())
+            .ok_or(Error::NotEnoughSpace)?;
+        *slot = Some((profile, cluster));
+        Ok(())
+    }
+
+    /// Stop requiring APS security for `cluster` in `profile`
+    pub fn release(&mut self, cluster: u16, profile: u16) {
+        for entry in self.clusters.iter_mut() {
+            if *entry == Some((profile, cluster)) {
+                *entry = None;
+            }
+        }
+    }
+
+    /// Check if `cluster` in `profile` requires APS security
+    pub fn is_required(&self, cluster: u16, profile: u16) -> bool {
+        self.clusters.contains(&Some((profile, cluster)))
+    }
+
+    /// The security level to apply to a APS frame for `cluster` in
+    /// `profile`, `SecurityLevel::None` if APS security isn't required
+    pub fn level_for(&self, cluster: u16, profile: u16) -> SecurityLevel {
+        if self.is_required(cluster, profile) {
+            self.level
+        } else {
+            SecurityLevel::None
+        }
+    }
+}
+
+impl Default for SecurityPolicy {
+    /// Policy using encryption with a 32-bit MIC, the level used by Zigbee
+    fn default() -> Self {
+        Self::new(SecurityLevel::EncryptedIntegrity32)
+    }
+}
+
+#[cfg(all(test, not(feature = "core")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn level_for_cluster() {
+        let mut policy = SecurityPolicy::default();
+        // Door lock cluster requires encryption, on/off doesn't
+        policy.require(0x0101, 0x0104).unwrap();
+        assert_eq!(
+            policy.level_for(0x0101, 0x0104),
+            SecurityLevel::EncryptedIntegrity32
+        );
+        assert_eq!(policy.level_for(0x0006, 0x0104), SecurityLevel::None);
+        // Same cluster in another profile
+        assert_eq!(policy.level_for(0x0101, 0xc05e), SecurityLevel::None);
+
+        policy.release(0x0101, 0x0104);
+        assert_eq!(policy.level_for(0x0101, 0x0104), SecurityLevel::None);
+    }
+
+    #[test]
+    fn require_too_many_clusters() {
+        let mut policy = SecurityPolicy::new(SecurityLevel::EncryptedIntegrity64);
+        for cluster in 0..MAX_SECURED_CLUSTERS as u16 {
+            policy.require(cluster, 0x0104).unwrap();
+        }
+        // Already required clusters don't need a free slot
+        policy.require(0x0000, 0x0104).unwrap();
+        assert_eq!(policy.require(0x0100, 0x0104), Err(Error::NotEnoughSpace));
+        assert_eq!(
+            policy.level_for(0x0003, 0x0104),
+            SecurityLevel::EncryptedIntegrity64
+        );
+    }
+}
@@ -0,0 +1,180 @@
+//! # Secure sender
+//!
+//! Owns the outgoing frame counter so that no nonce is used twice.
+
+use psila_crypto::CryptoBackend;
+
+use crate::application_service::{self, ApplicationServiceHeader};
+use crate::common::address::ExtendedAddress;
+use crate::common::key::KEY_SIZE;
+use crate::error::Error;
+use crate::network::NetworkHeader;
+use crate::security::{CryptoProvider, FrameCounter, SecurityHeader, SecurityLevel};
+
+/// Sender of secured frames
+///
+/// The nonce is built from the source address and the outgoing frame
+/// counter. The counter is taken and incremented for each frame, also when
+/// the encryption fails, so a nonce is never reused with the same key. The
+/// counter never wraps, once exhausted `Error::FrameCounterExhausted` is
+/// returned until a new key is used.
+#[derive(Clone, Debug)]
+pub struct SecureSender {
+    source: ExtendedAddress,
+    level: SecurityLevel,
+    counter: Option<FrameCounter>,
+}
+
+impl SecureSender {
+    /// Create a sender for `source`, starting with frame counter zero
+    pub fn new(source: ExtendedAddress, level: SecurityLevel) -> Self {
+        Self::with_counter(source, level, FrameCounter::default())
+    }
+
+    /// Create a sender for `source`, continuing from `counter`, e.g. a
+    /// counter restored from persistent storage
+    pub fn with_counter(
+        source: ExtendedAddress,
+        level: SecurityLevel,
+        counter: FrameCounter,
+    ) -> Self {
+        Self {
+            source,
+            level,
+            counter: Some(counter),
+        }
+    }
+
+    /// Source address of the frames
+    pub fn source(&self) -> ExtendedAddress {
+        self.source
+    }
+
+    /// Security level of the frames
+    pub fn level(&self) -> SecurityLevel {
+        self.level
+    }
+
+    /// Change the security level of the following frames
+    pub fn set_level(&mut self, level: SecurityLevel) {
+        self.level = level;
+    }
+
+    /// The frame counter of the next frame, `None` if exhausted
+    pub fn counter(&self) -> Option<FrameCounter> {
+        self.counter
+    }
+
+    /// Restart the frame counter, only allowed when the key has changed
+    pub fn reset_counter(&mut self) {
+        self.counter = Some(FrameCounter::default());
+    }
+
+    /// Take the frame counter for the next frame
+    fn next_counter(&mut self) -> Result<FrameCounter, Error> {
+        let counter = self.counter.ok_or(Error::FrameCounterExhausted)?;
+        self.counter = counter.next();
+        Ok(counter)
+    }
+
+    /// Create the auxiliary header of the next network frame, using a new
+    /// frame counter
+    pub fn next_network_header(&mut self, key_sequence: u8) -> Result<SecurityHeader, Error> {
+        let counter = self.next_counter()?;
+        Ok(SecurityHeader::network_header(
+            self.level,
+            counter,
+            self.source,
+            key_sequence,
+        ))
+    }
+
+    /// Create the auxiliary header of the next application service frame
+    /// secured with the data key, using a new frame counter
+    pub fn next_data_header(&mut self) -> Result<SecurityHeader, Error> {
+        let counter = self.next_counter()?;
+        Ok(SecurityHeader::data_header(self.level, counter))
+    }
+
+    /// Encrypt and authenticate a network frame with `header` and `payload`
+    /// into `output`, see `CryptoProvider::encrypt_network_frame`
+    pub fn encrypt_network_frame<Backend>(
+        &mut self,
+        provider: &mut CryptoProvider<Backend>,
+        header: NetworkHeader,
+        key: &[u8; KEY_SIZE],
+        key_sequence: u8,
+        payload: &[u8],
+        output: &mut [u8],
+    ) -> Result<usize, Error>
+    where
+        Backend: CryptoBackend,
+    {
+        let security_header = self.next_network_header(key_sequence)?;
+        provider.encrypt_network_frame(header, key, security_header, payload, output)
+    }
+
+    /// Encrypt and authenticate a APS frame with `header` and `payload`,
+    /// secured with the link key `key`, into `output`, see
+    /// `application_service::encrypt_frame`
+    pub fn encrypt_application_service_frame<Backend>(
+        &mut self,
+        backend: &mut Backend,
+        header: &ApplicationServiceHeader,
+        key: &[u8; KEY_SIZE],
+        payload: &[u8],
+        output: &mut [u8],
+    ) -> Result<usize, Error>
+    where
+        Backend: CryptoBackend,
+    {
+        let security_header = self.next_data_header()?;
+        application_service::encrypt_frame(
+            backend,
+            key,
+            self.source,
+            header,
+            &security_header,
+            payload,
+            output,
+        )
+    }
+}
+
+#[cfg(all(test, not(feature = "core")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counter_increments() {
+        let source = ExtendedAddress::new(0x0011_2233_4455_6677);
+        let mut sender = SecureSender::new(source, SecurityLevel::EncryptedIntegrity32);
+        assert_eq!(sender.counter(), Some(FrameCounter::new(0)));
+        let first = sender.next_network_header(1).unwrap();
+        let second = sender.next_network_header(1).unwrap();
+        assert_eq!(first.counter, FrameCounter::new(0));
+        assert_eq!(second.counter, FrameCounter::new(1));
+        assert_eq!(second.source, Some(source));
+        assert_eq!(second.sequence, Some(1));
+        assert_eq!(sender.counter(), Some(FrameCounter::new(2)));
+
+        // The counter never wraps
+        let mut sender = SecureSender::with_counter(
+            source,
+            SecurityLevel::EncryptedIntegrity32,
+            FrameCounter::new(u32::max_value()),
+        );
+        let last = sender.next_network_header(1).unwrap();
+        assert_eq!(last.counter, FrameCounter::new(u32::max_value()));
+        assert_eq!(sender.counter(), None);
+        assert_eq!(
+            sender.next_network_header(1).unwrap_err(),
+            Error::FrameCounterExhausted
+        );
+        sender.reset_counter();
+        assert_eq!(
+            sender.next_network_header(2).unwrap().counter,
+            FrameCounter::new(0)
+        );
+    }
+}
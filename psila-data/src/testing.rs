@@ -0,0 +1,47 @@
+//! # Testing helpers
+//!
+//! Lower level packing helpers and key derivations, only reachable through
+//! the public types otherwise. Enabled with the `testing` feature for
+//! interoperability tests outside of this crate, not part of the stable
+//! interface.
+
+use psila_crypto::CryptoBackend;
+
+use crate::cluster_library::scenes::ExtensionFieldSetVec;
+use crate::common::key::KEY_SIZE;
+use crate::security::KeyIdentifier;
+use crate::Error;
+
+/// Keyed hash function for message authentication (HMAC) of `input`, the
+/// hash is written to `result`
+pub fn keyed_hash<Backend: CryptoBackend>(
+    backend: &mut Backend,
+    key: &[u8; KEY_SIZE],
+    input: u8,
+    result: &mut [u8],
+) -> Result<(), Error> {
+    crate::security::keyed_hash(backend, key, input, result)
+}
+
+/// Derive the APS key for `identifier` from the link key `key`
+pub fn derive_key<Backend: CryptoBackend>(
+    backend: &mut Backend,
+    key: &[u8; KEY_SIZE],
+    identifier: KeyIdentifier,
+) -> Result<[u8; KEY_SIZE], Error> {
+    crate::security::derive_key(backend, key, identifier)
+}
+
+/// Pack the extension field sets of a scene, returning the number of bytes
+/// written
+pub fn pack_extension_field_sets(
+    extensions: &ExtensionFieldSetVec,
+    data: &mut [u8],
+) -> Result<usize, Error> {
+    crate::cluster_library::scenes::pack_extensions(extensions, data)
+}
+
+/// Unpack extension field sets of a scene, all of `data` is used
+pub fn unpack_extension_field_sets(data: &[u8]) -> Result<(ExtensionFieldSetVec, usize), Error> {
+    crate::cluster_library::scenes::unpack_extensions(data)
+}
@@ -43,8 +43,65 @@ macro_rules! extended_enum {
 }
 
 /// Zero out all values in the slice
+///
+/// Intended for wiping key material. The writes are volatile and followed by
+/// a compiler fence so that the clearing is not optimised away, even if the
+/// slice is never read again.
 pub fn clear(slice: &mut [u8]) {
     for v in slice.iter_mut() {
-        *v = 0;
+        unsafe { core::ptr::write_volatile(v, 0) };
+    }
+    core::sync::atomic::compiler_fence(core::sync::atomic::Ordering::SeqCst);
+}
+
+/// Format the bytes in `data` as lower-case hexadecimal into `output`
+///
+/// Each byte is written as two characters. Bytes that does not fit in
+/// `output` are omitted. Returns the number of characters written.
+pub fn hex_into(data: &[u8], output: &mut [u8]) -> usize {
+    const DIGITS: &[u8; 16] = b"0123456789abcdef";
+    let mut offset = 0;
+    for byte in data.iter() {
+        if offset + 2 > output.len() {
+            break;
+        }
+        output[offset] = DIGITS[usize::from(byte >> 4)];
+        output[offset + 1] = DIGITS[usize::from(byte & 0x0f)];
+        offset += 2;
+    }
+    offset
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clear_slice() {
+        let mut data = [0x5au8; 16];
+        clear(&mut data[4..12]);
+        assert_eq!(data[..4], [0x5a; 4]);
+        assert_eq!(data[4..12], [0x00; 8]);
+        assert_eq!(data[12..], [0x5a; 4]);
+
+        let mut data = [0xffu8, 0x01, 0x80];
+        clear(&mut data);
+        assert_eq!(data, [0x00; 3]);
+    }
+
+    #[test]
+    fn format_hex() {
+        let mut output = [0u8; 16];
+        let used = hex_into(&[0x00, 0x1f, 0xa5, 0xff], &mut output);
+        assert_eq!(used, 8);
+        assert_eq!(&output[..used], b"001fa5ff");
+
+        let mut output = [0u8; 5];
+        let used = hex_into(&[0x12, 0x34, 0x56], &mut output);
+        assert_eq!(used, 4);
+        assert_eq!(&output[..used], b"1234");
+
+        let used = hex_into(&[], &mut output);
+        assert_eq!(used, 0);
     }
 }
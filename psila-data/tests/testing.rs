@@ -0,0 +1,35 @@
+#![cfg(all(feature = "testing", not(feature = "core")))]
+
+use psila_data::cluster_library::scenes::ExtensionFieldSet;
+use psila_data::testing::{pack_extension_field_sets, unpack_extension_field_sets};
+use psila_data::Error;
+
+#[test]
+fn extension_field_sets() {
+    // On/off on, level 0x80
+    let data = [0x06, 0x00, 0x01, 0x01, 0x08, 0x00, 0x01, 0x80];
+    let (extensions, used) = unpack_extension_field_sets(&data).unwrap();
+    assert_eq!(used, data.len());
+    assert_eq!(
+        extensions,
+        [
+            ExtensionFieldSet {
+                cluster: 0x0006,
+                data: vec![0x01],
+            },
+            ExtensionFieldSet {
+                cluster: 0x0008,
+                data: vec![0x80],
+            },
+        ]
+    );
+    let mut buffer = [0u8; 8];
+    assert_eq!(pack_extension_field_sets(&extensions, &mut buffer), Ok(8));
+    assert_eq!(buffer, data);
+
+    assert_eq!(
+        unpack_extension_field_sets(&data[..7]).err(),
+        Some(Error::WrongNumberOfBytes)
+    );
+    assert!(unpack_extension_field_sets(&[]).unwrap().0.is_empty());
+}
@@ -256,65 +256,30 @@ impl Parser {
                             rsp.descriptor.maximum_incoming_transfer_size,
                             rsp.descriptor.maximum_outgoing_transfer_size
                         );
-                        if rsp
-                            .descriptor
-                            .server_mask
-                            .flags
-                            .contains(node_descriptor::ServerFlags::PRIMARY_TRUST_CENTER)
-                        {
+                        if rsp.descriptor.server_mask.is_primary_trust_center() {
                             print!(" TC");
                         }
-                        if rsp
-                            .descriptor
-                            .server_mask
-                            .flags
-                            .contains(node_descriptor::ServerFlags::BACKUP_TRUST_CENTER)
-                        {
+                        if rsp.descriptor.server_mask.is_backup_trust_center() {
                             print!(" BTC");
                         }
-                        if rsp
-                            .descriptor
-                            .server_mask
-                            .flags
-                            .contains(node_descriptor::ServerFlags::PRIMARY_BINDING_TABLE)
-                        {
+                        if rsp.descriptor.server_mask.is_primary_binding_table_cache() {
                             print!(" BT");
                         }
-                        if rsp
-                            .descriptor
-                            .server_mask
-                            .flags
-                            .contains(node_descriptor::ServerFlags::BACKUP_BINDING_TABLE)
-                        {
+                        if rsp.descriptor.server_mask.is_backup_binding_table_cache() {
                             print!(" BBT");
                         }
-                        if rsp
-                            .descriptor
-                            .server_mask
-                            .flags
-                            .contains(node_descriptor::ServerFlags::PRIMARY_DISCOVERY_CACHE)
-                        {
+                        if rsp.descriptor.server_mask.is_primary_discovery_cache() {
                             print!(" DC");
                         }
-                        if rsp
-                            .descriptor
-                            .server_mask
-                            .flags
-                            .contains(node_descriptor::ServerFlags::BACKUP_DISCOVERY_CACHE)
-                        {
+                        if rsp.descriptor.server_mask.is_backup_discovery_cache() {
                             print!(" BDC");
                         }
-                        if rsp
-                            .descriptor
-                            .server_mask
-                            .flags
-                            .contains(node_descriptor::ServerFlags::NETWORK_MANAGER)
-                        {
+                        if rsp.descriptor.server_mask.is_network_manager() {
                             print!(" NM");
                         }
                         print!(
                             " {:02}",
-                            rsp.descriptor.server_mask.stack_complience_version
+                            rsp.descriptor.server_mask.stack_compliance_revision()
                         );
                         if rsp.descriptor.descriptor_capability.contains(node_descriptor::DescriptorCapability::EXTENDED_ACTIVE_END_POINT_LIST_AVAILABLE) {
                             print!(" EEL");
@@ -337,6 +302,21 @@ impl Parser {
                             rsp.descriptor.level
                         );
                     }
+                    DeviceProfileMessage::ComplexDescriptorRequest(req) => {
+                        print!("Complex Descriptor Request {}", req.address);
+                    }
+                    DeviceProfileMessage::ComplexDescriptorResponse(rsp) => {
+                        print!(
+                            "Complex Descriptor Response {} {:?}",
+                            rsp.address, rsp.status
+                        );
+                        for (tag, field) in rsp.fields() {
+                            print!(" {:?} ", tag);
+                            for b in field.iter() {
+                                print!("{:02x}", b);
+                            }
+                        }
+                    }
                     DeviceProfileMessage::SimpleDescriptorRequest(req) => {
                         print!(
                             "Simple Descriptor Request {} Endpoint {:02x}",
@@ -401,6 +381,36 @@ impl Parser {
                             da.network_address, da.ieee_address, da.capability
                         );
                     }
+                    DeviceProfileMessage::SystemServerDiscoveryRequest(req) => {
+                        print!(
+                            "System Server Discovery Request {:?}",
+                            req.server_mask.flags
+                        );
+                    }
+                    DeviceProfileMessage::SystemServerDiscoveryResponse(rsp) => {
+                        print!(
+                            "System Server Discovery Response {:?} {:?} Stack Compliance Revision {}",
+                            rsp.status,
+                            rsp.server_mask.flags,
+                            rsp.server_mask.stack_compliance_revision()
+                        );
+                    }
+                    DeviceProfileMessage::EndDeviceBindRequest(req) => {
+                        print!(
+                            "End Device Bind Request: Target {} Source {} Endpoint {:02x} Profile {:04x} Input",
+                            req.target, req.source_address, req.source_endpoint, req.profile
+                        );
+                        for cluster in req.input_clusters_entries() {
+                            print!(" {:04x}", cluster);
+                        }
+                        print!(" Output");
+                        for cluster in req.output_clusters_entries() {
+                            print!(" {:04x}", cluster);
+                        }
+                    }
+                    DeviceProfileMessage::EndDeviceBindResponse(rsp) => {
+                        print!("End Device Bind Response: {:?}", rsp.status);
+                    }
                     DeviceProfileMessage::ManagementLinkQualityIndicatorRequest(start_index) => {
                         print!("LQI Request {} ", start_index);
                     }
@@ -597,7 +607,7 @@ impl Parser {
                     print!("Dst {:02x} ", addr);
                 }
                 if let Some(group) = header.group {
-                    print!("Group {:04x} ", group);
+                    print!("Group {} ", group);
                 }
                 if let Some(cluster) = header.cluster {
                     print!("Cluster {:04x} ", cluster);
@@ -608,7 +618,16 @@ impl Parser {
                 if let Some(addr) = header.source {
                     print!("Src {:02x} ", addr);
                 }
-                println!("Counter {:02x}", header.counter);
+                print!("Counter {:02x}", header.counter);
+                if let Some(extended) = header.extended {
+                    if let Some(block) = extended.block_number {
+                        print!(" Block {}", block);
+                    }
+                    if let Some(bitfield) = extended.acknowledge_bitfield {
+                        print!(" Ack {:08b}", bitfield);
+                    }
+                }
+                println!();
                 let mut processed_payload = [0u8; 256];
                 let length = if header.control.security {
                     self.security
@@ -758,15 +777,16 @@ impl Parser {
                     println!();
                 }
                 Command::NetworkReport(nr) => {
-                    println!(
-                        "Network Conflict {} {}",
-                        nr.extended_pan_identifier, nr.pan_identifier
-                    );
+                    print!("Network Conflict {}", nr.extended_pan_identifier);
+                    for pan_identifier in nr.pan_identifiers() {
+                        print!(" {}", pan_identifier);
+                    }
+                    println!();
                 }
                 Command::NetworkUpdate(nu) => {
                     println!(
-                        "Network Update {} {}",
-                        nu.extended_pan_identifier, nu.pan_identifier
+                        "Network Update {} Update {} {}",
+                        nu.extended_pan_identifier, nu.update_identifier, nu.pan_identifier
                     );
                 }
                 Command::EndDeviceTimeoutRequest(edtr) => {
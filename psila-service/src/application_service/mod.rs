@@ -1,7 +1,7 @@
-use core::cell::{Cell, RefCell};
+use core::cell::RefCell;
 
 use crate::security::SecurityManager;
-use crate::{Error, Identity};
+use crate::{Counters, Error, Identity};
 use psila_crypto::CryptoBackend;
 use psila_data::{
     application_service::ApplicationServiceHeader,
@@ -10,48 +10,28 @@ use psila_data::{
     },
     network::{header::DiscoverRoute, NetworkHeader},
     pack::Pack,
-    CapabilityInformation, NetworkAddress,
+    security::SecurityLevel,
+    CapabilityInformation, ExtendedAddress, NetworkAddress,
 };
 
 pub struct ApplicationServiceContext {
-    aps_sequence: Cell<u8>,
-    dp_sequence: Cell<u8>,
-    nwk_sequence: Cell<u8>,
+    counters: Counters,
     buffer: RefCell<[u8; 128]>,
 }
 
 impl Default for ApplicationServiceContext {
     fn default() -> Self {
         Self {
-            aps_sequence: Cell::new(0),
-            dp_sequence: Cell::new(0),
-            nwk_sequence: Cell::new(0),
+            counters: Counters::default(),
             buffer: RefCell::new([0u8; 128]),
         }
     }
 }
 
 impl ApplicationServiceContext {
-    /// Get the next sequence number
-    fn aps_sequence_next(&self) -> u8 {
-        let sequence = (*self).aps_sequence.get();
-        let sequence = sequence.wrapping_add(1);
-        (*self).aps_sequence.set(sequence);
-        sequence
-    }
-    /// Get the next sequence number
-    fn dp_sequence_next(&self) -> u8 {
-        let sequence = (*self).dp_sequence.get();
-        let sequence = sequence.wrapping_add(1);
-        (*self).dp_sequence.set(sequence);
-        sequence
-    }
-    /// Get the next sequence number
-    fn nwk_sequence_next(&self) -> u8 {
-        let sequence = (*self).nwk_sequence.get();
-        let sequence = sequence.wrapping_add(1);
-        (*self).nwk_sequence.set(sequence);
-        sequence
+    /// The sequence numbers and counters used when building frames
+    pub fn counters(&self) -> &Counters {
+        &self.counters
     }
 
     pub fn build_acknowledge<CB: CryptoBackend>(
@@ -63,26 +43,110 @@ impl ApplicationServiceContext {
         security: &mut SecurityManager<CB>,
     ) -> Result<usize, Error> {
         let aps_header = ApplicationServiceHeader::new_acknowledge_header(source_header);
+        self.build_data(
+            source,
+            destination,
+            None,
+            &aps_header,
+            &[],
+            buffer,
+            security,
+        )
+    }
+
+    /// Build a secured network frame to `destination` carrying the
+    /// application service header and payload
+    ///
+    /// If the security bit of `aps_header` is set, or the security policy
+    /// requires security for the cluster and profile of the header, the
+    /// application service payload is also secured, with the link key shared
+    /// with `destination_address`, the extended address of the destination
+    /// if known. Group addressed frames can't be secured with a link key and
+    /// fail with `Error::InvalidAddress`.
+    pub fn build_data<CB: CryptoBackend>(
+        &self,
+        identity: &Identity,
+        destination: NetworkAddress,
+        destination_address: Option<ExtendedAddress>,
+        aps_header: &ApplicationServiceHeader,
+        payload: &[u8],
+        buffer: &mut [u8],
+        security: &mut SecurityManager<CB>,
+    ) -> Result<usize, Error> {
         let network_header = NetworkHeader::new_data_header(
             2,                              // protocol version
             DiscoverRoute::EnableDiscovery, // discovery route
             true,                           // security
             destination,                    // destination address
-            source.short,                   // source address
+            identity.short,                 // source address
             16,                             // radius
-            self.nwk_sequence_next(),       // network sequence number
+            self.counters.next_network(),   // network sequence number
             None,                           // source route frame
         );
-        let used = aps_header.pack(&mut self.buffer.borrow_mut()[..])?;
+        let mut aps_header = *aps_header;
+        if let (Some(cluster), Some(profile)) = (aps_header.cluster, aps_header.profile) {
+            if security.application_service_level(cluster, profile) != SecurityLevel::None {
+                aps_header.control.security = true;
+            }
+        }
+        let offset = if aps_header.control.security {
+            if aps_header.group.is_some() {
+                return Err(Error::InvalidAddress);
+            }
+            security.encrypt_application_service_payload(
+                destination_address,
+                &aps_header,
+                payload,
+                &mut self.buffer.borrow_mut()[..],
+            )?
+        } else {
+            let offset = aps_header.pack(&mut self.buffer.borrow_mut()[..])?;
+            if self.buffer.borrow().len() < offset + payload.len() {
+                return Err(Error::NotEnoughSpace);
+            }
+            self.buffer.borrow_mut()[offset..offset + payload.len()].copy_from_slice(payload);
+            offset + payload.len()
+        };
         let used = security.encrypt_network_payload(
-            source.extended,
             network_header,
-            &self.buffer.borrow()[..used],
+            &self.buffer.borrow()[..offset],
             buffer,
         )?;
         Ok(used)
     }
 
+    /// Build a device profile frame to `destination`, see `build_data`
+    fn build_device_profile<CB: CryptoBackend>(
+        &self,
+        identity: &Identity,
+        destination: NetworkAddress,
+        cluster: u16,
+        frame: &DeviceProfileFrame,
+        buffer: &mut [u8],
+        security: &mut SecurityManager<CB>,
+    ) -> Result<usize, Error> {
+        let aps_header = ApplicationServiceHeader::new_data_header(
+            0,                                        // destination
+            cluster,                                  // cluster
+            0,                                        // profile
+            0,                                        // source
+            self.counters.next_application_service(), // counter
+            false,                                    // acknowledge request
+            false,                                    // security
+        );
+        let mut payload = [0u8; 128];
+        let used = frame.pack(&mut payload)?;
+        self.build_data(
+            identity,
+            destination,
+            None,
+            &aps_header,
+            &payload[..used],
+            buffer,
+            security,
+        )
+    }
+
     pub fn build_device_announce<CB: CryptoBackend>(
         &self,
         identity: &Identity,
@@ -97,38 +161,16 @@ impl ApplicationServiceContext {
         };
         let message = DeviceProfileMessage::DeviceAnnounce(device_announce);
         let device_profile_frame = DeviceProfileFrame {
-            transaction_sequence: self.dp_sequence_next(),
+            transaction_sequence: self.counters.next_device_profile(),
             message,
         };
-        let aps_header = ApplicationServiceHeader::new_data_header(
-            0,                                        // destination
-            ClusterIdentifier::DeviceAnnounce.into(), // cluster
-            0,                                        // profile
-            0,                                        // source
-            self.aps_sequence_next(),                 // counter
-            false,                                    // acknowledge request
-            false,                                    // security
-        );
-        let network_header = NetworkHeader::new_data_header(
-            2,                              // protocol version
-            DiscoverRoute::EnableDiscovery, // discovery route
-            true,                           // security
-            NetworkAddress::new(0xfffd),    // destination address
-            identity.short,                 // source address
-            16,                             // radius
-            self.nwk_sequence_next(),       // network sequence number
-            None,                           // source route frame
-        );
-        let mut offset = 0;
-        let used = aps_header.pack(&mut self.buffer.borrow_mut()[offset..])?;
-        offset += used;
-        let used = device_profile_frame.pack(&mut self.buffer.borrow_mut()[offset..])?;
-        offset += used;
-        let used = security.encrypt_network_payload(
-            identity.extended,
-            network_header,
-            &self.buffer.borrow()[..offset],
+        let used = self.build_device_profile(
+            identity,
+            NetworkAddress::new(0xfffd),
+            ClusterIdentifier::DeviceAnnounce.into(),
+            &device_profile_frame,
             buffer,
+            security,
         )?;
         Ok(used)
     }
@@ -170,41 +212,18 @@ impl ApplicationServiceContext {
         };
         let message = DeviceProfileMessage::NodeDescriptorResponse(ndr);
         let device_profile_frame = DeviceProfileFrame {
-            transaction_sequence: self.dp_sequence_next(),
+            transaction_sequence: self.counters.next_device_profile(),
             message,
         };
         let cluster =
             device_profile::RESPONSE | u16::from(ClusterIdentifier::NodeDescriptorRequest);
-        let aps_header = ApplicationServiceHeader::new_data_header(
-            0,                        // destination
-            cluster,                  // cluster
-            0,                        // profile
-            0,                        // source
-            self.aps_sequence_next(), // counter
-            false,                    // acknowledge request
-            false,                    // security
-        );
-        let network_header = NetworkHeader::new_data_header(
-            2,                              // protocol version
-            DiscoverRoute::EnableDiscovery, // discovery route
-            true,                           // security
-            destination,                    // destination address
-            source.short,                   // source address
-            16,                             // radius
-            self.nwk_sequence_next(),       // network sequence number
-            None,                           // source route frame
-        );
-        let mut offset = 0;
-
-        let used = aps_header.pack(&mut self.buffer.borrow_mut()[offset..])?;
-        offset += used;
-        let used = device_profile_frame.pack(&mut self.buffer.borrow_mut()[offset..])?;
-        offset += used;
-        let used = security.encrypt_network_payload(
-            source.extended,
-            network_header,
-            &self.buffer.borrow()[..offset],
+        let used = self.build_device_profile(
+            source,
+            destination,
+            cluster,
+            &device_profile_frame,
             buffer,
+            security,
         )?;
         log::info!("Node descriptor response");
         Ok(used)
@@ -229,43 +248,19 @@ impl ApplicationServiceContext {
         };
         let message = DeviceProfileMessage::ActiveEndpointResponse(aer);
         let device_profile_frame = DeviceProfileFrame {
-            transaction_sequence: self.dp_sequence_next(),
+            transaction_sequence: self.counters.next_device_profile(),
             message,
         };
         let cluster =
             device_profile::RESPONSE | u16::from(ClusterIdentifier::ActiveEndpointRequest);
-        let aps_header = ApplicationServiceHeader::new_data_header(
-            0,                        // destination
-            cluster,                  // cluster
-            0,                        // profile
-            0,                        // source
-            self.aps_sequence_next(), // counter
-            false,                    // acknowledge request
-            false,                    // security
-        );
-        let network_header = NetworkHeader::new_data_header(
-            2,                              // protocol version
-            DiscoverRoute::EnableDiscovery, // discovery route
-            true,                           // security
-            destination,                    // destination address
-            source.short,                   // source address
-            16,                             // radius
-            self.nwk_sequence_next(),       // network sequence number
-            None,                           // source route frame
-        );
-        let mut offset = 0;
-
-        let used = aps_header.pack(&mut self.buffer.borrow_mut()[offset..])?;
-        offset += used;
-        let used = device_profile_frame.pack(&mut self.buffer.borrow_mut()[offset..])?;
-        offset += used;
-        let used = security.encrypt_network_payload(
-            source.extended,
-            network_header,
-            &self.buffer.borrow()[..offset],
+        let used = self.build_device_profile(
+            source,
+            destination,
+            cluster,
+            &device_profile_frame,
             buffer,
+            security,
         )?;
-
         log::info!("Active endpint response");
         Ok(used)
     }
@@ -293,40 +288,18 @@ impl ApplicationServiceContext {
         };
         let message = DeviceProfileMessage::PowerDescriptorResponse(pdr);
         let device_profile_frame = DeviceProfileFrame {
-            transaction_sequence: self.dp_sequence_next(),
+            transaction_sequence: self.counters.next_device_profile(),
             message,
         };
         let cluster =
             device_profile::RESPONSE | u16::from(ClusterIdentifier::PowerDescriptorRequest);
-        let aps_header = ApplicationServiceHeader::new_data_header(
-            0,                        // destination
-            cluster,                  // cluster
-            0,                        // profile
-            0,                        // source
-            self.aps_sequence_next(), // counter
-            false,                    // acknowledge request
-            false,                    // security
-        );
-        let network_header = NetworkHeader::new_data_header(
-            2,                              // protocol version
-            DiscoverRoute::EnableDiscovery, // discovery route
-            true,                           // security
-            destination,                    // destination address
-            source.short,                   // source address
-            16,                             // radius
-            self.nwk_sequence_next(),       // network sequence number
-            None,                           // source route frame
-        );
-        let mut offset = 0;
-        let used = aps_header.pack(&mut self.buffer.borrow_mut()[offset..])?;
-        offset += used;
-        let used = device_profile_frame.pack(&mut self.buffer.borrow_mut()[offset..])?;
-        offset += used;
-        let used = security.encrypt_network_payload(
-            source.extended,
-            network_header,
-            &self.buffer.borrow()[..offset],
+        let used = self.build_device_profile(
+            source,
+            destination,
+            cluster,
+            &device_profile_frame,
             buffer,
+            security,
         )?;
         log::info!("Power descriptor response");
         Ok(used)
@@ -358,40 +331,18 @@ impl ApplicationServiceContext {
         };
         let message = DeviceProfileMessage::SimpleDescriptorResponse(sdr);
         let device_profile_frame = DeviceProfileFrame {
-            transaction_sequence: self.dp_sequence_next(),
+            transaction_sequence: self.counters.next_device_profile(),
             message,
         };
         let cluster =
             device_profile::RESPONSE | u16::from(ClusterIdentifier::SimpleDescriptorRequest);
-        let aps_header = ApplicationServiceHeader::new_data_header(
-            0,                        // destination
-            cluster,                  // cluster
-            0,                        // profile
-            0,                        // source
-            self.aps_sequence_next(), // counter
-            false,                    // acknowledge request
-            false,                    // security
-        );
-        let network_header = NetworkHeader::new_data_header(
-            2,                              // protocol version
-            DiscoverRoute::EnableDiscovery, // discovery route
-            true,                           // security
-            destination,                    // destination address
-            source.short,                   // source address
-            16,                             // radius
-            self.nwk_sequence_next(),       // network sequence number
-            None,                           // source route frame
-        );
-        let mut offset = 0;
-        let used = aps_header.pack(&mut self.buffer.borrow_mut()[offset..])?;
-        offset += used;
-        let used = device_profile_frame.pack(&mut self.buffer.borrow_mut()[offset..])?;
-        offset += used;
-        let used = security.encrypt_network_payload(
-            source.extended,
-            network_header,
-            &self.buffer.borrow()[..offset],
+        let used = self.build_device_profile(
+            source,
+            destination,
+            cluster,
+            &device_profile_frame,
             buffer,
+            security,
         )?;
         log::info!("Simple descriptor response");
         Ok(used)
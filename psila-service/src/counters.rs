@@ -0,0 +1,105 @@
+//! # Sequence numbers and counters
+//!
+//! The sequence numbers and counters used by the different layers, kept in
+//! one place so that they can be persisted and restored together.
+
+use core::cell::Cell;
+
+/// Sequence numbers and counters for the network (NWK), application service
+/// (APS), device profile (ZDP) and cluster library (ZCL) layers
+#[derive(Debug, Default)]
+pub struct Counters {
+    network: Cell<u8>,
+    application_service: Cell<u8>,
+    device_profile: Cell<u8>,
+    cluster_library: Cell<u8>,
+}
+
+impl Counters {
+    /// Create counters starting from previously stored values
+    pub fn new(
+        network: u8,
+        application_service: u8,
+        device_profile: u8,
+        cluster_library: u8,
+    ) -> Self {
+        Self {
+            network: Cell::new(network),
+            application_service: Cell::new(application_service),
+            device_profile: Cell::new(device_profile),
+            cluster_library: Cell::new(cluster_library),
+        }
+    }
+
+    fn next(counter: &Cell<u8>) -> u8 {
+        let value = counter.get().wrapping_add(1);
+        counter.set(value);
+        value
+    }
+
+    /// Get the next network (NWK) sequence number
+    pub fn next_network(&self) -> u8 {
+        Self::next(&self.network)
+    }
+
+    /// Get the next application service (APS) counter
+    pub fn next_application_service(&self) -> u8 {
+        Self::next(&self.application_service)
+    }
+
+    /// Get the next device profile (ZDP) transaction sequence number
+    pub fn next_device_profile(&self) -> u8 {
+        Self::next(&self.device_profile)
+    }
+
+    /// Get the next cluster library (ZCL) transaction sequence number
+    pub fn next_cluster_library(&self) -> u8 {
+        Self::next(&self.cluster_library)
+    }
+
+    /// The last used network (NWK) sequence number
+    pub fn network(&self) -> u8 {
+        self.network.get()
+    }
+
+    /// The last used application service (APS) counter
+    pub fn application_service(&self) -> u8 {
+        self.application_service.get()
+    }
+
+    /// The last used device profile (ZDP) transaction sequence number
+    pub fn device_profile(&self) -> u8 {
+        self.device_profile.get()
+    }
+
+    /// The last used cluster library (ZCL) transaction sequence number
+    pub fn cluster_library(&self) -> u8 {
+        self.cluster_library.get()
+    }
+}
+
+#[cfg(all(test, not(feature = "core")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counters_advance_independently() {
+        let counters = Counters::default();
+        assert_eq!(counters.next_network(), 1);
+        assert_eq!(counters.next_network(), 2);
+        assert_eq!(counters.next_application_service(), 1);
+        assert_eq!(counters.next_device_profile(), 1);
+        assert_eq!(counters.next_device_profile(), 2);
+        assert_eq!(counters.next_device_profile(), 3);
+        assert_eq!(counters.network(), 2);
+        assert_eq!(counters.application_service(), 1);
+        assert_eq!(counters.device_profile(), 3);
+        assert_eq!(counters.cluster_library(), 0);
+
+        let counters = Counters::new(0xff, 0x10, 0x20, 0x30);
+        assert_eq!(counters.next_network(), 0x00);
+        assert_eq!(counters.next_application_service(), 0x11);
+        assert_eq!(counters.next_device_profile(), 0x21);
+        assert_eq!(counters.next_cluster_library(), 0x31);
+    }
+}
@@ -11,10 +11,16 @@ pub enum Error {
     InvalidExtendedAddress,
     /// The provided address is invalid
     InvalidAddress,
+    /// The provided endpoint is invalid
+    InvalidEndpoint,
+    /// The channel is not valid on the channel page
+    InvalidChannel,
     /// Could not parse the packet
     MalformedPacket,
     /// Not enough space to complete the operation
     NotEnoughSpace,
+    /// The buffer is too small, the number of bytes needed is provided
+    BufferTooSmall(usize),
     /// A psila-data error occurred
     DataError(psila_data::Error),
     /// A psila-crypto error occurred
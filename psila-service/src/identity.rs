@@ -1,7 +1,7 @@
 use core::default::Default;
 
 use ieee802154::mac::Address;
-use psila_data::{ExtendedAddress, ShortAddress};
+use psila_data::{ExtendedAddress, ExtendedPanIdentifier, ShortAddress};
 
 /// Node identity
 #[derive(Clone, Copy, Debug)]
@@ -10,6 +10,8 @@ pub struct Identity {
     pub short: ShortAddress,
     /// The extended address of the node
     pub extended: ExtendedAddress,
+    /// The extended PAN identifier of the network the node has joined
+    pub extended_pan: ExtendedPanIdentifier,
 }
 
 impl Identity {
@@ -19,6 +21,7 @@ impl Identity {
         Identity {
             short: psila_data::ShortAddress::broadcast(),
             extended: extended_address,
+            extended_pan: ExtendedPanIdentifier::broadcast(),
         }
     }
 
@@ -32,6 +35,11 @@ impl Identity {
         self.extended != ExtendedAddress::broadcast()
     }
 
+    /// Check if the extended PAN identifier has been assigned
+    pub fn assigned_extended_pan(&self) -> bool {
+        !self.extended_pan.is_broadcast()
+    }
+
     /// Check if the provided address was addressed to this identity
     pub fn addressed_to(&self, address: &Address) -> bool {
         match *address {
@@ -60,6 +68,7 @@ impl Default for Identity {
         Identity {
             short: ShortAddress::broadcast(),
             extended: ExtendedAddress::broadcast(),
+            extended_pan: ExtendedPanIdentifier::broadcast(),
         }
     }
 }
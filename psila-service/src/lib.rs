@@ -1,6 +1,9 @@
 //! # Psila Service
 
-#![no_std]
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(feature = "alloc")]
+extern crate alloc;
 
 use core::cell::Cell;
 use core::convert::TryFrom;
@@ -12,16 +15,23 @@ use psila_data::{self, pack::Pack, CapabilityInformation, ExtendedAddress, Key};
 use psila_crypto::CryptoBackend;
 
 mod application_service;
+mod counters;
 mod error;
 mod identity;
 pub mod mac;
+pub mod neighbor_table;
+pub mod registry;
 mod security;
 
+pub use counters::Counters;
 pub use error::Error;
 pub use identity::Identity;
+pub use security::MAX_LINK_KEYS;
 
 use application_service::ApplicationServiceContext;
 use mac::MacService;
+use neighbor_table::NeighborTable;
+use registry::Registry;
 
 /// Max buffer size
 pub const PACKET_BUFFER_MAX: usize = 128;
@@ -41,6 +51,8 @@ pub struct PsilaService<'a, N: ArrayLength<u8>, CB> {
     tx_queue: Producer<'a, N>,
     state: Cell<NetworkState>,
     identity: Identity,
+    neighbors: NeighborTable,
+    registry: Registry,
 }
 
 impl<'a, N: ArrayLength<u8>, CB> PsilaService<'a, N, CB>
@@ -64,14 +76,49 @@ where
         Self {
             mac: MacService::new(address, capability),
             application_service: ApplicationServiceContext::default(),
-            security_manager: security::SecurityManager::new(crypto, default_link_key),
+            security_manager: security::SecurityManager::new(crypto, address, default_link_key),
             capability,
             tx_queue,
             state: Cell::new(NetworkState::Orphan),
             identity: Identity::default(),
+            neighbors: NeighborTable::default(),
+            registry: Registry::default(),
         }
     }
 
+    /// The endpoints supported by the application
+    pub fn registry(&self) -> &Registry {
+        &self.registry
+    }
+
+    /// The endpoints supported by the application, add the application
+    /// endpoints here to have the device profile and cluster library requests
+    /// answered
+    pub fn registry_mut(&mut self) -> &mut Registry {
+        &mut self.registry
+    }
+
+    /// Set the trust center security policy, deciding which application
+    /// service frames are secured with the link key
+    pub fn set_security_policy(&mut self, policy: psila_data::security::SecurityPolicy) {
+        self.security_manager.set_policy(policy);
+    }
+
+    /// Set the link key shared with the device with the extended address
+    /// `address`, used instead of the default link key when securing
+    /// application service frames to the device
+    ///
+    /// At most `MAX_LINK_KEYS` link keys are kept, further keys fail with
+    /// `Error::NotEnoughSpace`.
+    pub fn set_link_key(&mut self, address: ExtendedAddress, key: Key) -> Result<(), Error> {
+        self.security_manager.set_link_key(address, key)
+    }
+
+    /// The sequence numbers and counters used by the service
+    pub fn counters(&self) -> &Counters {
+        self.application_service.counters()
+    }
+
     pub fn get_state(&self) -> NetworkState {
         self.state.get()
     }
@@ -80,6 +127,12 @@ where
         (*self).state.set(state);
     }
 
+    /// Network addresses which has been detected to be used by more than one
+    /// device, these should be reassigned
+    pub fn address_conflicts(&self) -> impl Iterator<Item = &psila_data::NetworkAddress> {
+        self.neighbors.conflicts()
+    }
+
     /// Push a packet onto the queue
     fn queue_packet(&mut self, data: &[u8]) -> Result<(), Error> {
         assert!(data.len() < (u8::max_value() as usize));
@@ -129,6 +182,116 @@ where
         }
     }
 
+    /// Send a cluster specific command to all endpoints which are members of
+    /// the group
+    ///
+    /// The frame is sent as a network broadcast to all devices with the
+    /// receiver on when idle, using application service group
+    /// addressing. No default response is requested. Fails with
+    /// `Error::InvalidAddress` if the security policy requires application
+    /// service security for the cluster, group frames can't be secured with
+    /// a link key.
+    pub fn send_group_cluster_command(
+        &mut self,
+        group: psila_data::GroupIdentifier,
+        cluster: u16,
+        profile: u16,
+        source_endpoint: u8,
+        command: u8,
+        payload: &[u8],
+    ) -> Result<(), Error> {
+        use psila_data::{
+            application_service::ApplicationServiceHeader,
+            cluster_library::{ClusterLibraryHeader, Direction},
+            common::address::SHORT_ADDRESS_BROADCAST_RX_ON_WHEN_IDLE,
+        };
+        let mut message = [0u8; PACKET_BUFFER_MAX];
+        let zcl_header = ClusterLibraryHeader::new_cluster_command(
+            Direction::ToServer,
+            self.counters().next_cluster_library(),
+            command,
+            true,
+        );
+        let mut message_size = zcl_header.pack(&mut message)?;
+        if message.len() < message_size + payload.len() {
+            return Err(Error::NotEnoughSpace);
+        }
+        message[message_size..message_size + payload.len()].copy_from_slice(payload);
+        message_size += payload.len();
+
+        let aps_header = ApplicationServiceHeader::new_group_data_header(
+            group,
+            cluster,
+            profile,
+            source_endpoint,
+            self.counters().next_application_service(),
+            false,
+        );
+        let mut buffer = [0u8; PACKET_BUFFER_MAX];
+        let mac_header = self
+            .mac
+            .build_data_header(psila_data::NetworkAddress::broadcast(), false);
+        let mac_header_len = mac_header.encode(&mut buffer);
+        let nwk_frame_size = self.application_service.build_data(
+            &self.identity,
+            psila_data::NetworkAddress::new(SHORT_ADDRESS_BROADCAST_RX_ON_WHEN_IDLE),
+            None,
+            &aps_header,
+            &message[..message_size],
+            &mut buffer[mac_header_len..],
+            &mut self.security_manager,
+        )?;
+        self.queue_packet(&buffer[..(mac_header_len + nwk_frame_size)])
+    }
+
+    /// Leave the network
+    ///
+    /// Broadcasts a network leave command to the neighbors and returns to
+    /// the orphan state. With `rejoin` set the neighbors are told that the
+    /// device will rejoin the network. This is a network layer leave, not a
+    /// MAC disassociation.
+    pub fn leave(&mut self, rejoin: bool) -> Result<(), Error> {
+        use psila_data::{
+            common::address::SHORT_ADDRESS_BROADCAST_RX_ON_WHEN_IDLE,
+            network::{
+                commands::{Command, Leave},
+                NetworkHeader,
+            },
+        };
+        if let NetworkState::Orphan = self.get_state() {
+            return Ok(());
+        }
+        let destination = psila_data::NetworkAddress::new(SHORT_ADDRESS_BROADCAST_RX_ON_WHEN_IDLE);
+        let network_header = NetworkHeader::new_command_header(
+            2,                              // protocol version
+            true,                           // security
+            destination,                    // destination address
+            self.identity.short,            // source address
+            Some(self.identity.extended),   // source IEEE address
+            1,                              // radius
+            self.counters().next_network(), // network sequence number
+        );
+        let mut command = [0u8; 2];
+        let command_size = Command::Leave(Leave::new(rejoin, false)).pack(&mut command)?;
+
+        let mut buffer = [0u8; PACKET_BUFFER_MAX];
+        let mac_header = self
+            .mac
+            .build_data_header(psila_data::NetworkAddress::broadcast(), false);
+        let mac_header_len = mac_header.encode(&mut buffer);
+        let nwk_frame_size = self.security_manager.encrypt_network_payload(
+            network_header,
+            &command[..command_size],
+            &mut buffer[mac_header_len..],
+        )?;
+        self.queue_packet(&buffer[..(mac_header_len + nwk_frame_size)])?;
+
+        self.mac.reset();
+        self.identity = Identity::default();
+        self.set_state(NetworkState::Orphan);
+        Ok(())
+    }
+
     /// Receive, call this method when new data has been received by the radio
     /// ### Return
     /// true if the message was addressed to this device
@@ -136,15 +299,16 @@ where
         let mut buffer = [0u8; PACKET_BUFFER_MAX];
         match mac::Frame::decode(data, false) {
             Ok(frame) => {
-                if !self.mac.destination_me_or_broadcast(&frame) {
+                if !self.mac.is_for_us(&frame) {
                     return Ok(false);
                 }
                 if self.mac.requests_acknowledge(&frame) {
                     // If the frame is a data request frame, send an acknowledge with pending set
                     // Use the frame sequence number from the received frame in the acknowledge
+                    let pending = self.mac.data_pending(&frame);
                     let packet_length =
                         self.mac
-                            .build_acknowledge(frame.header.seq, false, &mut buffer);
+                            .build_acknowledge(frame.header.seq, pending, &mut buffer);
                     self.queue_packet(&buffer[..packet_length])?;
                 }
                 Ok(true)
@@ -161,7 +325,7 @@ where
         let mut buffer = [0u8; PACKET_BUFFER_MAX];
         match mac::Frame::decode(data, false) {
             Ok(frame) => {
-                if !self.mac.destination_me_or_broadcast(&frame) {
+                if !self.mac.is_for_us(&frame) {
                     return Ok(0);
                 }
                 let (packet_length, timeout) = self.mac.handle_frame(&frame, &mut buffer)?;
@@ -200,6 +364,7 @@ where
         match frame.header.frame_type {
             mac::FrameType::Data => {
                 let (header, used) = NetworkHeader::unpack(frame.payload)?;
+                self.update_neighbors(&header);
                 let mut payload = [0u8; PACKET_BUFFER_MAX];
                 let payload_size = if header.control.security {
                     self.security_manager
@@ -223,6 +388,26 @@ where
         Ok(())
     }
 
+    /// Update the neighbor table and look for address conflicts
+    fn update_neighbors(&mut self, header: &psila_data::network::NetworkHeader) {
+        let identity = self.mac.identity();
+        if let Some(extended_address) = header.source_ieee_address {
+            if identity.assigned_short()
+                && header.source_address == identity.short
+                && extended_address != identity.extended
+            {
+                log::warn!(
+                    "Address conflict with own address {}",
+                    header.source_address
+                );
+                self.neighbors.flag_conflict(header.source_address);
+            }
+        }
+        if self.neighbors.update_from_header(header) {
+            log::warn!("Address conflict for {}", header.source_address);
+        }
+    }
+
     /// Handle a network frame
     fn handle_network_frame(
         &mut self,
@@ -256,7 +441,7 @@ where
             }
             FrameType::Command => {
                 // handle command
-                self.handle_network_command(nwk_payload)?;
+                self.handle_network_command(nwk_header, nwk_payload)?;
             }
             FrameType::InterPan => {
                 log::info!("Handle inter-PAN");
@@ -266,7 +451,11 @@ where
         Ok(())
     }
 
-    fn handle_network_command(&self, payload: &[u8]) -> Result<(), Error> {
+    fn handle_network_command(
+        &mut self,
+        header: &psila_data::network::NetworkHeader,
+        payload: &[u8],
+    ) -> Result<(), Error> {
         use psila_data::network::commands::Command;
         match Command::unpack(payload) {
             Ok((cmd, _used)) => match cmd {
@@ -279,8 +468,11 @@ where
                 Command::NetworkStatus(_) => {
                     log::info!("> Network Network status");
                 }
-                Command::Leave(_) => {
+                Command::Leave(leave) => {
                     log::info!("> Network Leave");
+                    if leave.request && header.destination_address == self.identity.short {
+                        self.leave(leave.rejoin)?;
+                    }
                 }
                 Command::RouteRecord(_) => {
                     log::info!("> Network Route record");
@@ -381,6 +573,7 @@ where
                             }
                             _ => {
                                 log::info!("Profile {:04x} {:?}", profile, profile_id);
+                                self.handle_cluster_library(nwk_header, aps_header, aps_payload)?;
                             }
                         }
                     } else {
@@ -428,6 +621,108 @@ where
         Ok(())
     }
 
+    /// Handle a cluster library frame, the global commands are answered
+    /// from the registry
+    fn handle_cluster_library(
+        &mut self,
+        nwk_header: &psila_data::network::NetworkHeader,
+        aps_header: &psila_data::application_service::ApplicationServiceHeader,
+        aps_payload: &[u8],
+    ) -> Result<(), Error> {
+        use psila_data::{
+            application_service::ApplicationServiceHeader,
+            cluster_library::{
+                ClusterLibraryHeader, Command, Direction, FrameType, GeneralCommandIdentifier,
+            },
+        };
+
+        let (header, used) = match ClusterLibraryHeader::unpack(aps_payload) {
+            Ok(result) => result,
+            Err(err) => {
+                log::warn!("> ZCL Failed to decode header, {:?}", err);
+                return Ok(());
+            }
+        };
+        if header.control.frame_type != FrameType::Global {
+            return Ok(());
+        }
+        let (cluster, profile, endpoint, source) = match (
+            aps_header.cluster,
+            aps_header.profile,
+            aps_header.destination,
+            aps_header.source,
+        ) {
+            (Some(cluster), Some(profile), Some(endpoint), Some(source)) => {
+                (cluster, profile, endpoint, source)
+            }
+            _ => return Ok(()),
+        };
+        let identifier = match GeneralCommandIdentifier::try_from(header.command) {
+            Ok(identifier) => identifier,
+            Err(_) => {
+                log::info!("> ZCL Unknown command {:02x}", header.command);
+                return Ok(());
+            }
+        };
+        let command = match Command::unpack(&aps_payload[used..], identifier) {
+            Ok((command, _)) => command,
+            Err(err) => {
+                log::warn!("> ZCL Failed to decode command, {:?}", err);
+                return Ok(());
+            }
+        };
+        let response = match command {
+            Command::DiscoverAttributes(req) => {
+                log::info!("> ZCL Discover attributes {:02x} {:04x}", endpoint, cluster);
+                match self.registry.discover_attributes(endpoint, cluster, &req) {
+                    Some(response) => Command::DiscoverAttributesResponse(response),
+                    None => return Ok(()),
+                }
+            }
+            _ => return Ok(()),
+        };
+
+        let mut message = [0u8; PACKET_BUFFER_MAX];
+        let zcl_header = ClusterLibraryHeader::new_global_command(
+            Direction::ToClient,
+            header.transaction_sequence,
+            GeneralCommandIdentifier::DiscoverAttributesResponse,
+            true,
+        );
+        let mut message_size = zcl_header.pack(&mut message)?;
+        let (used, _) = response.pack(&mut message[message_size..])?;
+        message_size += used;
+
+        let aps_header = ApplicationServiceHeader::new_data_header(
+            source,
+            cluster,
+            profile,
+            endpoint,
+            self.counters().next_application_service(),
+            false,
+            false,
+        );
+        let mut buffer = [0u8; PACKET_BUFFER_MAX];
+        let mac_header = self.mac.build_data_header(
+            nwk_header.source_address, // destination address
+            false,                     // request acknowledge
+        );
+        let mac_header_len = mac_header.encode(&mut buffer);
+        let destination_address = nwk_header
+            .source_ieee_address
+            .or_else(|| self.neighbors.extended_address(nwk_header.source_address));
+        let nwk_frame_size = self.application_service.build_data(
+            &self.identity,
+            nwk_header.source_address,
+            destination_address,
+            &aps_header,
+            &message[..message_size],
+            &mut buffer[mac_header_len..],
+            &mut self.security_manager,
+        )?;
+        self.queue_packet(&buffer[..(mac_header_len + nwk_frame_size)])
+    }
+
     fn handle_device_profile(
         &mut self,
         nwk_header: &psila_data::network::NetworkHeader,
@@ -492,20 +787,7 @@ where
                     nwk_header.source_address, // destination address
                     false,                     // request acknowledge
                 );
-                use psila_data::device_profile::SimpleDescriptor;
-                let descriptor = match req.endpoint {
-                    0x01 => {
-                        Some(SimpleDescriptor::new(
-                            req.endpoint,                                                     // endpoint
-                            u16::from(psila_data::common::ProfileIdentifier::HomeAutomation), // profile
-                            0x0100, // device, HA On-off light
-                            0,      // device version
-                            &[0x0000, 0x0006],
-                            &[],
-                        ))
-                    }
-                    _ => None,
-                };
+                let descriptor = self.registry.simple_descriptor(req.endpoint)?;
                 let mac_header_len = mac_header.encode(&mut buffer);
                 let nwk_frame_size = self.application_service.build_simple_descriptor_response(
                     &self.identity,
@@ -523,13 +805,14 @@ where
                     nwk_header.source_address, // destination address
                     false,                     // request acknowledge
                 );
-                let endpoints = [0x00, 0x01];
+                let mut endpoints = [0u8; registry::MAX_ENDPOINTS];
+                let count = self.registry.active_endpoints(&mut endpoints);
                 let mac_header_len = mac_header.encode(&mut buffer);
                 let nwk_frame_size = self.application_service.build_active_endpoint_response(
                     &self.identity,
                     nwk_header.source_address,
                     &req,
-                    &endpoints,
+                    &endpoints[..count],
                     &mut buffer[mac_header_len..],
                     &mut self.security_manager,
                 )?;
@@ -541,12 +824,18 @@ where
             DeviceProfileMessage::DeviceAnnounce(_req) => {
                 log::info!("> DP Device announce");
             }
+            DeviceProfileMessage::EndDeviceBindRequest(_req) => {
+                log::info!("> DP End device bind request");
+            }
             DeviceProfileMessage::ManagementLinkQualityIndicatorRequest(_req) => {
                 log::info!("> DP Link quality indicator request");
             }
             DeviceProfileMessage::NetworkAddressResponse(_rsp) => {
                 log::info!("> DP Network address response");
             }
+            DeviceProfileMessage::EndDeviceBindResponse(_rsp) => {
+                log::info!("> DP End device bind response");
+            }
             DeviceProfileMessage::IeeeAddressResponse(_rsp) => {
                 log::info!("> DP IEEE address response");
             }
@@ -556,6 +845,12 @@ where
             DeviceProfileMessage::PowerDescriptorResponse(_rsp) => {
                 log::info!("> DP Power descriptor response");
             }
+            DeviceProfileMessage::ComplexDescriptorRequest(_req) => {
+                log::info!("> DP Complex descriptor request");
+            }
+            DeviceProfileMessage::ComplexDescriptorResponse(_rsp) => {
+                log::info!("> DP Complex descriptor response");
+            }
             DeviceProfileMessage::SimpleDescriptorResponse(_rsp) => {
                 log::info!("> DP Simple descriptor response");
             }
@@ -578,6 +873,52 @@ mod tests {
     use super::*;
     use bbqueue::{consts::U512, BBBuffer};
     use psila_crypto_openssl::OpenSslBackend;
+    use psila_data::application_service::commands::transport_key::NetworkKey;
+    use psila_data::network::KeyStore;
+
+    /// Network key used by the tests
+    const NETWORK_KEY: [u8; 16] = [
+        0x01, 0x03, 0x05, 0x07, 0x09, 0x0b, 0x0d, 0x0f, 0x00, 0x02, 0x04, 0x06, 0x08, 0x0a, 0x0c,
+        0x0d,
+    ];
+
+    /// Key store with the test network key, the link key is shared with any
+    /// device
+    struct Keys(Key);
+
+    impl Default for Keys {
+        fn default() -> Self {
+            Keys(psila_data::security::DEFAULT_LINK_KEY.into())
+        }
+    }
+
+    impl KeyStore for Keys {
+        fn network_key(&self, _sequence: u8) -> Option<Key> {
+            Some(NETWORK_KEY.into())
+        }
+        fn link_key(&self, _address: psila_data::ExtendedAddress) -> Option<Key> {
+            Some(self.0)
+        }
+    }
+
+    /// The test network key, as sent by the trust center
+    fn test_network_key() -> NetworkKey {
+        NetworkKey {
+            key: NETWORK_KEY.into(),
+            sequence: 0,
+            destination: psila_data::ExtendedAddress::broadcast(),
+            source: psila_data::ExtendedAddress::new(0x0011_2233_4455_6677),
+        }
+    }
+
+    /// Identity of the device under test, joined to the test network
+    fn test_identity() -> Identity {
+        Identity {
+            short: psila_data::NetworkAddress::new(0x1234),
+            extended: psila_data::ExtendedAddress::new(0x8899_aabb_ccdd_eeff),
+            extended_pan: psila_data::ExtendedPanIdentifier::new(0x0021_2eff_ff03_2e38),
+        }
+    }
 
     #[test]
     fn build_beacon_request() {
@@ -612,4 +953,424 @@ mod tests {
 
         assert!(tx_consumer.read().is_err());
     }
+
+    #[test]
+    fn send_group_off() {
+        use psila_data::network::decrypt_frame;
+
+        let identity = test_identity();
+        let address = identity.extended;
+        let tx_queue: BBBuffer<U512> = BBBuffer::new();
+        let (tx_producer, mut tx_consumer) = tx_queue.try_split().unwrap();
+        let mut service = PsilaService::new(
+            OpenSslBackend::default(),
+            tx_producer,
+            address,
+            psila_data::security::DEFAULT_LINK_KEY.into(),
+        );
+        service.identity = identity;
+        service.security_manager.set_network_key(test_network_key());
+
+        // On/Off cluster, Home automation profile, Off command
+        service
+            .send_group_cluster_command(
+                psila_data::GroupIdentifier::new(0x4321),
+                0x0006,
+                0x0104,
+                0x01,
+                0x00,
+                &[],
+            )
+            .unwrap();
+
+        let grant = tx_consumer.read().unwrap();
+        let packet_length = grant[0] as usize;
+        let frame = mac::Frame::decode(&grant[1..=packet_length], false).unwrap();
+        assert!(matches!(
+            frame.header.destination,
+            mac::Address::Short(_, mac::ShortAddress(0xffff))
+        ));
+        let mut payload = [0u8; PACKET_BUFFER_MAX];
+        let payload = &mut payload[..frame.payload.len()];
+        payload.copy_from_slice(frame.payload);
+        grant.release(packet_length + 1);
+
+        let mut backend = OpenSslBackend::default();
+        let (network, aps) = decrypt_frame(&mut backend, &Keys::default(), payload).unwrap();
+        assert_eq!(
+            network.destination_address,
+            psila_data::NetworkAddress::new(0xfffd)
+        );
+        assert_eq!(
+            network.source_address,
+            psila_data::NetworkAddress::new(0x1234)
+        );
+        assert_eq!(
+            aps,
+            [
+                // APS, group 4321, cluster 0006, profile 0104, endpoint 01
+                0x0c, 0x21, 0x43, 0x06, 0x00, 0x04, 0x01, 0x01, 0x01,
+                // ZCL, cluster specific, no default response, Off
+                0x11, 0x01, 0x00,
+            ]
+        );
+    }
+
+    #[test]
+    fn leave_with_rejoin() {
+        use psila_data::network::commands::{Command, Leave};
+        use psila_data::network::{decrypt_frame, header::FrameType};
+
+        let identity = test_identity();
+        let address = identity.extended;
+        let tx_queue: BBBuffer<U512> = BBBuffer::new();
+        let (tx_producer, mut tx_consumer) = tx_queue.try_split().unwrap();
+        let mut service = PsilaService::new(
+            OpenSslBackend::default(),
+            tx_producer,
+            address,
+            psila_data::security::DEFAULT_LINK_KEY.into(),
+        );
+
+        // Nothing to leave while orphan
+        service.leave(true).unwrap();
+        assert!(tx_consumer.read().is_err());
+
+        service.identity = identity;
+        service.set_state(NetworkState::Secure);
+        service.security_manager.set_network_key(test_network_key());
+
+        service.leave(true).unwrap();
+        assert!(matches!(service.get_state(), NetworkState::Orphan));
+        assert!(!service.identity.assigned_short());
+        assert_eq!(service.mac.state(), mac::State::Orphan);
+
+        let grant = tx_consumer.read().unwrap();
+        let packet_length = grant[0] as usize;
+        let frame = mac::Frame::decode(&grant[1..=packet_length], false).unwrap();
+        let mut payload = [0u8; PACKET_BUFFER_MAX];
+        let payload = &mut payload[..frame.payload.len()];
+        payload.copy_from_slice(frame.payload);
+        grant.release(packet_length + 1);
+
+        let mut backend = OpenSslBackend::default();
+        let (network, command) = decrypt_frame(&mut backend, &Keys::default(), payload).unwrap();
+        assert_eq!(network.control.frame_type, FrameType::Command);
+        assert_eq!(
+            network.destination_address,
+            psila_data::NetworkAddress::new(0xfffd)
+        );
+        assert_eq!(network.radius, 1);
+        assert_eq!(network.source_ieee_address, Some(address));
+        assert_eq!(command, [0x04, 0x20]);
+        assert_eq!(
+            Command::unpack(command).unwrap().0,
+            Command::Leave(Leave::new(true, false))
+        );
+    }
+
+    #[test]
+    fn build_secured_data() {
+        use psila_data::application_service::{self, ApplicationServiceHeader};
+        use psila_data::network::decrypt_frame;
+        use psila_data::security::DEFAULT_LINK_KEY;
+
+        let identity = test_identity();
+        let address = identity.extended;
+        let mut security = security::SecurityManager::new(
+            OpenSslBackend::default(),
+            address,
+            DEFAULT_LINK_KEY.into(),
+        );
+        security.set_network_key(test_network_key());
+        let context = ApplicationServiceContext::default();
+
+        // On/Off cluster, Home automation profile, Toggle command
+        let aps_header = ApplicationServiceHeader::new_data_header(
+            0x01, 0x0006, 0x0104, 0x01, 0x42, false, true,
+        );
+        let payload = [0x01, 0x17, 0x02];
+        let mut frame = [0u8; PACKET_BUFFER_MAX];
+        let size = context
+            .build_data(
+                &identity,
+                psila_data::NetworkAddress::new(0x0000),
+                None,
+                &aps_header,
+                &payload,
+                &mut frame,
+                &mut security,
+            )
+            .unwrap();
+
+        let keys = Keys::default();
+        let mut backend = OpenSslBackend::default();
+        let (network, aps) = decrypt_frame(&mut backend, &keys, &mut frame[..size]).unwrap();
+        assert_eq!(
+            network.source_address,
+            psila_data::NetworkAddress::new(0x1234)
+        );
+        let mut aps_frame = [0u8; PACKET_BUFFER_MAX];
+        let aps_frame = &mut aps_frame[..aps.len()];
+        aps_frame.copy_from_slice(aps);
+        // Auxiliary header with the frame counter and a 32-bit MIC
+        assert_eq!(aps_frame.len(), 8 + 5 + payload.len() + 4);
+        assert_ne!(aps_frame[13..16], payload);
+
+        let (header, decrypted) =
+            application_service::decrypt_frame(&mut backend, &keys, address, aps_frame).unwrap();
+        assert!(header.control.security);
+        assert_eq!(header.cluster, Some(0x0006));
+        assert_eq!(header.counter, 0x42);
+        assert_eq!(decrypted, payload);
+
+        // The security policy secures the frame without the security bit set
+        let mut policy = psila_data::security::SecurityPolicy::default();
+        policy.require(0x0006, 0x0104).unwrap();
+        security.set_policy(policy);
+        let aps_header = ApplicationServiceHeader::new_data_header(
+            0x01, 0x0006, 0x0104, 0x01, 0x43, false, false,
+        );
+        let size = context
+            .build_data(
+                &identity,
+                psila_data::NetworkAddress::new(0x0000),
+                None,
+                &aps_header,
+                &payload,
+                &mut frame,
+                &mut security,
+            )
+            .unwrap();
+        let (_, aps) = decrypt_frame(&mut backend, &keys, &mut frame[..size]).unwrap();
+        let mut aps_frame = [0u8; PACKET_BUFFER_MAX];
+        let aps_frame = &mut aps_frame[..aps.len()];
+        aps_frame.copy_from_slice(aps);
+        assert_eq!(aps_frame.len(), 8 + 5 + payload.len() + 4);
+        let (header, decrypted) =
+            application_service::decrypt_frame(&mut backend, &keys, address, aps_frame).unwrap();
+        assert!(header.control.security);
+        assert_eq!(decrypted, payload);
+
+        // Group frames can't be secured with the link key
+        let aps_header = ApplicationServiceHeader::new_group_data_header(
+            psila_data::GroupIdentifier::new(0x4321),
+            0x0006,
+            0x0104,
+            0x01,
+            0x44,
+            false,
+        );
+        assert_eq!(
+            context.build_data(
+                &identity,
+                psila_data::NetworkAddress::new(0xfffd),
+                None,
+                &aps_header,
+                &payload,
+                &mut frame,
+                &mut security,
+            ),
+            Err(Error::InvalidAddress)
+        );
+    }
+
+    #[test]
+    fn build_data_with_link_keys() {
+        use psila_data::application_service::{self, ApplicationServiceHeader};
+        use psila_data::network::decrypt_frame;
+        use psila_data::security::DEFAULT_LINK_KEY;
+
+        let first_key = Key::from([0x11; 16]);
+        let second_key = Key::from([0x22; 16]);
+        let first = psila_data::ExtendedAddress::new(0x0011_2233_4455_6677);
+        let second = psila_data::ExtendedAddress::new(0x0011_2233_4455_6688);
+        let identity = test_identity();
+        let address = identity.extended;
+        let mut security = security::SecurityManager::new(
+            OpenSslBackend::default(),
+            address,
+            DEFAULT_LINK_KEY.into(),
+        );
+        security.set_network_key(test_network_key());
+        security.set_link_key(first, first_key).unwrap();
+        security.set_link_key(second, second_key).unwrap();
+        assert_eq!(security.link_key(first), Some(first_key));
+        assert_eq!(security.link_key(second), Some(second_key));
+        let context = ApplicationServiceContext::default();
+        let mut backend = OpenSslBackend::default();
+        let payload = [0x01, 0x17, 0x02];
+
+        let destinations = [
+            (first, first_key),
+            (second, second_key),
+            (
+                psila_data::ExtendedAddress::new(0x0011_2233_4455_6699),
+                Key::from(DEFAULT_LINK_KEY),
+            ),
+        ];
+        for (counter, (destination, link_key)) in destinations.iter().enumerate() {
+            let aps_header = ApplicationServiceHeader::new_data_header(
+                0x01,
+                0x0006,
+                0x0104,
+                0x01,
+                counter as u8,
+                false,
+                true,
+            );
+            let mut frame = [0u8; PACKET_BUFFER_MAX];
+            let size = context
+                .build_data(
+                    &identity,
+                    psila_data::NetworkAddress::new(0x0001),
+                    Some(*destination),
+                    &aps_header,
+                    &payload,
+                    &mut frame,
+                    &mut security,
+                )
+                .unwrap();
+            let (_, aps) =
+                decrypt_frame(&mut backend, &Keys(*link_key), &mut frame[..size]).unwrap();
+            let mut aps_frame = [0u8; PACKET_BUFFER_MAX];
+            let aps_frame = &mut aps_frame[..aps.len()];
+            aps_frame.copy_from_slice(aps);
+            // Only the link key shared with the destination decrypts the frame
+            let mut other_frame = [0u8; PACKET_BUFFER_MAX];
+            let other_frame = &mut other_frame[..aps.len()];
+            other_frame.copy_from_slice(aps);
+            let other_key = if *link_key == first_key {
+                second_key
+            } else {
+                first_key
+            };
+            assert!(application_service::decrypt_frame(
+                &mut backend,
+                &Keys(other_key),
+                address,
+                other_frame
+            )
+            .is_err());
+            let (_, decrypted) = application_service::decrypt_frame(
+                &mut backend,
+                &Keys(*link_key),
+                address,
+                aps_frame,
+            )
+            .unwrap();
+            assert_eq!(decrypted, payload);
+        }
+
+        assert_eq!(security.remove_link_key(first), Some(first_key));
+        assert_eq!(security.link_key(first), None);
+    }
+
+    #[test]
+    fn simple_descriptor_from_registry() {
+        use psila_data::application_service::ApplicationServiceHeader;
+        use psila_data::cluster_library::AttributeDataType;
+        use psila_data::device_profile::{
+            ClusterIdentifier, DeviceProfileFrame, DeviceProfileMessage, SimpleDescriptorRequest,
+            Status, RESPONSE,
+        };
+        use psila_data::network::{decrypt_frame, header::DiscoverRoute, NetworkHeader};
+
+        let identity = test_identity();
+        let address = identity.extended;
+        let tx_queue: BBBuffer<U512> = BBBuffer::new();
+        let (tx_producer, mut tx_consumer) = tx_queue.try_split().unwrap();
+        let mut service = PsilaService::new(
+            OpenSslBackend::default(),
+            tx_producer,
+            address,
+            psila_data::security::DEFAULT_LINK_KEY.into(),
+        );
+        service.identity = identity;
+        service.security_manager.set_network_key(test_network_key());
+        service
+            .registry_mut()
+            .add(registry::Endpoint {
+                identifier: 0x0b,
+                profile: 0x0104,
+                device: 0x0100,
+                device_version: 1,
+                input_clusters: &[
+                    registry::Cluster {
+                        identifier: 0x0000,
+                        attributes: &[(0x0000, AttributeDataType::Unsigned8)],
+                    },
+                    registry::Cluster {
+                        identifier: 0x0006,
+                        attributes: &[(0x0000, AttributeDataType::Boolean)],
+                    },
+                ],
+                output_clusters: &[0x0019],
+            })
+            .unwrap();
+
+        let nwk_header = NetworkHeader::new_data_header(
+            2,
+            DiscoverRoute::EnableDiscovery,
+            true,
+            psila_data::NetworkAddress::new(0x1234),
+            psila_data::NetworkAddress::new(0x0000),
+            30,
+            0x10,
+            None,
+        );
+        let aps_header = ApplicationServiceHeader::new_data_header(
+            0x00,
+            ClusterIdentifier::SimpleDescriptorRequest.into(),
+            0x0000,
+            0x00,
+            0x20,
+            false,
+            false,
+        );
+        let frame = DeviceProfileFrame {
+            transaction_sequence: 0x30,
+            message: DeviceProfileMessage::SimpleDescriptorRequest(SimpleDescriptorRequest {
+                address: psila_data::NetworkAddress::new(0x1234),
+                endpoint: 0x0b,
+            }),
+        };
+        service
+            .handle_device_profile(&nwk_header, &aps_header, frame)
+            .unwrap();
+
+        let grant = tx_consumer.read().unwrap();
+        let packet_length = grant[0] as usize;
+        let frame = mac::Frame::decode(&grant[1..=packet_length], false).unwrap();
+        let mut payload = [0u8; PACKET_BUFFER_MAX];
+        let payload = &mut payload[..frame.payload.len()];
+        payload.copy_from_slice(frame.payload);
+        grant.release(packet_length + 1);
+
+        let mut backend = OpenSslBackend::default();
+        let (network, aps) = decrypt_frame(&mut backend, &Keys::default(), payload).unwrap();
+        assert_eq!(
+            network.destination_address,
+            psila_data::NetworkAddress::new(0x0000)
+        );
+        let (aps_header, used) = ApplicationServiceHeader::unpack(aps).unwrap();
+        let cluster = aps_header.cluster.unwrap();
+        assert_eq!(
+            cluster,
+            RESPONSE | u16::from(ClusterIdentifier::SimpleDescriptorRequest)
+        );
+        let (frame, _) = DeviceProfileFrame::unpack(&aps[used..], cluster).unwrap();
+        match frame.message {
+            DeviceProfileMessage::SimpleDescriptorResponse(rsp) => {
+                assert_eq!(rsp.status, Status::Success);
+                assert_eq!(rsp.descriptor.endpoint, 0x0b);
+                assert_eq!(rsp.descriptor.profile, 0x0104);
+                assert_eq!(rsp.descriptor.device, 0x0100);
+                assert_eq!(rsp.descriptor.device_version, 1);
+                assert_eq!(rsp.descriptor.input_clusters(), [0x0000, 0x0006]);
+                assert_eq!(rsp.descriptor.output_clusters(), [0x0019]);
+            }
+            _ => panic!("Expected a simple descriptor response"),
+        }
+    }
 }
@@ -0,0 +1,167 @@
+//! # Address pool
+//!
+//! Short addresses handed out by a coordinator to associating devices.
+
+use psila_data::ShortAddress;
+
+/// Default number of 32-bit words in the address pool of the MAC service
+pub const DEFAULT_ADDRESS_POOL_WORDS: usize = 4;
+
+/// Addresses from this and up are reserved for broadcasts, and the
+/// unassigned address
+const RESERVED_ADDRESSES: u16 = 0xfff8;
+
+/// Pool of short addresses, kept as a bit set of `WORDS` 32-bit words
+///
+/// Covers the addresses from `first` to `last`, at most `WORDS * 32`
+/// addresses. Broadcast and reserved addresses are never handed out.
+#[derive(Clone, Debug)]
+pub struct AddressPool<const WORDS: usize> {
+    first: u16,
+    count: usize,
+    allocated: [u32; WORDS],
+}
+
+impl<const WORDS: usize> AddressPool<WORDS> {
+    /// Create a pool with the addresses from `first` to `last`, inclusive
+    ///
+    /// The range is truncated to the capacity of the pool and to the
+    /// addresses below the reserved addresses.
+    pub fn new(first: ShortAddress, last: ShortAddress) -> Self {
+        let first = u16::from(first);
+        let last = u16::from(last).min(RESERVED_ADDRESSES - 1);
+        let count = if last < first {
+            0
+        } else {
+            usize::from(last - first) + 1
+        };
+        Self {
+            first,
+            count: count.min(WORDS * 32),
+            allocated: [0; WORDS],
+        }
+    }
+
+    /// Number of addresses covered by the pool
+    pub fn capacity(&self) -> usize {
+        self.count
+    }
+
+    /// Number of addresses handed out
+    pub fn len(&self) -> usize {
+        self.allocated
+            .iter()
+            .map(|word| word.count_ones() as usize)
+            .sum()
+    }
+
+    /// Check if no addresses are handed out
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Position of `address` in the bit set
+    fn index(&self, address: ShortAddress) -> Option<usize> {
+        let address = u16::from(address);
+        if address < self.first {
+            return None;
+        }
+        let index = usize::from(address - self.first);
+        if index < self.count {
+            Some(index)
+        } else {
+            None
+        }
+    }
+
+    /// Word and bit mask of `index` in the bit set
+    fn bit(index: usize) -> (usize, u32) {
+        (index / 32, 1 << (index % 32))
+    }
+
+    fn is_set(&self, index: usize) -> bool {
+        let (word, mask) = Self::bit(index);
+        self.allocated[word] & mask != 0
+    }
+
+    /// Check if `address` has been handed out
+    pub fn is_allocated(&self, address: ShortAddress) -> bool {
+        self.index(address)
+            .map_or(false, |index| self.is_set(index))
+    }
+
+    /// Hand out the lowest unused address
+    ///
+    /// Returns `None` if the pool is exhausted.
+    pub fn allocate(&mut self) -> Option<ShortAddress> {
+        let index = (0..self.count).find(|index| !self.is_set(*index))?;
+        let (word, mask) = Self::bit(index);
+        self.allocated[word] |= mask;
+        Some(ShortAddress::new(self.first + index as u16))
+    }
+
+    /// Return `address` to the pool, e.g. when a device leaves
+    ///
+    /// Returns false if the address wasn't handed out by the pool.
+    pub fn release(&mut self, address: ShortAddress) -> bool {
+        match self.index(address) {
+            Some(index) if self.is_set(index) => {
+                let (word, mask) = Self::bit(index);
+                self.allocated[word] &= !mask;
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+#[cfg(all(test, not(feature = "core")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn exhaustion() {
+        let mut pool = AddressPool::<1>::new(ShortAddress::new(0x0001), ShortAddress::new(0x0003));
+        assert_eq!(pool.capacity(), 3);
+        assert!(pool.is_empty());
+        assert_eq!(pool.allocate(), Some(ShortAddress::new(0x0001)));
+        assert_eq!(pool.allocate(), Some(ShortAddress::new(0x0002)));
+        assert_eq!(pool.allocate(), Some(ShortAddress::new(0x0003)));
+        assert_eq!(pool.allocate(), None);
+        assert_eq!(pool.len(), 3);
+
+        // Limited by the size of the bit set
+        let mut pool = AddressPool::<1>::new(ShortAddress::new(0x0100), ShortAddress::new(0x0200));
+        assert_eq!(pool.capacity(), 32);
+        for _ in 0..32 {
+            assert!(pool.allocate().is_some());
+        }
+        assert_eq!(pool.allocate(), None);
+
+        // Reserved addresses are never handed out
+        let mut pool = AddressPool::<1>::new(ShortAddress::new(0xfff6), ShortAddress::broadcast());
+        assert_eq!(pool.capacity(), 2);
+        assert_eq!(pool.allocate(), Some(ShortAddress::new(0xfff6)));
+        assert_eq!(pool.allocate(), Some(ShortAddress::new(0xfff7)));
+        assert_eq!(pool.allocate(), None);
+    }
+
+    #[test]
+    fn release_and_reallocate() {
+        let mut pool = AddressPool::<2>::new(ShortAddress::new(0x0001), ShortAddress::new(0xfff7));
+        assert_eq!(pool.capacity(), 64);
+        for _ in 0..40 {
+            assert!(pool.allocate().is_some());
+        }
+        assert!(pool.is_allocated(ShortAddress::new(0x0022)));
+        assert!(pool.release(ShortAddress::new(0x0022)));
+        assert!(!pool.is_allocated(ShortAddress::new(0x0022)));
+        // Already released or outside the pool
+        assert!(!pool.release(ShortAddress::new(0x0022)));
+        assert!(!pool.release(ShortAddress::new(0x0000)));
+        assert!(!pool.release(ShortAddress::new(0x1000)));
+        assert_eq!(pool.len(), 39);
+        assert_eq!(pool.allocate(), Some(ShortAddress::new(0x0022)));
+        assert_eq!(pool.allocate(), Some(ShortAddress::new(0x0029)));
+    }
+}
@@ -0,0 +1,153 @@
+//! # Associations
+//!
+//! Devices associated with a coordinator, and the association responses
+//! waiting to be retrieved by the devices with a data request.
+
+use ieee802154::mac::command::AssociationStatus;
+use psila_data::{ExtendedAddress, ShortAddress};
+
+use crate::Error;
+
+/// Maximum number of devices remembered by a coordinator
+pub const MAX_ASSOCIATED_DEVICES: usize = 32;
+
+/// A device which has requested association
+#[derive(Clone, Copy, Debug)]
+pub struct Association {
+    /// Extended address of the device
+    pub device: ExtendedAddress,
+    /// Short address given to the device
+    pub address: ShortAddress,
+    /// Status of the association response waiting to be retrieved by the
+    /// device, as indirect data
+    pub pending: Option<AssociationStatus>,
+}
+
+/// Devices associated with a coordinator, at most `MAX_ASSOCIATED_DEVICES`
+#[derive(Clone, Debug)]
+pub struct Associations {
+    entries: [Option<Association>; MAX_ASSOCIATED_DEVICES],
+}
+
+impl Associations {
+    /// Create a empty table
+    pub fn new() -> Self {
+        Self {
+            entries: [None; MAX_ASSOCIATED_DEVICES],
+        }
+    }
+
+    /// The association of `device`
+    pub fn find(&self, device: ExtendedAddress) -> Option<&Association> {
+        self.entries
+            .iter()
+            .flatten()
+            .find(|association| association.device == device)
+    }
+
+    /// Add `association`, replacing any association of the same device
+    ///
+    /// Fails with `Error::NotEnoughSpace` if the table is full.
+    pub fn insert(&mut self, association: Association) -> Result<(), Error> {
+        let slot = match self
+            .entries
+            .iter()
+            .position(|entry| entry.map_or(false, |entry| entry.device == association.device))
+        {
+            Some(index) => index,
+            None => self
+                .entries
+                .iter()
+                .position(Option::is_none)
+                .ok_or(Error::NotEnoughSpace)?,
+        };
+        self.entries[slot] = Some(association);
+        Ok(())
+    }
+
+    /// Check if a association response is waiting for `device`
+    pub fn is_pending(&self, device: ExtendedAddress) -> bool {
+        self.find(device)
+            .map_or(false, |association| association.pending.is_some())
+    }
+
+    /// Take the association response waiting for `device`
+    ///
+    /// Devices which weren't given a address are forgotten once the response
+    /// has been retrieved.
+    pub fn take_pending(
+        &mut self,
+        device: ExtendedAddress,
+    ) -> Option<(ShortAddress, AssociationStatus)> {
+        let entry = self
+            .entries
+            .iter_mut()
+            .find(|entry| entry.map_or(false, |entry| entry.device == device))?;
+        let association = entry.as_mut()?;
+        let status = association.pending.take()?;
+        let address = association.address;
+        if !matches!(status, AssociationStatus::Successful) {
+            *entry = None;
+        }
+        Some((address, status))
+    }
+
+    /// Forget the device with the short address `address`
+    pub fn remove_address(&mut self, address: ShortAddress) -> Option<Association> {
+        let entry = self
+            .entries
+            .iter_mut()
+            .find(|entry| entry.map_or(false, |entry| entry.address == address))?;
+        entry.take()
+    }
+}
+
+impl Default for Associations {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(all(test, not(feature = "core")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pending_responses() {
+        let device = ExtendedAddress::new(0x8899_aabb_ccdd_eeff);
+        let mut associations = Associations::new();
+        associations
+            .insert(Association {
+                device,
+                address: ShortAddress::new(0x0001),
+                pending: Some(AssociationStatus::Successful),
+            })
+            .unwrap();
+        assert!(associations.is_pending(device));
+        let (address, status) = associations.take_pending(device).unwrap();
+        assert_eq!(address, ShortAddress::new(0x0001));
+        assert!(matches!(status, AssociationStatus::Successful));
+        assert!(!associations.is_pending(device));
+        assert!(associations.take_pending(device).is_none());
+        // The device is remembered
+        assert_eq!(
+            associations.find(device).unwrap().address,
+            ShortAddress::new(0x0001)
+        );
+        assert!(associations
+            .remove_address(ShortAddress::new(0x0001))
+            .is_some());
+        assert!(associations.find(device).is_none());
+
+        // Unsuccessful associations are forgotten after the response
+        associations
+            .insert(Association {
+                device,
+                address: ShortAddress::broadcast(),
+                pending: Some(AssociationStatus::NetworkAtCapacity),
+            })
+            .unwrap();
+        assert!(associations.take_pending(device).is_some());
+        assert!(associations.find(device).is_none());
+    }
+}
@@ -0,0 +1,164 @@
+//! # Beacon results
+//!
+//! Beacons received during a scan.
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+use psila_data::{ExtendedPanIdentifier, PanIdentifier, ShortAddress};
+
+/// Default number of beacon results kept by the MAC service
+pub const DEFAULT_BEACON_RESULTS: usize = 8;
+
+/// Guaranteed time slot (GTS) allocated to this device
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TimeSlot {
+    /// First superframe slot of the GTS
+    pub starting_slot: u8,
+    /// Number of superframe slots of the GTS
+    pub length: u8,
+    /// The GTS is used for receiving, otherwise for transmitting
+    pub receive: bool,
+}
+
+/// Information about a received beacon
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BeaconResult {
+    /// PAN identifier of the beacon source
+    pub pan_identifier: PanIdentifier,
+    /// Short address of the beacon source
+    pub coordinator: ShortAddress,
+    /// Extended PAN identifier, if the beacon carried Zigbee beacon information
+    pub extended_pan_identifier: Option<ExtendedPanIdentifier>,
+    /// The beacon was sent by the PAN coordinator
+    pub pan_coordinator: bool,
+    /// The beacon source accepts associations
+    pub association_permit: bool,
+    /// The beacon source accepts GTS requests
+    pub gts_permit: bool,
+    /// GTS allocated to this device
+    pub time_slot: Option<TimeSlot>,
+    /// The beacon source has indirect data pending for this device
+    pub data_pending: bool,
+}
+
+/// List of received beacons, holding at most `N` results
+#[derive(Clone, Debug)]
+pub struct BeaconResults<const N: usize> {
+    entries: [Option<BeaconResult>; N],
+}
+
+impl<const N: usize> BeaconResults<N> {
+    /// Create a empty list
+    pub fn new() -> Self {
+        Self { entries: [None; N] }
+    }
+
+    /// Maximum number of results held
+    pub fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Number of results held
+    pub fn len(&self) -> usize {
+        self.iter().count()
+    }
+
+    /// Check if there are no results
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Add a result, replacing any earlier result from the same PAN and
+    /// coordinator
+    ///
+    /// Returns false if the list is full.
+    pub fn insert(&mut self, result: BeaconResult) -> bool {
+        if let Some(entry) = self.entries.iter_mut().flatten().find(|e| {
+            e.pan_identifier == result.pan_identifier && e.coordinator == result.coordinator
+        }) {
+            *entry = result;
+            return true;
+        }
+        match self.entries.iter_mut().find(|e| e.is_none()) {
+            Some(slot) => {
+                *slot = Some(result);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Remove all results
+    pub fn clear(&mut self) {
+        for entry in self.entries.iter_mut() {
+            *entry = None;
+        }
+    }
+
+    /// Iterate over the results
+    pub fn iter(&self) -> impl Iterator<Item = &BeaconResult> {
+        self.entries.iter().flatten()
+    }
+
+    /// Copy the results into a growable list
+    #[cfg(feature = "alloc")]
+    pub fn to_vec(&self) -> Vec<BeaconResult> {
+        self.iter().copied().collect()
+    }
+}
+
+impl<const N: usize> Default for BeaconResults<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(all(test, not(feature = "core")))]
+mod tests {
+    use super::*;
+
+    fn result(pan_identifier: u16, coordinator: u16) -> BeaconResult {
+        BeaconResult {
+            pan_identifier: PanIdentifier::new(pan_identifier),
+            coordinator: ShortAddress::new(coordinator),
+            extended_pan_identifier: None,
+            pan_coordinator: true,
+            association_permit: false,
+            gts_permit: false,
+            time_slot: None,
+            data_pending: false,
+        }
+    }
+
+    #[test]
+    fn small_capacity() {
+        let mut results = BeaconResults::<2>::new();
+        assert_eq!(results.capacity(), 2);
+        assert!(results.is_empty());
+        assert!(results.insert(result(0x1234, 0x0000)));
+        assert!(results.insert(result(0x4321, 0x0000)));
+        // Update of a known beacon source
+        let mut updated = result(0x1234, 0x0000);
+        updated.association_permit = true;
+        assert!(results.insert(updated));
+        assert_eq!(results.len(), 2);
+        assert!(results.iter().any(|r| *r == updated));
+        // Full
+        assert!(!results.insert(result(0x5678, 0x0000)));
+        results.clear();
+        assert!(results.is_empty());
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn to_vec() {
+        let mut results = BeaconResults::<4>::new();
+        assert!(results.insert(result(0x1234, 0x0000)));
+        assert!(results.insert(result(0x4321, 0x0000)));
+        let list = results.to_vec();
+        assert_eq!(list.len(), 2);
+        assert_eq!(list[0], result(0x1234, 0x0000));
+        assert_eq!(list[1], result(0x4321, 0x0000));
+    }
+}
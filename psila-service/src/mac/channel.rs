@@ -0,0 +1,131 @@
+//! # Channels
+//!
+//! Channel pages and the channels valid on each page.
+
+use core::ops::RangeInclusive;
+
+use crate::Error;
+
+/// Channel page, selecting the PHY and the numbering of the channels
+///
+/// Only the pages used by Zigbee are supported, the 2.4 GHz page 0 and the
+/// sub-GHz pages 28 to 31.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ChannelPage {
+    /// 2.4 GHz O-QPSK, channels 11 to 26
+    Page0 = 0,
+    /// 863 to 876 MHz, channels 0 to 26
+    Page28 = 28,
+    /// 863 to 876 and 915 to 921 MHz, channels 27 to 34 and 62
+    Page29 = 29,
+    /// 870 to 876 MHz, channels 35 to 61
+    Page30 = 30,
+    /// 915 to 921 MHz, channels 0 to 26
+    Page31 = 31,
+}
+
+impl ChannelPage {
+    fn ranges(self) -> &'static [RangeInclusive<u8>] {
+        const PAGE_0: [RangeInclusive<u8>; 1] = [11..=26];
+        const PAGE_28: [RangeInclusive<u8>; 1] = [0..=26];
+        const PAGE_29: [RangeInclusive<u8>; 2] = [27..=34, 62..=62];
+        const PAGE_30: [RangeInclusive<u8>; 1] = [35..=61];
+        match self {
+            ChannelPage::Page0 => &PAGE_0,
+            ChannelPage::Page28 | ChannelPage::Page31 => &PAGE_28,
+            ChannelPage::Page29 => &PAGE_29,
+            ChannelPage::Page30 => &PAGE_30,
+        }
+    }
+
+    /// Check if `channel` is valid on the page
+    pub fn is_valid(self, channel: u8) -> bool {
+        self.ranges().iter().any(|range| range.contains(&channel))
+    }
+
+    /// The valid channels of the page, in ascending order
+    pub fn channels(self) -> impl Iterator<Item = u8> {
+        self.ranges().iter().cloned().flatten()
+    }
+}
+
+impl Default for ChannelPage {
+    fn default() -> Self {
+        ChannelPage::Page0
+    }
+}
+
+/// A channel on a channel page
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Channel {
+    page: ChannelPage,
+    number: u8,
+}
+
+impl Channel {
+    /// Create a channel, fails with `Error::InvalidChannel` if `number` is
+    /// not valid on `page`
+    pub fn new(page: ChannelPage, number: u8) -> Result<Self, Error> {
+        if page.is_valid(number) {
+            Ok(Self { page, number })
+        } else {
+            Err(Error::InvalidChannel)
+        }
+    }
+
+    /// The channel page
+    pub fn page(&self) -> ChannelPage {
+        self.page
+    }
+
+    /// The channel number on the page
+    pub fn number(&self) -> u8 {
+        self.number
+    }
+}
+
+impl Default for Channel {
+    /// Channel 11 on page 0
+    fn default() -> Self {
+        Self {
+            page: ChannelPage::Page0,
+            number: 11,
+        }
+    }
+}
+
+#[cfg(all(test, not(feature = "core")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn page_0_channels() {
+        assert_eq!(
+            Channel::new(ChannelPage::Page0, 27),
+            Err(Error::InvalidChannel)
+        );
+        assert_eq!(
+            Channel::new(ChannelPage::Page0, 10),
+            Err(Error::InvalidChannel)
+        );
+        let channel = Channel::new(ChannelPage::Page0, 15).unwrap();
+        assert_eq!(channel.page(), ChannelPage::Page0);
+        assert_eq!(channel.number(), 15);
+        assert_eq!(Channel::default().number(), 11);
+
+        let mut channels = ChannelPage::Page0.channels();
+        assert_eq!(channels.next(), Some(11));
+        assert_eq!(channels.last(), Some(26));
+        assert_eq!(ChannelPage::Page0.channels().count(), 16);
+    }
+
+    #[test]
+    fn sub_ghz_channels() {
+        assert!(ChannelPage::Page28.is_valid(0));
+        assert!(!ChannelPage::Page28.is_valid(27));
+        assert!(ChannelPage::Page29.is_valid(62));
+        assert!(!ChannelPage::Page29.is_valid(35));
+        assert_eq!(ChannelPage::Page29.channels().count(), 9);
+        assert_eq!(ChannelPage::Page30.channels().next(), Some(35));
+    }
+}
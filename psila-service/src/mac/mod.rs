@@ -1,13 +1,45 @@
 use core::cell::Cell;
 
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+
+mod address_pool;
+mod associations;
+mod beacon_results;
+mod channel;
+mod superframe;
+
+pub use address_pool::{AddressPool, DEFAULT_ADDRESS_POOL_WORDS};
+pub use associations::MAX_ASSOCIATED_DEVICES;
+pub use beacon_results::{BeaconResult, BeaconResults, TimeSlot, DEFAULT_BEACON_RESULTS};
+pub use channel::{Channel, ChannelPage};
+pub use superframe::{
+    beacon_interval, beacon_interval_symbols, superframe_duration, superframe_duration_symbols,
+    SuperframeTiming, BASE_SLOT_DURATION, BASE_SUPERFRAME_DURATION, MAX_ORDER,
+    NUMBER_OF_SUPERFRAME_SLOTS, SYMBOL_DURATION,
+};
+
 pub use ieee802154::mac::{
+    beacon::{
+        Beacon, BeaconOrder, Direction, GuaranteedTimeSlotInformation, PendingAddress,
+        SuperframeOrder, SuperframeSpecification,
+    },
     command::{AssociationStatus, CapabilityInformation, Command},
     Address, AddressMode, ExtendedAddress, Frame, FrameContent, FrameType, FrameVersion, Header,
     Security, ShortAddress, WriteFooter,
 };
 
-use psila_data::PanIdentifier;
+use psila_data::{
+    common::address::SHORT_ADDRESS_UNASSIGNED,
+    network::{
+        beacon::{ProtocolIdentifier, StackProfile},
+        BeaconInformation,
+    },
+    pack::Pack,
+    ExtendedPanIdentifier, PanIdentifier,
+};
 
+use self::associations::{Association, Associations};
 use crate::identity::Identity;
 use crate::Error;
 
@@ -16,12 +48,153 @@ pub enum State {
     Orphan,
     Scan,
     Associate,
+    /// Association request acknowledged, waiting the response wait time
+    /// before requesting the association response
     QueryAssociationStatus,
+    /// Data request sent, waiting for the association response
+    WaitAssociationResponse,
     Associated,
+    /// Coordinator of the PAN, accepting associations
+    Coordinator,
+}
+
+/// Snapshot of the state of a `MacService`, for display and debugging
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MacStatus {
+    /// State of the service
+    pub state: State,
+    /// Channel used, or scanned, by the device
+    pub channel: Channel,
+    /// Number of scans started while trying to join a network
+    pub attempts: u8,
+    /// Sequence number of the last frame sent requesting a acknowledge, which
+    /// hasn't been acknowledged yet
+    pub pending_acknowledge: Option<u8>,
+    /// Time, in microseconds, spent in the current state
+    ///
+    /// Accumulated from the timeouts which have expired since the last state
+    /// transition.
+    pub elapsed: u32,
+}
+
+/// Default time, in microseconds, to wait for the association response
+/// before requesting it with a data request
+///
+/// macResponseWaitTime, 32 times aBaseSuperframeDuration (960 symbols), with
+/// 16 microseconds per symbol for the 2.4 GHz PHY
+pub const DEFAULT_RESPONSE_WAIT_TIME: u32 = 32 * BASE_SUPERFRAME_DURATION * SYMBOL_DURATION;
+
+/// Time, in microseconds, to wait before scanning again after a failed
+/// association
+const ASSOCIATION_RETRY_TIME: u32 = 28_000_000;
+
+/// Permit joining for ever, see `MacService::set_permit_join`
+pub const PERMIT_JOIN_FOREVER: u8 = 0xff;
+
+/// Interval, in microseconds, of the permit join countdown
+const PERMIT_JOIN_TICK: u32 = 1_000_000;
+
+/// Size of the frame content of a beacon without any GTS or pending
+/// addresses; superframe specification, GTS and pending address
+/// specification
+const BEACON_CONTENT_SIZE: usize = 4;
+/// Size of the content of commands without any fields, the command identifier
+const COMMAND_CONTENT_SIZE: usize = 1;
+/// Size of the association request command content, with the capabilities
+const ASSOCIATION_REQUEST_CONTENT_SIZE: usize = 2;
+/// Size of the association response command content, with the short address
+/// and status
+const ASSOCIATION_RESPONSE_CONTENT_SIZE: usize = 4;
+/// Size of the enhanced beacon request with the EB filter
+const ENHANCED_BEACON_REQUEST_SIZE: usize = 17;
+
+/// Size of a address and the PAN identifier, if included
+fn address_size(address: &Address, with_pan_identifier: bool) -> usize {
+    let pan_identifier_size = if with_pan_identifier { 2 } else { 0 };
+    match address {
+        Address::None => 0,
+        Address::Short(..) => pan_identifier_size + 2,
+        Address::Extended(..) => pan_identifier_size + 8,
+    }
+}
+
+/// Size of a unsecured MAC header; frame control, sequence number and
+/// addressing fields
+fn header_size(header: &Header) -> usize {
+    3 + address_size(&header.destination, true)
+        + address_size(&header.source, !header.pan_id_compress)
+}
+
+/// Encode `frame`, without footer, into `data`
+///
+/// `content_size` is the size of the frame content, e.g. the command
+/// identifier and fields. If `data` is too small `Error::BufferTooSmall` is
+/// returned with the size of the frame.
+fn encode_frame(frame: &Frame, content_size: usize, data: &mut [u8]) -> Result<usize, Error> {
+    let size = header_size(&frame.header) + content_size + frame.payload.len();
+    if data.len() < size {
+        return Err(Error::BufferTooSmall(size));
+    }
+    Ok(frame.encode(data, WriteFooter::No))
+}
+
+/// The part of `data` following `offset`, the headroom before it is left
+/// for the caller
+fn after_headroom(data: &mut [u8], offset: usize) -> &mut [u8] {
+    let offset = offset.min(data.len());
+    &mut data[offset..]
+}
+
+/// Adjust the result of a builder called on the part of a buffer following
+/// `offset`, to the end offset or the size needed including the headroom
+fn with_headroom(
+    result: Result<(usize, u32), Error>,
+    offset: usize,
+) -> Result<(usize, u32), Error> {
+    match result {
+        Ok((size, timeout)) => Ok((offset + size, timeout)),
+        Err(Error::BufferTooSmall(size)) => Err(Error::BufferTooSmall(offset + size)),
+        Err(error) => Err(error),
+    }
+}
+
+/// Offset of the sequence number in a MAC frame, following the frame control
+const SEQUENCE_OFFSET: usize = 2;
+/// Sequence number suppression bit, in the second octet of the frame control
+const SEQUENCE_SUPPRESSION: u8 = 0b0000_0001;
+/// Frame version bits, in the second octet of the frame control
+const FRAME_VERSION_MASK: u8 = 0b0011_0000;
+/// Frame version 802.15.4-2015, in the second octet of the frame control
+const FRAME_VERSION_2015: u8 = 0b0010_0000;
+
+/// Rewrite the sequence number of the encoded MAC frame in `data`
+///
+/// Used when retransmitting a frame, everything but the sequence number is
+/// left as is, including the frame check sequence (FCS) if present, which
+/// shall be calculated by the radio. Fails with `Error::MalformedPacket` if
+/// `data` is too short or the frame has no sequence number.
+pub fn rewrite_mac_sequence(data: &mut [u8], sequence: u8) -> Result<(), Error> {
+    if data.len() <= SEQUENCE_OFFSET {
+        return Err(Error::MalformedPacket);
+    }
+    if data[1] & FRAME_VERSION_MASK == FRAME_VERSION_2015
+        && data[1] & SEQUENCE_SUPPRESSION == SEQUENCE_SUPPRESSION
+    {
+        return Err(Error::MalformedPacket);
+    }
+    data[SEQUENCE_OFFSET] = sequence;
+    Ok(())
 }
 
 /// MAC-layer service
-pub struct MacService {
+///
+/// Keeps at most `BEACONS` results from beacons received while scanning. As
+/// coordinator, hands out short addresses from a pool of `ADDRESS_WORDS`
+/// 32-bit words, see `AddressPool`.
+pub struct MacService<
+    const BEACONS: usize = DEFAULT_BEACON_RESULTS,
+    const ADDRESS_WORDS: usize = DEFAULT_ADDRESS_POOL_WORDS,
+> {
     state: State,
     version: FrameVersion,
     sequence: Cell<u8>,
@@ -29,6 +202,17 @@ pub struct MacService {
     identity: Identity,
     capabilities: CapabilityInformation,
     coordinator: Identity,
+    beacons: BeaconResults<BEACONS>,
+    association_permit: bool,
+    permit_join: u8,
+    addresses: AddressPool<ADDRESS_WORDS>,
+    associations: Associations,
+    response_wait_time: u32,
+    channel: Channel,
+    attempts: u8,
+    pending_acknowledge: Cell<Option<u8>>,
+    timer: u32,
+    elapsed: u32,
 }
 
 impl MacService {
@@ -38,6 +222,57 @@ impl MacService {
     pub fn new(
         address: psila_data::ExtendedAddress,
         capabilities: psila_data::CapabilityInformation,
+    ) -> Self {
+        Self::with_beacon_capacity(address, capabilities)
+    }
+
+    /// Create a new `MacService` acting as coordinator of the PAN
+    ///
+    /// The coordinator uses the short address 0x0000, advertises the PAN
+    /// with `pan_identifier` and `extended_pan_identifier` in its beacons and
+    /// accepts associations.
+    pub fn new_coordinator(
+        address: psila_data::ExtendedAddress,
+        capabilities: psila_data::CapabilityInformation,
+        pan_identifier: PanIdentifier,
+        extended_pan_identifier: ExtendedPanIdentifier,
+    ) -> Self {
+        Self::coordinator_with_capacity(
+            address,
+            capabilities,
+            pan_identifier,
+            extended_pan_identifier,
+        )
+    }
+}
+
+impl<const BEACONS: usize, const ADDRESS_WORDS: usize> MacService<BEACONS, ADDRESS_WORDS> {
+    /// Create a new `MacService` acting as coordinator of the PAN, keeping
+    /// at most `BEACONS` beacon results and `ADDRESS_WORDS` words of short
+    /// addresses, see `new_coordinator`
+    pub fn coordinator_with_capacity(
+        address: psila_data::ExtendedAddress,
+        capabilities: psila_data::CapabilityInformation,
+        pan_identifier: PanIdentifier,
+        extended_pan_identifier: ExtendedPanIdentifier,
+    ) -> Self {
+        let mut service = Self::with_beacon_capacity(address, capabilities);
+        service.state = State::Coordinator;
+        service.pan_identifier = pan_identifier;
+        service.identity.short = psila_data::ShortAddress::new(0x0000);
+        service.identity.extended_pan = extended_pan_identifier;
+        service.coordinator = service.identity;
+        service.association_permit = true;
+        service.permit_join = PERMIT_JOIN_FOREVER;
+        service
+    }
+
+    /// Create a new `MacService` keeping at most `BEACONS` beacon results
+    ///
+    /// Will use the 802.15.4-2003 version without security
+    pub fn with_beacon_capacity(
+        address: psila_data::ExtendedAddress,
+        capabilities: psila_data::CapabilityInformation,
     ) -> Self {
         let capabilities = CapabilityInformation {
             full_function_device: capabilities.router_capable,
@@ -54,13 +289,111 @@ impl MacService {
             identity: Identity::from_extended(address),
             capabilities,
             coordinator: Identity::default(),
+            beacons: BeaconResults::new(),
+            association_permit: false,
+            permit_join: 0,
+            addresses: AddressPool::new(
+                psila_data::ShortAddress::new(0x0001),
+                psila_data::ShortAddress::broadcast(),
+            ),
+            associations: Associations::new(),
+            response_wait_time: DEFAULT_RESPONSE_WAIT_TIME,
+            channel: Channel::default(),
+            attempts: 0,
+            pending_acknowledge: Cell::new(None),
+            timer: 0,
+            elapsed: 0,
         }
     }
 
+    /// Time, in microseconds, waited for the association response
+    pub fn response_wait_time(&self) -> u32 {
+        self.response_wait_time
+    }
+
+    /// Set the time, in microseconds, waited for the association response
+    ///
+    /// After the association request has been acknowledged the device waits
+    /// this long before sending a data request to retrieve the response, and
+    /// then this long for the response. Defaults to
+    /// `DEFAULT_RESPONSE_WAIT_TIME`.
+    pub fn set_response_wait_time(&mut self, microseconds: u32) {
+        self.response_wait_time = microseconds;
+    }
+
     pub fn state(&self) -> State {
         self.state
     }
 
+    /// Snapshot of the state of the service
+    pub fn status(&self) -> MacStatus {
+        MacStatus {
+            state: self.state,
+            channel: self.channel,
+            attempts: self.attempts,
+            pending_acknowledge: self.pending_acknowledge.get(),
+            elapsed: self.elapsed,
+        }
+    }
+
+    /// Transition to `state`, restarting the time spent in the state
+    fn set_state(&mut self, state: State) {
+        if state != self.state {
+            self.state = state;
+            self.elapsed = 0;
+        }
+    }
+
+    /// Keep track of the timer started by the caller, a timeout of zero
+    /// leaves the timer as is
+    fn schedule(&mut self, result: Result<(usize, u32), Error>) -> Result<(usize, u32), Error> {
+        if let Ok((_, timeout)) = result {
+            if timeout > 0 {
+                self.timer = timeout;
+            }
+        }
+        result
+    }
+
+    /// The channel used, or scanned, by the device
+    pub fn channel(&self) -> Channel {
+        self.channel
+    }
+
+    /// Start a new scan for networks on `channel` of `page`
+    ///
+    /// Fails with `Error::InvalidChannel` if the channel is not valid on the
+    /// page. Otherwise the beacon results are cleared and the device is
+    /// orphaned, the beacon request is sent on the next timeout. The radio
+    /// shall be tuned to `channel()` by the caller.
+    pub fn start_scan(&mut self, page: ChannelPage, channel: u8) -> Result<(), Error> {
+        self.channel = Channel::new(page, channel)?;
+        self.beacons.clear();
+        self.attempts = 0;
+        self.set_state(State::Orphan);
+        Ok(())
+    }
+
+    /// Reset the service to orphan, e.g. after leaving the network
+    ///
+    /// The short address, PAN identifier, extended PAN identifier and the
+    /// coordinator are forgotten and the beacon results are cleared. The
+    /// extended address, capabilities and channel are kept. A new scan is
+    /// started on the next timeout.
+    pub fn reset(&mut self) {
+        self.pan_identifier = PanIdentifier::broadcast();
+        self.identity = Identity::from_extended(self.identity.extended);
+        self.coordinator = Identity::default();
+        self.beacons.clear();
+        self.association_permit = false;
+        self.permit_join = 0;
+        self.attempts = 0;
+        self.pending_acknowledge.set(None);
+        self.timer = 0;
+        self.set_state(State::Orphan);
+        self.elapsed = 0;
+    }
+
     pub fn identity(&self) -> &Identity {
         &self.identity
     }
@@ -73,6 +406,17 @@ impl MacService {
         self.coordinator
     }
 
+    /// Beacons received while scanning
+    pub fn beacon_results(&self) -> &BeaconResults<BEACONS> {
+        &self.beacons
+    }
+
+    /// Get the extended PAN identifier of the network, learned from the
+    /// beacon of the coordinator
+    pub fn extended_pan_identifier(&self) -> ExtendedPanIdentifier {
+        self.identity.extended_pan
+    }
+
     /// Get the next sequence number
     fn sequence_next(&self) -> u8 {
         let sequence = (*self).sequence.get();
@@ -100,6 +444,9 @@ impl MacService {
         } else {
             false
         };
+        if acknowledge {
+            self.pending_acknowledge.set(Some(sequence));
+        }
         Header {
             seq: sequence,
             frame_type,
@@ -200,7 +547,85 @@ impl MacService {
             payload: &[],
             footer: [0u8; 2],
         };
-        Ok((frame.encode(data, WriteFooter::No), 2_000_000))
+        let size = encode_frame(&frame, COMMAND_CONTENT_SIZE, data)?;
+        Ok((size, 2_000_000))
+    }
+
+    /// Build a beacon request frame at `offset` in `data`, see
+    /// `build_beacon_request`
+    ///
+    /// The bytes before `offset` are left as is, as headroom for radio
+    /// headers. Returns the end offset of the frame and the timeout.
+    pub fn build_beacon_request_at(
+        &self,
+        data: &mut [u8],
+        offset: usize,
+    ) -> Result<(usize, u32), Error> {
+        with_headroom(
+            self.build_beacon_request(after_headroom(data, offset)),
+            offset,
+        )
+    }
+
+    /// Build a enhanced beacon request frame
+    ///
+    /// IEEE 802.15.4-2015 chapter 7.5.10
+    ///
+    /// ```notrust
+    /// +-------------+--------+---------+-------------+----------+----------+-----+---------+
+    /// | Destination | Source | Pending | Acknowledge | Compress | Security | IEs | Version |
+    /// +-------------+--------+---------+-------------+----------+----------+-----+---------+
+    /// | Short       | None   | false   | false       | false    | false    | 1   | 2015    |
+    /// +-------------+--------+---------+-------------+----------+----------+-----+---------+
+    /// ```
+    ///
+    /// ```notrust
+    /// +------------+------------+-------------+-----------+
+    /// | Dst PAN Id | Src PAN Id | Destination | Source    |
+    /// +------------+------------+-------------+-----------+
+    /// | Broadcast  | None       | Broadcast   | None      |
+    /// +------------+------------+-------------+-----------+
+    /// ```
+    ///
+    /// ```notrust
+    /// +-----------------+-------------------+-----------------+------------+
+    /// | Header IE       | Payload IE        | Payload IE      | Command    |
+    /// +-----------------+-------------------+-----------------+------------+
+    /// | Termination 1   | MLME (EB Filter)  | Termination     | 0x07       |
+    /// +-----------------+-------------------+-----------------+------------+
+    /// ```
+    ///
+    /// 1. Header termination 1, signals that payload IEs follows
+    ///
+    /// The EB filter requests that only coordinators permitting joining
+    /// responds.
+    ///
+    pub fn build_enhanced_beacon_request(&self, data: &mut [u8]) -> Result<(usize, u32), Error> {
+        const FRAME_CONTROL: u16 = 0b011 // MAC command
+            | 1 << 9 // IE present
+            | 0b10 << 10 // Short destination address
+            | 0b10 << 12; // IEEE 802.15.4-2015
+        const HEADER_TERMINATION_1: u16 = 0x7e << 7;
+        const MLME_GROUP: u16 = 0x8000 | 0x1 << 11 | 3;
+        const EB_FILTER: u16 = 0x1e << 8 | 1;
+        const EB_FILTER_PERMIT_JOINING: u8 = 0x01;
+        const PAYLOAD_TERMINATION: u16 = 0x8000 | 0xf << 11;
+        const BEACON_REQUEST: u8 = 0x07;
+
+        if data.len() < ENHANCED_BEACON_REQUEST_SIZE {
+            return Err(Error::BufferTooSmall(ENHANCED_BEACON_REQUEST_SIZE));
+        }
+        data[0..2].copy_from_slice(&FRAME_CONTROL.to_le_bytes());
+        data[2] = self.sequence_next();
+        data[3..5].copy_from_slice(&u16::from(PanIdentifier::broadcast()).to_le_bytes());
+        data[5..7].copy_from_slice(&u16::from(psila_data::ShortAddress::broadcast()).to_le_bytes());
+        data[7..9].copy_from_slice(&HEADER_TERMINATION_1.to_le_bytes());
+        data[9..11].copy_from_slice(&MLME_GROUP.to_le_bytes());
+        data[11..13].copy_from_slice(&EB_FILTER.to_le_bytes());
+        data[13] = EB_FILTER_PERMIT_JOINING;
+        data[14..16].copy_from_slice(&PAYLOAD_TERMINATION.to_le_bytes());
+        data[16] = BEACON_REQUEST;
+        Ok((ENHANCED_BEACON_REQUEST_SIZE, 2_000_000))
     }
 
     pub fn build_association_request(
@@ -221,7 +646,8 @@ impl MacService {
             payload: &[],
             footer: [0u8; 2],
         };
-        Ok((frame.encode(data, WriteFooter::No), 5_000_000))
+        let size = encode_frame(&frame, ASSOCIATION_REQUEST_CONTENT_SIZE, data)?;
+        Ok((size, 5_000_000))
     }
 
     pub fn build_data_request(
@@ -241,7 +667,148 @@ impl MacService {
             payload: &[0u8; 0],
             footer: [0u8; 2],
         };
-        Ok((frame.encode(data, WriteFooter::No), 0))
+        Ok((encode_frame(&frame, COMMAND_CONTENT_SIZE, data)?, 0))
+    }
+
+    /// Build a data request frame at `offset` in `data`, see
+    /// `build_data_request`
+    ///
+    /// The bytes before `offset` are left as is, as headroom for radio
+    /// headers. Returns the end offset of the frame and the timeout.
+    pub fn build_data_request_at(
+        &self,
+        destination: psila_data::ShortAddress,
+        data: &mut [u8],
+        offset: usize,
+    ) -> Result<(usize, u32), Error> {
+        with_headroom(
+            self.build_data_request(destination, after_headroom(data, offset)),
+            offset,
+        )
+    }
+
+    /// Build a PAN identifier conflict notification frame
+    ///
+    /// IEEE 802.15.4-2015 chapter 7.5.6
+    ///
+    /// ```notrust
+    /// +-------------+----------+---------+-------------+----------+----------+
+    /// | Destination | Source   | Pending | Acknowledge | Compress | Security |
+    /// +-------------+----------+---------+-------------+----------+----------+
+    /// | Short       | Extended | false   | true        | true     | false    |
+    /// +-------------+----------+---------+-------------+----------+----------+
+    /// ```
+    ///
+    /// Sent to the coordinator when a beacon of another network using the
+    /// same PAN identifier is received.
+    ///
+    pub fn build_pan_id_conflict(&self, data: &mut [u8]) -> Result<(usize, u32), Error> {
+        let destination = Address::Short(self.pan_identifier.into(), self.coordinator.short.into());
+        let source = Address::Extended(self.pan_identifier.into(), self.identity.extended.into());
+        let header = self.create_header(FrameType::MacCommand, false, true, destination, source);
+        let frame = Frame {
+            header,
+            content: FrameContent::Command(Command::PanIdConflictNotification),
+            payload: &[0u8; 0],
+            footer: [0u8; 2],
+        };
+        Ok((encode_frame(&frame, COMMAND_CONTENT_SIZE, data)?, 0))
+    }
+
+    /// Build a beacon frame
+    ///
+    /// IEEE 802.15.4-2015 chapter 7.3.1
+    ///
+    /// ```notrust
+    /// +-------------+--------+---------+-------------+----------+----------+
+    /// | Destination | Source | Pending | Acknowledge | Compress | Security |
+    /// +-------------+--------+---------+-------------+----------+----------+
+    /// | None        | Short  | false   | false       | false    | false    |
+    /// +-------------+--------+---------+-------------+----------+----------+
+    /// ```
+    ///
+    /// Sent by the coordinator in response to a beacon request. The beacon
+    /// advertises a beacon-less network and if associations are permitted.
+    /// The payload is the Zigbee beacon information.
+    ///
+    pub fn build_beacon(&self, data: &mut [u8]) -> Result<(usize, u32), Error> {
+        let header = self.create_header(
+            FrameType::Beacon,
+            false,
+            false,
+            Address::None,
+            Address::Short(self.pan_identifier.into(), self.identity.short.into()),
+        );
+        let beacon = Beacon {
+            superframe_spec: SuperframeSpecification {
+                beacon_order: BeaconOrder::OnDemand,
+                superframe_order: SuperframeOrder::Inactive,
+                final_cap_slot: 0x0f,
+                battery_life_extension: false,
+                pan_coordinator: self.state == State::Coordinator,
+                association_permit: self.association_permit,
+            },
+            guaranteed_time_slot_info: GuaranteedTimeSlotInformation::new(),
+            pending_address: PendingAddress::new(),
+        };
+        let information = BeaconInformation {
+            protocol_indentifier: ProtocolIdentifier::Zbee,
+            stack_profile: StackProfile::ZbeePro,
+            network_protocol_version: 2,
+            router_capacity: self.association_permit,
+            device_depth: 0,
+            end_device_capacity: self.association_permit,
+            extended_pan_address: self.identity.extended_pan,
+            tx_offset: 0x00ff_ffff,
+            network_update_identifier: 0,
+        };
+        let mut payload = [0u8; 15];
+        let _ = information.pack(&mut payload)?;
+        let frame = Frame {
+            header,
+            content: FrameContent::Beacon(beacon),
+            payload: &payload,
+            footer: [0u8; 2],
+        };
+        Ok((encode_frame(&frame, BEACON_CONTENT_SIZE, data)?, 0))
+    }
+
+    /// Build a association response frame
+    ///
+    /// IEEE 802.15.4-2015 chapter 7.5.3
+    ///
+    /// ```notrust
+    /// +-------------+----------+---------+-------------+----------+----------+
+    /// | Destination | Source   | Pending | Acknowledge | Compress | Security |
+    /// +-------------+----------+---------+-------------+----------+----------+
+    /// | Extended    | Extended | false   | true        | true     | false    |
+    /// +-------------+----------+---------+-------------+----------+----------+
+    /// ```
+    ///
+    /// Sent by the coordinator to the device requesting association, with
+    /// the allocated short address. Sent as indirect data, when the device
+    /// requests it with a data request.
+    ///
+    pub fn build_association_response(
+        &self,
+        destination: psila_data::ExtendedAddress,
+        address: psila_data::ShortAddress,
+        status: AssociationStatus,
+        data: &mut [u8],
+    ) -> Result<(usize, u32), Error> {
+        let destination = Address::Extended(self.pan_identifier.into(), destination.into());
+        let source = Address::Extended(self.pan_identifier.into(), self.identity.extended.into());
+        let header = self.create_header(FrameType::MacCommand, false, true, destination, source);
+        let frame = Frame {
+            header,
+            content: FrameContent::Command(Command::AssociationResponse(address.into(), status)),
+            payload: &[0u8; 0],
+            footer: [0u8; 2],
+        };
+        Ok((
+            encode_frame(&frame, ASSOCIATION_RESPONSE_CONTENT_SIZE, data)?,
+            0,
+        ))
     }
 
     pub fn build_data_header(
@@ -265,13 +832,133 @@ impl MacService {
         }
     }
 
-    fn handle_beacon(&mut self, frame: &Frame) -> Result<(usize, u32), Error> {
+    /// Check if the beacon lists this device in the pending address list,
+    /// i.e. the beacon source has indirect data for this device
+    pub fn beacon_data_pending(&self, beacon: &Beacon) -> bool {
+        let short: ShortAddress = self.identity.short.into();
+        let extended: ExtendedAddress = self.identity.extended.into();
+        (self.identity.assigned_short()
+            && beacon
+                .pending_address
+                .short_addresses()
+                .iter()
+                .any(|a| *a == short))
+            || (self.identity.assigned_extended()
+                && beacon
+                    .pending_address
+                    .extended_addresses()
+                    .iter()
+                    .any(|a| *a == extended))
+    }
+
+    /// Get the guaranteed time slot (GTS) allocated to this device by the
+    /// beacon source
+    pub fn beacon_time_slot(&self, beacon: &Beacon) -> Option<TimeSlot> {
+        if !self.identity.assigned_short() {
+            return None;
+        }
+        let short: ShortAddress = self.identity.short.into();
+        beacon
+            .guaranteed_time_slot_info
+            .slots()
+            .iter()
+            .find(|slot| slot.short_address == short)
+            .map(|slot| TimeSlot {
+                starting_slot: slot.starting_slot,
+                length: slot.length,
+                receive: slot.direction == Direction::Receive,
+            })
+    }
+
+    /// Check if a network with `pan_identifier` and `extended_pan_identifier`
+    /// conflicts with the network of this device or a network found while
+    /// scanning
+    ///
+    /// Two networks conflict when they use the same PAN identifier but have
+    /// different extended PAN identifiers.
+    pub fn pan_id_conflict(
+        &self,
+        pan_identifier: PanIdentifier,
+        extended_pan_identifier: ExtendedPanIdentifier,
+    ) -> bool {
+        if self.state == State::Associated
+            && self.identity.assigned_extended_pan()
+            && pan_identifier == self.pan_identifier
+            && extended_pan_identifier != self.identity.extended_pan
+        {
+            return true;
+        }
+        self.beacons.iter().any(|result| {
+            result.pan_identifier == pan_identifier
+                && result
+                    .extended_pan_identifier
+                    .map_or(false, |e| e != extended_pan_identifier)
+        })
+    }
+
+    /// Handle a received beacon
+    ///
+    /// When associated and the beacon of the coordinator lists this device
+    /// in the pending address list, a data request is sent to the
+    /// coordinator to retrieve the indirect data. A beacon from another
+    /// network using the PAN identifier of this network results in a PAN
+    /// identifier conflict notification sent to the coordinator.
+    fn handle_beacon(&mut self, frame: &Frame, buffer: &mut [u8]) -> Result<(usize, u32), Error> {
         let (src_id, src_short) = if let Address::Short(id, short) = frame.header.source {
             (id.into(), short.into())
         } else {
             return Err(Error::InvalidAddress);
         };
         if let FrameContent::Beacon(beacon) = &frame.content {
+            let information = BeaconInformation::unpack(frame.payload)
+                .ok()
+                .map(|(information, _)| information)
+                .filter(|information| information.protocol_indentifier == ProtocolIdentifier::Zbee);
+            let extended_pan_identifier =
+                information.map(|information| information.extended_pan_address);
+            let conflict = extended_pan_identifier
+                .map_or(false, |extended| self.pan_id_conflict(src_id, extended));
+            if let State::Associated = self.state {
+                if conflict {
+                    log::warn!("mac: PAN identifier conflict {:04x}", u16::from(src_id));
+                    return self.build_pan_id_conflict(buffer);
+                }
+                if src_id == self.pan_identifier
+                    && src_short == self.coordinator.short
+                    && self.beacon_data_pending(beacon)
+                {
+                    log::info!("mac: Data pending, send data request");
+                    return self.build_data_request(self.coordinator.short, buffer);
+                }
+                return Ok((0, 0));
+            }
+            if let State::Scan = self.state {
+                if information.is_none() {
+                    // Beacon of a network not using Zigbee, or without the
+                    // beacon payload, continue scanning
+                    log::info!(
+                        "mac: Ignore beacon {:04x}:{:04x}, not Zigbee",
+                        u16::from(src_id),
+                        u16::from(src_short)
+                    );
+                    return Ok((0, 0));
+                }
+                if conflict {
+                    log::warn!("mac: PAN identifier conflict {:04x}", u16::from(src_id));
+                }
+                let time_slot = self.beacon_time_slot(beacon);
+                let data_pending = self.beacon_data_pending(beacon);
+                let _ = self.beacons.insert(BeaconResult {
+                    pan_identifier: src_id,
+                    coordinator: src_short,
+                    extended_pan_identifier,
+                    pan_coordinator: beacon.superframe_spec.pan_coordinator,
+                    association_permit: beacon.superframe_spec.association_permit,
+                    gts_permit: beacon.guaranteed_time_slot_info.permit,
+                    time_slot,
+                    data_pending,
+                });
+            }
             if beacon.superframe_spec.pan_coordinator && beacon.superframe_spec.association_permit {
                 if let State::Scan = self.state {
                     log::info!(
@@ -281,7 +968,11 @@ impl MacService {
                     );
                     self.pan_identifier = src_id;
                     self.coordinator.short = src_short;
-                    self.state = State::Associate;
+                    if let Some(extended_pan_identifier) = extended_pan_identifier {
+                        self.identity.extended_pan = extended_pan_identifier;
+                        self.coordinator.extended_pan = extended_pan_identifier;
+                    }
+                    self.set_state(State::Associate);
                 }
             } else {
                 log::info!(
@@ -294,11 +985,14 @@ impl MacService {
         Ok((0, 0))
     }
 
+    /// Handle a association response, retrieved from the coordinator with a
+    /// data request as indirect data
     fn handle_association_response(
         &mut self,
         header: &Header,
         address: ShortAddress,
         status: AssociationStatus,
+        buffer: &mut [u8],
     ) -> Result<(usize, u32), Error> {
         let pan_id = if let Some(pan_id) = header.source.pan_id() {
             pan_id.into()
@@ -314,8 +1008,12 @@ impl MacService {
             );
             return Err(Error::InvalidPanIdentifier);
         }
-        match (self.state, status) {
-            (State::QueryAssociationStatus, AssociationStatus::Successful) => {
+        let waiting = matches!(
+            self.state,
+            State::QueryAssociationStatus | State::WaitAssociationResponse
+        );
+        match (waiting, status) {
+            (true, AssociationStatus::Successful) => {
                 log::info!(
                     "mac: Association Response, Success, {:04x}:{:04x}",
                     u16::from(pan_id),
@@ -323,9 +1021,15 @@ impl MacService {
                 );
                 self.pan_identifier = pan_id;
                 self.identity.short = address.into();
-                self.state = State::Associated;
+                self.attempts = 0;
+                self.set_state(State::Associated);
+                if header.frame_pending {
+                    // More indirect data is waiting at the coordinator
+                    log::info!("mac: Send data request");
+                    return self.build_data_request(self.coordinator.short, buffer);
+                }
             }
-            (State::QueryAssociationStatus, _) => {
+            (true, _) => {
                 log::info!(
                     "mac: Association Response {:04x} {:02x}",
                     u16::from(pan_id),
@@ -333,25 +1037,185 @@ impl MacService {
                 );
                 self.pan_identifier = PanIdentifier::broadcast();
                 self.identity.short = psila_data::ShortAddress::broadcast();
-                self.state = State::Orphan;
+                self.identity.extended_pan = ExtendedPanIdentifier::broadcast();
+                self.set_state(State::Orphan);
             }
-            (_, AssociationStatus::Successful) => {
+            (false, AssociationStatus::Successful) => {
                 log::info!(
                     "mac: Association Response, Success, {:04x}:{:04x}, Bad state",
                     u16::from(pan_id),
                     address.0
                 );
             }
-            (_, _) => {}
+            (false, _) => {}
+        }
+        Ok((0, 0))
+    }
+
+    /// Permit associations for `seconds`, when acting as coordinator
+    ///
+    /// Zero stops permitting associations and `PERMIT_JOIN_FOREVER` permits
+    /// associations until changed. The beacons advertise whether associations
+    /// are permitted. The countdown is driven by the timeouts.
+    ///
+    /// Returns the timeout value the timer shall be configured with, zero if
+    /// no countdown is needed.
+    pub fn set_permit_join(&mut self, seconds: u8) -> u32 {
+        self.permit_join = seconds;
+        self.association_permit = seconds > 0;
+        log::info!("mac: Permit join {}", seconds);
+        match seconds {
+            0 | PERMIT_JOIN_FOREVER => 0,
+            _ => {
+                self.timer = PERMIT_JOIN_TICK;
+                PERMIT_JOIN_TICK
+            }
+        }
+    }
+
+    /// Whether associations are permitted, and advertised in the beacons
+    pub fn association_permit(&self) -> bool {
+        self.association_permit
+    }
+
+    /// Count down the permit join time, a second has passed
+    fn permit_join_tick(&mut self) -> u32 {
+        match self.permit_join {
+            0 | PERMIT_JOIN_FOREVER => 0,
+            1 => {
+                log::info!("mac: Permit join expired");
+                self.permit_join = 0;
+                self.association_permit = false;
+                0
+            }
+            _ => {
+                self.permit_join -= 1;
+                PERMIT_JOIN_TICK
+            }
+        }
+    }
+
+    /// Return the short address of a device which has left the network to
+    /// the address pool, when acting as coordinator
+    ///
+    /// The device is forgotten, it is given a new address if it associates
+    /// again. Returns false if the address wasn't allocated by this
+    /// coordinator.
+    pub fn release_short_address(&mut self, address: psila_data::ShortAddress) -> bool {
+        self.associations.remove_address(address);
+        self.addresses.release(address)
+    }
+
+    /// Handle a association request, when acting as coordinator
+    ///
+    /// A short address is allocated for the device, if requested by the
+    /// device capabilities, and a association response is queued for the
+    /// device. A device which associates again keeps its address. The device
+    /// retrieves the response as indirect data with a data request.
+    /// Association requests are ignored unless associations are permitted.
+    fn handle_association_request(
+        &mut self,
+        header: &Header,
+        capabilities: &CapabilityInformation,
+    ) -> Result<(usize, u32), Error> {
+        if self.state != State::Coordinator || !self.association_permit {
+            return Ok((0, 0));
+        }
+        match header.destination.pan_id() {
+            Some(pan_id) if self.pan_identifier == pan_id => (),
+            _ => return Err(Error::InvalidPanIdentifier),
+        }
+        let device = if let Address::Extended(_, address) = header.source {
+            psila_data::ExtendedAddress::from(address)
+        } else {
+            return Err(Error::InvalidAddress);
+        };
+        let mut allocated = false;
+        let (address, status) = if let Some(association) = self.associations.find(device) {
+            (association.address, AssociationStatus::Successful)
+        } else if !capabilities.allocate_address {
+            (
+                psila_data::ShortAddress::new(SHORT_ADDRESS_UNASSIGNED),
+                AssociationStatus::Successful,
+            )
+        } else if let Some(address) = self.addresses.allocate() {
+            allocated = true;
+            (address, AssociationStatus::Successful)
+        } else {
+            (
+                psila_data::ShortAddress::broadcast(),
+                AssociationStatus::NetworkAtCapacity,
+            )
+        };
+        log::info!(
+            "mac: Association Request {}, {:04x} {:02x}",
+            device,
+            u16::from(address),
+            u8::from(status)
+        );
+        let association = Association {
+            device,
+            address,
+            pending: Some(status),
+        };
+        if let Err(error) = self.associations.insert(association) {
+            if allocated {
+                self.addresses.release(address);
+            }
+            return Err(error);
         }
         Ok((0, 0))
     }
 
-    fn handle_command(&mut self, frame: &Frame) -> Result<(usize, u32), Error> {
+    /// Handle a data request, when acting as coordinator
+    ///
+    /// Sends the association response queued for the device, if any.
+    fn handle_data_request(
+        &mut self,
+        header: &Header,
+        buffer: &mut [u8],
+    ) -> Result<(usize, u32), Error> {
+        let device = if let Address::Extended(_, address) = header.source {
+            psila_data::ExtendedAddress::from(address)
+        } else {
+            return Ok((0, 0));
+        };
+        match self.associations.take_pending(device) {
+            Some((address, status)) => {
+                log::info!("mac: Data request {}, send association response", device);
+                self.build_association_response(device, address, status, buffer)
+            }
+            None => Ok((0, 0)),
+        }
+    }
+
+    /// Check if indirect data is waiting for the sender of `frame`
+    ///
+    /// True for data requests from devices with a queued association
+    /// response, used for the frame pending bit of the acknowledge.
+    pub fn data_pending(&self, frame: &Frame) -> bool {
+        match (&frame.content, frame.header.source) {
+            (FrameContent::Command(Command::DataRequest), Address::Extended(_, address)) => {
+                self.associations.is_pending(address.into())
+            }
+            _ => false,
+        }
+    }
+
+    fn handle_command(&mut self, frame: &Frame, buffer: &mut [u8]) -> Result<(usize, u32), Error> {
         if let FrameContent::Command(command) = &frame.content {
             match command {
+                Command::AssociationRequest(capabilities) => {
+                    self.handle_association_request(&frame.header, capabilities)
+                }
                 Command::AssociationResponse(address, status) => {
-                    self.handle_association_response(&frame.header, *address, *status)
+                    self.handle_association_response(&frame.header, *address, *status, buffer)
+                }
+                Command::BeaconRequest if self.state == State::Coordinator => {
+                    self.build_beacon(buffer)
+                }
+                Command::DataRequest if self.state == State::Coordinator => {
+                    self.handle_data_request(&frame.header, buffer)
                 }
                 _ => Ok((0, 0)),
             }
@@ -367,10 +1231,25 @@ impl MacService {
     ) -> Result<(usize, u32), Error> {
         if frame.header.seq == self.sequence.get() {
             log::info!("mac: Acknowledge {}", frame.header.seq);
-            if let State::Associate = self.state {
-                self.state = State::QueryAssociationStatus;
-                log::info!("mac: Send data request");
-                return self.build_data_request(self.coordinator.short, buffer);
+            if self.pending_acknowledge.get() == Some(frame.header.seq) {
+                self.pending_acknowledge.set(None);
+            }
+            match self.state {
+                State::Associate => {
+                    self.set_state(State::QueryAssociationStatus);
+                    return Ok((0, self.response_wait_time));
+                }
+                State::WaitAssociationResponse if frame.header.frame_pending => {
+                    // The association response follows as indirect data,
+                    // keep waiting for it
+                    log::info!("mac: Association response pending");
+                }
+                State::WaitAssociationResponse => {
+                    log::info!("mac: No association response pending, retry");
+                    self.set_state(State::Orphan);
+                    return Ok((0, ASSOCIATION_RETRY_TIME));
+                }
+                _ => (),
             }
         } else {
             log::warn!("mac: Acknowledge, unknown sequence {}", frame.header.seq);
@@ -383,31 +1262,52 @@ impl MacService {
         frame: &Frame,
         buffer: &mut [u8],
     ) -> Result<(usize, u32), Error> {
-        match frame.header.frame_type {
+        let result = match frame.header.frame_type {
             FrameType::Acknowledgement => self.handle_acknowledge(&frame, buffer),
-            FrameType::Beacon => self.handle_beacon(&frame),
+            FrameType::Beacon => self.handle_beacon(&frame, buffer),
             FrameType::Data => Ok((0, 0)),
-            FrameType::MacCommand => self.handle_command(&frame),
-        }
+            FrameType::MacCommand => self.handle_command(&frame, buffer),
+        };
+        self.schedule(result)
     }
 
     pub fn timeout(&mut self, buffer: &mut [u8]) -> Result<(usize, u32), Error> {
+        let expired = core::mem::take(&mut self.timer);
+        let state = self.state;
+        let result = self.handle_timeout(buffer);
+        if self.state == state {
+            self.elapsed = self.elapsed.saturating_add(expired);
+        }
+        self.schedule(result)
+    }
+
+    fn handle_timeout(&mut self, buffer: &mut [u8]) -> Result<(usize, u32), Error> {
         match self.state {
             State::Orphan => {
-                self.state = State::Scan;
+                self.set_state(State::Scan);
+                self.attempts = self.attempts.saturating_add(1);
+                self.beacons.clear();
                 log::info!("mac: Send beacon request");
                 self.build_beacon_request(buffer)
             }
-            State::Scan | State::QueryAssociationStatus => {
+            State::QueryAssociationStatus => {
+                // The response wait time has passed, request the response
+                self.set_state(State::WaitAssociationResponse);
+                log::info!("mac: Send data request");
+                let (size, _) = self.build_data_request(self.coordinator.short, buffer)?;
+                Ok((size, self.response_wait_time))
+            }
+            State::Scan | State::WaitAssociationResponse => {
                 log::info!("mac: Association failed, retry");
-                self.state = State::Orphan;
-                Ok((0, 28_000_000))
+                self.set_state(State::Orphan);
+                Ok((0, ASSOCIATION_RETRY_TIME))
             }
             State::Associate => {
                 // Send a association request
                 log::info!("mac: Send association request");
                 self.build_association_request(self.pan_identifier, self.coordinator.short, buffer)
             }
+            State::Coordinator => Ok((0, self.permit_join_tick())),
             State::Associated => Ok((0, 0)),
         }
     }
@@ -477,26 +1377,143 @@ impl MacService {
         }
     }
 
-    pub fn destination_me_or_broadcast(&self, frame: &Frame) -> bool {
+    /// Check if the frame is addressed to this device
+    ///
+    /// True for frames sent to the short or extended address of this device
+    /// in the associated PAN, or sent to the broadcast address of the
+    /// associated PAN or the broadcast PAN. Frames without destination, such
+    /// as beacons, are also accepted.
+    pub fn is_for_us(&self, frame: &Frame) -> bool {
         self.destination_me(frame) || self.broadcast_destination(frame)
     }
+
+    /// Check if the frame is addressed to this device, see `is_for_us`
+    #[deprecated(note = "use `is_for_us`")]
+    pub fn destination_me_or_broadcast(&self, frame: &Frame) -> bool {
+        self.is_for_us(frame)
+    }
+}
+
+/// Builders returning owned buffers, for use in desktop tools and targets
+/// with a allocator
+#[cfg(feature = "alloc")]
+impl<const BEACONS: usize, const ADDRESS_WORDS: usize> MacService<BEACONS, ADDRESS_WORDS> {
+    /// Build a beacon request frame, see `build_beacon_request`
+    pub fn build_beacon_request_vec(&self) -> Result<(Vec<u8>, u32), Error> {
+        let mut buffer = [0u8; crate::PACKET_BUFFER_MAX];
+        let (size, timeout) = self.build_beacon_request(&mut buffer)?;
+        Ok((buffer[..size].to_vec(), timeout))
+    }
+
+    /// Build a enhanced beacon request frame, see
+    /// `build_enhanced_beacon_request`
+    pub fn build_enhanced_beacon_request_vec(&self) -> Result<(Vec<u8>, u32), Error> {
+        let mut buffer = [0u8; crate::PACKET_BUFFER_MAX];
+        let (size, timeout) = self.build_enhanced_beacon_request(&mut buffer)?;
+        Ok((buffer[..size].to_vec(), timeout))
+    }
+
+    /// Build a data request frame, see `build_data_request`
+    pub fn build_data_request_vec(
+        &self,
+        destination: psila_data::ShortAddress,
+    ) -> Result<(Vec<u8>, u32), Error> {
+        let mut buffer = [0u8; crate::PACKET_BUFFER_MAX];
+        let (size, timeout) = self.build_data_request(destination, &mut buffer)?;
+        Ok((buffer[..size].to_vec(), timeout))
+    }
+
+    /// Build a Imm-Ack frame, see `build_acknowledge`
+    pub fn build_acknowledge_vec(&self, sequence: u8, pending: bool) -> Vec<u8> {
+        let mut buffer = [0u8; crate::PACKET_BUFFER_MAX];
+        let size = self.build_acknowledge(sequence, pending, &mut buffer);
+        buffer[..size].to_vec()
+    }
 }
 
 #[cfg(all(test, not(feature = "core")))]
 mod tests {
     use super::*;
+    use ieee802154::mac::PanId;
 
-    #[test]
-    fn build_acknowledge() {
-        let address = psila_data::ExtendedAddress::new(0x8899_aabb_ccdd_eeff);
-        let capabilities = psila_data::CapabilityInformation {
+    /// Capabilities of a mains powered end device
+    fn capability() -> psila_data::CapabilityInformation {
+        psila_data::CapabilityInformation {
             alternate_pan_coordinator: false,
             router_capable: false,
             mains_power: true,
             idle_receive: true,
             frame_protection: false,
             allocate_address: true,
+        }
+    }
+
+    fn associated_service() -> MacService {
+        let address = psila_data::ExtendedAddress::new(0x8899_aabb_ccdd_eeff);
+        let capabilities = capability();
+        let mut service = MacService::new(address, capabilities);
+        service.pan_identifier = psila_data::PanIdentifier::new(0x6745);
+        service.identity.short = psila_data::ShortAddress::new(0x1234);
+        service.state = State::Associated;
+        service
+    }
+
+    fn data_frame(destination: Address) -> Frame<'static> {
+        Frame {
+            header: Header {
+                seq: 1,
+                frame_type: FrameType::Data,
+                security: Security::None,
+                frame_pending: false,
+                ack_request: false,
+                pan_id_compress: false,
+                version: FrameVersion::Ieee802154_2003,
+                destination,
+                source: Address::Short(PanId(0x6745), ShortAddress(0x0000)),
+            },
+            content: FrameContent::Data,
+            payload: &[],
+            footer: [0u8; 2],
+        }
+    }
+
+    #[test]
+    fn rewrite_sequence() {
+        let service = associated_service();
+        let frame = Frame {
+            header: service.build_data_header(psila_data::ShortAddress::new(0x0000), true),
+            content: FrameContent::Data,
+            payload: &[0x08, 0x00, 0x00, 0x00, 0x34, 0x12, 0x1e, 0x42],
+            footer: [0u8; 2],
         };
+        let mut data = [0u8; 64];
+        let size = frame.encode(&mut data, WriteFooter::No);
+        let original = data;
+
+        rewrite_mac_sequence(&mut data[..size], 0x5a).unwrap();
+        assert_eq!(data[SEQUENCE_OFFSET], 0x5a);
+        assert_eq!(data[..SEQUENCE_OFFSET], original[..SEQUENCE_OFFSET]);
+        assert_eq!(data[SEQUENCE_OFFSET + 1..], original[SEQUENCE_OFFSET + 1..]);
+        let decoded = Frame::decode(&data[..size], false).unwrap();
+        assert_eq!(decoded.header.seq, 0x5a);
+        assert_eq!(decoded.payload, frame.payload);
+
+        assert_eq!(
+            rewrite_mac_sequence(&mut data[..2], 0x5a),
+            Err(Error::MalformedPacket)
+        );
+        // 802.15.4-2015 frame with the sequence number suppressed
+        let mut data = [0x41, 0x29, 0x45, 0x67, 0xff, 0xff];
+        assert_eq!(
+            rewrite_mac_sequence(&mut data, 0x5a),
+            Err(Error::MalformedPacket)
+        );
+    }
+
+    #[test]
+    fn build_acknowledge() {
+        let address = psila_data::ExtendedAddress::new(0x8899_aabb_ccdd_eeff);
+        let capabilities = capability();
         let service = MacService::new(address, capabilities);
 
         let mut data = [0u8; 256];
@@ -509,14 +1526,7 @@ mod tests {
     #[test]
     fn build_beacon_request() {
         let address = psila_data::ExtendedAddress::new(0x8899_aabb_ccdd_eeff);
-        let capabilities = psila_data::CapabilityInformation {
-            alternate_pan_coordinator: false,
-            router_capable: false,
-            mains_power: true,
-            idle_receive: true,
-            frame_protection: false,
-            allocate_address: true,
-        };
+        let capabilities = capability();
         let service = MacService::new(address, capabilities);
 
         let mut data = [0u8; 256];
@@ -530,17 +1540,54 @@ mod tests {
         );
     }
 
+    #[test]
+    fn build_enhanced_beacon_request() {
+        let address = psila_data::ExtendedAddress::new(0x8899_aabb_ccdd_eeff);
+        let capabilities = capability();
+        let service = MacService::new(address, capabilities);
+
+        let mut data = [0u8; 256];
+        let (size, timeout) = service.build_enhanced_beacon_request(&mut data).unwrap();
+
+        assert_eq!(size, 17);
+        assert_eq!(timeout, 2_000_000);
+        // MAC command, IE present, short destination, no source, 2015 version
+        assert_eq!(data[..2], [0x03, 0x2a]);
+        assert_eq!(data[2], 0x01);
+        assert_eq!(data[3..7], [0xff, 0xff, 0xff, 0xff]);
+        assert_eq!(
+            data[7..16],
+            [0x00, 0x3f, 0x03, 0x88, 0x01, 0x1e, 0x01, 0x00, 0xf8]
+        );
+        // Beacon request command identifier
+        assert_eq!(data[16], 0x07);
+
+        let mut data = [0u8; 16];
+        assert_eq!(
+            service.build_enhanced_beacon_request(&mut data),
+            Err(Error::NotEnoughSpace)
+        );
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn build_vec() {
+        let address = psila_data::ExtendedAddress::new(0x8899_aabb_ccdd_eeff);
+        let capabilities = capability();
+        let service = MacService::new(address, capabilities);
+
+        let (packet, timeout) = service.build_beacon_request_vec().unwrap();
+        assert_eq!(timeout, 2_000_000);
+        assert_eq!(packet, [0x03, 0x08, 0x01, 0xff, 0xff, 0xff, 0xff, 0x07]);
+
+        let packet = service.build_acknowledge_vec(0xaa, false);
+        assert_eq!(packet, [0x02, 0x00, 0xaa]);
+    }
+
     #[test]
     fn build_association_request() {
         let address = psila_data::ExtendedAddress::new(0x8899_aabb_ccdd_eeff);
-        let capabilities = psila_data::CapabilityInformation {
-            alternate_pan_coordinator: false,
-            router_capable: false,
-            mains_power: true,
-            idle_receive: true,
-            frame_protection: false,
-            allocate_address: true,
-        };
+        let capabilities = capability();
         let service = MacService::new(address, capabilities);
         let network_id = psila_data::PanIdentifier::new(0x6745);
         let coordinator_address = psila_data::ShortAddress::new(0xa987);
@@ -564,14 +1611,7 @@ mod tests {
     #[test]
     fn build_data_request() {
         let address = psila_data::ExtendedAddress::new(0x8899_aabb_ccdd_eeff);
-        let capabilities = psila_data::CapabilityInformation {
-            alternate_pan_coordinator: false,
-            router_capable: false,
-            mains_power: true,
-            idle_receive: true,
-            frame_protection: false,
-            allocate_address: true,
-        };
+        let capabilities = capability();
         let mut service = MacService::new(address, capabilities);
         let destination = psila_data::ShortAddress::new(0xa987);
         let network_id = psila_data::PanIdentifier::new(0x6745);
@@ -590,4 +1630,725 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn build_with_headroom() {
+        let mut service = associated_service();
+        service.identity.short = psila_data::ShortAddress::broadcast();
+
+        let mut data = [0xaau8; 32];
+        let (end, timeout) = service.build_beacon_request_at(&mut data, 4).unwrap();
+        assert_eq!(end, 12);
+        assert_eq!(timeout, 2_000_000);
+        assert_eq!(data[..4], [0xaa; 4]);
+        assert_eq!(
+            data[4..end],
+            [0x03, 0x08, 0x01, 0xff, 0xff, 0xff, 0xff, 0x07]
+        );
+
+        let destination = psila_data::ShortAddress::new(0xa987);
+        let (end, timeout) = service
+            .build_data_request_at(destination, &mut data, 1)
+            .unwrap();
+        assert_eq!(end, 17);
+        assert_eq!(timeout, 0);
+        assert_eq!(data[0], 0xaa);
+        assert_eq!(
+            data[1..end],
+            [
+                0x63, 0xc8, 0x02, 0x45, 0x67, 0x87, 0xa9, 0xff, 0xee, 0xdd, 0xcc, 0xbb, 0xaa, 0x99,
+                0x88, 0x04
+            ]
+        );
+
+        assert_eq!(
+            service.build_beacon_request_at(&mut data, 33),
+            Err(Error::BufferTooSmall(41))
+        );
+    }
+
+    #[test]
+    fn build_reports_needed_size() {
+        let mut service = associated_service();
+        service.identity.short = psila_data::ShortAddress::broadcast();
+        let destination = psila_data::ShortAddress::new(0xa987);
+
+        let mut data = [0u8; 8];
+        assert_eq!(
+            service.build_data_request(destination, &mut data),
+            Err(Error::BufferTooSmall(16))
+        );
+        assert_eq!(
+            service.build_data_request_at(destination, &mut data, 2),
+            Err(Error::BufferTooSmall(18))
+        );
+        assert_eq!(
+            service.build_enhanced_beacon_request(&mut data),
+            Err(Error::BufferTooSmall(17))
+        );
+        assert_eq!(
+            service.build_pan_id_conflict(&mut data),
+            Err(Error::BufferTooSmall(16))
+        );
+
+        // Retry with a buffer of the reported size
+        let mut data = [0u8; 16];
+        let (size, _) = service.build_data_request(destination, &mut data).unwrap();
+        assert_eq!(size, 16);
+        let (size, _) = service.build_pan_id_conflict(&mut data).unwrap();
+        assert_eq!(size, 16);
+
+        let mut data = [0u8; 7];
+        assert_eq!(
+            service.build_beacon_request(&mut data),
+            Err(Error::BufferTooSmall(8))
+        );
+    }
+
+    #[test]
+    fn frame_is_for_us_unicast() {
+        let service = associated_service();
+
+        let frame = data_frame(Address::Short(PanId(0x6745), ShortAddress(0x1234)));
+        assert!(service.is_for_us(&frame));
+
+        let frame = data_frame(Address::Extended(
+            PanId(0x6745),
+            ExtendedAddress(0x8899_aabb_ccdd_eeff),
+        ));
+        assert!(service.is_for_us(&frame));
+    }
+
+    #[test]
+    fn frame_is_for_us_broadcast() {
+        let service = associated_service();
+
+        let frame = data_frame(Address::Short(PanId(0x6745), ShortAddress(0xffff)));
+        assert!(service.is_for_us(&frame));
+
+        let frame = data_frame(Address::Short(PanId(0xffff), ShortAddress(0xffff)));
+        assert!(service.is_for_us(&frame));
+
+        let frame = data_frame(Address::None);
+        assert!(service.is_for_us(&frame));
+    }
+
+    #[test]
+    fn frame_is_not_for_us() {
+        let service = associated_service();
+
+        let frame = data_frame(Address::Short(PanId(0x6745), ShortAddress(0x4321)));
+        assert!(!service.is_for_us(&frame));
+
+        let frame = data_frame(Address::Short(PanId(0x1111), ShortAddress(0x1234)));
+        assert!(!service.is_for_us(&frame));
+
+        let frame = data_frame(Address::Short(PanId(0x1111), ShortAddress(0xffff)));
+        assert!(!service.is_for_us(&frame));
+
+        let frame = data_frame(Address::Extended(
+            PanId(0x6745),
+            ExtendedAddress(0x0011_2233_4455_6677),
+        ));
+        assert!(!service.is_for_us(&frame));
+    }
+
+    #[test]
+    fn beacon_extended_pan_identifier() {
+        let address = psila_data::ExtendedAddress::new(0x8899_aabb_ccdd_eeff);
+        let capabilities = capability();
+        let mut service = MacService::new(address, capabilities);
+        service.state = State::Scan;
+        assert!(!service.identity().assigned_extended_pan());
+
+        let data = [
+            0x00, 0x80, 0x01, 0x45, 0x67, 0x00, 0x00, 0xff, 0xcf, 0x00, 0x00, 0x00, 0x22, 0x84,
+            0x38, 0x2e, 0x03, 0xff, 0xff, 0x2e, 0x21, 0x00, 0xff, 0xff, 0xff, 0x00,
+        ];
+        let frame = Frame::decode(&data, false).unwrap();
+        let mut buffer = [0u8; 128];
+        let (size, _) = service.handle_frame(&frame, &mut buffer).unwrap();
+
+        assert_eq!(size, 0);
+        assert_eq!(service.state(), State::Associate);
+        assert_eq!(
+            service.pan_identifier(),
+            psila_data::PanIdentifier::new(0x6745)
+        );
+        assert!(service.identity().assigned_extended_pan());
+        assert_eq!(
+            service.extended_pan_identifier(),
+            ExtendedPanIdentifier::new(0x0021_2eff_ff03_2e38)
+        );
+    }
+
+    #[test]
+    fn start_scan_validates_channel() {
+        let address = psila_data::ExtendedAddress::new(0x8899_aabb_ccdd_eeff);
+        let capabilities = capability();
+        let mut service = MacService::new(address, capabilities);
+        service.state = State::Associate;
+        assert_eq!(
+            service.start_scan(ChannelPage::Page0, 27),
+            Err(Error::InvalidChannel)
+        );
+        assert_eq!(service.state(), State::Associate);
+        assert_eq!(service.channel(), Channel::default());
+
+        service.start_scan(ChannelPage::Page0, 20).unwrap();
+        assert_eq!(service.state(), State::Orphan);
+        assert_eq!(service.channel().number(), 20);
+        let mut buffer = [0u8; 128];
+        let (size, _) = service.timeout(&mut buffer).unwrap();
+        assert!(size > 0);
+        assert_eq!(service.state(), State::Scan);
+    }
+
+    #[test]
+    fn beacon_results_capacity() {
+        let address = psila_data::ExtendedAddress::new(0x8899_aabb_ccdd_eeff);
+        let capabilities = capability();
+        let mut service = MacService::<1>::with_beacon_capacity(address, capabilities);
+        service.state = State::Scan;
+        assert_eq!(service.beacon_results().capacity(), 1);
+
+        let data = [
+            0x00, 0x80, 0x01, 0x45, 0x67, 0x00, 0x00, 0xff, 0xcf, 0x00, 0x00, 0x00, 0x22, 0x84,
+            0x38, 0x2e, 0x03, 0xff, 0xff, 0x2e, 0x21, 0x00, 0xff, 0xff, 0xff, 0x00,
+        ];
+        let frame = Frame::decode(&data, false).unwrap();
+        let mut buffer = [0u8; 128];
+        let _ = service.handle_frame(&frame, &mut buffer).unwrap();
+
+        assert_eq!(service.beacon_results().len(), 1);
+        let result = service.beacon_results().iter().next().unwrap();
+        assert_eq!(
+            result.pan_identifier,
+            psila_data::PanIdentifier::new(0x6745)
+        );
+        assert_eq!(result.coordinator, psila_data::ShortAddress::new(0x0000));
+        assert_eq!(
+            result.extended_pan_identifier,
+            Some(ExtendedPanIdentifier::new(0x0021_2eff_ff03_2e38))
+        );
+        assert!(result.pan_coordinator);
+        assert!(result.association_permit);
+    }
+
+    #[test]
+    fn beacon_not_zigbee() {
+        let mut service = associated_service();
+        service.state = State::Scan;
+        let mut buffer = [0u8; 128];
+
+        // Beacon from a PAN coordinator permitting association, without
+        // beacon payload
+        let data = [
+            0x00, 0x80, 0x01, 0x45, 0x67, 0x00, 0x00, 0xff, 0xcf, 0x00, 0x00,
+        ];
+        let frame = Frame::decode(&data, false).unwrap();
+        let (size, timeout) = service.handle_frame(&frame, &mut buffer).unwrap();
+        assert_eq!((size, timeout), (0, 0));
+        assert_eq!(service.state(), State::Scan);
+        assert!(service.beacon_results().is_empty());
+
+        // Same beacon with another protocol identifier
+        let data = [
+            0x00, 0x80, 0x01, 0x45, 0x67, 0x00, 0x00, 0xff, 0xcf, 0x00, 0x00, 0x01, 0x22, 0x84,
+            0x38, 0x2e, 0x03, 0xff, 0xff, 0x2e, 0x21, 0x00, 0xff, 0xff, 0xff, 0x00,
+        ];
+        let frame = Frame::decode(&data, false).unwrap();
+        let (size, _) = service.handle_frame(&frame, &mut buffer).unwrap();
+        assert_eq!(size, 0);
+        assert_eq!(service.state(), State::Scan);
+        assert!(service.beacon_results().is_empty());
+    }
+
+    #[test]
+    fn beacon_pending_address() {
+        let mut service = associated_service();
+        service.state = State::Scan;
+
+        // Beacon from 6745:0000 with 1 pending short address, 0x1234, and
+        // 1 pending extended address
+        let data = [
+            0x00, 0x80, 0x01, 0x45, 0x67, 0x00, 0x00, 0xff, 0xcf, 0x00, 0x11, 0x34, 0x12, 0x77,
+            0x66, 0x55, 0x44, 0x33, 0x22, 0x11, 0x00, 0x00, 0x22, 0x84, 0x38, 0x2e, 0x03, 0xff,
+            0xff, 0x2e, 0x21, 0x00, 0xff, 0xff, 0xff, 0x00,
+        ];
+        let frame = Frame::decode(&data, false).unwrap();
+        if let FrameContent::Beacon(beacon) = &frame.content {
+            assert!(service.beacon_data_pending(beacon));
+            assert_eq!(service.beacon_time_slot(beacon), None);
+        } else {
+            unreachable!();
+        }
+        let mut buffer = [0u8; 128];
+        let _ = service.handle_frame(&frame, &mut buffer).unwrap();
+        let result = service.beacon_results().iter().next().unwrap();
+        assert!(result.data_pending);
+        assert!(!result.gts_permit);
+
+        // Same beacon without our address
+        let mut data = data;
+        data[11] = 0x35;
+        let frame = Frame::decode(&data, false).unwrap();
+        if let FrameContent::Beacon(beacon) = &frame.content {
+            assert!(!service.beacon_data_pending(beacon));
+        } else {
+            unreachable!();
+        }
+    }
+
+    #[test]
+    fn beacon_pending_address_data_request() {
+        let mut service = associated_service();
+        service.coordinator.short = psila_data::ShortAddress::new(0x0000);
+
+        // Beacon from the coordinator, 6745:0000, with our address pending
+        let data = [
+            0x00, 0x80, 0x01, 0x45, 0x67, 0x00, 0x00, 0xff, 0xcf, 0x00, 0x01, 0x34, 0x12, 0x00,
+            0x22, 0x84, 0x38, 0x2e, 0x03, 0xff, 0xff, 0x2e, 0x21, 0x00, 0xff, 0xff, 0xff, 0x00,
+        ];
+        let frame = Frame::decode(&data, false).unwrap();
+        let mut buffer = [0u8; 128];
+        let (size, timeout) = service.handle_frame(&frame, &mut buffer).unwrap();
+        assert_eq!(timeout, 0);
+        assert_eq!(size, 10);
+        // Data request from 0x1234 to 0x0000
+        assert_eq!(
+            buffer[..size],
+            [0x63, 0x88, 0x01, 0x45, 0x67, 0x00, 0x00, 0x34, 0x12, 0x04]
+        );
+        assert_eq!(service.state(), State::Associated);
+
+        // Beacon from another device is ignored
+        let mut data = data;
+        data[5] = 0x01;
+        let frame = Frame::decode(&data, false).unwrap();
+        let (size, _) = service.handle_frame(&frame, &mut buffer).unwrap();
+        assert_eq!(size, 0);
+    }
+
+    #[test]
+    fn beacon_pan_id_conflict() {
+        let mut service = associated_service();
+        service.coordinator.short = psila_data::ShortAddress::new(0x0000);
+        service.identity.extended_pan = ExtendedPanIdentifier::new(0x0021_2eff_ff03_2e38);
+
+        // Beacon from the coordinator
+        let data = [
+            0x00, 0x80, 0x01, 0x45, 0x67, 0x00, 0x00, 0xff, 0xcf, 0x00, 0x00, 0x00, 0x22, 0x84,
+            0x38, 0x2e, 0x03, 0xff, 0xff, 0x2e, 0x21, 0x00, 0xff, 0xff, 0xff, 0x00,
+        ];
+        let frame = Frame::decode(&data, false).unwrap();
+        let mut buffer = [0u8; 128];
+        let (size, _) = service.handle_frame(&frame, &mut buffer).unwrap();
+        assert_eq!(size, 0);
+
+        // Beacon with the same PAN identifier but another extended PAN identifier
+        let mut data = data;
+        data[14] = 0x39;
+        let frame = Frame::decode(&data, false).unwrap();
+        assert!(service.pan_id_conflict(
+            psila_data::PanIdentifier::new(0x6745),
+            ExtendedPanIdentifier::new(0x0021_2eff_ff03_2e39)
+        ));
+        let (size, timeout) = service.handle_frame(&frame, &mut buffer).unwrap();
+        assert_eq!(timeout, 0);
+        assert_eq!(size, 16);
+        // PAN identifier conflict from 8899aabbccddeeff to 0x0000
+        assert_eq!(
+            buffer[..size],
+            [
+                0x63, 0xc8, 0x01, 0x45, 0x67, 0x00, 0x00, 0xff, 0xee, 0xdd, 0xcc, 0xbb, 0xaa, 0x99,
+                0x88, 0x05
+            ]
+        );
+        assert_eq!(service.state(), State::Associated);
+    }
+
+    fn coordinator_service() -> MacService {
+        let address = psila_data::ExtendedAddress::new(0x0011_2233_4455_6677);
+        let capabilities = psila_data::CapabilityInformation {
+            alternate_pan_coordinator: true,
+            router_capable: true,
+            ..capability()
+        };
+        MacService::new_coordinator(
+            address,
+            capabilities,
+            psila_data::PanIdentifier::new(0x6745),
+            ExtendedPanIdentifier::new(0x0021_2eff_ff03_2e38),
+        )
+    }
+
+    /// Association request from 8899aabbccddee`low` to 6745:0000
+    fn coordinator_association_request_frame(low: u8) -> [u8; 19] {
+        [
+            0x23, 0xc8, 0x01, 0x45, 0x67, 0x00, 0x00, 0xff, 0xff, low, 0xee, 0xdd, 0xcc, 0xbb,
+            0xaa, 0x99, 0x88, 0x01, 0x8c,
+        ]
+    }
+
+    /// Data request from 8899aabbccddee`low` to 6745:0000
+    fn coordinator_data_request_frame(low: u8) -> [u8; 16] {
+        [
+            0x63, 0xc8, 0x02, 0x45, 0x67, 0x00, 0x00, low, 0xee, 0xdd, 0xcc, 0xbb, 0xaa, 0x99,
+            0x88, 0x04,
+        ]
+    }
+
+    /// Associate the device 8899aabbccddee`low`, returns the association
+    /// response retrieved with a data request
+    fn coordinator_associate(service: &mut MacService, low: u8, buffer: &mut [u8]) -> usize {
+        let data = coordinator_association_request_frame(low);
+        let frame = Frame::decode(&data, false).unwrap();
+        // The response is queued as indirect data
+        assert_eq!(service.handle_frame(&frame, buffer).unwrap(), (0, 0));
+        let data = coordinator_data_request_frame(low);
+        let frame = Frame::decode(&data, false).unwrap();
+        assert!(service.data_pending(&frame));
+        let (size, timeout) = service.handle_frame(&frame, buffer).unwrap();
+        assert_eq!(timeout, 0);
+        assert!(!service.data_pending(&frame));
+        size
+    }
+
+    #[test]
+    fn coordinator_association_request() {
+        let mut service = coordinator_service();
+        assert_eq!(service.state(), State::Coordinator);
+        assert_eq!(
+            service.identity().short,
+            psila_data::ShortAddress::new(0x0000)
+        );
+
+        let mut buffer = [0u8; 128];
+        let size = coordinator_associate(&mut service, 0xff, &mut buffer);
+        assert_eq!(size, 25);
+        // Association response from 0011223344556677 to 8899aabbccddeeff,
+        // address 0x0001, successful
+        assert_eq!(
+            buffer[..size],
+            [
+                0x63, 0xcc, 0x01, 0x45, 0x67, 0xff, 0xee, 0xdd, 0xcc, 0xbb, 0xaa, 0x99, 0x88, 0x77,
+                0x66, 0x55, 0x44, 0x33, 0x22, 0x11, 0x00, 0x02, 0x01, 0x00, 0x00
+            ]
+        );
+
+        // Nothing more to retrieve
+        let data = coordinator_data_request_frame(0xff);
+        let frame = Frame::decode(&data, false).unwrap();
+        assert_eq!(service.handle_frame(&frame, &mut buffer).unwrap(), (0, 0));
+
+        // The same device associating again keeps its address
+        let size = coordinator_associate(&mut service, 0xff, &mut buffer);
+        assert_eq!(buffer[size - 3..size], [0x01, 0x00, 0x00]);
+
+        // Another device is given the next address
+        let size = coordinator_associate(&mut service, 0xf0, &mut buffer);
+        assert_eq!(buffer[5], 0xf0);
+        assert_eq!(buffer[size - 3..size], [0x02, 0x00, 0x00]);
+
+        // A released address is handed out again
+        assert!(service.release_short_address(psila_data::ShortAddress::new(0x0001)));
+        assert!(!service.release_short_address(psila_data::ShortAddress::new(0x0001)));
+        let size = coordinator_associate(&mut service, 0xf1, &mut buffer);
+        assert_eq!(buffer[size - 3..size], [0x01, 0x00, 0x00]);
+
+        // Associations not permitted
+        service.association_permit = false;
+        let data = coordinator_association_request_frame(0xf2);
+        let frame = Frame::decode(&data, false).unwrap();
+        assert_eq!(service.handle_frame(&frame, &mut buffer).unwrap(), (0, 0));
+        let data = coordinator_data_request_frame(0xf2);
+        let frame = Frame::decode(&data, false).unwrap();
+        assert!(!service.data_pending(&frame));
+        assert_eq!(service.handle_frame(&frame, &mut buffer).unwrap(), (0, 0));
+    }
+
+    #[test]
+    fn coordinator_beacon_request() {
+        let mut service = coordinator_service();
+
+        let data = [0x03, 0x08, 0x01, 0xff, 0xff, 0xff, 0xff, 0x07];
+        let frame = Frame::decode(&data, false).unwrap();
+        let mut buffer = [0u8; 128];
+        let (size, _) = service.handle_frame(&frame, &mut buffer).unwrap();
+        assert_eq!(size, 26);
+        // Beacon from 6745:0000, PAN coordinator permitting association
+        assert_eq!(
+            buffer[..size],
+            [
+                0x00, 0x80, 0x01, 0x45, 0x67, 0x00, 0x00, 0xff, 0xcf, 0x00, 0x00, 0x00, 0x22, 0x84,
+                0x38, 0x2e, 0x03, 0xff, 0xff, 0x2e, 0x21, 0x00, 0xff, 0xff, 0xff, 0x00
+            ]
+        );
+
+        // Devices ignore beacon requests
+        let mut service = associated_service();
+        let (size, _) = service.handle_frame(&frame, &mut buffer).unwrap();
+        assert_eq!(size, 0);
+    }
+
+    #[test]
+    fn association_response_wait_time() {
+        let mut service = associated_service();
+        service.state = State::Associate;
+        service.identity.short = psila_data::ShortAddress::broadcast();
+        service.coordinator.short = psila_data::ShortAddress::new(0x0000);
+        assert_eq!(service.response_wait_time(), DEFAULT_RESPONSE_WAIT_TIME);
+        service.set_response_wait_time(100_000);
+
+        let mut buffer = [0u8; 128];
+        let (size, _) = service.timeout(&mut buffer).unwrap();
+        assert!(size > 0);
+        let sequence = buffer[2];
+
+        // Acknowledge of the association request, no data request is sent
+        // until the response wait time has passed
+        let data = [0x02, 0x00, sequence];
+        let frame = Frame::decode(&data, false).unwrap();
+        let (size, timeout) = service.handle_frame(&frame, &mut buffer).unwrap();
+        assert_eq!(size, 0);
+        assert_eq!(timeout, 100_000);
+        assert_eq!(service.state(), State::QueryAssociationStatus);
+
+        let (size, timeout) = service.timeout(&mut buffer).unwrap();
+        assert_eq!(timeout, 100_000);
+        assert_eq!(service.state(), State::WaitAssociationResponse);
+        let frame = Frame::decode(&buffer[..size], false).unwrap();
+        assert!(matches!(
+            frame.content,
+            FrameContent::Command(Command::DataRequest)
+        ));
+        assert!(matches!(
+            frame.header.destination,
+            Address::Short(PanId(0x6745), ShortAddress(0x0000))
+        ));
+
+        // No response within the wait time
+        let (size, _) = service.timeout(&mut buffer).unwrap();
+        assert_eq!(size, 0);
+        assert_eq!(service.state(), State::Orphan);
+    }
+
+    fn association_response_frame(sequence: u8, frame_pending: bool) -> [u8; 25] {
+        let frame = Frame {
+            header: Header {
+                seq: sequence,
+                frame_type: FrameType::MacCommand,
+                security: Security::None,
+                frame_pending,
+                ack_request: true,
+                pan_id_compress: true,
+                version: FrameVersion::Ieee802154_2003,
+                destination: Address::Extended(
+                    PanId(0x6745),
+                    ExtendedAddress(0x8899_aabb_ccdd_eeff),
+                ),
+                source: Address::Extended(PanId(0x6745), ExtendedAddress(0x0011_2233_4455_6677)),
+            },
+            content: FrameContent::Command(Command::AssociationResponse(
+                ShortAddress(0x1234),
+                AssociationStatus::Successful,
+            )),
+            payload: &[],
+            footer: [0u8; 2],
+        };
+        let mut data = [0u8; 25];
+        let size = encode_frame(&frame, ASSOCIATION_RESPONSE_CONTENT_SIZE, &mut data).unwrap();
+        assert_eq!(size, data.len());
+        data
+    }
+
+    #[test]
+    fn indirect_association_response() {
+        let mut service = associated_service();
+        service.state = State::QueryAssociationStatus;
+        service.identity.short = psila_data::ShortAddress::broadcast();
+        service.coordinator.short = psila_data::ShortAddress::new(0x0000);
+
+        let mut buffer = [0u8; 128];
+        let (size, _) = service.timeout(&mut buffer).unwrap();
+        assert_eq!(service.state(), State::WaitAssociationResponse);
+        let sequence = buffer[2];
+        let frame = Frame::decode(&buffer[..size], false).unwrap();
+        assert!(matches!(
+            frame.content,
+            FrameContent::Command(Command::DataRequest)
+        ));
+        assert!(frame.header.ack_request);
+
+        // Acknowledge of the data request with frame pending set, the
+        // coordinator has the association response
+        let data = [0x12, 0x00, sequence];
+        let frame = Frame::decode(&data, false).unwrap();
+        let (size, timeout) = service.handle_frame(&frame, &mut buffer).unwrap();
+        assert_eq!((size, timeout), (0, 0));
+        assert_eq!(service.state(), State::WaitAssociationResponse);
+        assert_eq!(service.status().pending_acknowledge, None);
+
+        // The association response as indirect data, more data pending
+        let data = association_response_frame(0x55, true);
+        let frame = Frame::decode(&data, false).unwrap();
+        assert!(service.is_for_us(&frame));
+        assert!(service.requests_acknowledge(&frame));
+        let (size, _) = service.handle_frame(&frame, &mut buffer).unwrap();
+        assert_eq!(service.state(), State::Associated);
+        assert_eq!(
+            service.identity().short,
+            psila_data::ShortAddress::new(0x1234)
+        );
+
+        // Poll the coordinator for the pending data
+        let frame = Frame::decode(&buffer[..size], false).unwrap();
+        assert!(matches!(
+            frame.content,
+            FrameContent::Command(Command::DataRequest)
+        ));
+        assert!(matches!(
+            frame.header.destination,
+            Address::Short(PanId(0x6745), ShortAddress(0x0000))
+        ));
+    }
+
+    #[test]
+    fn indirect_association_response_not_pending() {
+        let mut service = associated_service();
+        service.state = State::QueryAssociationStatus;
+        service.identity.short = psila_data::ShortAddress::broadcast();
+        service.coordinator.short = psila_data::ShortAddress::new(0x0000);
+
+        let mut buffer = [0u8; 128];
+        service.timeout(&mut buffer).unwrap();
+        let sequence = buffer[2];
+
+        // Acknowledge without frame pending, there is no response to retrieve
+        let data = [0x02, 0x00, sequence];
+        let frame = Frame::decode(&data, false).unwrap();
+        let (size, timeout) = service.handle_frame(&frame, &mut buffer).unwrap();
+        assert_eq!((size, timeout), (0, ASSOCIATION_RETRY_TIME));
+        assert_eq!(service.state(), State::Orphan);
+
+        // A late response is ignored
+        let data = association_response_frame(0x56, false);
+        let frame = Frame::decode(&data, false).unwrap();
+        assert_eq!(service.handle_frame(&frame, &mut buffer).unwrap(), (0, 0));
+        assert_eq!(service.state(), State::Orphan);
+    }
+
+    #[test]
+    fn status_reflects_transition() {
+        let mut service = associated_service();
+        service.state = State::Associate;
+        service.identity.short = psila_data::ShortAddress::broadcast();
+        service.coordinator.short = psila_data::ShortAddress::new(0x0000);
+        service.set_response_wait_time(100_000);
+        let status = service.status();
+        assert_eq!(status.state, State::Associate);
+        assert_eq!(status.channel, Channel::default());
+        assert_eq!(status.pending_acknowledge, None);
+
+        // The association request waits for a acknowledge
+        let mut buffer = [0u8; 128];
+        let (size, timeout) = service.timeout(&mut buffer).unwrap();
+        assert!(size > 0);
+        assert_eq!(service.status().pending_acknowledge, Some(buffer[2]));
+
+        // Not acknowledged, the association request is sent again
+        let _ = service.timeout(&mut buffer).unwrap();
+        let sequence = buffer[2];
+        let status = service.status();
+        assert_eq!(status.state, State::Associate);
+        assert_eq!(status.elapsed, timeout);
+        assert_eq!(status.pending_acknowledge, Some(sequence));
+
+        let data = [0x02, 0x00, sequence];
+        let frame = Frame::decode(&data, false).unwrap();
+        let _ = service.handle_frame(&frame, &mut buffer).unwrap();
+        let status = service.status();
+        assert_eq!(status.state, State::QueryAssociationStatus);
+        assert_eq!(status.pending_acknowledge, None);
+        assert_eq!(status.elapsed, 0);
+
+        // The data request doesn't get a response, retry the scan
+        let _ = service.timeout(&mut buffer).unwrap();
+        assert_eq!(service.status().state, State::WaitAssociationResponse);
+        let _ = service.timeout(&mut buffer).unwrap();
+        let status = service.status();
+        assert_eq!(status.state, State::Orphan);
+        assert_eq!(status.elapsed, 0);
+        assert_eq!(status.attempts, 0);
+
+        let _ = service.timeout(&mut buffer).unwrap();
+        let status = service.status();
+        assert_eq!(status.state, State::Scan);
+        assert_eq!(status.attempts, 1);
+    }
+
+    #[test]
+    fn reset_to_orphan() {
+        let mut service = associated_service();
+        service.coordinator.short = psila_data::ShortAddress::new(0x0000);
+        service.identity.extended_pan = ExtendedPanIdentifier::new(0x0011_2233_4455_6677);
+        service.reset();
+
+        assert_eq!(service.state(), State::Orphan);
+        assert_eq!(service.pan_identifier(), PanIdentifier::broadcast());
+        let identity = service.identity();
+        assert!(!identity.assigned_short());
+        assert!(!identity.assigned_extended_pan());
+        assert_eq!(
+            identity.extended,
+            psila_data::ExtendedAddress::new(0x8899_aabb_ccdd_eeff)
+        );
+        assert!(!service.coordinator_identity().assigned_short());
+        assert!(service.capabilities.mains_power);
+        assert!(service.capabilities.allocate_address);
+
+        // A new scan is started
+        let mut buffer = [0u8; 128];
+        let (size, _) = service.timeout(&mut buffer).unwrap();
+        assert!(size > 0);
+        assert_eq!(service.state(), State::Scan);
+    }
+
+    #[test]
+    fn coordinator_permit_join_expires() {
+        let mut service = coordinator_service();
+        assert!(service.association_permit());
+        let mut buffer = [0u8; 128];
+        // Permitted for ever, no countdown
+        assert_eq!(service.timeout(&mut buffer).unwrap(), (0, 0));
+        assert!(service.association_permit());
+
+        assert_eq!(service.set_permit_join(2), PERMIT_JOIN_TICK);
+        let (size, _) = service.build_beacon(&mut buffer).unwrap();
+        let frame = Frame::decode(&buffer[..size], false).unwrap();
+        assert!(matches!(
+            frame.content,
+            FrameContent::Beacon(ref beacon) if beacon.superframe_spec.association_permit
+        ));
+
+        assert_eq!(service.timeout(&mut buffer).unwrap(), (0, PERMIT_JOIN_TICK));
+        assert!(service.association_permit());
+        assert_eq!(service.timeout(&mut buffer).unwrap(), (0, 0));
+        assert!(!service.association_permit());
+
+        let (size, _) = service.build_beacon(&mut buffer).unwrap();
+        let frame = Frame::decode(&buffer[..size], false).unwrap();
+        assert!(matches!(
+            frame.content,
+            FrameContent::Beacon(ref beacon) if !beacon.superframe_spec.association_permit
+        ));
+        // The beacon payload tells there is no capacity for new devices
+        let (information, _) = BeaconInformation::unpack(frame.payload).unwrap();
+        assert!(!information.router_capacity);
+        assert!(!information.end_device_capacity);
+
+        assert_eq!(service.set_permit_join(0), 0);
+        assert!(!service.association_permit());
+    }
 }
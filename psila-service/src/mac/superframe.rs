@@ -0,0 +1,134 @@
+//! # Superframe timing
+//!
+//! Beacon interval and superframe duration of beacon enabled networks,
+//! derived from the beacon order and superframe order of a beacon. Used by
+//! end devices to wake up around the beacons of the coordinator.
+
+use ieee802154::mac::beacon::{Beacon, BeaconOrder, SuperframeOrder};
+
+/// aBaseSlotDuration, symbols in a superframe slot with superframe order
+/// zero
+pub const BASE_SLOT_DURATION: u32 = 60;
+
+/// aNumSuperframeSlots, slots in a superframe
+pub const NUMBER_OF_SUPERFRAME_SLOTS: u32 = 16;
+
+/// aBaseSuperframeDuration, symbols in a superframe with superframe order
+/// zero
+pub const BASE_SUPERFRAME_DURATION: u32 = BASE_SLOT_DURATION * NUMBER_OF_SUPERFRAME_SLOTS;
+
+/// Duration of a symbol in microseconds, for the 2.4 GHz PHY
+pub const SYMBOL_DURATION: u32 = 16;
+
+/// Highest beacon or superframe order, 15 means no beacons or no active
+/// period
+pub const MAX_ORDER: u8 = 14;
+
+/// `aBaseSuperframeDuration * 2^order` symbols, `None` if the order is too
+/// high
+fn duration(order: u8) -> Option<u32> {
+    if order > MAX_ORDER {
+        None
+    } else {
+        Some(BASE_SUPERFRAME_DURATION << order)
+    }
+}
+
+/// Beacon interval in symbols, `None` if beacons are only sent on demand
+pub fn beacon_interval_symbols(order: BeaconOrder) -> Option<u32> {
+    match order {
+        BeaconOrder::BeaconOrder(order) => duration(order),
+        BeaconOrder::OnDemand => None,
+    }
+}
+
+/// Beacon interval in microseconds, `None` if beacons are only sent on
+/// demand
+pub fn beacon_interval(order: BeaconOrder) -> Option<u32> {
+    beacon_interval_symbols(order).map(|symbols| symbols * SYMBOL_DURATION)
+}
+
+/// Superframe duration, the active period following a beacon, in symbols,
+/// `None` if the superframe is inactive
+pub fn superframe_duration_symbols(order: SuperframeOrder) -> Option<u32> {
+    match order {
+        SuperframeOrder::SuperframeOrder(order) => duration(order),
+        SuperframeOrder::Inactive => None,
+    }
+}
+
+/// Superframe duration, the active period following a beacon, in
+/// microseconds, `None` if the superframe is inactive
+pub fn superframe_duration(order: SuperframeOrder) -> Option<u32> {
+    superframe_duration_symbols(order).map(|symbols| symbols * SYMBOL_DURATION)
+}
+
+/// Timing of the superframes of a beacon enabled network
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SuperframeTiming {
+    /// Time between the start of two beacons, in microseconds
+    pub beacon_interval: u32,
+    /// Duration of the active period following a beacon, in microseconds
+    pub superframe_duration: u32,
+}
+
+impl SuperframeTiming {
+    /// Timing of the network which sent `beacon`, `None` if the network
+    /// doesn't send periodic beacons
+    pub fn from_beacon(beacon: &Beacon) -> Option<Self> {
+        let spec = &beacon.superframe_spec;
+        let beacon_interval = beacon_interval(spec.beacon_order)?;
+        let superframe_duration = superframe_duration(spec.superframe_order).unwrap_or(0);
+        Some(Self {
+            beacon_interval,
+            superframe_duration,
+        })
+    }
+
+    /// Duration of a superframe slot in microseconds
+    pub fn slot_duration(&self) -> u32 {
+        self.superframe_duration / NUMBER_OF_SUPERFRAME_SLOTS
+    }
+
+    /// Duration of the inactive period, in which the device sleeps, from the
+    /// end of the active period to the next beacon, in microseconds
+    pub fn inactive_duration(&self) -> u32 {
+        self.beacon_interval
+            .saturating_sub(self.superframe_duration)
+    }
+}
+
+#[cfg(all(test, not(feature = "core")))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn beacon_order_6() {
+        // 960 * 2^6 symbols, 16 microseconds each
+        let order = BeaconOrder::BeaconOrder(6);
+        assert_eq!(beacon_interval_symbols(order), Some(61_440));
+        assert_eq!(beacon_interval(order), Some(983_040));
+
+        let order = SuperframeOrder::SuperframeOrder(6);
+        assert_eq!(superframe_duration_symbols(order), Some(61_440));
+        assert_eq!(superframe_duration(order), Some(983_040));
+
+        let timing = SuperframeTiming {
+            beacon_interval: beacon_interval(BeaconOrder::BeaconOrder(6)).unwrap(),
+            superframe_duration: superframe_duration(SuperframeOrder::SuperframeOrder(4)).unwrap(),
+        };
+        assert_eq!(timing.slot_duration(), 15_360);
+        assert_eq!(timing.inactive_duration(), 983_040 - 245_760);
+    }
+
+    #[test]
+    fn no_beacons() {
+        assert_eq!(beacon_interval(BeaconOrder::OnDemand), None);
+        assert_eq!(superframe_duration(SuperframeOrder::Inactive), None);
+        assert_eq!(beacon_interval(BeaconOrder::BeaconOrder(15)), None);
+        assert_eq!(
+            beacon_interval(BeaconOrder::BeaconOrder(MAX_ORDER)),
+            Some(251_658_240)
+        );
+    }
+}
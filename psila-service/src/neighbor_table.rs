@@ -0,0 +1,175 @@
+//! # Neighbor table
+//!
+//! Keeps track of the short to extended address mapping of the neighboring
+//! devices. Used to detect address conflicts, 3.6.1.9 Address Conflicts.
+
+use psila_data::{network::NetworkHeader, ExtendedAddress, NetworkAddress};
+
+/// Number of neighbors tracked
+pub const NEIGHBOR_TABLE_SIZE: usize = 16;
+/// Number of unresolved address conflicts tracked
+pub const ADDRESS_CONFLICTS_SIZE: usize = 4;
+
+/// Neighbor table entry
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Neighbor {
+    /// Network (short) address of the neighbor
+    pub network_address: NetworkAddress,
+    /// IEEE (extended) address of the neighbor
+    pub extended_address: ExtendedAddress,
+}
+
+/// Table of known neighbors and detected address conflicts
+#[derive(Clone, Debug, Default)]
+pub struct NeighborTable {
+    entries: [Option<Neighbor>; NEIGHBOR_TABLE_SIZE],
+    next: usize,
+    conflicts: [Option<NetworkAddress>; ADDRESS_CONFLICTS_SIZE],
+}
+
+impl NeighborTable {
+    /// Find the extended address of a neighbor using its network address
+    pub fn extended_address(&self, network_address: NetworkAddress) -> Option<ExtendedAddress> {
+        self.entries
+            .iter()
+            .flatten()
+            .find(|n| n.network_address == network_address)
+            .map(|n| n.extended_address)
+    }
+
+    /// Update the table with a network and extended address pair
+    ///
+    /// If the network address is known but mapped to another extended
+    /// address, the network address is flagged as conflicting and `true` is
+    /// returned. A device which has changed its network address is updated.
+    pub fn update(
+        &mut self,
+        network_address: NetworkAddress,
+        extended_address: ExtendedAddress,
+    ) -> bool {
+        if let Some(known) = self.extended_address(network_address) {
+            if known == extended_address {
+                return false;
+            }
+            self.flag_conflict(network_address);
+            return true;
+        }
+        if let Some(entry) = self
+            .entries
+            .iter_mut()
+            .flatten()
+            .find(|n| n.extended_address == extended_address)
+        {
+            entry.network_address = network_address;
+            return false;
+        }
+        self.entries[self.next] = Some(Neighbor {
+            network_address,
+            extended_address,
+        });
+        self.next = (self.next + 1) % NEIGHBOR_TABLE_SIZE;
+        false
+    }
+
+    /// Update the table from a received network header, only headers which
+    /// carry the source IEEE address are used
+    pub fn update_from_header(&mut self, header: &NetworkHeader) -> bool {
+        match header.source_ieee_address {
+            Some(extended_address) => self.update(header.source_address, extended_address),
+            None => false,
+        }
+    }
+
+    /// Flag a network address as conflicting
+    pub fn flag_conflict(&mut self, network_address: NetworkAddress) {
+        if self.has_conflict(network_address) {
+            return;
+        }
+        if let Some(slot) = self.conflicts.iter_mut().find(|c| c.is_none()) {
+            *slot = Some(network_address);
+        }
+    }
+
+    /// Check if the network address has been flagged as conflicting
+    pub fn has_conflict(&self, network_address: NetworkAddress) -> bool {
+        self.conflicts
+            .iter()
+            .flatten()
+            .any(|c| *c == network_address)
+    }
+
+    /// Network addresses with detected conflicts
+    pub fn conflicts(&self) -> impl Iterator<Item = &NetworkAddress> {
+        self.conflicts.iter().flatten()
+    }
+
+    /// Remove a conflict, i.e. when the address has been reassigned. Any
+    /// neighbor using the address is forgotten.
+    pub fn resolve_conflict(&mut self, network_address: NetworkAddress) {
+        for conflict in self.conflicts.iter_mut() {
+            if *conflict == Some(network_address) {
+                *conflict = None;
+            }
+        }
+        for entry in self.entries.iter_mut() {
+            if entry.map_or(false, |n| n.network_address == network_address) {
+                *entry = None;
+            }
+        }
+    }
+}
+
+#[cfg(all(test, not(feature = "core")))]
+mod tests {
+    use super::*;
+    use psila_data::pack::Pack;
+
+    #[test]
+    fn detect_address_conflict() {
+        let mut table = NeighborTable::default();
+        // Data frame from 0x1234 with source IEEE address 0x0011223344556677
+        let data = [
+            0x08, 0x10, 0x00, 0x00, 0x34, 0x12, 0x1e, 0x01, 0x77, 0x66, 0x55, 0x44, 0x33, 0x22,
+            0x11, 0x00,
+        ];
+        let (header, _) = NetworkHeader::unpack(&data[..]).unwrap();
+        assert!(!table.update_from_header(&header));
+        assert_eq!(
+            table.extended_address(NetworkAddress::new(0x1234)),
+            Some(ExtendedAddress::new(0x0011_2233_4455_6677))
+        );
+        assert!(!table.update_from_header(&header));
+        assert_eq!(table.conflicts().count(), 0);
+
+        // Data frame from 0x1234 with source IEEE address 0x8899aabbccddeeff
+        let data = [
+            0x08, 0x10, 0x00, 0x00, 0x34, 0x12, 0x1e, 0x02, 0xff, 0xee, 0xdd, 0xcc, 0xbb, 0xaa,
+            0x99, 0x88,
+        ];
+        let (header, _) = NetworkHeader::unpack(&data[..]).unwrap();
+        assert!(table.update_from_header(&header));
+        assert!(table.has_conflict(NetworkAddress::new(0x1234)));
+        assert_eq!(table.conflicts().next(), Some(&NetworkAddress::new(0x1234)));
+        // Flagged once
+        assert!(table.update_from_header(&header));
+        assert_eq!(table.conflicts().count(), 1);
+
+        table.resolve_conflict(NetworkAddress::new(0x1234));
+        assert_eq!(table.conflicts().count(), 0);
+        assert_eq!(table.extended_address(NetworkAddress::new(0x1234)), None);
+    }
+
+    #[test]
+    fn address_change_is_not_a_conflict() {
+        let mut table = NeighborTable::default();
+        let extended_address = ExtendedAddress::new(0x0011_2233_4455_6677);
+        assert!(!table.update(NetworkAddress::new(0x1234), extended_address));
+        assert!(!table.update(NetworkAddress::new(0x4321), extended_address));
+        assert_eq!(
+            table.extended_address(NetworkAddress::new(0x4321)),
+            Some(extended_address)
+        );
+        assert_eq!(table.extended_address(NetworkAddress::new(0x1234)), None);
+        assert_eq!(table.conflicts().count(), 0);
+    }
+}
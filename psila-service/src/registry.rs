@@ -0,0 +1,328 @@
+//! # Endpoint registry
+//!
+//! The endpoints, clusters and attributes supported by the application. Used
+//! to answer the simple descriptor, active endpoint and discover attributes
+//! requests.
+
+use psila_data::{
+    application_service,
+    cluster_library::{
+        AttributeDataType, AttributeIdentifier, DiscoverAttributeVec, DiscoverAttributes,
+        DiscoverAttributesResponse,
+    },
+    device_profile::{SimpleDescriptor, MAX_CLUSTERS},
+};
+
+use crate::Error;
+
+/// Maximum number of application endpoints in the registry
+pub const MAX_ENDPOINTS: usize = 8;
+
+/// Maximum number of attributes in a discover attributes response, the
+/// capacity of `DiscoverAttributeVec` without the standard library
+const MAX_DISCOVERED_ATTRIBUTES: usize = 16;
+
+/// A server cluster supported on a endpoint, with its attributes
+#[derive(Clone, Copy, Debug)]
+pub struct Cluster {
+    /// Cluster identifier
+    pub identifier: u16,
+    /// Attribute identifiers and data types, in ascending order
+    pub attributes: &'static [(u16, AttributeDataType)],
+}
+
+impl Cluster {
+    /// Answer a discover attributes request
+    ///
+    /// Lists the attributes starting from the requested attribute identifier,
+    /// at most the requested count. The response is marked incomplete if
+    /// more attributes remain, also when the response is full.
+    pub fn discover_attributes(&self, request: &DiscoverAttributes) -> DiscoverAttributesResponse {
+        let start = u16::from(request.start);
+        let count = usize::from(request.count).min(MAX_DISCOVERED_ATTRIBUTES);
+        let mut attributes = DiscoverAttributeVec::new();
+        let mut remaining = self
+            .attributes
+            .iter()
+            .filter(|(identifier, _)| *identifier >= start);
+        for (identifier, data_type) in remaining.by_ref().take(count) {
+            attributes.push((AttributeIdentifier::new(*identifier), *data_type));
+        }
+        DiscoverAttributesResponse {
+            complete: remaining.next().is_none(),
+            attributes,
+        }
+    }
+}
+
+/// A application endpoint
+#[derive(Clone, Copy, Debug)]
+pub struct Endpoint {
+    /// Endpoint identifier, 1 to 240
+    pub identifier: u8,
+    /// Application profile identifier
+    pub profile: u16,
+    /// Application device identifier
+    pub device: u16,
+    /// Application device version
+    pub device_version: u8,
+    /// Server clusters implemented by the endpoint
+    pub input_clusters: &'static [Cluster],
+    /// Client clusters implemented by the endpoint
+    pub output_clusters: &'static [u16],
+}
+
+impl Endpoint {
+    /// The server cluster with identifier `cluster`
+    pub fn input_cluster(&self, cluster: u16) -> Option<&Cluster> {
+        self.input_clusters
+            .iter()
+            .find(|input| input.identifier == cluster)
+    }
+
+    /// Simple descriptor of the endpoint
+    ///
+    /// Fails with `Error::NotEnoughSpace` if the endpoint has more than
+    /// `MAX_CLUSTERS` input or output clusters.
+    pub fn simple_descriptor(&self) -> Result<SimpleDescriptor, Error> {
+        let input_count = self.input_clusters.len();
+        if input_count > MAX_CLUSTERS || self.output_clusters.len() > MAX_CLUSTERS {
+            return Err(Error::NotEnoughSpace);
+        }
+        let mut input_clusters = [0u16; MAX_CLUSTERS];
+        for (identifier, cluster) in input_clusters.iter_mut().zip(self.input_clusters.iter()) {
+            *identifier = cluster.identifier;
+        }
+        Ok(SimpleDescriptor::new(
+            self.identifier,
+            self.profile,
+            self.device,
+            self.device_version,
+            &input_clusters[..input_count],
+            self.output_clusters,
+        ))
+    }
+}
+
+/// Registry of the application endpoints
+#[derive(Clone, Debug, Default)]
+pub struct Registry {
+    endpoints: [Option<Endpoint>; MAX_ENDPOINTS],
+}
+
+impl Registry {
+    /// Add `endpoint`, replacing any endpoint with the same identifier
+    ///
+    /// Only application endpoints, 1 to 240, can be added.
+    pub fn add(&mut self, endpoint: Endpoint) -> Result<(), Error> {
+        if !application_service::is_application_endpoint(endpoint.identifier) {
+            return Err(Error::InvalidEndpoint);
+        }
+        let slot = match self.position(endpoint.identifier) {
+            Some(index) => index,
+            None => self
+                .endpoints
+                .iter()
+                .position(Option::is_none)
+                .ok_or(Error::NotEnoughSpace)?,
+        };
+        self.endpoints[slot] = Some(endpoint);
+        Ok(())
+    }
+
+    /// Remove the endpoint with `identifier`
+    pub fn remove(&mut self, identifier: u8) -> Option<Endpoint> {
+        let index = self.position(identifier)?;
+        self.endpoints[index].take()
+    }
+
+    fn position(&self, identifier: u8) -> Option<usize> {
+        self.endpoints.iter().position(|endpoint| {
+            endpoint.map_or(false, |endpoint| endpoint.identifier == identifier)
+        })
+    }
+
+    /// The endpoint with `identifier`
+    pub fn endpoint(&self, identifier: u8) -> Option<&Endpoint> {
+        self.endpoints
+            .iter()
+            .flatten()
+            .find(|endpoint| endpoint.identifier == identifier)
+    }
+
+    /// All registered endpoints
+    pub fn endpoints(&self) -> impl Iterator<Item = &Endpoint> {
+        self.endpoints.iter().flatten()
+    }
+
+    /// The active endpoint identifiers, written to `identifiers`
+    ///
+    /// Returns the number of endpoints.
+    pub fn active_endpoints(&self, identifiers: &mut [u8; MAX_ENDPOINTS]) -> usize {
+        let mut count = 0;
+        for endpoint in self.endpoints() {
+            identifiers[count] = endpoint.identifier;
+            count += 1;
+        }
+        count
+    }
+
+    /// Simple descriptor of the endpoint with `identifier`
+    ///
+    /// Returns `None` if the endpoint isn't registered, see
+    /// `Endpoint::simple_descriptor` for errors.
+    pub fn simple_descriptor(&self, identifier: u8) -> Result<Option<SimpleDescriptor>, Error> {
+        self.endpoint(identifier)
+            .map(|endpoint| endpoint.simple_descriptor())
+            .transpose()
+    }
+
+    /// Answer a discover attributes request for `cluster` on the endpoint
+    /// with `identifier`
+    ///
+    /// Returns `None` if the endpoint or cluster isn't supported.
+    pub fn discover_attributes(
+        &self,
+        identifier: u8,
+        cluster: u16,
+        request: &DiscoverAttributes,
+    ) -> Option<DiscoverAttributesResponse> {
+        self.endpoint(identifier)?
+            .input_cluster(cluster)
+            .map(|cluster| cluster.discover_attributes(request))
+    }
+}
+
+#[cfg(all(test, not(feature = "core")))]
+mod tests {
+    use super::*;
+
+    const ON_OFF_LIGHT: Endpoint = Endpoint {
+        identifier: 0x01,
+        profile: 0x0104,
+        device: 0x0100,
+        device_version: 0,
+        input_clusters: &[
+            Cluster {
+                identifier: 0x0000,
+                attributes: &[
+                    (0x0000, AttributeDataType::Unsigned8),
+                    (0x0004, AttributeDataType::CharacterString),
+                    (0x0005, AttributeDataType::CharacterString),
+                ],
+            },
+            Cluster {
+                identifier: 0x0006,
+                attributes: &[(0x0000, AttributeDataType::Boolean)],
+            },
+        ],
+        output_clusters: &[0x0019],
+    };
+
+    #[test]
+    fn registry_endpoints() {
+        let mut registry = Registry::default();
+        registry.add(ON_OFF_LIGHT).unwrap();
+        assert_eq!(
+            registry.add(Endpoint {
+                identifier: 0,
+                ..ON_OFF_LIGHT
+            }),
+            Err(Error::InvalidEndpoint)
+        );
+        for identifier in 2..=(MAX_ENDPOINTS as u8) {
+            registry
+                .add(Endpoint {
+                    identifier,
+                    ..ON_OFF_LIGHT
+                })
+                .unwrap();
+        }
+        assert_eq!(
+            registry.add(Endpoint {
+                identifier: 0x20,
+                ..ON_OFF_LIGHT
+            }),
+            Err(Error::NotEnoughSpace)
+        );
+        // Replacing a endpoint doesn't need a free slot
+        registry.add(ON_OFF_LIGHT).unwrap();
+        assert!(registry.remove(0x02).is_some());
+        assert!(registry.remove(0x02).is_none());
+
+        let mut identifiers = [0u8; MAX_ENDPOINTS];
+        let count = registry.active_endpoints(&mut identifiers);
+        assert_eq!(identifiers[..count], [1, 3, 4, 5, 6, 7, 8]);
+
+        let descriptor = registry.simple_descriptor(0x01).unwrap().unwrap();
+        assert_eq!(descriptor.profile, 0x0104);
+        assert_eq!(descriptor.input_clusters(), [0x0000, 0x0006]);
+        assert_eq!(descriptor.output_clusters(), [0x0019]);
+        assert!(registry.simple_descriptor(0x02).unwrap().is_none());
+
+        // Too many clusters for a simple descriptor
+        const OUTPUT_CLUSTERS: [u16; MAX_CLUSTERS + 1] = [0x0019; MAX_CLUSTERS + 1];
+        registry
+            .add(Endpoint {
+                identifier: 0x02,
+                output_clusters: &OUTPUT_CLUSTERS,
+                ..ON_OFF_LIGHT
+            })
+            .unwrap();
+        assert_eq!(
+            registry.simple_descriptor(0x02).unwrap_err(),
+            Error::NotEnoughSpace
+        );
+    }
+
+    #[test]
+    fn discover_attributes() {
+        let mut registry = Registry::default();
+        registry.add(ON_OFF_LIGHT).unwrap();
+        let request = DiscoverAttributes {
+            start: AttributeIdentifier::new(0x0001),
+            count: 1,
+        };
+        let response = registry
+            .discover_attributes(0x01, 0x0000, &request)
+            .unwrap();
+        assert!(!response.complete);
+        assert_eq!(
+            response.attributes,
+            [(
+                AttributeIdentifier::new(0x0004),
+                AttributeDataType::CharacterString
+            )]
+        );
+        let request = DiscoverAttributes {
+            start: AttributeIdentifier::new(0x0000),
+            count: 10,
+        };
+        let response = registry
+            .discover_attributes(0x01, 0x0006, &request)
+            .unwrap();
+        assert!(response.complete);
+        assert_eq!(response.attributes.len(), 1);
+
+        // The response stops when full
+        const ATTRIBUTES: [(u16, AttributeDataType); MAX_DISCOVERED_ATTRIBUTES + 1] =
+            [(0x0000, AttributeDataType::Boolean); MAX_DISCOVERED_ATTRIBUTES + 1];
+        let cluster = Cluster {
+            identifier: 0x0006,
+            attributes: &ATTRIBUTES,
+        };
+        let request = DiscoverAttributes {
+            start: AttributeIdentifier::new(0x0000),
+            count: 0xff,
+        };
+        let response = cluster.discover_attributes(&request);
+        assert!(!response.complete);
+        assert_eq!(response.attributes.len(), MAX_DISCOVERED_ATTRIBUTES);
+        assert!(registry
+            .discover_attributes(0x01, 0x0008, &request)
+            .is_none());
+        assert!(registry
+            .discover_attributes(0x02, 0x0006, &request)
+            .is_none());
+    }
+}
@@ -1,32 +1,43 @@
 use crate::Error;
 use psila_crypto::CryptoBackend;
 use psila_data::{
-    application_service::commands::transport_key::NetworkKey,
-    network::NetworkHeader,
+    application_service::{commands::transport_key::NetworkKey, ApplicationServiceHeader},
+    network::{KeyStore, NetworkHeader},
     pack::Pack,
-    security::{CryptoProvider, KeyIdentifier, SecurityHeader, SecurityLevel},
+    security::{
+        CryptoProvider, KeyIdentifier, SecureSender, SecurityHeader, SecurityLevel, SecurityPolicy,
+    },
     ExtendedAddress, Key,
 };
 
+/// Maximum number of link keys, besides the default link key
+pub const MAX_LINK_KEYS: usize = 8;
+
 pub struct SecurityManager<CB> {
     crypto_provider: CryptoProvider<CB>,
     default_link_key: Key,
+    link_keys: [Option<(ExtendedAddress, Key)>; MAX_LINK_KEYS],
     network_key: Option<NetworkKey>,
     security_level: SecurityLevel,
-    sequence: u32,
+    sender: SecureSender,
+    link_sender: SecureSender,
+    policy: SecurityPolicy,
 }
 
 impl<CB> SecurityManager<CB>
 where
     CB: CryptoBackend,
 {
-    pub fn new(crypto_backend: CB, default_link_key: Key) -> Self {
+    pub fn new(crypto_backend: CB, address: ExtendedAddress, default_link_key: Key) -> Self {
         Self {
             crypto_provider: CryptoProvider::new(crypto_backend),
             default_link_key,
+            link_keys: [None; MAX_LINK_KEYS],
             network_key: None,
             security_level: SecurityLevel::EncryptedIntegrity32,
-            sequence: 0,
+            sender: SecureSender::new(address, SecurityLevel::EncryptedIntegrity32),
+            link_sender: SecureSender::new(address, SecurityLevel::EncryptedIntegrity32),
+            policy: SecurityPolicy::default(),
         }
     }
 
@@ -48,10 +59,64 @@ where
         }
     }
 
+    /// Set the network key, the outgoing frame counter restarts when the
+    /// key sequence number changes
     pub fn set_network_key(&mut self, key: NetworkKey) {
+        if self.network_key.map(|k| k.sequence) != Some(key.sequence) {
+            self.sender.reset_counter();
+        }
         self.network_key = Some(key);
     }
 
+    /// Set the link key shared with the device with the extended address
+    /// `address`, replacing any previous key for the device
+    ///
+    /// Fails with `Error::NotEnoughSpace` if all link key slots are used.
+    pub fn set_link_key(&mut self, address: ExtendedAddress, key: Key) -> Result<(), Error> {
+        let slot = match self
+            .link_keys
+            .iter()
+            .position(|entry| entry.map_or(false, |(known, _)| known == address))
+        {
+            Some(index) => index,
+            None => self
+                .link_keys
+                .iter()
+                .position(Option::is_none)
+                .ok_or(Error::NotEnoughSpace)?,
+        };
+        self.link_keys[slot] = Some((address, key));
+        Ok(())
+    }
+
+    /// Forget the link key shared with the device with the extended address
+    /// `address`
+    pub fn remove_link_key(&mut self, address: ExtendedAddress) -> Option<Key> {
+        let entry = self
+            .link_keys
+            .iter_mut()
+            .find(|entry| entry.map_or(false, |(known, _)| known == address))?;
+        entry.take().map(|(_, key)| key)
+    }
+
+    /// The policy deciding which APS frames are secured with the link key
+    pub fn policy(&self) -> &SecurityPolicy {
+        &self.policy
+    }
+
+    /// Set the APS security policy, the link key secured frames use the
+    /// level of the policy
+    pub fn set_policy(&mut self, policy: SecurityPolicy) {
+        self.link_sender.set_level(policy.level());
+        self.policy = policy;
+    }
+
+    /// The security level of APS frames for `cluster` in `profile`,
+    /// `SecurityLevel::None` if they aren't secured with the link key
+    pub fn application_service_level(&self, cluster: u16, profile: u16) -> SecurityLevel {
+        self.policy.level_for(cluster, profile)
+    }
+
     pub fn decrypt_payload(
         &mut self,
         payload: &[u8],
@@ -76,7 +141,6 @@ where
 
     pub fn encrypt_network_payload(
         &mut self,
-        source_address: ExtendedAddress,
         header: NetworkHeader,
         payload: &[u8],
         encrypted_payload: &mut [u8],
@@ -86,20 +150,56 @@ where
         } else {
             return Err(Error::CryptoError(psila_crypto::Error::InvalidKey));
         };
-        let security_header = SecurityHeader::network_header(
-            self.security_level,
-            self.sequence,
-            source_address,
-            key_sequence,
-        );
-        let size = self.crypto_provider.encrypt_network_frame(
+        let size = self.sender.encrypt_network_frame(
+            &mut self.crypto_provider,
             header,
             &key.into(),
-            security_header,
+            key_sequence,
             payload,
             encrypted_payload,
         )?;
-        self.sequence = self.sequence.wrapping_add(1);
         Ok(size)
     }
+
+    /// Build a APS frame with `header` and `payload` secured with the link
+    /// key shared with `destination`
+    ///
+    /// The default link key is used if the extended address of the
+    /// destination isn't known or no link key is shared with it.
+    pub fn encrypt_application_service_payload(
+        &mut self,
+        destination: Option<ExtendedAddress>,
+        header: &ApplicationServiceHeader,
+        payload: &[u8],
+        encrypted_payload: &mut [u8],
+    ) -> Result<usize, Error> {
+        let key = destination
+            .and_then(|address| self.link_key(address))
+            .unwrap_or(self.default_link_key)
+            .into();
+        let size = self.link_sender.encrypt_application_service_frame(
+            self.crypto_provider.backend(),
+            header,
+            &key,
+            payload,
+            encrypted_payload,
+        )?;
+        Ok(size)
+    }
+}
+
+impl<CB> KeyStore for SecurityManager<CB> {
+    fn network_key(&self, sequence: u8) -> Option<Key> {
+        self.network_key
+            .filter(|key| key.sequence == sequence)
+            .map(|key| key.key)
+    }
+
+    fn link_key(&self, address: ExtendedAddress) -> Option<Key> {
+        self.link_keys
+            .iter()
+            .flatten()
+            .find(|(known, _)| *known == address)
+            .map(|(_, key)| *key)
+    }
 }